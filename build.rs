@@ -0,0 +1,12 @@
+fn main() {
+    // The gRPC service and messages, generated from the proto
+    #[cfg(feature = "grpc")]
+    {
+        const PROTO: &str = "proto/cloudflare_speed/v1/speedtest.proto";
+        println!("cargo:rerun-if-changed={PROTO}");
+        let descriptors = protox::compile([PROTO], ["proto"]).expect("parse the gRPC proto");
+        tonic_build::configure()
+            .compile_fds(descriptors)
+            .expect("generate the gRPC code");
+    }
+}
@@ -0,0 +1,42 @@
+# English (US) catalog for cloudflare-speed-cli.
+#
+# To add a translation, copy this file to locales/<language>/main.ftl,
+# translate the values (keep the message IDs and $variables unchanged) and
+# register the new catalog in src/i18n.rs.
+
+## TUI tabs
+
+tab-dashboard = Dashboard
+tab-history = History
+tab-charts = Charts
+tab-diagnostics = Diagnostics
+tab-settings = Settings
+tab-help = Help
+
+## Text mode summary
+## Numbers are passed in already formatted.
+
+summary-ip-colo-asn = IP/Colo/ASN: { $ip } / { $colo } / { $asn } ({ $org })
+summary-server = Server: { $server }
+summary-cellular = Cellular: { $details }
+summary-distance = Distance to { $colo }: ~{ $km } km
+summary-comments = Comments: { $comments }
+summary-correlation-id = Correlation ID: { $id } ({ $header })
+summary-download = Download: avg { $avg } med { $median } p25 { $p25 } p75 { $p75 }
+summary-stream-comparison = Single vs multi-stream: { $single } vs { $multi } Mbps over { $streams } streams ({ $ratio }x)
+summary-per-stream = Per stream: { $mbps } Mbps
+summary-saturation-streams = Saturated at: { $streams } stream(s)
+summary-ramp-unfinished = Ramp-up unfinished: reached { $streams } stream(s) before the phase ended
+summary-truncated = Truncated: { $truncated } of { $responses } responses ({ $pct }%) ended before the bytes requested
+summary-upload = Upload:   avg { $avg } med { $median } p25 { $p25 } p75 { $p75 }
+summary-normalized = Normalized to { $profile }: { $download } down, { $upload } up Mbps (from { $runs } calibration run(s))
+summary-website-parity = As the website reports it (90th percentile of requests): { $download } down, { $upload } up Mbps
+summary-website-parity-note = Remaining differences from speed.cloudflare.com: the website runs in the browser's network stack, alternates download and upload sizes rather than finishing one phase first, and may be served from another Cloudflare location. A browser on Wi-Fi or with extensions also measures differently from this machine.
+summary-plan = Share of plan: { $download } down, { $upload } up
+summary-client-cpu = Client CPU: { $details }
+summary-idle-latency = Idle latency: avg { $avg } med { $median } p25 { $p25 } p75 { $p75 } ms (loss { $loss }%, jitter { $jitter } ms)
+summary-loaded-latency-download = Loaded latency (download): avg { $avg } med { $median } p25 { $p25 } p75 { $p75 } ms (loss { $loss }%, jitter { $jitter } ms)
+summary-loaded-latency-upload = Loaded latency (upload): avg { $avg } med { $median } p25 { $p25 } p75 { $p75 } ms (loss { $loss }%, jitter { $jitter } ms)
+summary-bufferbloat-grade = Bufferbloat grade: { $grade }
+summary-udp-quality = UDP quality: { $label } ({ $mos }) | loss { $loss }% jitter { $jitter } reorder { $reorder }% rtt { $rtt }ms
+summary-saved = Saved: { $path }
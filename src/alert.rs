@@ -0,0 +1,65 @@
+//! Audible alerts for unattended runs (`--bell-on-fail`, `--alert-sound`):
+//! when a run fails or misses the `[thresholds]` of the config file.
+
+use crate::cli::Cli;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Ring the bell and play the alert sound, as configured.
+pub fn raise(args: &Cli) {
+    if args.bell_on_fail {
+        // stdout may carry JSON output; stderr is the terminal all the same
+        let mut stderr = std::io::stderr();
+        stderr.write_all(b"\x07").ok();
+        stderr.flush().ok();
+    }
+    if let Some(sound) = args.alert_sound.as_deref() {
+        play(sound);
+    }
+}
+
+/// Commands that can play a sound file, tried in order.
+fn players(sound: &Path) -> Vec<Command> {
+    let command = |program: &str, args: &[&str]| {
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    };
+    if cfg!(target_os = "macos") {
+        let mut afplay = command("afplay", &[]);
+        afplay.arg(sound);
+        vec![afplay]
+    } else if cfg!(windows) {
+        let script = format!(
+            "(New-Object Media.SoundPlayer '{}').PlaySync()",
+            sound.display().to_string().replace('\'', "''")
+        );
+        vec![command("powershell", &["-NoProfile", "-Command", &script])]
+    } else {
+        ["paplay", "pw-play", "aplay"]
+            .into_iter()
+            .map(|program| {
+                let mut player = command(program, &[]);
+                player.arg(sound);
+                player
+            })
+            .collect()
+    }
+}
+
+/// Play `sound` in the background with the first player available.
+fn play(sound: &Path) {
+    for mut player in players(sound) {
+        let spawned = player
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = spawned {
+            // Reap the player once it's done
+            std::thread::spawn(move || child.wait());
+            return;
+        }
+    }
+}
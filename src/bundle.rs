@@ -0,0 +1,651 @@
+//! ISP complaint bundle (`bundle` subcommand): the saved runs of a date
+//! range in one zip to attach to a support ticket, with a plain-language
+//! summary, charts of the runs over time and the latest test's diagnostics.
+
+use crate::config::Config;
+use crate::metrics::median;
+use crate::model::RunResult;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use time::UtcOffset;
+
+/// Days covered when no `--since` is given.
+pub const DEFAULT_DAYS: i64 = 30;
+
+/// Share of the plan speed below which a test counts as short of it.
+const PLAN_SHORTFALL: f64 = 0.8;
+/// Fewest tests in an hour of the day for it to be compared with the rest.
+const MIN_HOUR_RUNS: usize = 3;
+/// Slowest tests listed in the summary.
+const WORST_RUNS: usize = 3;
+
+/// Parse a `--since`/`--until` date (YYYY-MM-DD).
+pub fn parse_date(value: &str) -> Result<String, String> {
+    match crate::report::unix_timestamp(&format!("{value}T00:00:00Z")) {
+        Some(_) if value.len() == 10 => Ok(value.to_string()),
+        _ => Err(format!("invalid date {value:?}, expected YYYY-MM-DD")),
+    }
+}
+
+/// UTC dates of the runs in a bundle, both included.
+#[derive(Debug, Clone)]
+pub struct Period {
+    pub since: String,
+    pub until: String,
+}
+
+impl Period {
+    /// From `--since` and `--until`, by default the last `DEFAULT_DAYS`
+    /// days up to today.
+    pub fn new(since: Option<String>, until: Option<String>) -> Self {
+        let today = time::OffsetDateTime::now_utc().date();
+        Self {
+            since: since
+                .unwrap_or_else(|| (today - time::Duration::days(DEFAULT_DAYS)).to_string()),
+            until: until.unwrap_or_else(|| today.to_string()),
+        }
+    }
+
+    fn contains(&self, run: &RunResult) -> bool {
+        run.timestamp_utc
+            .get(..10)
+            .is_some_and(|date| self.since.as_str() <= date && date <= self.until.as_str())
+    }
+}
+
+/// Download and upload speed of an internet plan, in Mbps.
+type Plan = (Option<f64>, Option<f64>);
+
+/// Speeds of the plan a run's network is on: `--plan-download-mbps` and
+/// `--plan-upload-mbps`, else its `[networks]` entry's.
+fn plan_for(config: &Config, plan: Plan, run: &RunResult) -> Plan {
+    let settings = config
+        .network_settings(
+            run.network_name.as_deref(),
+            run.interface_mac.as_deref(),
+            run.interface_name.as_deref(),
+        )
+        .map(|(_, settings)| settings);
+    (
+        plan.0.or(settings.and_then(|s| s.plan_download_mbps)),
+        plan.1.or(settings.and_then(|s| s.plan_upload_mbps)),
+    )
+}
+
+/// Occurrences of each value, most frequent first.
+fn tally<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<(&'a str, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    counts
+}
+
+fn latency(ms: Option<f64>) -> String {
+    ms.map_or_else(|| "-".to_string(), |ms| format!("{ms:.1} ms"))
+}
+
+/// The plan finding of one direction: the median share of the plan speed
+/// reached, and how many runs fell short of it.
+fn plan_finding(direction: &str, shares: &[f64]) -> Option<String> {
+    let share = median(shares)?;
+    let short = shares.iter().filter(|&&s| s < PLAN_SHORTFALL).count();
+    Some(format!(
+        "{direction} reached a median of {:.0}% of the plan speed; {short} of {} tests \
+         measured below {:.0}% of it.",
+        share * 100.0,
+        shares.len(),
+        PLAN_SHORTFALL * 100.0
+    ))
+}
+
+/// The hour of the day (at `offset`) with the slowest median download, with
+/// that median, when at least two hours have enough tests to compare.
+fn slowest_hour(runs: &[&RunResult], offset: UtcOffset) -> Option<(i64, f64)> {
+    let mut hours: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+    for run in runs {
+        if let Some(t) = crate::report::unix_timestamp(&run.timestamp_utc) {
+            let hour = (t + offset.whole_seconds() as i64).rem_euclid(86400) / 3600;
+            hours.entry(hour).or_default().push(run.download.mbps);
+        }
+    }
+    let medians: Vec<(i64, f64)> = hours
+        .into_iter()
+        .filter(|(_, values)| values.len() >= MIN_HOUR_RUNS)
+        .filter_map(|(hour, values)| Some((hour, median(&values)?)))
+        .collect();
+    if medians.len() < 2 {
+        return None;
+    }
+    medians.into_iter().min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// The plain-language summary of `runs`, the runs of `period`, for the
+/// ISP. `plan` is the plan speed given on the command line, if any, and
+/// `offset` the time zone hours of the day are given in.
+pub fn summary(
+    runs: &[RunResult],
+    period: &Period,
+    config: &Config,
+    plan: Plan,
+    offset: UtcOffset,
+) -> String {
+    let valid: Vec<&RunResult> = runs.iter().filter(|r| r.invalid.is_none()).collect();
+    let invalid = runs.len() - valid.len();
+    let mut out = String::new();
+    let _ = writeln!(out, "Internet speed test report");
+    let _ = writeln!(out, "==========================");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Period: {} to {} (UTC)", period.since, period.until);
+    let _ = write!(out, "Tests: {}", valid.len());
+    if invalid > 0 {
+        let _ = write!(out, " ({invalid} more left out as invalid)");
+    }
+    let _ = writeln!(out);
+    if valid.is_empty() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "No valid speed tests were saved in this period.");
+        return out;
+    }
+
+    let networks = tally(
+        valid
+            .iter()
+            .map(|r| config.network_label(r).unwrap_or("unknown network")),
+    );
+    let networks: Vec<String> = networks
+        .iter()
+        .map(|(name, n)| format!("{name} ({n} tests)"))
+        .collect();
+    let _ = writeln!(out, "Network: {}", networks.join(", "));
+    let isps: Vec<String> = valid
+        .iter()
+        .filter_map(|r| Some(crate::report::as_label(r)?.1))
+        .collect();
+    if let Some((isp, _)) = tally(isps.iter().map(String::as_str)).first() {
+        let _ = writeln!(out, "ISP: {isp}");
+    }
+    let plans: Vec<Plan> = valid.iter().map(|r| plan_for(config, plan, r)).collect();
+    let plan_text =
+        |mbps: Option<f64>, direction: &str| mbps.map(|m| format!("{m} Mbps {direction}"));
+    let described: Vec<String> = [
+        plan_text(plans.iter().find_map(|p| p.0), "down"),
+        plan_text(plans.iter().find_map(|p| p.1), "up"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !described.is_empty() {
+        let _ = writeln!(out, "Plan: {}", described.join(", "));
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Measured over the period:");
+    let owned: Vec<RunResult> = valid.iter().map(|r| (*r).clone()).collect();
+    for line in crate::repeat::summary_lines(&crate::repeat::summarize(&owned)) {
+        let _ = writeln!(out, "  {line}");
+    }
+
+    let mut findings = Vec::new();
+    let shares = |f: fn(&RunResult) -> f64, plan: fn(&Plan) -> Option<f64>| {
+        valid
+            .iter()
+            .zip(&plans)
+            .filter_map(|(r, p)| Some(f(r) / plan(p).filter(|&m| m > 0.0)?))
+            .collect::<Vec<f64>>()
+    };
+    findings.extend(plan_finding(
+        "Download",
+        &shares(|r| r.download.mbps, |p| p.0),
+    ));
+    findings.extend(plan_finding("Upload", &shares(|r| r.upload.mbps, |p| p.1)));
+
+    let breaching = valid
+        .iter()
+        .filter(|r| !config.thresholds_for(r).breaches(r).is_empty())
+        .count();
+    if breaching > 0 {
+        findings.push(format!(
+            "{breaching} of {} tests missed the configured minimum speeds or maximum latency.",
+            valid.len()
+        ));
+    }
+
+    let overall = median(&valid.iter().map(|r| r.download.mbps).collect::<Vec<_>>());
+    if let (Some((hour, slowest)), Some(overall)) = (slowest_hour(&valid, offset), overall) {
+        let zone = match offset.whole_seconds() {
+            0 => "UTC".to_string(),
+            _ => format!(
+                "UTC{:+03}:{:02}",
+                offset.whole_hours(),
+                offset.minutes_past_hour().abs()
+            ),
+        };
+        findings.push(format!(
+            "The slowest time of day is {hour:02}:00-{:02}:00 ({zone}), with a median download \
+             of {slowest:.1} Mbps against {overall:.1} Mbps over all tests.",
+            (hour + 1) % 24
+        ));
+    }
+
+    let as_changes = crate::report::asn_changes(&owned);
+    if !as_changes.is_empty() {
+        findings.push(format!(
+            "The network's AS changed {} time(s) during the period.",
+            as_changes.len()
+        ));
+    }
+
+    let verdicts: Vec<&str> = valid
+        .iter()
+        .filter_map(|r| crate::explain::verdict(&crate::explain::findings(r)))
+        .map(|b| b.label())
+        .collect();
+    if let Some((label, n)) = tally(verdicts).first() {
+        findings.push(format!(
+            "Across the tests, the most likely bottleneck is {label} ({n} of {} tests).",
+            valid.len()
+        ));
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Findings:");
+    if findings.is_empty() {
+        let _ = writeln!(out, "  - No problems stand out in these tests.");
+    }
+    for finding in findings {
+        let _ = writeln!(out, "  - {finding}");
+    }
+
+    let mut slowest = valid.clone();
+    slowest.sort_by(|a, b| a.download.mbps.total_cmp(&b.download.mbps));
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Slowest tests:");
+    for run in slowest.iter().take(WORST_RUNS) {
+        let _ = writeln!(
+            out,
+            "  {}  download {:.1} Mbps, upload {:.1} Mbps, idle latency {}",
+            run.timestamp_utc,
+            run.download.mbps,
+            run.upload.mbps,
+            latency(run.idle_latency.median_ms)
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "runs.csv and the runs folder hold every test of the period, the charts folder plots them \
+         over time, and diagnostics.txt has the route and connection details of the latest test."
+    );
+    out
+}
+
+/// Route, DNS, TLS and bottleneck breakdown of the latest run.
+pub fn diagnostics(config: &Config, runs: &[RunResult]) -> String {
+    let mut out = String::new();
+    let Some(run) = runs.iter().find(|r| r.invalid.is_none()).or(runs.first()) else {
+        let _ = writeln!(out, "No speed tests were saved in this period.");
+        return out;
+    };
+    let _ = writeln!(
+        out,
+        "Latest test: {} ({}){}",
+        run.timestamp_utc,
+        run.meas_id,
+        config
+            .network_label(run)
+            .map(|n| format!(" on {n}"))
+            .unwrap_or_default()
+    );
+    if let Some(server) = run.server.as_deref().or(run.colo.as_deref()) {
+        let _ = writeln!(out, "Test server: {server}");
+    }
+    if let Some(dns) = &run.dns {
+        let _ = writeln!(
+            out,
+            "DNS: {} resolved in {:.1} ms to {}",
+            dns.hostname,
+            dns.resolution_time_ms,
+            dns.resolved_ips.join(", ")
+        );
+        if !dns.dns_servers.is_empty() {
+            let _ = writeln!(out, "DNS servers: {}", dns.dns_servers.join(", "));
+        }
+    }
+    if let Some(tls) = &run.tls {
+        let _ = writeln!(
+            out,
+            "TLS: handshake in {:.1} ms ({}, {})",
+            tls.handshake_time_ms,
+            tls.protocol_version.as_deref().unwrap_or("-"),
+            tls.cipher_suite.as_deref().unwrap_or("-")
+        );
+    }
+
+    // The latest route, even if the latest test ran without a traceroute
+    if let Some((traced, route)) = runs.iter().find_map(|r| Some((r, r.traceroute.as_ref()?))) {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "Route to {} (test at {}){}:",
+            route.destination,
+            traced.timestamp_utc,
+            if route.completed { "" } else { ", incomplete" }
+        );
+        for hop in &route.hops {
+            let address = match (&hop.ip_address, &hop.hostname) {
+                _ if hop.timeout => "*".to_string(),
+                (Some(ip), Some(name)) if name != ip => format!("{ip} ({name})"),
+                (Some(ip), _) => ip.clone(),
+                (None, _) => "?".to_string(),
+            };
+            let rtts: Vec<String> = hop.rtt_ms.iter().map(|ms| format!("{ms:.1} ms")).collect();
+            let _ = writeln!(
+                out,
+                "  {:>2}  {address}  {}",
+                hop.hop_number,
+                rtts.join(" ")
+            );
+        }
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Breakdown of the latest test:");
+    let findings = crate::explain::findings(run);
+    for f in &findings {
+        let tag = f.category.map_or("", |(category, _)| category.label());
+        let _ = writeln!(out, "  {:<17} {}", tag, f.text);
+    }
+    match crate::explain::verdict(&findings) {
+        Some(category) => {
+            let _ = writeln!(out, "Most likely bottleneck: {}", category.label());
+        }
+        None => {
+            let _ = writeln!(out, "No bottleneck stands out in this test");
+        }
+    }
+    out
+}
+
+/// One line of a chart.
+struct Series {
+    name: &'static str,
+    color: &'static str,
+    /// Unix time and value, oldest first
+    points: Vec<(i64, f64)>,
+}
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 320.0;
+const CHART_LEFT: f64 = 70.0;
+const CHART_RIGHT: f64 = 20.0;
+const CHART_TOP: f64 = 40.0;
+const CHART_BOTTOM: f64 = 40.0;
+const CHART_TICKS: usize = 4;
+
+/// An SVG line chart of `series` over time, the value axis from zero.
+fn svg_chart(title: &str, unit: &str, series: &[Series]) -> String {
+    let points = series.iter().flat_map(|s| &s.points);
+    let t_min = points.clone().map(|p| p.0).min().unwrap_or(0);
+    let t_max = points
+        .clone()
+        .map(|p| p.0)
+        .max()
+        .unwrap_or(0)
+        .max(t_min + 1);
+    let v_max = points.map(|p| p.1).fold(0.0, f64::max);
+    let v_max = if v_max > 0.0 { v_max * 1.1 } else { 1.0 };
+    let plot_w = CHART_WIDTH - CHART_LEFT - CHART_RIGHT;
+    let plot_h = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    let x = |t: i64| CHART_LEFT + (t - t_min) as f64 / (t_max - t_min) as f64 * plot_w;
+    let y = |v: f64| CHART_TOP + plot_h - v / v_max * plot_h;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" \
+         font-family=\"sans-serif\" font-size=\"12\">"
+    );
+    let _ = writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <text x=\"{CHART_LEFT}\" y=\"24\" font-size=\"16\">{title}</text>"
+    );
+    for i in 0..=CHART_TICKS {
+        let v = v_max * i as f64 / CHART_TICKS as f64;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{CHART_LEFT}\" x2=\"{:.1}\" y1=\"{:.1}\" y2=\"{:.1}\" stroke=\"#ddd\"/>\n\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{v:.0} {unit}</text>",
+            CHART_WIDTH - CHART_RIGHT,
+            y(v),
+            y(v),
+            CHART_LEFT - 6.0,
+            y(v) + 4.0
+        );
+    }
+    let date = |t: i64| {
+        time::OffsetDateTime::from_unix_timestamp(t)
+            .map(|d| d.date().to_string())
+            .unwrap_or_default()
+    };
+    let _ = writeln!(
+        svg,
+        "<text x=\"{CHART_LEFT}\" y=\"{:.1}\">{}</text>\n\
+         <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+        CHART_HEIGHT - 12.0,
+        date(t_min),
+        CHART_WIDTH - CHART_RIGHT,
+        CHART_HEIGHT - 12.0,
+        date(t_max)
+    );
+    for (i, s) in series.iter().enumerate() {
+        let path: Vec<String> = s
+            .points
+            .iter()
+            .map(|&(t, v)| format!("{:.1},{:.1}", x(t), y(v)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>\n\
+             <text x=\"{:.1}\" y=\"24\" fill=\"{}\" text-anchor=\"end\">{}</text>",
+            s.color,
+            path.join(" "),
+            CHART_WIDTH - CHART_RIGHT - 110.0 * (series.len() - 1 - i) as f64,
+            s.color,
+            s.name
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Throughput and latency charts of `runs`, newest first, as (file name,
+/// SVG).
+fn charts(runs: &[RunResult]) -> Vec<(&'static str, String)> {
+    let series = |name, color, f: fn(&RunResult) -> Option<f64>| Series {
+        name,
+        color,
+        points: runs
+            .iter()
+            .rev()
+            .filter(|r| r.invalid.is_none())
+            .filter_map(|r| Some((crate::report::unix_timestamp(&r.timestamp_utc)?, f(r)?)))
+            .collect(),
+    };
+    vec![
+        (
+            "charts/throughput.svg",
+            svg_chart(
+                "Download and upload",
+                "Mbps",
+                &[
+                    series("Download", "#1f77b4", |r| Some(r.download.mbps)),
+                    series("Upload", "#ff7f0e", |r| Some(r.upload.mbps)),
+                ],
+            ),
+        ),
+        (
+            "charts/latency.svg",
+            svg_chart(
+                "Latency",
+                "ms",
+                &[
+                    series("Idle", "#2ca02c", |r| r.idle_latency.median_ms),
+                    series("Download", "#1f77b4", |r| {
+                        r.loaded_latency_download.median_ms
+                    }),
+                    series("Upload", "#ff7f0e", |r| r.loaded_latency_upload.median_ms),
+                ],
+            ),
+        ),
+    ]
+}
+
+/// Write the bundle of the saved runs in `period` to `output`, redacting
+/// them when `redact` is set. Returns the number of runs.
+pub fn write_bundle(
+    output: &Path,
+    period: &Period,
+    config: &Config,
+    plan: Plan,
+    redact: bool,
+    progress: &mut crate::progress::Progress,
+) -> Result<usize> {
+    if period.since > period.until {
+        anyhow::bail!("--since {} is after --until {}", period.since, period.until);
+    }
+    let mut runs: Vec<RunResult> = crate::storage::load_recent_with(usize::MAX, progress)?
+        .into_iter()
+        .filter(|r| period.contains(r))
+        .collect();
+    if runs.is_empty() {
+        anyhow::bail!(
+            "no saved runs between {} and {}",
+            period.since,
+            period.until
+        );
+    }
+    if redact {
+        for run in &mut runs {
+            run.redact();
+        }
+    }
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        (
+            "summary.txt".into(),
+            summary(&runs, period, config, plan, offset).into_bytes(),
+        ),
+        (
+            "diagnostics.txt".into(),
+            diagnostics(config, &runs).into_bytes(),
+        ),
+    ];
+    let mut oldest_first = runs.clone();
+    oldest_first.reverse();
+    let columns = crate::storage::csv_columns("full")?;
+    files.push((
+        "runs.csv".into(),
+        crate::storage::csv_text(&oldest_first, &columns).into_bytes(),
+    ));
+    for (name, svg) in charts(&runs) {
+        files.push((name.into(), svg.into_bytes()));
+    }
+    for run in &oldest_first {
+        let id = if run.meas_id.is_empty() {
+            run.timestamp_utc.replace(':', "")
+        } else {
+            run.meas_id.clone()
+        };
+        files.push((format!("runs/{id}.json"), serde_json::to_vec_pretty(run)?));
+    }
+    write_zip(output, &files).with_context(|| format!("write {}", output.display()))?;
+    Ok(runs.len())
+}
+
+#[cfg(feature = "bundle")]
+fn write_zip(path: &Path, files: &[(String, Vec<u8>)]) -> Result<()> {
+    use std::io::Write as _;
+    use zip::write::SimpleFileOptions;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create bundle directory")?;
+    }
+    let now = time::OffsetDateTime::now_utc();
+    let modified = zip::DateTime::from_date_and_time(
+        now.year().try_into().unwrap_or(1980),
+        now.month().into(),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+    )
+    .unwrap_or_default();
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(modified);
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+    for (name, data) in files {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(data)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "bundle"))]
+fn write_zip(_path: &Path, _files: &[(String, Vec<u8>)]) -> Result<()> {
+    anyhow::bail!("bundle requires building with the `bundle` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(timestamp: &str, download: f64) -> RunResult {
+        let mut r = test_run(download, 20.0);
+        r.timestamp_utc = timestamp.to_string();
+        r.idle_latency.median_ms = Some(10.0);
+        r
+    }
+
+    #[test]
+    fn test_bundle_summary() {
+        assert!(parse_date("2026-02-30").is_err());
+        let period = Period::new(Some("2026-10-01".into()), Some("2026-10-03".into()));
+        assert!(period.contains(&run("2026-10-03T23:00:00Z", 1.0)));
+        assert!(!period.contains(&run("2026-10-04T00:00:00Z", 1.0)));
+
+        // Evenings are slow; newest first, as loaded
+        let mut runs = Vec::new();
+        for day in 1..=3 {
+            runs.push(run(&format!("2026-10-0{day}T21:10:00Z"), 20.0));
+            runs.push(run(&format!("2026-10-0{day}T09:10:00Z"), 90.0));
+        }
+        runs.reverse();
+        let text = summary(
+            &runs,
+            &period,
+            &Config::default(),
+            (Some(100.0), None),
+            UtcOffset::UTC,
+        );
+        assert!(text.contains("Tests: 6\n"));
+        assert!(text.contains("Plan: 100 Mbps down\n"));
+        assert!(text.contains(
+            "Download reached a median of 55% of the plan speed; 3 of 6 tests measured below 80%"
+        ));
+        assert!(text.contains(
+            "The slowest time of day is 21:00-22:00 (UTC), with a median download of 20.0 Mbps"
+        ));
+        assert!(svg_chart("Download", "Mbps", &[]).starts_with("<svg"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Calibration of `--quick` tests against full ones (`calibrate`). A full
+//! reference run and a quick run back to back give the ratio of their
+//! throughputs on the network; later quick runs there carry an estimate of
+//! what a full run would have measured, so trends can mix the two.
+
+use crate::config::Config;
+use crate::model::{NormalizedEstimate, RunResult};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Download and upload phase of a `--quick` test.
+pub const QUICK_DURATION: Duration = Duration::from_secs(3);
+/// Idle latency phase of a `--quick` test.
+pub const QUICK_IDLE_DURATION: Duration = Duration::from_secs(1);
+
+/// Calibration runs kept per network; the correction is their median.
+const MAX_SAMPLES: usize = 5;
+
+/// Ratios of full to quick throughput of one calibration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp_utc: String,
+    pub download: f64,
+    pub upload: f64,
+}
+
+/// Calibration of one network.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkCalibration {
+    /// Configuration of the reference runs, e.g. "10s×6"
+    pub reference_profile: String,
+    /// Oldest first
+    pub samples: Vec<Sample>,
+}
+
+impl NetworkCalibration {
+    /// Median download and upload correction factors.
+    pub fn factors(&self) -> Option<(f64, f64)> {
+        let median = |f: fn(&Sample) -> f64| {
+            crate::metrics::median(&self.samples.iter().map(f).collect::<Vec<_>>())
+        };
+        Some((median(|s| s.download)?, median(|s| s.upload)?))
+    }
+
+    /// The estimate of a full run for `quick`.
+    fn estimate(&self, quick: &RunResult) -> Option<NormalizedEstimate> {
+        let (download, upload) = self.factors()?;
+        Some(NormalizedEstimate {
+            download_mbps: quick.download.mbps * download,
+            upload_mbps: quick.upload.mbps * upload,
+            reference_profile: self.reference_profile.clone(),
+            calibration_runs: self.samples.len(),
+        })
+    }
+}
+
+/// Key of the network a run was made on: the name it's shown under.
+pub fn network_key<'a>(config: &'a Config, run: &'a RunResult) -> &'a str {
+    config.network_label(run).unwrap_or("-")
+}
+
+/// Calibrations by network name.
+pub fn load() -> Result<BTreeMap<String, NetworkCalibration>> {
+    let path = crate::storage::calibration_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = std::fs::read(&path).context("read calibration")?;
+    serde_json::from_slice(&data).context("parse calibration")
+}
+
+fn save(calibrations: &BTreeMap<String, NetworkCalibration>) -> Result<()> {
+    let path = crate::storage::calibration_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create data dir")?;
+    }
+    std::fs::write(&path, serde_json::to_vec_pretty(calibrations)?).context("write calibration")
+}
+
+/// Set the normalized estimate of a fresh quick run made on `network` (its
+/// `network_key`), if that network is calibrated. Runs are annotated once,
+/// when they finish; saved ones keep the estimate they were saved with.
+pub fn annotate(run: &mut RunResult, network: &str) {
+    if !run.config.as_ref().is_some_and(|c| c.quick) {
+        return;
+    }
+    run.normalized = load()
+        .ok()
+        .and_then(|calibrations| calibrations.get(network)?.estimate(run));
+}
+
+/// Add the ratio of a `reference` and a `quick` run to `calibration`,
+/// starting over when the reference configuration changed.
+fn add_sample(
+    calibration: &mut NetworkCalibration,
+    reference: &RunResult,
+    quick: &RunResult,
+) -> Result<()> {
+    for (name, run) in [("reference", reference), ("quick", quick)] {
+        if let Some(reason) = &run.invalid {
+            anyhow::bail!("the {name} run is invalid: {reason}");
+        }
+        if run.download.mbps <= 0.0 || run.upload.mbps <= 0.0 {
+            anyhow::bail!("the {name} run measured no throughput");
+        }
+    }
+    let profile = crate::report::config_profile(reference).unwrap_or_default();
+    if calibration.reference_profile != profile {
+        calibration.reference_profile = profile;
+        calibration.samples.clear();
+    }
+    calibration.samples.push(Sample {
+        timestamp_utc: reference.timestamp_utc.clone(),
+        download: reference.download.mbps / quick.download.mbps,
+        upload: reference.upload.mbps / quick.upload.mbps,
+    });
+    let excess = calibration.samples.len().saturating_sub(MAX_SAMPLES);
+    calibration.samples.drain(..excess);
+    Ok(())
+}
+
+/// Store the calibration of a `reference` and a `quick` run made back to
+/// back, and return the network's calibration.
+pub fn record(
+    config: &Config,
+    reference: &RunResult,
+    quick: &RunResult,
+) -> Result<(String, NetworkCalibration)> {
+    let network = network_key(config, reference);
+    if network_key(config, quick) != network {
+        anyhow::bail!("the network changed during calibration");
+    }
+    let mut calibrations = load().unwrap_or_default();
+    let calibration = calibrations.entry(network.to_string()).or_default();
+    add_sample(calibration, reference, quick)?;
+    let calibration = calibration.clone();
+    save(&calibrations)?;
+    Ok((network.to_string(), calibration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run as run;
+
+    #[test]
+    fn test_calibration() {
+        let mut calibration = NetworkCalibration::default();
+        add_sample(&mut calibration, &run(100.0, 20.0), &run(80.0, 20.0)).unwrap();
+        add_sample(&mut calibration, &run(100.0, 20.0), &run(50.0, 10.0)).unwrap();
+        add_sample(&mut calibration, &run(90.0, 20.0), &run(75.0, 16.0)).unwrap();
+        assert_eq!(calibration.factors(), Some((1.25, 1.25)));
+        assert!(add_sample(&mut calibration, &run(100.0, 20.0), &run(0.0, 10.0)).is_err());
+
+        let estimate = calibration.estimate(&run(40.0, 8.0)).unwrap();
+        assert_eq!((estimate.download_mbps, estimate.upload_mbps), (50.0, 10.0));
+        assert_eq!(estimate.calibration_runs, 3);
+    }
+}
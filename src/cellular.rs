@@ -0,0 +1,256 @@
+//! Carrier, radio access technology and signal of cellular connections, from
+//! Android system properties and the Termux:API app, or from ModemManager
+//! (`mmcli`) on Linux.
+
+use crate::model::CellularSummary;
+use serde_json::Value;
+use std::process::Command;
+
+/// Whether `iface` is a cellular modem interface (Android radio or Linux WWAN).
+pub fn is_cellular_interface(iface: &str) -> bool {
+    ["rmnet", "ccmni", "wwan", "seth_", "pdp", "v4-rmnet"]
+        .iter()
+        .any(|prefix| iface.starts_with(prefix))
+}
+
+/// Run a command and parse its standard output as JSON.
+#[cfg_attr(windows, allow(dead_code))]
+fn command_json(program: &str, args: &[&str]) -> Option<Value> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// A number that may be a JSON number or a string like "-95.00" ("--" when
+/// the modem doesn't report it).
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Normalize technology names ("lte", "5gnr", "nr") to "LTE", "NR", ...
+fn technology_name(name: &str) -> String {
+    match name.to_ascii_lowercase().as_str() {
+        "5gnr" | "nr" | "5g" => "NR".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+/// First known entry of a per-SIM property list like "LTE,Unknown".
+#[cfg_attr(not(target_os = "android"), allow(dead_code))]
+fn first_sim_value(value: &str) -> Option<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .find(|v| !v.is_empty() && !v.eq_ignore_ascii_case("unknown"))
+        .map(str::to_string)
+}
+
+/// Read an Android system property.
+#[cfg(target_os = "android")]
+fn getprop(name: &str) -> Option<String> {
+    let output = Command::new("getprop").arg(name).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Fill in technology and signal from the registered cell in
+/// `termux-telephony-cellinfo` output.
+#[cfg_attr(not(target_os = "android"), allow(dead_code))]
+fn apply_termux_cellinfo(summary: &mut CellularSummary, cells: &Value) {
+    let Some(cell) = cells.as_array().and_then(|cells| {
+        cells
+            .iter()
+            .find(|c| c.get("registered").and_then(Value::as_bool) == Some(true))
+    }) else {
+        return;
+    };
+    if let Some(kind) = cell.get("type").and_then(Value::as_str) {
+        summary.technology = Some(technology_name(kind));
+    }
+    // NR cells report their signal as ss_rsrp etc.
+    let field = |names: [&str; 2]| names.iter().find_map(|n| number(cell.get(*n)));
+    summary.rsrp_dbm = field(["rsrp", "ss_rsrp"]);
+    summary.rsrq_db = field(["rsrq", "ss_rsrq"]);
+    summary.sinr_db = field(["rssnr", "ss_sinr"]);
+    summary.rssi_dbm = field(["rssi", "dbm"]);
+}
+
+/// Cellular details on Android: system properties, refined by the Termux:API
+/// app when it's installed.
+#[cfg(target_os = "android")]
+fn query(_iface: &str) -> Option<CellularSummary> {
+    let mut summary = CellularSummary {
+        carrier: getprop("gsm.operator.alpha").and_then(|v| first_sim_value(&v)),
+        technology: getprop("gsm.network.type")
+            .and_then(|v| first_sim_value(&v))
+            .map(|t| technology_name(&t)),
+        source: "android".to_string(),
+        ..Default::default()
+    };
+    if let Some(info) = command_json("termux-telephony-deviceinfo", &[]) {
+        summary.source = "termux-api".to_string();
+        if let Some(name) = info.get("network_operator_name").and_then(Value::as_str) {
+            summary.carrier = Some(name.to_string()).filter(|n| !n.is_empty());
+        }
+        if let Some(kind) = info.get("data_network_type").and_then(Value::as_str) {
+            summary.technology = Some(technology_name(kind));
+        }
+    }
+    if let Some(cells) = command_json("termux-telephony-cellinfo", &[]) {
+        apply_termux_cellinfo(&mut summary, &cells);
+    }
+    Some(summary)
+}
+
+/// Cellular details from `mmcli -m <modem> -J` output, if the modem owns `iface`.
+#[cfg_attr(any(windows, target_os = "android"), allow(dead_code))]
+fn parse_mmcli_modem(modem: &Value, iface: &str) -> Option<CellularSummary> {
+    let modem = modem.get("modem")?;
+    let generic = modem.get("generic")?;
+    // Ports are listed like "wwan0 (net)"
+    let owns_iface = generic
+        .get("ports")
+        .and_then(Value::as_array)
+        .is_some_and(|ports| {
+            ports
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|p| p.split_whitespace().next() == Some(iface))
+        });
+    if !owns_iface {
+        return None;
+    }
+    let technology = generic
+        .get("access-technologies")
+        .and_then(Value::as_array)
+        .and_then(|t| t.last())
+        .and_then(Value::as_str)
+        .map(technology_name);
+    let carrier = modem
+        .get("3gpp")
+        .and_then(|g| g.get("operator-name"))
+        .and_then(Value::as_str)
+        .filter(|n| !n.is_empty() && *n != "--")
+        .map(str::to_string);
+    let signal_quality_percent = number(generic.get("signal-quality").and_then(|q| q.get("value")))
+        .map(|q| q.clamp(0.0, 100.0) as u8);
+    Some(CellularSummary {
+        carrier,
+        technology,
+        signal_quality_percent,
+        source: "modemmanager".to_string(),
+        ..Default::default()
+    })
+}
+
+/// Fill in signal metrics from `mmcli -m <modem> --signal-get -J` output,
+/// preferring 5G over LTE.
+#[cfg_attr(any(windows, target_os = "android"), allow(dead_code))]
+fn apply_mmcli_signal(summary: &mut CellularSummary, signal: &Value) {
+    let Some(signal) = signal.get("modem").and_then(|m| m.get("signal")) else {
+        return;
+    };
+    for kind in ["5g", "lte"] {
+        let Some(values) = signal.get(kind) else {
+            continue;
+        };
+        let rsrp = number(values.get("rsrp"));
+        if rsrp.is_none() {
+            continue;
+        }
+        summary.rsrp_dbm = rsrp;
+        summary.rsrq_db = number(values.get("rsrq"));
+        summary.sinr_db = number(values.get("snr")).or_else(|| number(values.get("sinr")));
+        summary.rssi_dbm = number(values.get("rssi"));
+        return;
+    }
+}
+
+/// Cellular details from ModemManager for the modem owning `iface`.
+#[cfg(all(not(windows), not(target_os = "android")))]
+fn query(iface: &str) -> Option<CellularSummary> {
+    let list = command_json("mmcli", &["-L", "-J"])?;
+    let modems = list.get("modem-list")?.as_array()?;
+    for path in modems.iter().filter_map(Value::as_str) {
+        let Some(modem) = command_json("mmcli", &["-m", path, "-J"]) else {
+            continue;
+        };
+        if let Some(mut summary) = parse_mmcli_modem(&modem, iface) {
+            // Only reported once signal polling is enabled (mmcli --signal-setup)
+            if let Some(signal) = command_json("mmcli", &["-m", path, "--signal-get", "-J"]) {
+                apply_mmcli_signal(&mut summary, &signal);
+            }
+            return Some(summary);
+        }
+    }
+    None
+}
+
+#[cfg(windows)]
+fn query(_iface: &str) -> Option<CellularSummary> {
+    None
+}
+
+/// Cellular details for `iface` when it's a cellular modem interface.
+pub fn cellular_info(iface: &str) -> Option<CellularSummary> {
+    if !is_cellular_interface(iface) {
+        return None;
+    }
+    query(iface)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cellular_properties() {
+        assert!(is_cellular_interface("rmnet_data0"));
+        assert!(!is_cellular_interface("wlan0"));
+        assert_eq!(first_sim_value("Unknown,NR").as_deref(), Some("NR"));
+        assert_eq!(first_sim_value("LTE,").as_deref(), Some("LTE"));
+        assert_eq!(first_sim_value(","), None);
+    }
+
+    #[test]
+    fn test_parse_cellular_sources() {
+        let modem = json!({"modem": {
+            "generic": {
+                "ports": ["cdc-wdm0 (qmi)", "wwan0 (net)"],
+                "access-technologies": ["lte", "5gnr"],
+                "signal-quality": {"value": "67", "recent": "yes"}
+            },
+            "3gpp": {"operator-name": "Example Mobile"}
+        }});
+        assert!(parse_mmcli_modem(&modem, "wwan1").is_none());
+        let mut summary = parse_mmcli_modem(&modem, "wwan0").unwrap();
+        assert_eq!(summary.technology.as_deref(), Some("NR"));
+        assert_eq!(summary.carrier.as_deref(), Some("Example Mobile"));
+        assert_eq!(summary.signal_quality_percent, Some(67));
+
+        let signal = json!({"modem": {"signal": {
+            "5g": {"rsrp": "--", "rsrq": "--", "snr": "--"},
+            "lte": {"rsrp": "-95.00", "rsrq": "-11.00", "snr": "8.00", "rssi": "-65.00"}
+        }}});
+        apply_mmcli_signal(&mut summary, &signal);
+        assert_eq!(summary.rsrp_dbm, Some(-95.0));
+        assert_eq!(summary.sinr_db, Some(8.0));
+
+        let cells = json!([
+            {"type": "lte", "registered": false, "rsrp": -120},
+            {"type": "nr", "registered": true, "ss_rsrp": -88, "ss_rsrq": -10, "ss_sinr": 15}
+        ]);
+        let mut summary = CellularSummary::default();
+        apply_termux_cellinfo(&mut summary, &cells);
+        assert_eq!(summary.technology.as_deref(), Some("NR"));
+        assert_eq!(summary.rsrp_dbm, Some(-88.0));
+        assert_eq!(summary.sinr_db, Some(15.0));
+    }
+}
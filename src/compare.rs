@@ -0,0 +1,198 @@
+//! Provider comparison of `--compare-providers`: shortened runs against each
+//! backend in turn, to tell whether poor results come from the path to one
+//! provider or from the connection itself.
+
+use crate::model::{ProviderKind, RunResult};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Longest download and upload phase of a comparison run.
+pub const PHASE_DURATION: Duration = Duration::from_secs(5);
+
+/// A provider is flagged when its throughput is below this share of the
+/// best provider's.
+const SLOW_RATIO: f64 = 0.7;
+
+/// One entry of `--compare-providers`: a provider and, optionally, its
+/// server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderTarget {
+    pub kind: ProviderKind,
+    pub url: Option<String>,
+}
+
+impl ProviderTarget {
+    /// The server to test against: the given URL, else the provider's
+    /// default.
+    pub fn url(&self) -> anyhow::Result<&str> {
+        self.url
+            .as_deref()
+            .or(self.kind.default_url())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} needs a server URL in --compare-providers, e.g. {}=https://librespeed.example/",
+                    self.kind,
+                    self.kind
+                )
+            })
+    }
+
+    /// Name shown in the comparison: the provider, and the server unless it
+    /// is the provider's default.
+    pub fn label(&self) -> String {
+        match self.url.as_deref() {
+            Some(url) if Some(url) != self.kind.default_url() => {
+                let host = reqwest::Url::parse(url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(str::to_string))
+                    .unwrap_or_else(|| url.to_string());
+                format!("{}@{host}", self.kind)
+            }
+            _ => self.kind.to_string(),
+        }
+    }
+}
+
+/// Parse `PROVIDER[=URL]`.
+pub fn parse_target(value: &str) -> Result<ProviderTarget, String> {
+    let (name, url) = match value.split_once('=') {
+        Some((name, url)) => (name, Some(url.to_string())),
+        None => (value, None),
+    };
+    let kind = ProviderKind::from_str(name.trim(), true).map_err(|_| {
+        let available: Vec<String> = ProviderKind::value_variants()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        format!(
+            "unknown provider {name:?} (available: {})",
+            available.join(", ")
+        )
+    })?;
+    if let Some(url) = &url {
+        reqwest::Url::parse(url).map_err(|e| format!("invalid URL {url:?}: {e}"))?;
+    }
+    Ok(ProviderTarget { kind, url })
+}
+
+/// Outcome of one provider's run.
+#[derive(Debug, Serialize)]
+pub struct ProviderRun {
+    pub provider: String,
+    pub base_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<RunResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Which providers, if any, measured well below the others, in words.
+pub fn verdict(runs: &[ProviderRun]) -> Option<String> {
+    let done: Vec<(&str, &RunResult)> = runs
+        .iter()
+        .filter_map(|r| Some((r.provider.as_str(), r.result.as_ref()?)))
+        .collect();
+    if done.len() < 2 {
+        return None;
+    }
+    let best = |f: fn(&RunResult) -> f64| done.iter().map(|(_, r)| f(r)).fold(0.0, f64::max);
+    let (best_down, best_up) = (best(|r| r.download.mbps), best(|r| r.upload.mbps));
+    let (mut slow, mut fine) = (Vec::new(), Vec::new());
+    for (name, r) in done {
+        if r.download.mbps < best_down * SLOW_RATIO || r.upload.mbps < best_up * SLOW_RATIO {
+            slow.push(name);
+        } else {
+            fine.push(name);
+        }
+    }
+    Some(if slow.is_empty() {
+        format!(
+            "All providers measured within {:.0}% of the fastest: results aren't specific to \
+             one provider's path.",
+            (1.0 - SLOW_RATIO) * 100.0
+        )
+    } else {
+        format!(
+            "{} measured well below {}: the bottleneck is likely on the path to {}, not your \
+             connection.",
+            slow.join(", "),
+            fine.join(", "),
+            slow.join(", ")
+        )
+    })
+}
+
+/// Print the comparison after the runs' own text output.
+pub fn print_comparison(runs: &[ProviderRun]) {
+    let latency = |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{ms:.1} ms"));
+    println!();
+    println!("== Provider comparison ==");
+    println!(
+        "{:<32} {:>12} {:>12} {:>10} {:>10}",
+        "Provider", "Download", "Upload", "Idle", "Loaded"
+    );
+    for run in runs {
+        match (&run.result, &run.error) {
+            (Some(r), _) => println!(
+                "{:<32} {:>7.1} Mbps {:>7.1} Mbps {:>10} {:>10}",
+                run.provider,
+                r.download.mbps,
+                r.upload.mbps,
+                latency(r.idle_latency.median_ms),
+                latency(r.loaded_latency_download.median_ms)
+            ),
+            (None, error) => println!(
+                "{:<32} failed: {}",
+                run.provider,
+                error.as_deref().unwrap_or("-")
+            ),
+        }
+    }
+    if let Some(verdict) = verdict(runs) {
+        println!();
+        println!("{verdict}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(provider: &str, download: f64, upload: f64) -> ProviderRun {
+        ProviderRun {
+            provider: provider.into(),
+            base_url: String::new(),
+            result: Some(test_run(download, upload)),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_providers() {
+        assert_eq!(
+            parse_target("librespeed=https://speed.example.org/").unwrap(),
+            ProviderTarget {
+                kind: ProviderKind::Librespeed,
+                url: Some("https://speed.example.org/".into())
+            }
+        );
+        assert!(parse_target("mlab")
+            .unwrap_err()
+            .contains("available: cloudflare"));
+
+        let verdict_of = |runs: &[ProviderRun]| verdict(runs).unwrap();
+        assert!(verdict_of(&[
+            run("cloudflare", 90.0, 20.0),
+            run("librespeed", 100.0, 19.0)
+        ])
+        .starts_with("All providers measured within 30% of the fastest"));
+        assert!(verdict_of(&[
+            run("cloudflare", 40.0, 20.0),
+            run("librespeed", 100.0, 19.0)
+        ])
+        .starts_with("cloudflare measured well below librespeed"));
+        assert_eq!(verdict(&[run("cloudflare", 40.0, 20.0)]), None);
+    }
+}
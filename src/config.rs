@@ -0,0 +1,876 @@
+//! Persistent user configuration (`config.toml`).
+//!
+//! Values here replace the built-in defaults of the matching command-line
+//! flags; a flag given explicitly on the command line always wins.
+
+use crate::cli::Cli;
+use crate::model::RunResult;
+use crate::network::NetworkInfo;
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Save every completed run to the history directory
+    pub auto_save: Option<bool>,
+    /// Strip identifying network details from saved and exported results
+    pub redact: Option<bool>,
+    /// Set once the first-run privacy notice has been answered
+    pub onboarded: bool,
+    /// Display language (e.g. "en-US"); defaults to the system locale
+    pub language: Option<String>,
+    #[serde(with = "humantime_serde")]
+    pub download_duration: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub upload_duration: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub idle_latency_duration: Option<Duration>,
+    /// CSV export columns, as for `--csv-columns`
+    pub csv_columns: Option<String>,
+    /// Age after which saved runs are deleted, as for `--history-retention`
+    #[serde(with = "humantime_serde")]
+    pub history_retention: Option<Duration>,
+    /// Number of saved runs kept, as for `--history-max-runs`
+    pub history_max_runs: Option<u64>,
+    /// InfluxDB v2 server to push results to, as for `--influx-url`; needs
+    /// `influx_org` and `influx_bucket`
+    pub influx_url: Option<String>,
+    pub influx_org: Option<String>,
+    pub influx_bucket: Option<String>,
+    /// Line protocol file results are appended to, as for `--export-influx`
+    pub export_influx: Option<PathBuf>,
+    /// Prometheus textfile the latest results are written to, as for
+    /// `--export-prometheus`
+    pub export_prometheus: Option<PathBuf>,
+    pub ui: UiConfig,
+    pub thresholds: Thresholds,
+    pub email: EmailConfig,
+    pub grpc: GrpcConfig,
+    /// Display names for networks, keyed by network (SSID) name, interface
+    /// MAC address or interface name
+    #[serde(rename = "network-aliases")]
+    pub network_aliases: BTreeMap<String, String>,
+    /// Settings for particular networks, keyed like `[network-aliases]` or
+    /// by a network's alias
+    pub networks: BTreeMap<String, NetworkSettings>,
+}
+
+/// Settings used on one network (`[networks."Phone tether"]`) instead of
+/// the rest of the file and the built-in defaults; flags given on the
+/// command line still win.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSettings {
+    #[serde(with = "humantime_serde")]
+    pub download_duration: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub upload_duration: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub idle_latency_duration: Option<Duration>,
+    pub concurrency: Option<usize>,
+    /// Monthly data budget, as for `--monthly-data-cap` (e.g. "5GB")
+    pub monthly_data_cap: Option<String>,
+    /// Speeds of the network's plan, as for `--plan-download-mbps`
+    pub plan_download_mbps: Option<f64>,
+    pub plan_upload_mbps: Option<f64>,
+    /// Used instead of `[thresholds]` on this network
+    pub thresholds: Option<Thresholds>,
+}
+
+/// Problems of `thresholds`, with keys under `section`.
+fn threshold_problems(section: &str, thresholds: &Thresholds, problems: &mut Vec<String>) {
+    for (key, value) in [
+        ("min_download_mbps", thresholds.min_download_mbps),
+        ("min_upload_mbps", thresholds.min_upload_mbps),
+        ("max_loaded_latency_ms", thresholds.max_loaded_latency_ms),
+    ] {
+        if value.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+            problems.push(format!("{section}.{key} must be a positive number"));
+        }
+    }
+}
+
+impl Config {
+    /// Values that parse but can't be used, one message each.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (key, duration) in [
+            ("download_duration", self.download_duration),
+            ("upload_duration", self.upload_duration),
+        ] {
+            if duration.is_some_and(|d| d.is_zero()) {
+                problems.push(format!("{key} must be longer than 0s"));
+            }
+        }
+        if self.history_retention.is_some_and(|d| d.is_zero()) {
+            problems.push("history_retention must be longer than 0s".into());
+        }
+        if self.history_max_runs == Some(0) {
+            problems.push("history_max_runs must be at least 1".into());
+        }
+        let influx_target = self.influx_org.is_some() && self.influx_bucket.is_some();
+        if self.influx_url.is_some() && !influx_target {
+            problems.push("influx_url needs influx_org and influx_bucket".into());
+        }
+        if let Some(spec) = &self.csv_columns {
+            if let Err(e) = crate::storage::csv_columns(spec) {
+                problems.push(format!("csv_columns: {e:#}"));
+            }
+        }
+        if let Some(language) = &self.language {
+            if !crate::i18n::is_available(language) {
+                problems.push(format!(
+                    "language: no translation for \"{language}\" (available: {})",
+                    crate::i18n::languages().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+        threshold_problems("thresholds", &self.thresholds, &mut problems);
+        for (network, settings) in &self.networks {
+            let section = format!("networks.\"{network}\"");
+            for (key, duration) in [
+                ("download_duration", settings.download_duration),
+                ("upload_duration", settings.upload_duration),
+            ] {
+                if duration.is_some_and(|d| d.is_zero()) {
+                    problems.push(format!("{section}.{key} must be longer than 0s"));
+                }
+            }
+            if settings.concurrency == Some(0) {
+                problems.push(format!("{section}.concurrency must be at least 1"));
+            }
+            if let Some(cap) = &settings.monthly_data_cap {
+                if let Err(e) = crate::units::parse_byte_size(cap) {
+                    problems.push(format!("{section}.monthly_data_cap: {e}"));
+                }
+            }
+            for (key, value) in [
+                ("plan_download_mbps", settings.plan_download_mbps),
+                ("plan_upload_mbps", settings.plan_upload_mbps),
+            ] {
+                if value.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+                    problems.push(format!("{section}.{key} must be a positive number"));
+                }
+            }
+            if let Some(thresholds) = &settings.thresholds {
+                threshold_problems(&format!("{section}.thresholds"), thresholds, &mut problems);
+            }
+        }
+        let email = &self.email;
+        if email.server.as_deref().is_some_and(|s| s.trim().is_empty()) {
+            problems.push("email.server must not be empty".into());
+        }
+        if email.username.is_some() != email.password.is_some() {
+            problems.push("email.username and email.password must be set together".into());
+        }
+        if self
+            .grpc
+            .token
+            .as_deref()
+            .is_some_and(|t| t.trim().is_empty())
+        {
+            problems.push("grpc.token must not be empty".into());
+        }
+        // Aliases are looked up ignoring case, so only one of these would apply
+        let mut seen: BTreeMap<String, &str> = BTreeMap::new();
+        for key in self.network_aliases.keys() {
+            if let Some(other) = seen.insert(key.to_lowercase(), key) {
+                problems.push(format!(
+                    "network-aliases: \"{other}\" and \"{key}\" differ only in case"
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The `[networks]` entry for a network, with its key: the entry for its
+    /// alias, else one keyed like `[network-aliases]`, ignoring case.
+    pub fn network_settings(
+        &self,
+        network_name: Option<&str>,
+        interface_mac: Option<&str>,
+        interface_name: Option<&str>,
+    ) -> Option<(&str, &NetworkSettings)> {
+        let label = self.network_label_for(network_name, interface_mac, interface_name);
+        [label, network_name, interface_mac, interface_name]
+            .into_iter()
+            .flatten()
+            .find_map(|key| {
+                self.networks
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map(|(k, settings)| (k.as_str(), settings))
+            })
+    }
+
+    /// Thresholds on a network: its `[networks]` entry's, else
+    /// `[thresholds]`.
+    pub fn thresholds_on(
+        &self,
+        network_name: Option<&str>,
+        interface_mac: Option<&str>,
+        interface_name: Option<&str>,
+    ) -> &Thresholds {
+        self.network_settings(network_name, interface_mac, interface_name)
+            .and_then(|(_, settings)| settings.thresholds.as_ref())
+            .unwrap_or(&self.thresholds)
+    }
+
+    /// Thresholds for the network `result` was measured on.
+    pub fn thresholds_for(&self, result: &RunResult) -> &Thresholds {
+        self.thresholds_on(
+            result.network_name.as_deref(),
+            result.interface_mac.as_deref(),
+            result.interface_name.as_deref(),
+        )
+    }
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+impl Config {
+    /// Name to show for a network, applying `[network-aliases]` and falling
+    /// back to the network (SSID) name.
+    pub fn network_label_for<'a>(
+        &'a self,
+        network_name: Option<&'a str>,
+        interface_mac: Option<&str>,
+        interface_name: Option<&str>,
+    ) -> Option<&'a str> {
+        [network_name, interface_mac, interface_name]
+            .into_iter()
+            .flatten()
+            .find_map(|key| {
+                self.network_aliases
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map(|(_, alias)| alias.as_str())
+            })
+            .or(network_name)
+    }
+
+    /// Name to show for the network a result was measured on.
+    pub fn network_label<'a>(&'a self, result: &'a RunResult) -> Option<&'a str> {
+        self.network_label_for(
+            result.network_name.as_deref(),
+            result.interface_mac.as_deref(),
+            result.interface_name.as_deref(),
+        )
+    }
+}
+
+/// TUI display preferences.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Moving-average window (in samples) for live throughput charts; 1 = off
+    pub chart_smoothing: usize,
+    pub units: ThroughputUnit,
+    pub theme: Theme,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            chart_smoothing: 1,
+            units: ThroughputUnit::Mbps,
+            theme: Theme::Default,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThroughputUnit {
+    /// Megabits per second
+    #[serde(rename = "Mbps")]
+    Mbps,
+    /// Megabytes per second
+    #[serde(rename = "MB/s")]
+    MBps,
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+impl ThroughputUnit {
+    /// Convert a value in Mbps to this unit.
+    pub fn convert_mbps(self, mbps: f64) -> f64 {
+        match self {
+            ThroughputUnit::Mbps => mbps,
+            ThroughputUnit::MBps => mbps / 8.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThroughputUnit::Mbps => "Mbps",
+            ThroughputUnit::MBps => "MB/s",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    Default,
+    HighContrast,
+    Monochrome,
+}
+
+/// Limits that mark results as poor in the TUI and raise alerts in monitor
+/// mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub min_download_mbps: Option<f64>,
+    pub min_upload_mbps: Option<f64>,
+    pub max_loaded_latency_ms: Option<f64>,
+}
+
+impl Thresholds {
+    /// The thresholds `result` misses, described for the user.
+    pub fn breaches(&self, result: &RunResult) -> Vec<String> {
+        let mut breaches = Vec::new();
+        if let Some(min) = self
+            .min_download_mbps
+            .filter(|&min| result.download.mbps < min)
+        {
+            breaches.push(format!(
+                "download {:.1} Mbps < {min} Mbps",
+                result.download.mbps
+            ));
+        }
+        if let Some(min) = self.min_upload_mbps.filter(|&min| result.upload.mbps < min) {
+            breaches.push(format!(
+                "upload {:.1} Mbps < {min} Mbps",
+                result.upload.mbps
+            ));
+        }
+        if let Some(max) = self.max_loaded_latency_ms {
+            let loaded = [
+                result.loaded_latency_download.median_ms,
+                result.loaded_latency_upload.median_ms,
+            ];
+            if let Some(ms) = loaded.into_iter().flatten().reduce(f64::max) {
+                if ms > max {
+                    breaches.push(format!("loaded latency {ms:.1} ms > {max} ms"));
+                }
+            }
+        }
+        breaches
+    }
+}
+
+/// SMTP server that `--email-to` sends through.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmailConfig {
+    pub server: Option<String>,
+    /// Defaults to 465 with `tls`, 587 with `starttls` and 25 with `none`
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender address; defaults to the username
+    pub from: Option<String>,
+}
+
+/// How the connection to the SMTP server is encrypted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// TLS from the start (SMTPS)
+    Tls,
+    /// Plain connection upgraded with STARTTLS
+    #[default]
+    Starttls,
+    /// No encryption, e.g. for a relay on the local network
+    None,
+}
+
+impl EmailConfig {
+    #[cfg_attr(not(feature = "email"), allow(dead_code))]
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.security {
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::Starttls => 587,
+            SmtpSecurity::None => 25,
+        })
+    }
+}
+
+/// Settings of the `grpc-serve` and `agent` subcommands.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrpcConfig {
+    /// Shared token, as for `--token`
+    pub token: Option<String>,
+}
+
+/// Get the path of the configuration file.
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("cloudflare-speed-cli")
+        .join("config.toml")
+}
+
+/// Load the configuration file, falling back to defaults when it doesn't exist.
+pub fn load() -> Result<Config> {
+    let path = config_path();
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let data =
+        std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    let config: Config =
+        toml::from_str(&data).with_context(|| format!("parse {}", path.display()))?;
+    let problems = config.problems();
+    if !problems.is_empty() {
+        anyhow::bail!("invalid {}: {}", path.display(), problems.join("; "));
+    }
+    Ok(config)
+}
+
+/// Write the configuration file, returning its path. A file that doesn't
+/// load (e.g. broken by hand since startup) is left alone rather than
+/// replaced by settings that never came from it.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn save(config: &Config) -> Result<PathBuf> {
+    let path = config_path();
+    if path.exists() {
+        load().context("not overwriting the config file, fix it first")?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create config dir")?;
+    }
+    let data = toml::to_string_pretty(config).context("serialize config")?;
+    std::fs::write(&path, data).context("write config")?;
+    Ok(path)
+}
+
+/// Replace flag defaults in `args` with configured values. Flags that were
+/// given on the command line keep their value.
+pub fn apply_to_args(config: &Config, args: &mut Cli, matches: &ArgMatches) {
+    let from_default = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+    if let Some(v) = config.auto_save.filter(|_| from_default("auto_save")) {
+        args.auto_save = v;
+    }
+    if let Some(v) = config.redact.filter(|_| from_default("redact")) {
+        args.redact = v;
+    }
+    if let Some(v) = config
+        .download_duration
+        .filter(|_| from_default("download_duration"))
+    {
+        args.download_duration = v.into();
+    }
+    if let Some(v) = config
+        .upload_duration
+        .filter(|_| from_default("upload_duration"))
+    {
+        args.upload_duration = v.into();
+    }
+    if let Some(v) = config
+        .idle_latency_duration
+        .filter(|_| from_default("idle_latency_duration"))
+    {
+        args.idle_latency_duration = v.into();
+    }
+    if let Some(v) = config
+        .csv_columns
+        .as_ref()
+        .filter(|_| from_default("csv_columns"))
+    {
+        args.csv_columns = v.clone();
+    }
+    if args.history_retention.is_none() {
+        args.history_retention = config.history_retention.map(Into::into);
+    }
+    if args.history_max_runs.is_none() {
+        args.history_max_runs = config.history_max_runs;
+    }
+    // Only together with the URL, which the command line requires them with
+    if args.influx_url.is_none() && config.influx_url.is_some() {
+        args.influx_url = config.influx_url.clone();
+        args.influx_org = config.influx_org.clone();
+        args.influx_bucket = config.influx_bucket.clone();
+    }
+    if args.export_influx.is_none() {
+        args.export_influx = config.export_influx.clone();
+    }
+    if args.export_prometheus.is_none() {
+        args.export_prometheus = config.export_prometheus.clone();
+    }
+}
+
+/// Apply the `[networks]` entry for `network`, if any, to `args` like
+/// `apply_to_args`, over the values that came from the rest of the file.
+/// Returns the entry's key.
+pub fn apply_network_settings<'a>(
+    config: &'a Config,
+    args: &mut Cli,
+    matches: &ArgMatches,
+    network: &NetworkInfo,
+) -> Option<&'a str> {
+    let (key, settings) = config.network_settings(
+        network.network_name.as_deref(),
+        network.interface_mac.as_deref(),
+        network.interface_name.as_deref(),
+    )?;
+    let from_default = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+    if let Some(v) = settings
+        .download_duration
+        .filter(|_| from_default("download_duration"))
+    {
+        args.download_duration = v.into();
+    }
+    if let Some(v) = settings
+        .upload_duration
+        .filter(|_| from_default("upload_duration"))
+    {
+        args.upload_duration = v.into();
+    }
+    if let Some(v) = settings
+        .idle_latency_duration
+        .filter(|_| from_default("idle_latency_duration"))
+    {
+        args.idle_latency_duration = v.into();
+    }
+    if let Some(v) = settings
+        .concurrency
+        .filter(|_| from_default("concurrency") && from_default("single_connection"))
+    {
+        args.concurrency = v;
+    }
+    if let Some(v) = settings
+        .monthly_data_cap
+        .as_deref()
+        .filter(|_| from_default("monthly_data_cap"))
+        .and_then(|cap| crate::units::parse_byte_size(cap).ok())
+    {
+        args.monthly_data_cap = Some(v);
+    }
+    if let Some(v) = settings
+        .plan_download_mbps
+        .filter(|_| from_default("plan_download_mbps"))
+    {
+        args.plan_download_mbps = Some(v);
+    }
+    if let Some(v) = settings
+        .plan_upload_mbps
+        .filter(|_| from_default("plan_upload_mbps"))
+    {
+        args.plan_upload_mbps = Some(v);
+    }
+    Some(key)
+}
+
+/// Validate the config file and print the effective configuration: the
+/// file merged with environment variables and command-line flags (`config
+/// check`). Fails on an invalid file. `matches` tells flags given on the
+/// command line from defaults.
+pub fn check(args: &Cli, matches: &ArgMatches) -> Result<()> {
+    let path = config_path();
+    let config = load()?;
+    if path.exists() {
+        println!("# {} is valid", path.display());
+    } else {
+        println!("# No config file at {}", path.display());
+    }
+
+    let source = |id: &str, in_file: bool| {
+        if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            "command line"
+        } else if in_file {
+            "config file"
+        } else {
+            "default"
+        }
+    };
+    let setting = |key: &str, value: String, source: &str| {
+        println!("{:<40} # {source}", format!("{key} = {value}"));
+    };
+    println!();
+    setting(
+        "auto_save",
+        args.auto_save.to_string(),
+        source("auto_save", config.auto_save.is_some()),
+    );
+    setting(
+        "redact",
+        args.redact.to_string(),
+        source("redact", config.redact.is_some()),
+    );
+    setting(
+        "language",
+        format!("\"{}\"", crate::i18n::resolve(config.language.as_deref())),
+        if config.language.is_some() {
+            "config file"
+        } else {
+            "system locale"
+        },
+    );
+    for (key, value, in_file) in [
+        (
+            "download_duration",
+            args.download_duration,
+            config.download_duration.is_some(),
+        ),
+        (
+            "upload_duration",
+            args.upload_duration,
+            config.upload_duration.is_some(),
+        ),
+        (
+            "idle_latency_duration",
+            args.idle_latency_duration,
+            config.idle_latency_duration.is_some(),
+        ),
+    ] {
+        setting(key, format!("\"{value}\""), source(key, in_file));
+    }
+    setting(
+        "csv_columns",
+        format!("\"{}\"", args.csv_columns),
+        source("csv_columns", config.csv_columns.is_some()),
+    );
+    match args.history_retention {
+        Some(retention) => setting(
+            "history_retention",
+            format!("\"{retention}\""),
+            source("history_retention", config.history_retention.is_some()),
+        ),
+        None => setting("# history_retention", "unset".into(), "keep every run"),
+    }
+    match args.history_max_runs {
+        Some(max_runs) => setting(
+            "history_max_runs",
+            max_runs.to_string(),
+            source("history_max_runs", config.history_max_runs.is_some()),
+        ),
+        None => setting("# history_max_runs", "unset".into(), "keep every run"),
+    }
+    let display = |p: &Option<PathBuf>| p.as_ref().map(|p| p.display().to_string());
+    for (key, value, in_file) in [
+        (
+            "influx_url",
+            args.influx_url.clone(),
+            config.influx_url.is_some(),
+        ),
+        (
+            "influx_org",
+            args.influx_org.clone(),
+            config.influx_org.is_some(),
+        ),
+        (
+            "influx_bucket",
+            args.influx_bucket.clone(),
+            config.influx_bucket.is_some(),
+        ),
+        (
+            "export_influx",
+            display(&args.export_influx),
+            config.export_influx.is_some(),
+        ),
+        (
+            "export_prometheus",
+            display(&args.export_prometheus),
+            config.export_prometheus.is_some(),
+        ),
+    ] {
+        match value {
+            Some(value) => setting(key, format!("\"{value}\""), source(key, in_file)),
+            None => setting(&format!("# {key}"), "unset".into(), "not exported"),
+        }
+    }
+
+    // Only set in the file (or the TUI's Settings tab)
+    #[derive(Serialize)]
+    struct Sections<'a> {
+        ui: &'a UiConfig,
+        thresholds: &'a Thresholds,
+        email: EmailConfig,
+        grpc: GrpcConfig,
+        #[serde(rename = "network-aliases")]
+        network_aliases: &'a BTreeMap<String, String>,
+        networks: &'a BTreeMap<String, NetworkSettings>,
+    }
+    let sections = Sections {
+        ui: &config.ui,
+        thresholds: &config.thresholds,
+        email: EmailConfig {
+            password: config.email.password.as_ref().map(|_| "<redacted>".into()),
+            ..config.email.clone()
+        },
+        grpc: GrpcConfig {
+            token: config.grpc.token.as_ref().map(|_| "<redacted>".into()),
+        },
+        network_aliases: &config.network_aliases,
+        networks: &config.networks,
+    };
+    println!();
+    print!("{}", toml::to_string_pretty(&sections)?);
+
+    println!();
+    for (flag, set, variable) in [
+        (
+            "--access-client-id",
+            args.access_client_id.is_some(),
+            "CF_ACCESS_CLIENT_ID",
+        ),
+        (
+            "--access-client-secret",
+            args.access_client_secret.is_some(),
+            "CF_ACCESS_CLIENT_SECRET",
+        ),
+        (
+            "--influx-token",
+            args.influx_token.is_some(),
+            "INFLUX_TOKEN",
+        ),
+    ] {
+        let value = if set {
+            "set by flag"
+        } else if std::env::var_os(variable).is_some() {
+            "set by environment"
+        } else {
+            "not set"
+        };
+        println!("# {flag} ({variable}): {value}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+    use clap::{CommandFactory, FromArgMatches};
+
+    #[test]
+    fn test_config_does_not_override_explicit_flags() {
+        let config: Config = toml::from_str(
+            "download_duration = \"20s\"\nupload_duration = \"30s\"\n\n[ui]\nunits = \"MB/s\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.ui.units, ThroughputUnit::MBps);
+        assert_eq!(config.ui.chart_smoothing, 1);
+
+        let matches = Cli::command()
+            .try_get_matches_from(["cloudflare-speed-cli", "--upload-duration", "5s"])
+            .unwrap();
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        apply_to_args(&config, &mut args, &matches);
+        assert_eq!(
+            Duration::from(args.download_duration),
+            Duration::from_secs(20)
+        );
+        assert_eq!(Duration::from(args.upload_duration), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_network_aliases() {
+        let config: Config = toml::from_str(
+            "[network-aliases]\n\"aa:bb:cc:dd:ee:ff\" = \"Home fiber\"\nMyWifi-5G = \"Home Wi-Fi\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.network_label_for(None, Some("AA:BB:CC:DD:EE:FF"), Some("eth0")),
+            Some("Home fiber")
+        );
+        assert_eq!(
+            config.network_label_for(Some("MyWifi-5G"), None, Some("wlan0")),
+            Some("Home Wi-Fi")
+        );
+        assert_eq!(
+            config.network_label_for(Some("Cafe"), None, None),
+            Some("Cafe")
+        );
+    }
+
+    #[test]
+    fn test_network_settings() {
+        let config: Config = toml::from_str(
+            "[network-aliases]\nAndroidAP = \"Phone tether\"\n\n\
+             [networks.\"phone tether\"]\nconcurrency = 2\nmonthly_data_cap = \"5GB\"\n\
+             download_duration = \"5s\"\n\n\
+             [networks.\"phone tether\".thresholds]\nmin_download_mbps = 10.0\n",
+        )
+        .unwrap();
+        assert!(config.problems().is_empty(), "{:?}", config.problems());
+        let network = NetworkInfo {
+            network_name: Some("AndroidAP".into()),
+            ..Default::default()
+        };
+        let matches = Cli::command()
+            .try_get_matches_from(["cloudflare-speed-cli", "--download-duration", "3s"])
+            .unwrap();
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        let key = apply_network_settings(&config, &mut args, &matches, &network);
+        assert_eq!(key, Some("phone tether"));
+        assert_eq!(args.concurrency, 2);
+        assert_eq!(args.monthly_data_cap, Some(5_000_000_000));
+        assert_eq!(
+            Duration::from(args.download_duration),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            config
+                .thresholds_on(Some("AndroidAP"), None, None)
+                .min_download_mbps,
+            Some(10.0)
+        );
+        assert_eq!(
+            config
+                .thresholds_on(Some("Cafe"), None, None)
+                .min_download_mbps,
+            None
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(toml::from_str::<Config>("[ui]\nunit = \"MB/s\"\n").is_err());
+
+        let config: Config = toml::from_str(
+            "upload_duration = \"0s\"\ncsv_columns = \"v2,nope\"\ninflux_url = \"http://localhost:8086\"\n\n\
+             [thresholds]\nmin_download_mbps = 0.0\n",
+        )
+        .unwrap();
+        let problems = config.problems();
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[0].starts_with("upload_duration"));
+        assert!(problems[1].starts_with("influx_url"));
+        assert!(problems[2].starts_with("csv_columns"));
+        assert!(problems[3].starts_with("thresholds.min_download_mbps"));
+
+        assert!(Config::default().problems().is_empty());
+    }
+
+    #[test]
+    fn test_threshold_breaches() {
+        let mut result = test_run(90.0, 20.0);
+        result.loaded_latency_download.median_ms = Some(80.0);
+        result.loaded_latency_upload.median_ms = Some(250.0);
+        assert!(Thresholds::default().breaches(&result).is_empty());
+
+        let thresholds = Thresholds {
+            min_download_mbps: Some(100.0),
+            min_upload_mbps: Some(10.0),
+            max_loaded_latency_ms: Some(200.0),
+        };
+        assert_eq!(
+            thresholds.breaches(&result),
+            [
+                "download 90.0 Mbps < 100 Mbps",
+                "loaded latency 250.0 ms > 200 ms"
+            ]
+        );
+    }
+}
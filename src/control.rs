@@ -0,0 +1,273 @@
+//! Control socket of a running TUI or monitor mode instance (Unix only).
+//!
+//! While either runs, it listens on `control.sock` in the data directory so
+//! scripts and status bars can talk to it instead of starting a competing
+//! test. A client sends one command per connection and reads the reply:
+//! `run` starts a test unless one is active, `status` reports the run state
+//! and the last result, and `last-json` returns the last result as JSON. The
+//! `control` subcommand is such a client.
+
+use crate::model::RunResult;
+use crate::orchestrator::controller::RunState;
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+/// Commands understood by the control socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ControlCommand {
+    /// Start a test, unless one is running
+    Run,
+    /// Print the run state and the last result
+    Status,
+    /// Print the last result as JSON
+    LastJson,
+}
+
+impl ControlCommand {
+    fn as_str(self) -> &'static str {
+        match self {
+            ControlCommand::Run => "run",
+            ControlCommand::Status => "status",
+            ControlCommand::LastJson => "last-json",
+        }
+    }
+}
+
+/// What the instance reports, kept current by the front end.
+#[derive(Default)]
+struct Status {
+    state: RunState,
+    last_result: Option<RunResult>,
+}
+
+/// Reply to the command `line`, and whether to start a run.
+fn reply(line: &str, status: &Status) -> (String, bool) {
+    match line.trim() {
+        "run" if status.state.is_active() => ("error: a test is already running".into(), false),
+        "run" => ("started".into(), true),
+        "status" => {
+            let mut reply = status.state.to_string();
+            if let Some(r) = &status.last_result {
+                reply.push_str(&format!(
+                    "\nlast: {}  DL {:.2} Mbps  UL {:.2} Mbps  idle {:.1} ms",
+                    r.timestamp_utc,
+                    r.download.mbps,
+                    r.upload.mbps,
+                    r.idle_latency.median_ms.unwrap_or(f64::NAN)
+                ));
+            }
+            (reply, false)
+        }
+        "last-json" => match &status.last_result {
+            Some(r) => (serde_json::to_string(r).unwrap_or_default(), false),
+            None => ("error: no completed test yet".into(), false),
+        },
+        other => (
+            format!("error: unknown command '{other}' (run, status, last-json)"),
+            false,
+        ),
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{reply, Status};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+
+    /// How long a client gets to send its command.
+    const READ_TIMEOUT: Duration = Duration::from_secs(5);
+    /// Longest command line read.
+    const MAX_COMMAND: u64 = 256;
+
+    pub struct Listener {
+        path: PathBuf,
+        task: JoinHandle<()>,
+    }
+
+    impl Listener {
+        /// Listen at `path`; None if another instance already does, or the
+        /// socket can't be created.
+        pub fn bind(
+            path: PathBuf,
+            status: Arc<Mutex<Status>>,
+            runs: mpsc::Sender<()>,
+        ) -> Option<Self> {
+            if path.exists() {
+                if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                    return None;
+                }
+                // Left behind by an instance that didn't exit cleanly
+                std::fs::remove_file(&path).ok()?;
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).ok()?;
+            }
+            let listener = UnixListener::bind(&path).ok()?;
+            // Only this user may start tests
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).ok();
+            }
+            let task = tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let (status, runs) = (status.clone(), runs.clone());
+                    tokio::spawn(async move {
+                        handle(stream, &status, &runs).await.ok();
+                    });
+                }
+            });
+            Some(Self { path, task })
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            self.task.abort();
+            std::fs::remove_file(&self.path).ok();
+        }
+    }
+
+    async fn handle(
+        stream: UnixStream,
+        status: &Mutex<Status>,
+        runs: &mpsc::Sender<()>,
+    ) -> std::io::Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut line = String::new();
+        let mut reader = BufReader::new(read.take(MAX_COMMAND));
+        match tokio::time::timeout(READ_TIMEOUT, reader.read_line(&mut line)).await {
+            Ok(result) => result?,
+            Err(_) => return Ok(()),
+        };
+        let (mut reply, start) = reply(&line, &status.lock().unwrap());
+        // A run already requested but not yet started is enough
+        if start {
+            runs.try_send(()).ok();
+        }
+        reply.push('\n');
+        write.write_all(reply.as_bytes()).await
+    }
+
+    /// Send `command` to the instance listening at `path`.
+    pub async fn send(path: PathBuf, command: &str) -> anyhow::Result<String> {
+        use anyhow::Context;
+        let mut stream = UnixStream::connect(&path).await.with_context(|| {
+            format!(
+                "no TUI or monitor mode instance is listening on {}",
+                path.display()
+            )
+        })?;
+        stream.write_all(format!("{command}\n").as_bytes()).await?;
+        stream.shutdown().await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        Ok(reply)
+    }
+}
+
+/// The control socket of this instance.
+pub struct ControlSocket {
+    status: Arc<Mutex<Status>>,
+    runs: tokio::sync::mpsc::Receiver<()>,
+    #[cfg(unix)]
+    _listener: unix::Listener,
+}
+
+impl ControlSocket {
+    /// Start listening; None when another instance already listens, the
+    /// socket can't be created or the platform has no Unix sockets.
+    #[cfg(unix)]
+    pub fn bind() -> Option<Self> {
+        let status = Arc::new(Mutex::new(Status::default()));
+        let (runs_tx, runs) = tokio::sync::mpsc::channel(1);
+        let listener = unix::Listener::bind(
+            crate::storage::control_socket_path(),
+            status.clone(),
+            runs_tx,
+        )?;
+        Some(Self {
+            status,
+            runs,
+            _listener: listener,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind() -> Option<Self> {
+        None
+    }
+
+    pub fn set_state(&self, state: RunState) {
+        self.status.lock().unwrap().state = state;
+    }
+
+    pub fn set_last_result(&self, result: &RunResult) {
+        self.status.lock().unwrap().last_result = Some(result.clone());
+    }
+
+    /// Wait for a client to ask for a run. Cancel-safe.
+    pub async fn run_requested(&mut self) {
+        if self.runs.recv().await.is_none() {
+            std::future::pending().await
+        }
+    }
+}
+
+/// `ControlSocket::run_requested` when listening, otherwise never resolves,
+/// so it can be polled in `tokio::select!` either way.
+pub async fn run_requested(socket: &mut Option<ControlSocket>) {
+    match socket {
+        Some(socket) => socket.run_requested().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Send `command` to the running instance and print its reply (`control`
+/// subcommand).
+pub async fn send_command(command: ControlCommand) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let path = crate::storage::control_socket_path();
+        let reply = unix::send(path, command.as_str()).await?;
+        let reply = reply.trim_end();
+        if let Some(error) = reply.strip_prefix("error: ") {
+            anyhow::bail!("{error}");
+        }
+        println!("{reply}");
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = command.as_str();
+        anyhow::bail!("The control socket is only available on Unix")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_reply() {
+        let idle = Status::default();
+        assert_eq!(reply("run\n", &idle), ("started".to_string(), true));
+        assert_eq!(reply("status\n", &idle).0, "Idle");
+        assert!(reply("last-json", &idle).0.starts_with("error:"));
+        assert!(reply("stop", &idle).0.starts_with("error: unknown command"));
+
+        let running = Status {
+            state: RunState::Running { phase: None },
+            last_result: None,
+        };
+        assert_eq!(
+            reply("run", &running),
+            ("error: a test is already running".to_string(), false)
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Diff of two saved runs (`compare`): absolute and percentage deltas of
+//! the headline metrics, each with a hint of whether it stands out from the
+//! runs' own sample spread. Written as text, as JSON for scripts or as
+//! Markdown to paste into a ticket; the History tab of the TUI copies the
+//! Markdown one.
+
+use crate::config::Config;
+use crate::model::RunResult;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A delta this many combined standard deviations out is significant.
+const SIGNIFICANT_Z: f64 = 2.0;
+/// A delta this many combined standard deviations out may be real.
+const POSSIBLE_Z: f64 = 1.0;
+/// Interquartile range of a normal distribution, in standard deviations.
+const IQR_PER_SD: f64 = 1.349;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+/// How a delta compares with the spread of the samples behind it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Significance {
+    Significant,
+    Possible,
+    WithinNoise,
+}
+
+impl Significance {
+    fn label(self) -> &'static str {
+        match self {
+            Significance::Significant => "significant",
+            Significance::Possible => "possible",
+            Significance::WithinNoise => "within noise",
+        }
+    }
+}
+
+/// A compared metric: its value in a run and the standard deviation of the
+/// samples behind it.
+struct Metric {
+    name: &'static str,
+    label: &'static str,
+    unit: &'static str,
+    higher_is_better: bool,
+    value: fn(&RunResult) -> Option<f64>,
+    sd: fn(&RunResult) -> Option<f64>,
+}
+
+/// Standard deviation estimated from an interquartile range.
+fn iqr_sd(p25: Option<f64>, p75: Option<f64>) -> Option<f64> {
+    Some((p75? - p25?) / IQR_PER_SD)
+}
+
+/// Standard deviation of a loss percentage over `sent` probes.
+fn loss_sd(loss: f64, sent: u64) -> Option<f64> {
+    (sent > 0).then(|| (loss * (1.0 - loss) / sent as f64).sqrt() * 100.0)
+}
+
+const METRICS: &[Metric] = &[
+    Metric {
+        name: "download_mbps",
+        label: "Download",
+        unit: "Mbps",
+        higher_is_better: true,
+        value: |r| Some(r.download.mbps),
+        sd: |r| iqr_sd(r.download.p25_mbps, r.download.p75_mbps),
+    },
+    Metric {
+        name: "upload_mbps",
+        label: "Upload",
+        unit: "Mbps",
+        higher_is_better: true,
+        value: |r| Some(r.upload.mbps),
+        sd: |r| iqr_sd(r.upload.p25_mbps, r.upload.p75_mbps),
+    },
+    Metric {
+        name: "idle_latency_ms",
+        label: "Idle latency",
+        unit: "ms",
+        higher_is_better: false,
+        value: |r| r.idle_latency.median_ms,
+        sd: |r| iqr_sd(r.idle_latency.p25_ms, r.idle_latency.p75_ms),
+    },
+    Metric {
+        name: "loaded_latency_download_ms",
+        label: "Loaded latency down",
+        unit: "ms",
+        higher_is_better: false,
+        value: |r| r.loaded_latency_download.median_ms,
+        sd: |r| {
+            let l = &r.loaded_latency_download;
+            iqr_sd(l.p25_ms, l.p75_ms)
+        },
+    },
+    Metric {
+        name: "loaded_latency_upload_ms",
+        label: "Loaded latency up",
+        unit: "ms",
+        higher_is_better: false,
+        value: |r| r.loaded_latency_upload.median_ms,
+        sd: |r| {
+            let l = &r.loaded_latency_upload;
+            iqr_sd(l.p25_ms, l.p75_ms)
+        },
+    },
+    Metric {
+        name: "jitter_ms",
+        label: "Jitter",
+        unit: "ms",
+        higher_is_better: false,
+        value: |r| r.idle_latency.jitter_ms,
+        sd: |_| None,
+    },
+    Metric {
+        name: "packet_loss_pct",
+        label: "Packet loss",
+        unit: "%",
+        higher_is_better: false,
+        value: |r| {
+            let udp = &r.packet_loss.as_ref()?.latency;
+            (udp.sent > 0).then_some(udp.loss * 100.0)
+        },
+        sd: |r| {
+            let udp = &r.packet_loss.as_ref()?.latency;
+            loss_sd(udp.loss, udp.sent)
+        },
+    },
+];
+
+#[derive(Debug, Serialize)]
+pub struct RunRef {
+    pub meas_id: String,
+    pub timestamp_utc: String,
+    /// Network label, after the config file's aliases
+    pub network: Option<String>,
+    /// Test configuration, as in `stats` trends
+    pub profile: Option<String>,
+}
+
+impl RunRef {
+    fn new(config: &Config, run: &RunResult) -> Self {
+        Self {
+            meas_id: run.meas_id.clone(),
+            timestamp_utc: run.timestamp_utc.clone(),
+            network: config.network_label(run).map(str::to_string),
+            profile: crate::report::config_profile(run),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let network = self
+            .network
+            .as_deref()
+            .map(|n| format!(" on {n}"))
+            .unwrap_or_default();
+        format!("{} ({}){network}", self.meas_id, self.timestamp_utc)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricDiff {
+    pub metric: &'static str,
+    #[serde(skip)]
+    pub label: &'static str,
+    pub unit: &'static str,
+    pub higher_is_better: bool,
+    pub base: Option<f64>,
+    pub other: Option<f64>,
+    /// `other - base`
+    pub delta: Option<f64>,
+    /// `delta` as a percentage of `base`
+    pub delta_percent: Option<f64>,
+    /// Whether `other` is better than `base`, unless they're equal
+    pub improved: Option<bool>,
+    /// Unknown when the runs don't carry the metric's spread
+    pub significance: Option<Significance>,
+}
+
+/// The difference of `other` from `base`.
+#[derive(Debug, Serialize)]
+pub struct RunDiff {
+    pub base: RunRef,
+    pub other: RunRef,
+    pub metrics: Vec<MetricDiff>,
+}
+
+fn significance(delta: f64, sd: (Option<f64>, Option<f64>)) -> Option<Significance> {
+    let combined = sd.0?.hypot(sd.1?);
+    let z = if combined > 0.0 {
+        delta.abs() / combined
+    } else if delta == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY
+    };
+    Some(if z >= SIGNIFICANT_Z {
+        Significance::Significant
+    } else if z >= POSSIBLE_Z {
+        Significance::Possible
+    } else {
+        Significance::WithinNoise
+    })
+}
+
+pub fn diff(config: &Config, base: &RunResult, other: &RunResult) -> RunDiff {
+    let metrics = METRICS
+        .iter()
+        .map(|m| {
+            let (a, b) = ((m.value)(base), (m.value)(other));
+            let delta = a.zip(b).map(|(a, b)| b - a);
+            MetricDiff {
+                metric: m.name,
+                label: m.label,
+                unit: m.unit,
+                higher_is_better: m.higher_is_better,
+                base: a,
+                other: b,
+                delta,
+                delta_percent: a
+                    .zip(delta)
+                    .filter(|(a, _)| *a != 0.0)
+                    .map(|(a, d)| d / a * 100.0),
+                improved: delta
+                    .filter(|d| *d != 0.0)
+                    .map(|d| (d > 0.0) == m.higher_is_better),
+                significance: delta.and_then(|d| significance(d, ((m.sd)(base), (m.sd)(other)))),
+            }
+        })
+        .collect();
+    RunDiff {
+        base: RunRef::new(config, base),
+        other: RunRef::new(config, other),
+        metrics,
+    }
+}
+
+fn value(v: Option<f64>) -> String {
+    v.map_or_else(|| "-".to_string(), |v| format!("{v:.1}"))
+}
+
+fn signed(v: Option<f64>, suffix: &str) -> String {
+    v.map_or_else(|| "-".to_string(), |v| format!("{v:+.1}{suffix}"))
+}
+
+impl MetricDiff {
+    /// "better, significant", or "-" when the values are equal or unknown.
+    fn hint(&self) -> String {
+        let direction = match self.improved {
+            Some(true) => "better",
+            Some(false) => "worse",
+            None => return "-".to_string(),
+        };
+        match self.significance {
+            Some(s) => format!("{direction}, {}", s.label()),
+            None => direction.to_string(),
+        }
+    }
+}
+
+impl RunDiff {
+    /// A warning when the runs used different test configurations, which
+    /// accounts for part of any difference.
+    pub fn profile_note(&self) -> Option<String> {
+        let (base, other) = (self.base.profile.as_ref()?, self.other.profile.as_ref()?);
+        (base != other).then(|| {
+            format!("Note: the runs used different test configurations ({base} and {other}).")
+        })
+    }
+
+    pub fn text(&self) -> String {
+        let mut out = format!(
+            "Base:  {}\nOther: {}\n",
+            self.base.describe(),
+            self.other.describe()
+        );
+        if let Some(note) = self.profile_note() {
+            let _ = writeln!(out, "{note}");
+        }
+        out.push('\n');
+        for m in &self.metrics {
+            let _ = writeln!(
+                out,
+                "{:<22} {:>9} → {:>9} {:<4} {:>9} ({:>7})  {}",
+                m.label,
+                value(m.base),
+                value(m.other),
+                m.unit,
+                signed(m.delta, ""),
+                signed(m.delta_percent, "%"),
+                m.hint()
+            );
+        }
+        out
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut out = format!(
+            "### Speed test comparison\n\n\
+             - Base: `{}`\n- Other: `{}`\n\n\
+             | Metric | Base | Other | Δ | Δ % | Change |\n\
+             |---|---:|---:|---:|---:|---|\n",
+            self.base.describe(),
+            self.other.describe()
+        );
+        for m in &self.metrics {
+            let _ = writeln!(
+                out,
+                "| {} ({}) | {} | {} | {} | {} | {} |",
+                m.label,
+                m.unit,
+                value(m.base),
+                value(m.other),
+                signed(m.delta, ""),
+                signed(m.delta_percent, "%"),
+                m.hint()
+            );
+        }
+        if let Some(note) = self.profile_note() {
+            let _ = write!(out, "\n{note}\n");
+        }
+        out.push_str(
+            "\nSignificance compares each delta with the spread of the samples within both runs.\n",
+        );
+        out
+    }
+
+    pub fn render(&self, format: DiffFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            DiffFormat::Text => self.text(),
+            DiffFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+            DiffFormat::Markdown => self.markdown(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(download: f64, p25: f64, p75: f64, sent: u64, loss: f64) -> RunResult {
+        let mut r = test_run(download, 20.0);
+        r.meas_id = "a".to_string();
+        r.download.p25_mbps = Some(p25);
+        r.download.p75_mbps = Some(p75);
+        r.packet_loss = (sent > 0).then(|| {
+            serde_json::from_value(serde_json::json!({
+                "target": null, "latency": {"sent": sent, "received": sent, "loss": loss}
+            }))
+            .unwrap()
+        });
+        r
+    }
+
+    #[test]
+    fn test_run_diff() {
+        let config = Config::default();
+        let d = diff(
+            &config,
+            &run(100.0, 98.0, 102.0, 100, 0.0),
+            &run(80.0, 78.0, 82.0, 100, 5.0),
+        );
+        let download = &d.metrics[0];
+        assert_eq!(download.delta, Some(-20.0));
+        assert_eq!(download.delta_percent, Some(-20.0));
+        assert_eq!(download.improved, Some(false));
+        assert_eq!(download.significance, Some(Significance::Significant));
+        let loss = &d.metrics[6];
+        assert_eq!((loss.base, loss.other), (Some(0.0), Some(5.0)));
+        assert_eq!(loss.significance, Some(Significance::Significant));
+
+        let d = diff(
+            &config,
+            &run(100.0, 80.0, 120.0, 0, 0.0),
+            &run(90.0, 70.0, 110.0, 0, 0.0),
+        );
+        assert_eq!(d.metrics[0].significance, Some(Significance::WithinNoise));
+        // Upload carries no spread, and no probes means no loss figure
+        assert_eq!(
+            (d.metrics[1].improved, d.metrics[1].significance),
+            (None, None)
+        );
+        assert_eq!(d.metrics[6].base, None);
+        assert!(d
+            .markdown()
+            .contains("| Download (Mbps) | 100.0 | 90.0 | -10.0 | -10.0% | worse, within noise |"));
+    }
+}
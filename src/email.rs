@@ -0,0 +1,217 @@
+//! Email delivery over SMTP (`--email-to`) of monitor mode alerts, when a run
+//! fails or misses the `[thresholds]` of the config file, and of weekly
+//! reports over the saved runs. The server is set in the `[email]` section of
+//! the config file.
+
+use crate::config::{Config, EmailConfig};
+use crate::model::RunResult;
+use anyhow::Result;
+use std::time::Duration;
+
+/// How often monitor mode emails a report, and the period it covers.
+pub const REPORT_PERIOD: Duration = Duration::from_secs(7 * 86400);
+
+/// Fail early when `--email-to` can't be delivered: a build without the
+/// `email` feature, or no server configured.
+pub fn check_available(config: &EmailConfig) -> Result<()> {
+    #[cfg(not(feature = "email"))]
+    {
+        let _ = config;
+        anyhow::bail!("--email-to requires building with the `email` feature");
+    }
+    #[cfg(feature = "email")]
+    {
+        if config.server.is_none() {
+            anyhow::bail!(
+                "--email-to needs an SMTP server in the [email] section of the config file"
+            );
+        }
+        if config.from.is_none() && config.username.is_none() {
+            anyhow::bail!("--email-to needs email.from (or email.username) in the config file");
+        }
+        Ok(())
+    }
+}
+
+/// Subject and body of the alert for a run that missed the thresholds
+/// (`breaches`) or failed (`result` is the error).
+pub fn alert_message(
+    config: &Config,
+    result: Result<&RunResult, &anyhow::Error>,
+    breaches: &[String],
+) -> (String, String) {
+    match result {
+        Ok(run) => {
+            let network = config.network_label(run).unwrap_or("unknown network");
+            let mut body = format!(
+                "The speed test at {} on {network} missed the configured thresholds:\n\n",
+                run.timestamp_utc
+            );
+            for breach in breaches {
+                body.push_str(&format!("  - {breach}\n"));
+            }
+            body.push_str(&format!(
+                "\nDownload {:.1} Mbps, upload {:.1} Mbps, idle latency {}\nMeasurement ID: {}\n",
+                run.download.mbps,
+                run.upload.mbps,
+                run.idle_latency
+                    .median_ms
+                    .map_or("-".to_string(), |ms| format!("{ms:.1} ms")),
+                run.meas_id
+            ));
+            (format!("Speed test below thresholds on {network}"), body)
+        }
+        Err(e) => (
+            "Speed test failed".to_string(),
+            format!("The scheduled speed test failed:\n\n{e:#}\n"),
+        ),
+    }
+}
+
+/// Subject and body of the report over `runs`, the runs of the last `period`.
+pub fn report_message(runs: &[RunResult], period: Duration) -> (String, String) {
+    let period = humantime::format_duration(period);
+    if runs.is_empty() {
+        return (
+            format!("Speed test report: no runs in the last {period}"),
+            format!("No speed tests were saved in the last {period}.\n"),
+        );
+    }
+    // Invalid runs (e.g. interrupted by sleep) would skew the medians
+    let valid: Vec<RunResult> = runs
+        .iter()
+        .filter(|r| r.invalid.is_none())
+        .cloned()
+        .collect();
+    let summary = crate::repeat::summarize(&valid);
+    let mut body = format!("{} speed test(s) in the last {period}:\n\n", runs.len());
+    for line in crate::repeat::summary_lines(&summary) {
+        body.push_str(&format!("{line}\n"));
+    }
+    let invalid = runs.len() - valid.len();
+    if invalid > 0 {
+        body.push_str(&format!(
+            "\n{invalid} run(s) were invalid and are left out.\n"
+        ));
+    }
+    let subject = match (&summary.download_mbps, &summary.upload_mbps) {
+        (Some(down), Some(up)) => format!(
+            "Speed test report: {:.0}/{:.0} Mbps median over {} runs",
+            down.median,
+            up.median,
+            valid.len()
+        ),
+        _ => format!("Speed test report: {} runs", runs.len()),
+    };
+    (subject, body)
+}
+
+/// Saved runs of the last `period`, oldest first.
+pub fn recent_runs(period: Duration) -> Result<Vec<RunResult>> {
+    let since = time::OffsetDateTime::now_utc().unix_timestamp() - period.as_secs() as i64;
+    let mut runs: Vec<RunResult> = crate::storage::load_recent(usize::MAX)?
+        .into_iter()
+        .filter(|r| crate::report::unix_timestamp(&r.timestamp_utc).is_some_and(|t| t >= since))
+        .collect();
+    runs.reverse();
+    Ok(runs)
+}
+
+/// Email the report over the last `period` now (`email-report` subcommand).
+pub async fn send_report(config: &EmailConfig, to: &[String], period: Duration) -> Result<()> {
+    if to.is_empty() {
+        anyhow::bail!("email-report needs --email-to");
+    }
+    check_available(config)?;
+    let (subject, body) = report_message(&recent_runs(period)?, period);
+    send(config, to, &subject, &body).await
+}
+
+/// Send a plain-text email to every address in `to`.
+#[cfg(feature = "email")]
+pub async fn send(config: &EmailConfig, to: &[String], subject: &str, body: &str) -> Result<()> {
+    use crate::config::SmtpSecurity;
+    use anyhow::Context;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let server = config
+        .server
+        .as_deref()
+        .context("no SMTP server configured")?;
+    let from = config
+        .from
+        .as_deref()
+        .or(config.username.as_deref())
+        .context("no sender address configured")?;
+    let mut message = Message::builder()
+        .from(
+            from.parse()
+                .with_context(|| format!("invalid sender address {from:?}"))?,
+        )
+        .subject(subject);
+    for address in to {
+        message = message.to(address
+            .parse()
+            .with_context(|| format!("invalid address {address:?}"))?);
+    }
+    let message = message.body(body.to_string()).context("build email")?;
+
+    let mut transport = match config.security {
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(server)?,
+        SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(server)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(server),
+    }
+    .port(config.port());
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("send email through {server}"))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "email"))]
+pub async fn send(
+    _config: &EmailConfig,
+    _to: &[String],
+    _subject: &str,
+    _body: &str,
+) -> Result<()> {
+    anyhow::bail!("sending email requires building with the `email` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    #[test]
+    fn test_alert_message() {
+        let mut run = test_run(40.0, 10.0);
+        run.meas_id = "42".to_string();
+        run.idle_latency.median_ms = Some(12.0);
+        run.network_name = Some("home".into());
+        let breaches = vec!["download 40.0 Mbps < 100 Mbps".to_string()];
+        let mut config = Config::default();
+        config
+            .network_aliases
+            .insert("home".into(), "Home fibre".into());
+        let (subject, body) = alert_message(&config, Ok(&run), &breaches);
+        assert_eq!(subject, "Speed test below thresholds on Home fibre");
+        assert!(body.contains("  - download 40.0 Mbps < 100 Mbps\n"));
+        assert!(body.contains("idle latency 12.0 ms"));
+
+        let (subject, _) = report_message(&[run.clone(), run.clone()], REPORT_PERIOD);
+        assert_eq!(subject, "Speed test report: 40/10 Mbps median over 2 runs");
+
+        let mut asleep = test_run(5.0, 1.0);
+        asleep.invalid = Some("system slept during the test".into());
+        let (subject, body) = report_message(&[run.clone(), run, asleep], REPORT_PERIOD);
+        assert_eq!(subject, "Speed test report: 40/10 Mbps median over 2 runs");
+        assert!(body.contains("1 run(s) were invalid and are left out."));
+    }
+}
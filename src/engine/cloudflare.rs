@@ -1,163 +1,77 @@
 use anyhow::{Context, Result};
 use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::model::RunConfig;
+use super::provider::{self, Provider};
+use crate::model::{HttpVersion, RunConfig};
 
 #[derive(Clone)]
 pub struct CloudflareClient {
     pub base_url: Url,
-    pub meas_id: String,
+    /// Where the test's requests go
+    pub provider: Arc<dyn Provider>,
     pub http: reqwest::Client,
+    /// Client for latency probes: without the credentials when they go to
+    /// `latency_url`
+    latency_http: reqwest::Client,
+    /// Address connections are bound to, with `--interface` or `--source`
+    pub local_address: Option<IpAddr>,
 }
 
 impl CloudflareClient {
     pub fn new(cfg: &RunConfig) -> Result<Self> {
         let base_url = Url::parse(&cfg.base_url).context("invalid base_url")?;
-
-        let mut default_headers = reqwest::header::HeaderMap::new();
-        default_headers.insert(
-            reqwest::header::REFERER,
-            "https://speed.cloudflare.com/".parse().unwrap(),
-        );
-
-        let mut builder = reqwest::Client::builder()
-            .user_agent(cfg.user_agent.clone())
-            .default_headers(default_headers)
-            .timeout(Duration::from_secs(30))
-            .tcp_keepalive(Duration::from_secs(15));
-
-        // Configure binding to interface or source IP if specified
-        if let Some(ref iface) = cfg.interface {
-            use crate::engine::network_bind;
-            match network_bind::get_interface_ip(iface) {
-                Ok(ip) => {
-                    builder = builder.local_address(ip);
-                    eprintln!(
-                        "Binding HTTP connections to interface {} (IP: {})",
-                        iface, ip
-                    );
-                }
-                Err(e) => {
-                    return Err(anyhow::anyhow!(
-                        "Failed to get IP address for interface {}: {}",
-                        iface,
-                        e
-                    ));
-                }
-            }
-        } else if let Some(ref source_ip) = cfg.source_ip {
-            // Bind to specific source IP address
-            match source_ip.parse::<std::net::IpAddr>() {
-                Ok(ip) => {
-                    builder = builder.local_address(ip);
-                    eprintln!("Binding HTTP connections to source IP: {}", ip);
-                }
-                Err(e) => {
-                    return Err(anyhow::anyhow!(
-                        "Invalid source IP address format '{}': {}",
-                        source_ip,
-                        e
-                    ));
-                }
-            }
+        let provider = provider::for_config(cfg)?;
+
+        let local_address = local_address(cfg)?;
+        match (local_address, &cfg.interface) {
+            (Some(ip), Some(iface)) => eprintln!(
+                "Binding HTTP connections to interface {} (IP: {})",
+                iface, ip
+            ),
+            (Some(ip), None) => eprintln!("Binding HTTP connections to source IP: {}", ip),
+            (None, _) => {}
         }
-
-        // Load custom certificate if provided
-        if let Some(ref cert_path) = cfg.certificate_path {
-            // Check file extension
-            let ext = cert_path
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| e.to_lowercase());
-
-            let valid_extensions = ["pem", "crt", "cer", "der"];
-            if let Some(ref ext) = ext {
-                if !valid_extensions.contains(&ext.as_str()) {
-                    return Err(anyhow::anyhow!(
-                        "Invalid certificate file extension '{}'. Expected one of: {}",
-                        ext,
-                        valid_extensions.join(", ")
-                    ));
-                }
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Certificate file has no extension. Expected one of: {}",
-                    valid_extensions.join(", ")
-                ));
-            }
-
-            let cert_data = std::fs::read(cert_path).with_context(|| {
-                format!("failed to read certificate from {}", cert_path.display())
-            })?;
-
-            // Parse based on file extension
-            let cert = match ext.as_deref() {
-                Some("der") => reqwest::Certificate::from_der(&cert_data).with_context(|| {
-                    format!(
-                        "failed to parse DER certificate from {}",
-                        cert_path.display()
-                    )
-                })?,
-                _ => reqwest::Certificate::from_pem(&cert_data).with_context(|| {
-                    format!(
-                        "failed to parse PEM certificate from {}",
-                        cert_path.display()
-                    )
-                })?,
+        let http = build_http(cfg, local_address)?;
+        let latency_http = if cfg.latency_url.is_some() && cfg.has_credentials() {
+            let without_credentials = RunConfig {
+                auth: None,
+                access_token: None,
+                ..cfg.clone()
             };
-
-            builder = builder.add_root_certificate(cert);
-        }
-
-        // Configure proxy if specified
-        if let Some(ref proxy_url) = cfg.proxy {
-            let proxy = reqwest::Proxy::all(proxy_url).with_context(|| {
-                format!(
-                    "invalid proxy URL '{}'. Expected format: [protocol://]host[:port]",
-                    proxy_url
-                )
-            })?;
-            builder = builder.proxy(proxy);
-        }
-
-        let http = builder.build().context("failed to build http client")?;
+            build_http(&without_credentials, local_address)?
+        } else {
+            http.clone()
+        };
 
         Ok(Self {
             base_url,
-            meas_id: cfg.meas_id.clone(),
+            provider,
             http,
+            latency_http,
+            local_address,
         })
     }
 
-    pub fn down_url(&self) -> Url {
-        self.base_url.join("/__down").expect("join __down")
-    }
-
-    pub fn up_url(&self) -> Url {
-        self.base_url.join("/__up").expect("join __up")
+    /// Build a new HTTP client for `cfg`. It shares no connections with
+    /// existing clients, so its requests open fresh connections (resolving
+    /// DNS again); used to replace a connection that keeps failing.
+    pub fn reconnect(cfg: &RunConfig) -> Result<reqwest::Client> {
+        build_http(cfg, local_address(cfg)?)
     }
 
-
     pub async fn probe_latency_ms(
         &self,
         during: Option<&str>,
         timeout_ms: u64,
     ) -> Result<(f64, Option<serde_json::Value>)> {
-        let mut url = self.down_url();
-        {
-            let mut qp = url.query_pairs_mut();
-            qp.append_pair("bytes", "0");
-            if let Some(d) = during {
-                qp.append_pair("during", d);
-            } else {
-                qp.append_pair("measId", &self.meas_id);
-            }
-        }
+        let url = self.provider.latency_url(during);
 
         let start = std::time::Instant::now();
         let resp = self
-            .http
+            .latency_http
             .get(url)
             .timeout(Duration::from_millis(timeout_ms))
             .send()
@@ -221,6 +135,18 @@ impl CloudflareClient {
             );
         }
 
+        for (header, key) in [
+            ("cf-meta-latitude", "latitude"),
+            ("cf-meta-longitude", "longitude"),
+        ] {
+            if let Some(value) = resp.headers().get(header).and_then(|h| h.to_str().ok()) {
+                meta.insert(
+                    key.to_string(),
+                    serde_json::Value::String(value.to_string()),
+                );
+            }
+        }
+
         if let Some(asn) = resp
             .headers()
             .get("cf-meta-asn")
@@ -274,30 +200,40 @@ impl CloudflareClient {
 
 pub async fn fetch_meta_from_response(client: &CloudflareClient) -> Result<serde_json::Value> {
     // Try to get meta info from a test request response headers
-    let mut url = client.down_url();
-    url.query_pairs_mut()
-        .append_pair("bytes", "0")
-        .append_pair("measId", &client.meas_id);
-
+    let url = client.provider.download_url(0);
     let resp = client.http.get(url).send().await?;
 
     Ok(client.extract_meta_from_response(&resp))
 }
 
 pub async fn fetch_meta(client: &CloudflareClient) -> Result<serde_json::Value> {
-    let mut url = client.base_url.join("/meta").context("join /meta")?;
-    // Try with measId parameter
-    url.query_pairs_mut().append_pair("measId", &client.meas_id);
+    let url = client
+        .provider
+        .meta_url()
+        .context("provider has no metadata endpoint")?;
     let v: serde_json::Value = client.http.get(url).send().await?.json().await?;
-    Ok(v)
+    Ok(client.provider.parse_meta(v))
+}
+
+/// HTTP version the client negotiates with the test server and the IP
+/// version it connects over, e.g. ("HTTP/2.0", "IPv6").
+pub async fn connection_info(client: &CloudflareClient) -> (Option<String>, Option<String>) {
+    let url = client.provider.download_url(0);
+    let Ok(resp) = client.http.get(url).send().await else {
+        return (None, None);
+    };
+    let ip_version = resp
+        .remote_addr()
+        .map(|addr| if addr.is_ipv4() { "IPv4" } else { "IPv6" }.to_string());
+    (Some(format!("{:?}", resp.version())), ip_version)
 }
 
 /// Parse the /cdn-cgi/trace endpoint which returns key=value pairs
 pub async fn fetch_trace(client: &CloudflareClient) -> Result<serde_json::Value> {
     let url = client
-        .base_url
-        .join("/cdn-cgi/trace")
-        .context("join /cdn-cgi/trace")?;
+        .provider
+        .trace_url()
+        .context("provider has no trace endpoint")?;
     let text = client.http.get(url).send().await?.text().await?;
 
     let mut meta = serde_json::Map::new();
@@ -345,42 +281,43 @@ pub async fn fetch_locations(client: &CloudflareClient) -> Result<serde_json::Va
     Ok(v)
 }
 
-pub fn map_colo_to_server(locations: &serde_json::Value, colo: &str) -> Option<String> {
-    // Try to get location info from dynamic locations data
-    fn visit(v: &serde_json::Value, colo: &str) -> Option<serde_json::Value> {
-        match v {
-            serde_json::Value::Array(a) => {
-                for x in a {
-                    if let Some(f) = visit(x, colo) {
-                        return Some(f);
-                    }
+/// Find the entry for `colo` in the /locations data.
+pub fn find_location(v: &serde_json::Value, colo: &str) -> Option<serde_json::Value> {
+    match v {
+        serde_json::Value::Array(a) => {
+            for x in a {
+                if let Some(f) = find_location(x, colo) {
+                    return Some(f);
                 }
-                None
             }
-            serde_json::Value::Object(m) => {
-                let keys = ["iata", "colo", "code", "id"];
-                let mut matched = false;
-                for k in keys {
-                    if m.get(k).and_then(|x| x.as_str()) == Some(colo) {
-                        matched = true;
-                        break;
-                    }
-                }
-                if matched {
-                    return Some(serde_json::Value::Object(m.clone()));
+            None
+        }
+        serde_json::Value::Object(m) => {
+            let keys = ["iata", "colo", "code", "id"];
+            let mut matched = false;
+            for k in keys {
+                if m.get(k).and_then(|x| x.as_str()) == Some(colo) {
+                    matched = true;
+                    break;
                 }
-                for (_, x) in m {
-                    if let Some(f) = visit(x, colo) {
-                        return Some(f);
-                    }
+            }
+            if matched {
+                return Some(serde_json::Value::Object(m.clone()));
+            }
+            for (_, x) in m {
+                if let Some(f) = find_location(x, colo) {
+                    return Some(f);
                 }
-                None
             }
-            _ => None,
+            None
         }
+        _ => None,
     }
+}
 
-    if let Some(obj) = visit(locations, colo) {
+pub fn map_colo_to_server(locations: &serde_json::Value, colo: &str) -> Option<String> {
+    // Try to get location info from dynamic locations data
+    if let Some(obj) = find_location(locations, colo) {
         if let Some(m) = obj.as_object() {
             let city = m
                 .get("city")
@@ -408,3 +345,162 @@ pub fn map_colo_to_server(locations: &serde_json::Value, colo: &str) -> Option<S
     // Just return the colo code if no location data available
     Some(colo.to_string())
 }
+
+/// Address to bind HTTP connections to, from the interface or source IP options.
+fn local_address(cfg: &RunConfig) -> Result<Option<IpAddr>> {
+    if let Some(ref iface) = cfg.interface {
+        use crate::engine::network_bind;
+        match network_bind::interface_address(iface, cfg.ipv6_address) {
+            Ok(ip) => Ok(Some(ip)),
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to get IP address for interface {}: {}",
+                iface,
+                e
+            )),
+        }
+    } else if let Some(ref source_ip) = cfg.source_ip {
+        // Bind to specific source IP address
+        match source_ip.parse::<IpAddr>() {
+            Ok(ip) => Ok(Some(ip)),
+            Err(e) => Err(anyhow::anyhow!(
+                "Invalid source IP address format '{}': {}",
+                source_ip,
+                e
+            )),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+/// Headers every request to the test server carries: the credentials for
+/// a private server or one behind Cloudflare Access, and the correlation
+/// ID, if any.
+pub fn request_headers(cfg: &RunConfig) -> Result<reqwest::header::HeaderMap> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(correlation) = cfg.correlation_id.as_ref() {
+        let name = reqwest::header::HeaderName::from_bytes(correlation.header.as_bytes())
+            .context("invalid correlation header name")?;
+        let value = reqwest::header::HeaderValue::from_str(&correlation.id)
+            .context("invalid characters in correlation ID")?;
+        headers.insert(name, value);
+    }
+    if let Some(auth) = cfg.auth.as_ref() {
+        let mut value = reqwest::header::HeaderValue::from_str(&auth.header_value())
+            .context("invalid characters in credentials")?;
+        value.set_sensitive(true);
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+    }
+    if let Some(token) = cfg.access_token.as_ref() {
+        let client_id = reqwest::header::HeaderValue::from_str(&token.client_id)
+            .context("invalid characters in Access client ID")?;
+        let mut client_secret = reqwest::header::HeaderValue::from_str(&token.client_secret)
+            .context("invalid characters in Access client secret")?;
+        client_secret.set_sensitive(true);
+        headers.insert("CF-Access-Client-Id", client_id);
+        headers.insert("CF-Access-Client-Secret", client_secret);
+    }
+    Ok(headers)
+}
+
+fn build_http(cfg: &RunConfig, local_address: Option<IpAddr>) -> Result<reqwest::Client> {
+    let mut default_headers = request_headers(cfg)?;
+    default_headers.insert(
+        reqwest::header::REFERER,
+        "https://speed.cloudflare.com/".parse().unwrap(),
+    );
+
+    let mut builder = reqwest::Client::builder()
+        .user_agent(cfg.user_agent.clone())
+        .default_headers(default_headers)
+        .timeout(cfg.request_timeout)
+        .connect_timeout(cfg.connect_timeout)
+        .tcp_keepalive(Duration::from_secs(15))
+        // Requests on a client are sequential, so one idle connection is enough
+        .pool_max_idle_per_host(1);
+
+    if let Some(ip) = local_address.filter(|ip| !cfg.allows_ip(*ip)) {
+        anyhow::bail!("source address {ip} doesn't match --ipv4-only/--ipv6-only");
+    }
+    // Binding to an unspecified address of one family makes the client skip
+    // resolved addresses of the other
+    let family_address = match (cfg.ipv4_only, cfg.ipv6_only) {
+        (true, _) => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        (_, true) => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        _ => None,
+    };
+    if let Some(ip) = local_address.or(family_address) {
+        builder = builder.local_address(ip);
+    }
+    if let Some(resolver) = &cfg.resolver {
+        builder = builder.dns_resolver(std::sync::Arc::new(super::dns::HttpResolver(
+            resolver.clone(),
+        )));
+    }
+
+    // Only offer the chosen protocol in the TLS handshake
+    match cfg.http_version {
+        Some(HttpVersion::Http11) => builder = builder.http1_only(),
+        Some(HttpVersion::Http2) => builder = builder.http2_prior_knowledge(),
+        None => {}
+    }
+
+    // Load custom certificate if provided
+    if let Some(ref cert_path) = cfg.certificate_path {
+        // Check file extension
+        let ext = cert_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        let valid_extensions = ["pem", "crt", "cer", "der"];
+        if let Some(ref ext) = ext {
+            if !valid_extensions.contains(&ext.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid certificate file extension '{}'. Expected one of: {}",
+                    ext,
+                    valid_extensions.join(", ")
+                ));
+            }
+        } else {
+            return Err(anyhow::anyhow!(
+                "Certificate file has no extension. Expected one of: {}",
+                valid_extensions.join(", ")
+            ));
+        }
+
+        let cert_data = std::fs::read(cert_path)
+            .with_context(|| format!("failed to read certificate from {}", cert_path.display()))?;
+
+        // Parse based on file extension
+        let cert = match ext.as_deref() {
+            Some("der") => reqwest::Certificate::from_der(&cert_data).with_context(|| {
+                format!(
+                    "failed to parse DER certificate from {}",
+                    cert_path.display()
+                )
+            })?,
+            _ => reqwest::Certificate::from_pem(&cert_data).with_context(|| {
+                format!(
+                    "failed to parse PEM certificate from {}",
+                    cert_path.display()
+                )
+            })?,
+        };
+
+        builder = builder.add_root_certificate(cert);
+    }
+
+    // Configure proxy if specified
+    if let Some(ref proxy_url) = cfg.proxy {
+        let proxy = reqwest::Proxy::all(proxy_url).with_context(|| {
+            format!(
+                "invalid proxy URL '{}'. Expected format: [protocol://]host[:port]",
+                proxy_url
+            )
+        })?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().context("failed to build http client")
+}
@@ -1,15 +1,49 @@
 //! DNS resolution time measurement module
 
-use crate::model::DnsSummary;
+use super::provider::Provider;
+use crate::model::{DnsSummary, Resolver, ResolverTiming};
 use anyhow::{Context, Result};
-use std::net::IpAddr;
-use std::time::Instant;
-use tokio::net::lookup_host;
-
-/// Measure DNS resolution time for a given hostname.
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, UdpSocket};
+
+/// Public resolvers compared with the system one (`--dns-compare`), over
+/// IPv4 and IPv6.
+const PUBLIC_RESOLVERS_V4: [&str; 2] = ["1.1.1.1", "8.8.8.8"];
+const PUBLIC_RESOLVERS_V6: [&str; 2] = ["2606:4700:4700::1111", "2001:4860:4860::8888"];
+/// How long to wait for a public resolver to answer.
+const RESOLVER_TIMEOUT: Duration = Duration::from_secs(3);
+/// Record types looked up.
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+/// Measure DNS resolution time for a given hostname, through `resolver`
+/// instead of the system resolver if given.
 ///
 /// Returns a `DnsSummary` containing the resolution time and resolved IP addresses.
-pub async fn measure_dns_resolution(hostname: &str) -> Result<DnsSummary> {
+pub async fn measure_dns_resolution(
+    hostname: &str,
+    resolver: Option<&Resolver>,
+) -> Result<DnsSummary> {
+    if let Some(resolver) = resolver {
+        let start = Instant::now();
+        let ips = resolve_with(resolver, hostname).await?;
+        let elapsed = start.elapsed();
+        let mut resolved_ips: Vec<String> = ips.iter().map(IpAddr::to_string).collect();
+        resolved_ips.sort();
+        return Ok(DnsSummary {
+            hostname: hostname.to_string(),
+            resolution_time_ms: elapsed.as_secs_f64() * 1000.0,
+            resolved_ips,
+            ipv4_count: ips.iter().filter(|ip| ip.is_ipv4()).count(),
+            ipv6_count: ips.iter().filter(|ip| ip.is_ipv6()).count(),
+            dns_servers: Vec::new(),
+            resolver: Some(resolver.to_string()),
+            resolvers: Vec::new(),
+        });
+    }
+
     // Get system DNS servers
     let dns_servers = get_system_dns_servers();
 
@@ -51,9 +85,214 @@ pub async fn measure_dns_resolution(hostname: &str) -> Result<DnsSummary> {
         ipv4_count,
         ipv6_count,
         dns_servers,
+        resolver: None,
+        resolvers: Vec::new(),
     })
 }
 
+/// A recursive DNS query for the `record_type` records of `hostname`.
+fn build_query(id: u16, hostname: &str, record_type: u16) -> Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(hostname.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired; one question, no other records
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in hostname.trim_end_matches('.').split('.') {
+        anyhow::ensure!(
+            !label.is_empty() && label.len() < 64,
+            "invalid hostname {hostname}"
+        );
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    // Root label, type, class IN
+    query.push(0);
+    query.extend_from_slice(&record_type.to_be_bytes());
+    query.extend_from_slice(&[0, 1]);
+    Ok(query)
+}
+
+/// Check that `response` answers the query with `id`, returning the number
+/// of answer records.
+fn check_response(id: u16, response: &[u8]) -> Result<u16> {
+    anyhow::ensure!(response.len() >= 12, "short response");
+    anyhow::ensure!(
+        response[..2] == id.to_be_bytes() && response[2] & 0x80 != 0,
+        "unexpected response"
+    );
+    match response[3] & 0x0f {
+        0 => Ok(u16::from_be_bytes([response[6], response[7]])),
+        2 => anyhow::bail!("server failure"),
+        3 => anyhow::bail!("no such name"),
+        5 => anyhow::bail!("refused"),
+        rcode => anyhow::bail!("error code {rcode}"),
+    }
+}
+
+/// Position after the possibly compressed name at `pos` in `message`.
+fn skip_name(message: &[u8], mut pos: usize) -> Result<usize> {
+    loop {
+        let len = *message.get(pos).context("truncated name")? as usize;
+        match len {
+            0 => return Ok(pos + 1),
+            // Pointer to a name elsewhere in the message
+            len if len & 0xc0 == 0xc0 => return Ok(pos + 2),
+            len => pos += 1 + len,
+        }
+    }
+}
+
+/// The A and AAAA addresses in `response` to the query with `id`.
+fn parse_addresses(id: u16, response: &[u8]) -> Result<Vec<IpAddr>> {
+    let answers = check_response(id, response)?;
+    let questions = u16::from_be_bytes([response[4], response[5]]);
+    let mut pos = 12;
+    for _ in 0..questions {
+        // Name, type and class
+        pos = skip_name(response, pos)? + 4;
+    }
+    let mut addresses = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(response, pos)?;
+        let header = response.get(pos..pos + 10).context("truncated record")?;
+        let record_type = u16::from_be_bytes([header[0], header[1]]);
+        let len = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data = response
+            .get(pos + 10..pos + 10 + len)
+            .context("truncated record")?;
+        // CNAMEs on the way to the addresses are skipped
+        match record_type {
+            TYPE_A => addresses.extend(<[u8; 4]>::try_from(data).map(IpAddr::from)),
+            TYPE_AAAA => addresses.extend(<[u8; 16]>::try_from(data).map(IpAddr::from)),
+            _ => {}
+        }
+        pos += 10 + len;
+    }
+    Ok(addresses)
+}
+
+/// Send `query` with `id` to the resolver at `server` over UDP, returning
+/// the response and how long it took.
+async fn exchange_udp(server: IpAddr, id: u16, query: &[u8]) -> Result<(Vec<u8>, Duration)> {
+    let bind: SocketAddr = if server.is_ipv4() {
+        (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect((server, 53)).await?;
+
+    let start = Instant::now();
+    socket.send(query).await?;
+    let mut buf = [0u8; 512];
+    loop {
+        let len = tokio::time::timeout(RESOLVER_TIMEOUT, socket.recv(&mut buf))
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out"))??;
+        // Stray datagrams for another id are skipped
+        if len >= 2 && buf[..2] != id.to_be_bytes() {
+            continue;
+        }
+        return Ok((buf[..len].to_vec(), start.elapsed()));
+    }
+}
+
+/// Send `query` to the DNS-over-HTTPS endpoint `url` (RFC 8484), returning
+/// the response.
+async fn exchange_doh(url: &str, query: &[u8]) -> Result<Vec<u8>> {
+    // The endpoint's own name goes through the system resolver
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    let client = CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(RESOLVER_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    });
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/dns-message")
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .body(query.to_vec())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// The `record_type` addresses of `hostname` from `resolver`.
+async fn lookup(resolver: &Resolver, hostname: &str, record_type: u16) -> Result<Vec<IpAddr>> {
+    let id = rand::random::<u16>();
+    let query = build_query(id, hostname, record_type)?;
+    let response = match resolver {
+        Resolver::Dns(server) => exchange_udp(*server, id, &query).await?.0,
+        Resolver::Doh(url) => exchange_doh(url, &query).await?,
+    };
+    parse_addresses(id, &response)
+}
+
+/// IPv4 and IPv6 addresses of `hostname` from `resolver`.
+pub async fn resolve_with(resolver: &Resolver, hostname: &str) -> Result<Vec<IpAddr>> {
+    let (v4, v6) = tokio::join!(
+        lookup(resolver, hostname, TYPE_A),
+        lookup(resolver, hostname, TYPE_AAAA)
+    );
+    let mut addresses =
+        v4.with_context(|| format!("DNS lookup failed for {hostname} via {resolver}"))?;
+    // IPv4 addresses alone will do
+    addresses.extend(v6.unwrap_or_default());
+    anyhow::ensure!(
+        !addresses.is_empty(),
+        "{resolver} has no addresses for {hostname}"
+    );
+    Ok(addresses)
+}
+
+/// Resolves names for reqwest through `--resolver` or `--doh`.
+pub struct HttpResolver(pub Resolver);
+
+impl reqwest::dns::Resolve for HttpResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.0.clone();
+        Box::pin(async move {
+            let addresses = resolve_with(&resolver, name.as_str()).await?;
+            let addrs: reqwest::dns::Addrs =
+                Box::new(addresses.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+/// Time one lookup of `hostname` against the resolver at `server`.
+async fn query_resolver(server: IpAddr, hostname: &str) -> Result<Duration> {
+    let id = rand::random::<u16>();
+    let query = build_query(id, hostname, TYPE_A)?;
+    let (response, elapsed) = exchange_udp(server, id, &query).await?;
+    let answers = check_response(id, &response)?;
+    anyhow::ensure!(answers > 0, "no records");
+    Ok(elapsed)
+}
+
+/// Time the lookup of `hostname` against the public resolvers, over IPv6
+/// when `ipv6` is set.
+pub async fn compare_resolvers(hostname: &str, ipv6: bool) -> Vec<ResolverTiming> {
+    let servers = if ipv6 {
+        PUBLIC_RESOLVERS_V6
+    } else {
+        PUBLIC_RESOLVERS_V4
+    };
+    let lookups = servers.map(|server| async move {
+        let result = match server.parse() {
+            Ok(ip) => query_resolver(ip, hostname).await,
+            Err(e) => Err(e.into()),
+        };
+        ResolverTiming {
+            server: server.to_string(),
+            time_ms: result.as_ref().ok().map(|d| d.as_secs_f64() * 1000.0),
+            error: result.err().map(|e| e.to_string()),
+        }
+    });
+    futures::future::join_all(lookups).await
+}
+
 /// Get the system's configured DNS servers.
 ///
 /// On Linux/macOS: Parses /etc/resolv.conf
@@ -206,20 +445,26 @@ pub fn extract_hostname(url: &str) -> Option<String> {
         .and_then(|u| u.host_str().map(|s| s.to_string()))
 }
 
-/// Fetch external IPv4 and IPv6 addresses by making requests to Cloudflare.
-/// Returns (ipv4, ipv6) - either may be None if not available.
-pub async fn fetch_external_ips(base_url: &str) -> (Option<String>, Option<String>) {
-    let hostname = match extract_hostname(base_url) {
-        Some(h) => h,
+/// Fetch external IPv4 and IPv6 addresses from the header the provider
+/// reports the client IP in. Returns (ipv4, ipv6) - either may be None if
+/// not available, and both are when the provider has no such header.
+pub async fn fetch_external_ips(
+    provider: &dyn Provider,
+    headers: &reqwest::header::HeaderMap,
+) -> (Option<String>, Option<String>) {
+    let Some(header) = provider.client_ip_header() else {
+        return (None, None);
+    };
+    let url = provider.download_url(0);
+    let hostname = match url.host_str() {
+        Some(h) => h.to_string(),
         None => return (None, None),
     };
 
     // Resolve to get IPv4 and IPv6 addresses
-    let url = format!("{}/__down?bytes=0", base_url);
-
     let (ipv4, ipv6) = tokio::join!(
-        fetch_external_ip_version(&url, &hostname, IpVersion::V4),
-        fetch_external_ip_version(&url, &hostname, IpVersion::V6)
+        fetch_external_ip_version(url.as_str(), &hostname, header, IpVersion::V4, headers),
+        fetch_external_ip_version(url.as_str(), &hostname, header, IpVersion::V6, headers)
     );
 
     (ipv4, ipv6)
@@ -234,7 +479,9 @@ enum IpVersion {
 async fn fetch_external_ip_version(
     url: &str,
     hostname: &str,
+    header: &str,
     version: IpVersion,
+    headers: &reqwest::header::HeaderMap,
 ) -> Option<String> {
     use std::net::SocketAddr;
     use std::time::Duration;
@@ -255,6 +502,7 @@ async fn fetch_external_ip_version(
     // Build client that resolves to the specific IP
     let client = reqwest::Client::builder()
         .resolve(hostname, target_addr)
+        .default_headers(headers.clone())
         .timeout(Duration::from_secs(5))
         .build()
         .ok()?;
@@ -262,9 +510,8 @@ async fn fetch_external_ip_version(
     // Make request and extract IP from response headers
     let resp = client.get(url).send().await.ok()?;
 
-    // Extract IP from cf-meta-ip header
     resp.headers()
-        .get("cf-meta-ip")
+        .get(header)
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string())
 }
@@ -285,4 +532,38 @@ mod tests {
         );
         assert_eq!(extract_hostname("not a url"), None);
     }
+
+    #[test]
+    fn test_dns_query() {
+        let query = build_query(0x1234, "speed.cloudflare.com", TYPE_A).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[12..18], b"\x05speed");
+        assert!(query.ends_with(b"\x03com\x00\x00\x01\x00\x01"));
+        assert!(build_query(1, "bad..host", TYPE_A).is_err());
+
+        // Answer with two records, then NXDOMAIN
+        let mut response = query.clone();
+        response[2] = 0x81;
+        response[3] = 0x80;
+        response[7] = 2;
+        assert_eq!(check_response(0x1234, &response).unwrap(), 2);
+        assert!(check_response(0x4321, &response).is_err());
+        response[3] = 0x83;
+        assert_eq!(
+            check_response(0x1234, &response).unwrap_err().to_string(),
+            "no such name"
+        );
+
+        // A CNAME, then an A record, both naming the question by pointer
+        response[3] = 0x80;
+        response.extend_from_slice(b"\xc0\x0c\x00\x05\x00\x01\x00\x00\x00\x3c\x00\x02\xc0\x12");
+        response.extend_from_slice(b"\xc0\x0c\x00\x01\x00\x01\x00\x00\x00\x3c\x00\x04");
+        response.extend_from_slice(&[104, 16, 0, 1]);
+        assert_eq!(
+            parse_addresses(0x1234, &response).unwrap(),
+            ["104.16.0.1".parse::<IpAddr>().unwrap()]
+        );
+        response.truncate(response.len() - 2);
+        assert!(parse_addresses(0x1234, &response).is_err());
+    }
 }
@@ -0,0 +1,71 @@
+//! Sequenced event delivery for engine tasks.
+//!
+//! Every event sent through an [`EventSender`] is wrapped in an
+//! [`EventEnvelope`] carrying the run ID and a per-run sequence number.
+
+use crate::model::{EventEnvelope, TestEvent};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// Cloneable sender that stamps events with the run ID and the next sequence number.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+    tx: mpsc::Sender<EventEnvelope>,
+    run_id: Arc<str>,
+    // Held across the send so envelopes arrive in sequence order, even when
+    // several tasks send concurrently.
+    next_seq: Arc<Mutex<u64>>,
+}
+
+impl EventSender {
+    pub fn new(tx: mpsc::Sender<EventEnvelope>, run_id: &str) -> Self {
+        Self {
+            tx,
+            run_id: Arc::from(run_id),
+            next_seq: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Stamp `event` and send it, failing only if the receiver was dropped.
+    pub async fn send(
+        &self,
+        event: TestEvent,
+    ) -> Result<(), mpsc::error::SendError<EventEnvelope>> {
+        let mut next_seq = self.next_seq.lock().await;
+        let envelope = EventEnvelope {
+            seq: *next_seq,
+            run_id: self.run_id.to_string(),
+            event,
+        };
+        self.tx.send(envelope).await?;
+        *next_seq += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_events_are_sequenced_and_flattened() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let sender = EventSender::new(tx, "42");
+        for message in ["a", "b"] {
+            sender
+                .send(TestEvent::Info {
+                    message: message.into(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!((first.seq, second.seq), (0, 1));
+        assert_eq!(
+            serde_json::to_value(&second).unwrap(),
+            serde_json::json!({"seq": 1, "run_id": "42", "type": "info", "message": "b"})
+        );
+    }
+}
@@ -2,8 +2,10 @@
 //!
 //! Runs abbreviated speed tests on both IPv4 and IPv6 to compare performance.
 
+use super::provider::Provider;
 use crate::model::{IpVersionComparison, IpVersionResult};
-use anyhow::Result;
+use anyhow::{Context, Result};
+use reqwest::header::HeaderMap;
 use reqwest::Url;
 use std::net::{IpAddr, SocketAddr};
 use std::time::{Duration, Instant};
@@ -16,11 +18,13 @@ const TEST_DURATION: Duration = Duration::from_secs(3);
 ///
 /// Resolves the hostname to both IPv4 and IPv6 addresses, then runs
 /// abbreviated speed tests on each protocol.
-pub async fn compare_ip_versions(base_url: &str, user_agent: &str) -> Result<IpVersionComparison> {
-    let url = Url::parse(base_url)?;
-    let hostname = url
-        .host_str()
-        .ok_or_else(|| anyhow::anyhow!("No host in URL"))?;
+pub async fn compare_ip_versions(
+    provider: &dyn Provider,
+    user_agent: &str,
+    headers: &HeaderMap,
+) -> Result<IpVersionComparison> {
+    let url = provider.download_url(0);
+    let hostname = url.host_str().context("No host in URL")?;
     let port = url.port_or_known_default().unwrap_or(443);
 
     // Resolve hostname to get both IPv4 and IPv6 addresses
@@ -43,7 +47,7 @@ pub async fn compare_ip_versions(base_url: &str, user_agent: &str) -> Result<IpV
 
     // Test IPv4
     let ipv4_result = if let Some(ip) = ipv4_addr {
-        Some(test_ip_version(base_url, hostname, port, ip, user_agent).await)
+        Some(test_ip_version(provider, hostname, port, ip, user_agent, headers).await)
     } else {
         Some(IpVersionResult {
             ip_address: "N/A".to_string(),
@@ -57,7 +61,7 @@ pub async fn compare_ip_versions(base_url: &str, user_agent: &str) -> Result<IpV
 
     // Test IPv6
     let ipv6_result = if let Some(ip) = ipv6_addr {
-        Some(test_ip_version(base_url, hostname, port, ip, user_agent).await)
+        Some(test_ip_version(provider, hostname, port, ip, user_agent, headers).await)
     } else {
         Some(IpVersionResult {
             ip_address: "N/A".to_string(),
@@ -77,17 +81,19 @@ pub async fn compare_ip_versions(base_url: &str, user_agent: &str) -> Result<IpV
 
 /// Test a specific IP version by forcing requests to that IP.
 async fn test_ip_version(
-    base_url: &str,
+    provider: &dyn Provider,
     hostname: &str,
     port: u16,
     ip: IpAddr,
     user_agent: &str,
+    headers: &HeaderMap,
 ) -> IpVersionResult {
     let socket_addr = SocketAddr::new(ip, port);
 
     // Build a client that resolves hostname to specific IP
     let client = match reqwest::Client::builder()
         .user_agent(user_agent)
+        .default_headers(headers.clone())
         .timeout(Duration::from_secs(30))
         .resolve(hostname, socket_addr)
         .build()
@@ -106,7 +112,7 @@ async fn test_ip_version(
     };
 
     // Measure latency first
-    let latency_ms = match measure_latency(&client, base_url).await {
+    let latency_ms = match measure_latency(&client, provider.latency_url(None)).await {
         Ok(lat) => lat,
         Err(e) => {
             return IpVersionResult {
@@ -121,22 +127,23 @@ async fn test_ip_version(
     };
 
     // Run abbreviated download test
-    let download_mbps = match run_download_test(&client, base_url, TEST_DURATION).await {
-        Ok(mbps) => mbps,
-        Err(e) => {
-            return IpVersionResult {
-                ip_address: ip.to_string(),
-                download_mbps: 0.0,
-                upload_mbps: 0.0,
-                latency_ms,
-                available: false,
-                error: Some(format!("Download test failed: {}", e)),
-            };
-        }
-    };
+    let download_mbps =
+        match run_download_test(&client, provider.download_url(5_000_000), TEST_DURATION).await {
+            Ok(mbps) => mbps,
+            Err(e) => {
+                return IpVersionResult {
+                    ip_address: ip.to_string(),
+                    download_mbps: 0.0,
+                    upload_mbps: 0.0,
+                    latency_ms,
+                    available: false,
+                    error: Some(format!("Download test failed: {}", e)),
+                };
+            }
+        };
 
     // Run abbreviated upload test
-    let upload_mbps = match run_upload_test(&client, base_url, TEST_DURATION).await {
+    let upload_mbps = match run_upload_test(&client, provider.upload_url(), TEST_DURATION).await {
         Ok(mbps) => mbps,
         Err(e) => {
             return IpVersionResult {
@@ -161,25 +168,19 @@ async fn test_ip_version(
 }
 
 /// Measure latency to the server.
-async fn measure_latency(client: &reqwest::Client, base_url: &str) -> Result<f64> {
-    let url = format!("{}/__down?bytes=0", base_url);
+async fn measure_latency(client: &reqwest::Client, url: Url) -> Result<f64> {
     let start = Instant::now();
-    let _resp = client.get(&url).send().await?;
+    let _resp = client.get(url).send().await?;
     Ok(start.elapsed().as_secs_f64() * 1000.0)
 }
 
 /// Run abbreviated download test.
-async fn run_download_test(
-    client: &reqwest::Client,
-    base_url: &str,
-    duration: Duration,
-) -> Result<f64> {
-    let url = format!("{}/__down?bytes=5000000", base_url); // 5MB chunks
+async fn run_download_test(client: &reqwest::Client, url: Url, duration: Duration) -> Result<f64> {
     let start = Instant::now();
     let mut total_bytes: u64 = 0;
 
     while start.elapsed() < duration {
-        let resp = client.get(&url).send().await?;
+        let resp = client.get(url.clone()).send().await?;
         let bytes = resp.bytes().await?;
         total_bytes += bytes.len() as u64;
     }
@@ -190,18 +191,17 @@ async fn run_download_test(
 }
 
 /// Run abbreviated upload test.
-async fn run_upload_test(
-    client: &reqwest::Client,
-    base_url: &str,
-    duration: Duration,
-) -> Result<f64> {
-    let url = format!("{}/__up", base_url);
+async fn run_upload_test(client: &reqwest::Client, url: Url, duration: Duration) -> Result<f64> {
     let upload_data = vec![0u8; 5_000_000]; // 5MB chunks
     let start = Instant::now();
     let mut total_bytes: u64 = 0;
 
     while start.elapsed() < duration {
-        let _resp = client.post(&url).body(upload_data.clone()).send().await?;
+        let _resp = client
+            .post(url.clone())
+            .body(upload_data.clone())
+            .send()
+            .await?;
         total_bytes += upload_data.len() as u64;
     }
 
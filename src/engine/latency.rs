@@ -1,11 +1,11 @@
 use crate::engine::cloudflare::CloudflareClient;
+use crate::engine::events::EventSender;
 use crate::engine::wait_if_paused_or_cancelled;
 use crate::model::{LatencySummary, Phase, TestEvent};
 use crate::stats::{latency_summary_from_samples, OnlineStats};
 use anyhow::Result;
 use std::sync::{atomic::AtomicBool, Arc};
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
 
 pub async fn run_latency_probes(
     client: &CloudflareClient,
@@ -14,7 +14,7 @@ pub async fn run_latency_probes(
     total_duration: Duration,
     interval_ms: u64,
     timeout_ms: u64,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
     paused: Arc<AtomicBool>,
     cancel: Arc<AtomicBool>,
 ) -> Result<LatencySummary> {
@@ -1,16 +1,19 @@
 mod cloudflare;
 pub mod dns;
+pub mod events;
 pub mod ip_comparison;
 mod latency;
-mod network_bind;
+pub mod network_bind;
+pub mod provider;
+mod suspend;
 mod throughput;
 pub mod tls;
 pub mod traceroute;
 mod turn_udp;
 
 use crate::model::{
-    DnsSummary, IpVersionComparison, Phase, RunConfig, RunResult, TestEvent, TlsSummary,
-    TracerouteSummary,
+    DnsSummary, EventEnvelope, IpVersionComparison, Phase, RunConfig, RunResult, StreamComparison,
+    TestEvent, TlsSummary, TracerouteSummary,
 };
 use anyhow::Result;
 use std::sync::{
@@ -30,6 +33,7 @@ pub(crate) async fn wait_if_paused_or_cancelled(paused: &AtomicBool, cancel: &At
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
 pub enum EngineControl {
     /// Pause (true) or resume (false) the running test
     Pause(bool),
@@ -48,13 +52,23 @@ impl TestEngine {
 
     pub async fn run(
         self,
-        event_tx: mpsc::Sender<TestEvent>,
+        event_tx: mpsc::Sender<EventEnvelope>,
         mut control_rx: mpsc::Receiver<EngineControl>,
     ) -> Result<RunResult> {
+        let event_tx = events::EventSender::new(event_tx, &self.cfg.meas_id);
         let client = cloudflare::CloudflareClient::new(&self.cfg)?;
+        // For the diagnostics that build their own clients
+        let request_headers = cloudflare::request_headers(&self.cfg)?;
 
         let paused = Arc::new(AtomicBool::new(false));
         let cancel = Arc::new(AtomicBool::new(false));
+        // Set when the system sleeps during the run
+        let clock = suspend::SuspendDetector::start();
+        let mut invalid = None;
+        let power = tokio::task::spawn_blocking(crate::power::power_status)
+            .await
+            .ok()
+            .flatten();
 
         // Try to get meta from multiple sources in order of preference:
         // 1. /meta endpoint (may have full details)
@@ -98,26 +112,100 @@ impl TestEngine {
             meta = cloudflare::fetch_meta_from_response(&client).await.ok();
         }
 
-        let locations = cloudflare::fetch_locations(&client).await.ok();
+        let (http_version, ip_version) = cloudflare::connection_info(&client).await;
+
+        // The locations list rarely changes, so reuse a recent copy. Fall back
+        // to an older one when /locations is unreachable.
+        let mut locations_age_secs = None;
+        let locations = match crate::storage::read_cache(
+            "locations",
+            Some(crate::storage::LOCATIONS_CACHE_TTL),
+        ) {
+            Some(cache) => {
+                locations_age_secs = Some(cache.age().as_secs());
+                Some(cache.value)
+            }
+            None => match cloudflare::fetch_locations(&client).await {
+                Ok(locations) => {
+                    crate::storage::write_cache("locations", &locations).ok();
+                    Some(locations)
+                }
+                Err(_) => match crate::storage::read_cache("locations", None) {
+                    Some(cache) => {
+                        let age = cache.age().as_secs();
+                        locations_age_secs = Some(age);
+                        let age = humantime::format_duration(Duration::from_secs(age / 60 * 60));
+                        event_tx
+                            .send(TestEvent::Info {
+                                message: format!("Using cached Cloudflare locations (age: {age})"),
+                            })
+                            .await
+                            .ok();
+                        Some(cache.value)
+                    }
+                    None => None,
+                },
+            },
+        };
         let server = meta
             .as_ref()
             .and_then(|m: &serde_json::Value| {
                 m.get("colo").and_then(|v: &serde_json::Value| v.as_str())
             })
-            .and_then(|colo| {
-                locations
-                    .as_ref()
-                    .and_then(|loc| cloudflare::map_colo_to_server(loc, colo))
+            .and_then(|colo| match locations.as_ref() {
+                Some(loc) => cloudflare::map_colo_to_server(loc, colo),
+                None => Some(colo.to_string()),
             });
 
         // Send meta info early so TUI can display server/colo/ip immediately
         if let Some(ref m) = meta {
+            crate::storage::write_cache("meta", m).ok();
             event_tx
                 .send(TestEvent::MetaInfo { meta: m.clone() })
                 .await
                 .ok();
         }
 
+        // Locate the client (from the GeoIP database if given) and the colo
+        let mut client_location = None;
+        if let Some(db) = self.cfg.geoip_db.as_deref() {
+            let client_ip = meta
+                .as_ref()
+                .and_then(|m| crate::network::extract_metadata(m).ip)
+                .and_then(|ip| ip.parse().ok());
+            let message = match client_ip.map(|ip| crate::geo::client_from_geoip_db(db, ip)) {
+                Some(Ok(location)) => {
+                    client_location = Some(location);
+                    None
+                }
+                Some(Err(e)) => Some(format!("GeoIP lookup failed: {e:#}")),
+                None => Some("GeoIP lookup skipped: client IP unknown".to_string()),
+            };
+            if let Some(message) = message {
+                event_tx.send(TestEvent::Info { message }).await.ok();
+            }
+        }
+        let geo = client_location
+            .or_else(|| meta.as_ref().map(crate::geo::client_from_meta))
+            .and_then(|client| {
+                let colo = meta
+                    .as_ref()
+                    .and_then(|m| m.get("colo"))
+                    .and_then(|v| v.as_str());
+                let colo_entry = colo
+                    .zip(locations.as_ref())
+                    .and_then(|(colo, loc)| cloudflare::find_location(loc, colo));
+                crate::geo::summarize(client, colo, colo_entry.as_ref())
+            });
+        if let Some(ref geo) = geo {
+            event_tx
+                .send(TestEvent::GeoInfo {
+                    geo: Box::new(geo.clone()),
+                })
+                .await
+                .ok();
+        }
+
         // Control listener.
         let paused2 = paused.clone();
         let cancel2 = cancel.clone();
@@ -151,8 +239,12 @@ impl TestEngine {
                     .await
                     .ok();
 
-                match dns::measure_dns_resolution(&hostname).await {
-                    Ok(summary) => {
+                match dns::measure_dns_resolution(&hostname, self.cfg.resolver.as_ref()).await {
+                    Ok(mut summary) => {
+                        if self.cfg.compare_resolvers {
+                            summary.resolvers =
+                                dns::compare_resolvers(&hostname, self.cfg.ipv6_only).await;
+                        }
                         event_tx
                             .send(TestEvent::DiagnosticDns {
                                 summary: summary.clone(),
@@ -207,7 +299,8 @@ impl TestEngine {
 
         // Fetch external IPs (runs in parallel, part of default diagnostics)
         if self.cfg.measure_dns {
-            let (v4, v6) = dns::fetch_external_ips(&self.cfg.base_url).await;
+            let (v4, v6) =
+                dns::fetch_external_ips(client.provider.as_ref(), &request_headers).await;
             external_ipv4 = v4.clone();
             external_ipv6 = v6.clone();
             event_tx
@@ -225,7 +318,12 @@ impl TestEngine {
                 .await
                 .ok();
 
-            match ip_comparison::compare_ip_versions(&self.cfg.base_url, &self.cfg.user_agent).await
+            match ip_comparison::compare_ip_versions(
+                client.provider.as_ref(),
+                &self.cfg.user_agent,
+                &request_headers,
+            )
+            .await
             {
                 Ok(comparison) => {
                     event_tx
@@ -284,6 +382,8 @@ impl TestEngine {
             }
         }
 
+        invalid = invalid.or_else(|| clock.check("diagnostics"));
+
         event_tx
             .send(TestEvent::PhaseStarted {
                 phase: Phase::IdleLatency,
@@ -303,6 +403,7 @@ impl TestEngine {
             cancel.clone(),
         )
         .await?;
+        invalid = invalid.or_else(|| clock.check("idle latency"));
 
         event_tx
             .send(TestEvent::PhaseStarted {
@@ -311,6 +412,9 @@ impl TestEngine {
             .await
             .ok();
 
+        // Sampled through both throughput phases
+        let thermal_monitor = self.cfg.thermal.then(crate::thermal::ThermalMonitor::start);
+
         let (download, loaded_latency_download) = throughput::run_download_with_loaded_latency(
             &client,
             &self.cfg,
@@ -320,6 +424,41 @@ impl TestEngine {
         )
         .await?;
 
+        // Single-stream download, to compare with the multi-stream result
+        let mut stream_comparison = None;
+        if self.cfg.compare_streams && !cancel.load(Ordering::Relaxed) {
+            event_tx
+                .send(TestEvent::Info {
+                    message: "Measuring single-stream download for comparison...".to_string(),
+                })
+                .await
+                .ok();
+
+            let message = match throughput::run_single_stream_download(
+                &client,
+                &self.cfg,
+                paused.clone(),
+                cancel.clone(),
+            )
+            .await
+            {
+                Ok(single_stream) => {
+                    let message = format!(
+                        "Download: {:.1} Mbps over 1 stream, {:.1} Mbps over {} streams",
+                        single_stream.mbps, download.mbps, self.cfg.download_streams()
+                    );
+                    stream_comparison = Some(StreamComparison {
+                        streams: self.cfg.download_streams() as u64,
+                        single_stream,
+                    });
+                    message
+                }
+                Err(e) => format!("Single-stream download failed: {}", e),
+            };
+            event_tx.send(TestEvent::Info { message }).await.ok();
+        }
+        invalid = invalid.or_else(|| clock.check("download"));
+
         event_tx
             .send(TestEvent::PhaseStarted {
                 phase: Phase::Upload,
@@ -328,11 +467,17 @@ impl TestEngine {
             .ok();
 
         // Prefetch DNS for STUN server during upload to eliminate delay before packet loss phase
+        let stun_cfg = self.cfg.clone();
+        let stun_target = client
+            .provider
+            .turn()
+            .and_then(|turn| turn_udp::stun_target(&turn).ok());
         let stun_dns_handle = tokio::spawn(async move {
-            tokio::net::lookup_host(("turn.cloudflare.com", 3478_u16))
+            let (host, port) = stun_target?;
+            tokio::net::lookup_host((host.as_str(), port))
                 .await
                 .ok()
-                .and_then(|mut addrs| addrs.next())
+                .and_then(|mut addrs| addrs.find(|a| stun_cfg.allows_ip(a.ip())))
         });
 
         let (upload, loaded_latency_upload) = throughput::run_upload_with_loaded_latency(
@@ -343,6 +488,23 @@ impl TestEngine {
             cancel.clone(),
         )
         .await?;
+        invalid = invalid.or_else(|| clock.check("upload"));
+
+        let thermal = match thermal_monitor {
+            Some(monitor) => monitor.finish().await,
+            None => None,
+        };
+        if let Some(thermal) = thermal.as_ref().filter(|t| t.throttled) {
+            event_tx
+                .send(TestEvent::Info {
+                    message: format!(
+                        "This machine's CPU was throttled ({}); throughput may be limited by it",
+                        thermal.describe()
+                    ),
+                })
+                .await
+                .ok();
+        }
 
         event_tx
             .send(TestEvent::PhaseStarted {
@@ -351,31 +513,40 @@ impl TestEngine {
             .await
             .ok();
 
-        let mut experimental_udp = None;
+        let mut packet_loss = None;
         let mut udp_error = None;
 
-        let info = crate::model::TurnInfo {
-            urls: vec!["stun:turn.cloudflare.com:3478".to_string()],
-            username: None,
-            credential: None,
-        };
-
         // Use prefetched DNS if available
         let pre_resolved = stun_dns_handle.await.ok().flatten();
 
-        match turn_udp::run_udp_like_loss_probe(&info, &self.cfg, &event_tx, pre_resolved).await {
+        let probe = match client.provider.turn() {
+            Some(info) => {
+                turn_udp::run_udp_like_loss_probe(&info, &self.cfg, &event_tx, pre_resolved).await
+            }
+            None => Err(anyhow::anyhow!(
+                "{} has no STUN server",
+                client.provider.name()
+            )),
+        };
+        match probe {
             Ok(udp) => {
-                experimental_udp = Some(udp);
+                packet_loss = Some(udp);
             }
             Err(e) => {
                 let msg = format!("UDP probe failed: {e:#}");
                 udp_error = Some(msg.clone());
-                event_tx
-                    .send(TestEvent::Info { message: msg })
-                    .await
-                    .ok();
+                event_tx.send(TestEvent::Info { message: msg }).await.ok();
             }
         }
+        invalid = invalid.or_else(|| clock.check("packet loss"));
+        if let Some(reason) = invalid.as_deref() {
+            event_tx
+                .send(TestEvent::Info {
+                    message: format!("Results are invalid: {reason}"),
+                })
+                .await
+                .ok();
+        }
 
         event_tx
             .send(TestEvent::PhaseStarted {
@@ -397,17 +568,21 @@ impl TestEngine {
                 .format(&time::format_description::well_known::Rfc3339)
                 .unwrap_or_else(|_| "now".into()),
             base_url: self.cfg.base_url.clone(),
+            latency_url: self.cfg.latency_url.clone(),
             meas_id: self.cfg.meas_id.clone(),
             comments: self.cfg.comments.clone(),
+            provider: Some(client.provider.name().to_string()),
+            server_id: meta.as_ref().and_then(|m| client.provider.server_id(m)),
             meta,
             server,
+            locations_age_secs,
             idle_latency,
             download,
             upload,
             loaded_latency_download,
             loaded_latency_upload,
             turn: None,
-            experimental_udp,
+            packet_loss,
             udp_error,
             // Network information - will be populated by TUI when available
             ip: None,
@@ -418,15 +593,28 @@ impl TestEngine {
             network_name: None,
             is_wireless: None,
             interface_mac: None,
+            link_speed_mbps: None,
+            route_interface: None,
+            cellular_type: None,
+            cellular: None,
+            power,
+            thermal,
             local_ipv4: None,
             local_ipv6: None,
+            source_address: client.local_address.map(|ip| ip.to_string()),
             external_ipv4,
             external_ipv6,
             // Diagnostic results
             dns: dns_summary,
             tls: tls_summary,
             ip_comparison: ip_comparison_result,
+            stream_comparison,
             traceroute: traceroute_summary,
+            geo,
+            client: Some(crate::model::ClientInfo::current(http_version, ip_version)),
+            config: Some(self.cfg.for_storage()),
+            invalid,
+            normalized: None,
         })
     }
 }
@@ -1,5 +1,6 @@
+use crate::model::Ipv6AddressChoice;
 use anyhow::{Context, Result};
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 
 /// Get the IP address of a network interface using the `if-addrs` crate
 pub fn get_interface_ip(interface: &str) -> Result<IpAddr> {
@@ -31,10 +32,162 @@ pub fn get_interface_ip(interface: &str) -> Result<IpAddr> {
     ))
 }
 
+/// An IPv6 address of an interface, with the system's flags for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ipv6Candidate {
+    ip: Ipv6Addr,
+    /// A temporary (privacy) address
+    temporary: bool,
+    /// Past its preferred lifetime; kept for existing connections only
+    deprecated: bool,
+}
+
+/// Get the address of `interface` to bind to: with `ipv6`, the IPv6
+/// address chosen, else the interface's IP address.
+pub fn interface_address(interface: &str, ipv6: Option<Ipv6AddressChoice>) -> Result<IpAddr> {
+    let Some(choice) = ipv6 else {
+        return get_interface_ip(interface);
+    };
+    if !matches!(choice, Ipv6AddressChoice::Prefix { .. })
+        && !cfg!(any(target_os = "linux", target_os = "macos"))
+    {
+        anyhow::bail!(
+            "telling temporary from stable IPv6 addresses isn't supported on this system; give \
+             --ipv6-address a prefix instead"
+        );
+    }
+    let candidates = ipv6_candidates(interface)?;
+    choose_ipv6(&candidates, choice)
+        .map(IpAddr::V6)
+        .with_context(|| {
+            let available: Vec<String> = candidates.iter().map(|c| c.ip.to_string()).collect();
+            format!(
+                "Interface {interface} has no {} IPv6 address (has: {})",
+                match choice {
+                    Ipv6AddressChoice::Temporary => "temporary".to_string(),
+                    Ipv6AddressChoice::Stable => "stable".to_string(),
+                    Ipv6AddressChoice::Prefix { network, len } => format!("{network}/{len}"),
+                },
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            )
+        })
+}
+
+/// The global address among `candidates` matching `choice`, preferring
+/// ones that aren't deprecated.
+fn choose_ipv6(candidates: &[Ipv6Candidate], choice: Ipv6AddressChoice) -> Option<Ipv6Addr> {
+    candidates
+        .iter()
+        .filter(|c| !c.ip.is_loopback() && !c.ip.is_unicast_link_local())
+        .filter(|c| choice.matches(c.ip, c.temporary))
+        .min_by_key(|c| c.deprecated)
+        .map(|c| c.ip)
+}
+
+/// IPv6 addresses of `interface` with their flags, from /proc/net/if_inet6.
+#[cfg(target_os = "linux")]
+fn ipv6_candidates(interface: &str) -> Result<Vec<Ipv6Candidate>> {
+    let table =
+        std::fs::read_to_string("/proc/net/if_inet6").context("Failed to read IPv6 addresses")?;
+    Ok(parse_if_inet6(&table, interface))
+}
+
+/// IPv6 addresses of `interface` with their flags, from `ifconfig`.
+#[cfg(target_os = "macos")]
+fn ipv6_candidates(interface: &str) -> Result<Vec<Ipv6Candidate>> {
+    let output = std::process::Command::new("ifconfig")
+        .arg(interface)
+        .output()
+        .context("Failed to run ifconfig")?;
+    Ok(parse_ifconfig(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// IPv6 addresses of `interface`; their flags aren't known here.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn ipv6_candidates(interface: &str) -> Result<Vec<Ipv6Candidate>> {
+    let addrs = if_addrs::get_if_addrs().context("Failed to enumerate network interfaces")?;
+    Ok(addrs
+        .iter()
+        .filter(|addr| addr.name == interface)
+        .filter_map(|addr| match addr.ip() {
+            IpAddr::V6(ip) => Some(Ipv6Candidate {
+                ip,
+                temporary: false,
+                deprecated: false,
+            }),
+            IpAddr::V4(_) => None,
+        })
+        .collect())
+}
+
+/// Lines of /proc/net/if_inet6 are: address, interface index, prefix
+/// length, scope and flags in hex, then the interface name.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_if_inet6(table: &str, interface: &str) -> Vec<Ipv6Candidate> {
+    const IFA_F_TEMPORARY: u32 = 0x01;
+    const IFA_F_DEPRECATED: u32 = 0x20;
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 6 || fields[5] != interface {
+                return None;
+            }
+            let ip = Ipv6Addr::from(u128::from_str_radix(fields[0], 16).ok()?);
+            let flags = u32::from_str_radix(fields[4], 16).ok()?;
+            Some(Ipv6Candidate {
+                ip,
+                temporary: flags & IFA_F_TEMPORARY != 0,
+                deprecated: flags & IFA_F_DEPRECATED != 0,
+            })
+        })
+        .collect()
+}
+
+/// `ifconfig` prints e.g. `inet6 2001:db8::1 prefixlen 64 autoconf temporary`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ifconfig(output: &str) -> Vec<Ipv6Candidate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            if words.next() != Some("inet6") {
+                return None;
+            }
+            // Link-local addresses carry their scope, e.g. fe80::1%en0
+            let ip = words.next()?.split('%').next()?.parse().ok()?;
+            let flags: Vec<&str> = words.collect();
+            Some(Ipv6Candidate {
+                ip,
+                temporary: flags.contains(&"temporary"),
+                deprecated: flags.contains(&"deprecated"),
+            })
+        })
+        .collect()
+}
+
+/// Fail unless `ip` is assigned to an interface of this machine, so a test
+/// bound to it would actually leave from it.
+pub fn check_local_address(ip: IpAddr) -> Result<()> {
+    let addrs = if_addrs::get_if_addrs().context("Failed to enumerate network interfaces")?;
+    if addrs.iter().any(|addr| addr.ip() == ip) {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "{ip} isn't assigned to any interface of this machine; add it (e.g. as a secondary \
+         address or on a VLAN interface) first"
+    ))
+}
+
 /// Resolve binding address from interface name or source IP
 pub fn resolve_bind_address(
     interface: Option<&String>,
     source_ip: Option<&String>,
+    ipv6: Option<Ipv6AddressChoice>,
 ) -> Result<Option<SocketAddr>> {
     if let Some(ip_str) = source_ip {
         let ip: IpAddr = ip_str.parse().context("Invalid source IP address format")?;
@@ -42,10 +195,52 @@ pub fn resolve_bind_address(
     }
 
     if let Some(iface) = interface {
-        let ip = get_interface_ip(iface)
+        let ip = interface_address(iface, ipv6)
             .with_context(|| format!("Failed to get IP for interface {}", iface))?;
         return Ok(Some(SocketAddr::new(ip, 0)));
     }
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_ipv6() {
+        let table = "\
+00000000000000000000000000000001 01 80 10 80       lo
+fe80000000000000021122fffe334455 02 40 20 80     eth0
+20010db800010002021122fffe334455 02 40 00 00     eth0
+20010db800010002a1b2c3d4e5f60718 02 40 00 21     eth0
+20010db80001000291a2b3c4d5e6f708 02 40 00 01     eth0
+20010db800090002021122fffe334455 02 40 00 00     eth1
+";
+        let candidates = parse_if_inet6(table, "eth0");
+        assert_eq!(candidates.len(), 4);
+        let choose = |choice: &str| choose_ipv6(&candidates, choice.parse().unwrap());
+        assert_eq!(
+            choose("stable"),
+            "2001:db8:1:2:211:22ff:fe33:4455".parse().ok()
+        );
+        // The deprecated temporary address is passed over
+        assert_eq!(
+            choose("temporary"),
+            "2001:db8:1:2:91a2:b3c4:d5e6:f708".parse().ok()
+        );
+        assert_eq!(choose("2001:db8:9::/48"), None);
+        assert!("2001:db8::/129".parse::<Ipv6AddressChoice>().is_err());
+
+        let ifconfig = "en0: flags=8863<UP,BROADCAST,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tinet6 fe80::1c2b:3a4d:5e6f:7081%en0 prefixlen 64 secured scopeid 0x6
+\tinet6 2001:db8:1:2:1c2b:3a4d:5e6f:7081 prefixlen 64 autoconf secured
+\tinet6 2001:db8:1:2:aaaa:bbbb:cccc:dddd prefixlen 64 autoconf temporary
+";
+        let candidates = parse_ifconfig(ifconfig);
+        assert_eq!(
+            choose_ipv6(&candidates, Ipv6AddressChoice::Temporary),
+            "2001:db8:1:2:aaaa:bbbb:cccc:dddd".parse().ok()
+        );
+    }
+}
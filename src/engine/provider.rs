@@ -0,0 +1,350 @@
+//! Speed test backends. The engine asks a [`Provider`] where each kind of
+//! request goes and which server answered: Cloudflare, or a LibreSpeed
+//! server. Other backends implement the same trait instead of forking the
+//! engine; M-Lab's ndt7 isn't one of them, as it measures over WebSockets
+//! rather than plain HTTP requests.
+
+use crate::model::{ProviderKind, RunConfig, TurnInfo};
+use anyhow::{Context, Result};
+use reqwest::Url;
+use std::sync::Arc;
+
+/// Endpoints of a speed test backend.
+pub trait Provider: Send + Sync {
+    /// Name recorded with each result, e.g. "cloudflare".
+    fn name(&self) -> &'static str;
+
+    /// Endpoint describing the client and the serving location as JSON, if
+    /// the backend has one.
+    fn meta_url(&self) -> Option<Url>;
+
+    /// The backend's metadata in the shape of Cloudflare's (`clientIp`,
+    /// `asn`, `asOrganization`, `city`, `country`), which the rest of the
+    /// tool reads.
+    fn parse_meta(&self, meta: serde_json::Value) -> serde_json::Value {
+        meta
+    }
+
+    /// One download request of `bytes` bytes.
+    fn download_url(&self, bytes: u64) -> Url;
+
+    /// Where upload requests are posted.
+    fn upload_url(&self) -> Url;
+
+    /// One latency probe, sent while `during` ("download", "upload") runs or
+    /// at idle.
+    fn latency_url(&self, during: Option<&str>) -> Url;
+
+    /// STUN/TURN servers for the UDP packet loss probe, if the backend has
+    /// any.
+    fn turn(&self) -> Option<TurnInfo>;
+
+    /// Identity of the server that ran the test, from the backend's
+    /// metadata, e.g. a Cloudflare colo code.
+    fn server_id(&self, meta: &serde_json::Value) -> Option<String>;
+
+    /// Plain-text trace endpoint (`key=value` lines naming the client IP and
+    /// the serving location), if the backend has one.
+    fn trace_url(&self) -> Option<Url> {
+        None
+    }
+
+    /// Response header carrying the client's public IP, if the backend
+    /// sends one.
+    fn client_ip_header(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// The provider `cfg` runs against.
+pub fn for_config(cfg: &RunConfig) -> Result<Arc<dyn Provider>> {
+    Ok(match cfg.provider {
+        ProviderKind::Cloudflare => Arc::new(Cloudflare::new(cfg)?),
+        ProviderKind::Librespeed => Arc::new(LibreSpeed::new(cfg)?),
+    })
+}
+
+/// speed.cloudflare.com, or a server with the same API at `base_url`.
+pub struct Cloudflare {
+    base_url: Url,
+    /// Target of latency probes, when not the speed test server
+    latency_url: Option<Url>,
+    meas_id: String,
+}
+
+impl Cloudflare {
+    pub fn new(cfg: &RunConfig) -> Result<Self> {
+        let base_url = Url::parse(&cfg.base_url).context("invalid base_url")?;
+        let latency_url = cfg
+            .latency_url
+            .as_deref()
+            .map(Url::parse)
+            .transpose()
+            .context("invalid latency URL")?;
+        Ok(Self {
+            base_url,
+            latency_url,
+            meas_id: cfg.meas_id.clone(),
+        })
+    }
+
+    fn endpoint(&self, path: &str) -> Url {
+        let mut url = self.base_url.join(path).expect("join endpoint path");
+        url.query_pairs_mut().append_pair("measId", &self.meas_id);
+        url
+    }
+}
+
+impl Provider for Cloudflare {
+    fn name(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    fn meta_url(&self) -> Option<Url> {
+        Some(self.endpoint("/meta"))
+    }
+
+    fn download_url(&self, bytes: u64) -> Url {
+        let mut url = self.endpoint("/__down");
+        url.query_pairs_mut()
+            .append_pair("bytes", &bytes.to_string());
+        url
+    }
+
+    fn upload_url(&self) -> Url {
+        self.endpoint("/__up")
+    }
+
+    fn latency_url(&self, during: Option<&str>) -> Url {
+        // Another server gets the URL as given; it won't know the query
+        if let Some(url) = &self.latency_url {
+            return url.clone();
+        }
+        let mut url = self.base_url.join("/__down").expect("join __down");
+        {
+            let mut qp = url.query_pairs_mut();
+            qp.append_pair("bytes", "0");
+            if let Some(d) = during {
+                qp.append_pair("during", d);
+            } else {
+                qp.append_pair("measId", &self.meas_id);
+            }
+        }
+        url
+    }
+
+    fn turn(&self) -> Option<TurnInfo> {
+        Some(TurnInfo {
+            urls: vec!["stun:turn.cloudflare.com:3478".to_string()],
+            username: None,
+            credential: None,
+        })
+    }
+
+    fn server_id(&self, meta: &serde_json::Value) -> Option<String> {
+        meta.get("colo")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    }
+
+    fn trace_url(&self) -> Option<Url> {
+        Some(
+            self.base_url
+                .join("/cdn-cgi/trace")
+                .expect("join trace path"),
+        )
+    }
+
+    fn client_ip_header(&self) -> Option<&'static str> {
+        Some("cf-meta-ip")
+    }
+}
+
+/// A LibreSpeed server at `base_url`, with its backend under `backend/`.
+pub struct LibreSpeed {
+    base_url: Url,
+    latency_url: Option<Url>,
+}
+
+impl LibreSpeed {
+    pub fn new(cfg: &RunConfig) -> Result<Self> {
+        let mut base_url = Url::parse(&cfg.base_url).context("invalid base_url")?;
+        // Endpoints are relative to the server's directory
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        let latency_url = cfg
+            .latency_url
+            .as_deref()
+            .map(Url::parse)
+            .transpose()
+            .context("invalid latency URL")?;
+        Ok(Self {
+            base_url,
+            latency_url,
+        })
+    }
+
+    fn endpoint(&self, path: &str) -> Url {
+        self.base_url.join(path).expect("join endpoint path")
+    }
+}
+
+impl Provider for LibreSpeed {
+    fn name(&self) -> &'static str {
+        "librespeed"
+    }
+
+    fn meta_url(&self) -> Option<Url> {
+        Some(self.endpoint("backend/getIP.php?isp=true"))
+    }
+
+    fn parse_meta(&self, meta: serde_json::Value) -> serde_json::Value {
+        let isp = meta.get("rawIspInfo").filter(|v| v.is_object());
+        let field = |key: &str| {
+            isp.and_then(|i| i.get(key))
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+        };
+        let mut parsed = serde_json::Map::new();
+        // "processedString" starts with the client IP, e.g. "192.0.2.1 - ISP, NL"
+        let ip = field("ip").or_else(|| {
+            meta.get("processedString")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.split(" - ").next())
+        });
+        if let Some(ip) = ip {
+            parsed.insert("clientIp".into(), ip.into());
+        }
+        for key in ["city", "region", "country"] {
+            if let Some(value) = field(key) {
+                parsed.insert(key.into(), value.into());
+            }
+        }
+        // ipinfo.io's "org" is e.g. "AS64496 Example ISP"
+        let org = field("org").and_then(|o| o.strip_prefix("AS")?.split_once(' '));
+        if let Some((asn, org)) = org {
+            if let Ok(asn) = asn.parse::<u64>() {
+                parsed.insert("asn".into(), asn.into());
+                parsed.insert("asOrganization".into(), org.into());
+            }
+        }
+        serde_json::Value::Object(parsed)
+    }
+
+    fn download_url(&self, bytes: u64) -> Url {
+        // The server sends whole chunks of 1 MiB
+        let chunks = bytes.div_ceil(1 << 20).max(1);
+        let mut url = self.endpoint("backend/garbage.php");
+        url.query_pairs_mut()
+            .append_pair("ckSize", &chunks.to_string());
+        url
+    }
+
+    fn upload_url(&self) -> Url {
+        self.endpoint("backend/empty.php")
+    }
+
+    fn latency_url(&self, _during: Option<&str>) -> Url {
+        match &self.latency_url {
+            Some(url) => url.clone(),
+            None => self.endpoint("backend/empty.php"),
+        }
+    }
+
+    fn turn(&self) -> Option<TurnInfo> {
+        None
+    }
+
+    fn server_id(&self, _meta: &serde_json::Value) -> Option<String> {
+        // LibreSpeed doesn't name its servers; the host does
+        self.base_url.host_str().map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloudflare_urls() {
+        use clap::Parser;
+
+        let args = crate::cli::Cli::parse_from(["cloudflare-speed-cli"]);
+        let cfg = RunConfig {
+            meas_id: "42".into(),
+            ..crate::cli::build_config(&args)
+        };
+        let provider = Cloudflare::new(&cfg).unwrap();
+        assert_eq!(
+            provider.download_url(1000).as_str(),
+            "https://speed.cloudflare.com/__down?measId=42&bytes=1000"
+        );
+        assert_eq!(
+            provider.latency_url(Some("upload")).as_str(),
+            "https://speed.cloudflare.com/__down?bytes=0&during=upload"
+        );
+        assert_eq!(
+            provider.server_id(&serde_json::json!({"colo": "FRA"})),
+            Some("FRA".to_string())
+        );
+        assert_eq!(
+            provider.trace_url().unwrap().as_str(),
+            "https://speed.cloudflare.com/cdn-cgi/trace"
+        );
+        assert_eq!(provider.client_ip_header(), Some("cf-meta-ip"));
+    }
+
+    #[test]
+    fn test_librespeed() {
+        use clap::Parser;
+
+        let args = crate::cli::Cli::parse_from([
+            "cloudflare-speed-cli",
+            "--provider",
+            "librespeed",
+            "--base-url",
+            "https://speed.example.org/test",
+        ]);
+        let provider = for_config(&crate::cli::build_config(&args)).unwrap();
+        assert_eq!(provider.name(), "librespeed");
+        assert_eq!(
+            provider.download_url(10_000_000).as_str(),
+            "https://speed.example.org/test/backend/garbage.php?ckSize=10"
+        );
+        let meta = provider.parse_meta(serde_json::json!({
+            "processedString": "192.0.2.1 - Example ISP, NL",
+            "rawIspInfo": {"ip": "192.0.2.1", "city": "Amsterdam", "country": "NL",
+                           "org": "AS64496 Example ISP"}
+        }));
+        assert_eq!(
+            meta,
+            serde_json::json!({"clientIp": "192.0.2.1", "city": "Amsterdam", "country": "NL",
+                               "asn": 64496, "asOrganization": "Example ISP"})
+        );
+        assert_eq!(
+            provider.server_id(&meta),
+            Some("speed.example.org".to_string())
+        );
+        // The Cloudflare-only diagnostics are skipped rather than failing
+        assert!(provider.trace_url().is_none());
+        assert!(provider.client_ip_header().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_librespeed_external_ips() {
+        use clap::Parser;
+
+        let args = crate::cli::Cli::parse_from([
+            "cloudflare-speed-cli",
+            "--provider",
+            "librespeed",
+            "--base-url",
+            "http://127.0.0.1:9/",
+        ]);
+        let provider = for_config(&crate::cli::build_config(&args)).unwrap();
+        let ips =
+            crate::engine::dns::fetch_external_ips(provider.as_ref(), &Default::default()).await;
+        assert_eq!(ips, (None, None));
+    }
+}
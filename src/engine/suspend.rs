@@ -0,0 +1,58 @@
+//! Detects the system sleeping during a run.
+//!
+//! The monotonic clock the phases are timed with stops while the system is
+//! suspended on Linux and macOS, but the wall clock keeps going, so a run
+//! that spans a suspend sees the two diverge. Such a run measured timeouts
+//! and reconnects rather than the network.
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// Divergence between the clocks above which the system counts as having
+/// slept; small enough to catch a short suspend, large enough to ignore NTP
+/// adjustments.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// How long the system slept, given the time that passed on the monotonic
+/// and the wall clock.
+fn suspend_gap(monotonic: Duration, wall: Duration) -> Option<Duration> {
+    wall.checked_sub(monotonic)
+        .filter(|gap| *gap > SUSPEND_THRESHOLD)
+}
+
+pub struct SuspendDetector {
+    started: Instant,
+    started_wall: SystemTime,
+}
+
+impl SuspendDetector {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            started_wall: SystemTime::now(),
+        }
+    }
+
+    /// If the system slept since the run started, why the run is invalid,
+    /// naming the `phase` that just finished.
+    pub fn check(&self, phase: &str) -> Option<String> {
+        let wall = self.started_wall.elapsed().ok()?;
+        let gap = suspend_gap(self.started.elapsed(), wall)?;
+        let gap = humantime::format_duration(Duration::from_secs(gap.as_secs()));
+        Some(format!("system slept for {gap} during the {phase} phase"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspend_gap() {
+        let secs = Duration::from_secs;
+        assert_eq!(suspend_gap(secs(30), secs(30)), None);
+        // Clock adjustments either way are ignored
+        assert_eq!(suspend_gap(secs(30), secs(33)), None);
+        assert_eq!(suspend_gap(secs(30), secs(10)), None);
+        assert_eq!(suspend_gap(secs(30), secs(330)), Some(secs(300)));
+    }
+}
@@ -1,14 +1,18 @@
 use crate::engine::cloudflare::CloudflareClient;
+use crate::engine::events::EventSender;
 use crate::engine::latency::run_latency_probes;
 use crate::engine::wait_if_paused_or_cancelled;
-use crate::model::{LatencySummary, Phase, RunConfig, TestEvent, ThroughputSummary};
+use crate::model::{LatencySummary, Phase, RunConfig, TestEvent, ThroughputSummary, UploadPattern};
+use crate::parity::Ladder;
 use anyhow::{Context, Result};
 use bytes::Bytes;
 use futures::{stream, StreamExt};
+use rand::RngCore;
 use reqwest::StatusCode;
+use std::future::Future;
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -17,6 +21,125 @@ use tokio::time::Instant;
 /// Chunk size for upload stream generation (64 KB)
 const UPLOAD_CHUNK_SIZE: u64 = 64 * 1024;
 const MIN_DOWNLOAD_BYTES_PER_REQ: u64 = 100_000;
+/// Consecutive failed requests after which a worker replaces its connection
+const RECONNECT_AFTER_FAILURES: u32 = 3;
+/// Longest pause between a worker's retries
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Sampling ticks (200 ms each) at link speed before a phase may end early
+const LINK_SATURATED_TICKS: usize = 10;
+/// Shortest phase when ending early, leaving a usable steady window
+const MIN_EARLY_STOP_DURATION: Duration = Duration::from_secs(3);
+
+/// Whether the last `LINK_SATURATED_TICKS` samples all saturate the link.
+fn link_saturated(mbps_samples: &[f64], link_speed_mbps: u64) -> bool {
+    mbps_samples.len() >= LINK_SATURATED_TICKS
+        && mbps_samples[mbps_samples.len() - LINK_SATURATED_TICKS..]
+            .iter()
+            .all(|&mbps| crate::network::saturates_link(mbps, link_speed_mbps))
+}
+
+/// Window over which a connection's rate is checked against the minimum rate
+const MIN_RATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Run `request`, abandoning it (returning `None`) when fewer than `min_rate`
+/// bytes per second are added to `transferred` over a `MIN_RATE_WINDOW`.
+async fn enforce_min_rate<F: Future>(
+    request: F,
+    transferred: &AtomicU64,
+    min_rate: Option<u64>,
+) -> Option<F::Output> {
+    let Some(min_rate) = min_rate else {
+        return Some(request.await);
+    };
+    tokio::pin!(request);
+    let min_bytes = (min_rate as f64 * MIN_RATE_WINDOW.as_secs_f64()) as u64;
+    // Skip the first window to allow for TCP slow start
+    let mut checks =
+        tokio::time::interval_at(Instant::now() + MIN_RATE_WINDOW * 2, MIN_RATE_WINDOW);
+    let mut last = transferred.load(Ordering::Relaxed);
+    loop {
+        tokio::select! {
+            output = &mut request => return Some(output),
+            _ = checks.tick() => {
+                let now = transferred.load(Ordering::Relaxed);
+                if now.saturating_sub(last) < min_bytes {
+                    return None;
+                }
+                last = now;
+            }
+        }
+    }
+}
+
+/// HTTP client for a download/upload worker. Each worker gets a client of
+/// its own, so workers don't share a pooled connection (or get multiplexed
+/// onto one HTTP/2 connection) and each measures a separate flow.
+fn worker_http(client: &CloudflareClient, cfg: &RunConfig) -> reqwest::Client {
+    CloudflareClient::reconnect(cfg).unwrap_or_else(|_| client.http.clone())
+}
+
+/// Upload body chunk as long as `zeros`: `zeros` itself, or fresh bytes for
+/// the other patterns, so no two chunks repeat.
+fn upload_chunk(pattern: UploadPattern, zeros: &Bytes) -> Bytes {
+    if pattern == UploadPattern::Zeros {
+        return zeros.clone();
+    }
+    let mut chunk = vec![0u8; zeros.len()];
+    rand::thread_rng().fill_bytes(&mut chunk);
+    if pattern == UploadPattern::Compressible {
+        // 16 letters carry 4 bits per byte
+        for b in &mut chunk {
+            *b = b'a' + (*b & 0x0f);
+        }
+    }
+    chunk.into()
+}
+
+/// Retry state of a download/upload worker. Failed requests are retried with
+/// exponential backoff, and a worker whose requests keep failing reconnects
+/// instead of spinning on a broken connection for the rest of the phase.
+struct WorkerRetry {
+    consecutive_failures: u32,
+    restarts: Arc<AtomicU64>,
+    /// Connections that completed a request, counted once per client
+    connections: Arc<AtomicU64>,
+    connected: bool,
+}
+
+impl WorkerRetry {
+    fn new(restarts: Arc<AtomicU64>, connections: Arc<AtomicU64>) -> Self {
+        Self {
+            consecutive_failures: 0,
+            restarts,
+            connections,
+            connected: false,
+        }
+    }
+
+    fn succeeded(&mut self) {
+        self.consecutive_failures = 0;
+        if !self.connected {
+            self.connected = true;
+            self.connections.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a failed request: reconnect `http` every few consecutive
+    /// failures, then wait before the next attempt.
+    async fn failed(&mut self, http: &mut reqwest::Client, cfg: &RunConfig) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures.is_multiple_of(RECONNECT_AFTER_FAILURES) {
+            if let Ok(fresh) = CloudflareClient::reconnect(cfg) {
+                *http = fresh;
+                self.restarts.fetch_add(1, Ordering::Relaxed);
+                self.connected = false;
+            }
+        }
+        let backoff = Duration::from_millis(100) * (1 << (self.consecutive_failures - 1).min(4));
+        tokio::time::sleep(backoff.min(MAX_RETRY_BACKOFF)).await;
+    }
+}
 
 fn throughput_summary(bytes: u64, duration: Duration, mbps_samples: &[f64]) -> ThroughputSummary {
     // Compute metrics using the same method as metrics.rs for consistency
@@ -34,15 +157,120 @@ fn throughput_summary(bytes: u64, duration: Duration, mbps_samples: &[f64]) -> T
 
     ThroughputSummary {
         bytes,
+        bytes_total: bytes,
         duration_ms: duration.as_millis() as u64,
         mbps,
         mean_mbps: Some(mean_mbps),
         median_mbps: Some(median_mbps),
         p25_mbps: Some(p25_mbps),
         p75_mbps: Some(p75_mbps),
+        worker_restarts: 0,
+        connections: 0,
+        link_limited: false,
+        stream_mbps: Vec::new(),
+        saturation_streams: None,
+        ramp_unfinished: false,
+        responses: None,
+        truncated_responses: None,
     }
 }
 
+/// Sampling ticks each stream count of an adaptive ramp-up runs for
+const RAMP_STEP_TICKS: usize = 5;
+/// Ticks at the end of a step that its throughput is taken from, leaving the
+/// new stream time to ramp up
+const RAMP_MEASURE_TICKS: usize = 3;
+/// Gain in throughput a stream must bring for the ramp-up to go on
+const RAMP_MIN_GAIN: f64 = 0.10;
+
+/// Adaptive concurrency: start with one stream and add one per step for as
+/// long as the last one raised throughput, up to the phase's stream count.
+struct Ramp {
+    max_streams: usize,
+    /// Streams running
+    streams: usize,
+    /// Sample at which the current step started
+    step_start: usize,
+    /// Throughput of the previous step
+    previous_mbps: Option<f64>,
+    done: bool,
+    /// Streams that saturated the path, once an adaptive ramp-up is over
+    saturated_at: Option<usize>,
+}
+
+impl Ramp {
+    fn new(adaptive: bool, max_streams: usize) -> Self {
+        // Nothing to ramp up to with a single stream
+        let adaptive = adaptive && max_streams > 1;
+        Self {
+            max_streams,
+            streams: if adaptive { 1 } else { max_streams },
+            step_start: 0,
+            previous_mbps: None,
+            done: !adaptive,
+            saturated_at: None,
+        }
+    }
+
+    /// Whether to start another stream, given the phase's samples so far.
+    fn add_stream(&mut self, mbps_samples: &[f64]) -> bool {
+        if self.done || mbps_samples.len() < self.step_start + RAMP_STEP_TICKS {
+            return false;
+        }
+        let window = &mbps_samples[mbps_samples.len() - RAMP_MEASURE_TICKS..];
+        let mbps = window.iter().sum::<f64>() / window.len() as f64;
+        self.step_start = mbps_samples.len();
+        if let Some(previous) = self.previous_mbps {
+            if mbps < previous * (1.0 + RAMP_MIN_GAIN) {
+                // The last stream didn't help
+                self.done = true;
+                self.saturated_at = Some(self.streams - 1);
+                return false;
+            }
+        }
+        if self.streams >= self.max_streams {
+            self.done = true;
+            self.saturated_at = Some(self.streams);
+            return false;
+        }
+        self.previous_mbps = Some(mbps);
+        self.streams += 1;
+        true
+    }
+
+    /// Whether the phase ended before the ramp-up found where throughput
+    /// stops rising.
+    fn unfinished(&self) -> bool {
+        !self.done
+    }
+
+    /// Streams that saturated the path, or the streams reached by an
+    /// unfinished ramp-up.
+    fn saturation_streams(&self) -> Option<u64> {
+        let streams = self
+            .saturated_at
+            .or(self.unfinished().then_some(self.streams));
+        streams.map(|streams| streams as u64)
+    }
+}
+
+/// Split the phase's `mbps` between its streams by the bytes each worker
+/// transferred; empty for a single stream.
+fn stream_mbps(mbps: f64, worker_bytes: &[Arc<AtomicU64>]) -> Vec<f64> {
+    let bytes: Vec<u64> = worker_bytes
+        .iter()
+        .map(|b| b.load(Ordering::Relaxed))
+        .collect();
+    let total: u64 = bytes.iter().sum();
+    if bytes.len() < 2 || total == 0 {
+        return Vec::new();
+    }
+    bytes
+        .iter()
+        .map(|&b| mbps * b as f64 / total as f64)
+        .collect()
+}
+
 fn estimate_steady_window(
     samples: &[(Instant, u64)],
     total_duration: Duration,
@@ -62,39 +290,84 @@ fn estimate_steady_window(
     Some((b_end.saturating_sub(b_start), dt))
 }
 
+/// Read a download response body until it ends or `stop` is set, calling
+/// `received` with the length of each chunk. Whether the body ended short of
+/// the `requested` bytes; None on a failed read.
+async fn read_body<E>(
+    body: impl futures::Stream<Item = Result<Bytes, E>>,
+    requested: u64,
+    stop: &AtomicBool,
+    mut received: impl FnMut(u64),
+) -> Option<bool> {
+    let mut body = std::pin::pin!(body);
+    let mut total = 0u64;
+    while let Some(chunk) = body.next().await {
+        let Ok(b) = chunk else { return None };
+        received(b.len() as u64);
+        total += b.len() as u64;
+        if stop.load(Ordering::Relaxed) {
+            return Some(false);
+        }
+    }
+    Some(total < requested)
+}
+
 pub async fn run_download_with_loaded_latency(
     client: &CloudflareClient,
     cfg: &RunConfig,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
     paused: Arc<AtomicBool>,
     cancel: Arc<AtomicBool>,
 ) -> Result<(ThroughputSummary, LatencySummary)> {
     let stop = Arc::new(AtomicBool::new(false));
     let total = Arc::new(AtomicU64::new(0));
     let errors = Arc::new(AtomicU64::new(0));
+    let restarts = Arc::new(AtomicU64::new(0));
+    let connections = Arc::new(AtomicU64::new(0));
+    let responses = Arc::new(AtomicU64::new(0));
+    let truncated = Arc::new(AtomicU64::new(0));
+    // With `website_parity`, speeds of the requests and whether the plan is done
+    let request_speeds = Arc::new(Mutex::new(Vec::new()));
+    let plan_done = Arc::new(AtomicBool::new(false));
 
     let mut handles = Vec::new();
-    for _ in 0..cfg.concurrency {
-        let http = client.http.clone();
-        let base_url = client.down_url();
-        let meas_id = client.meas_id.clone();
+    let mut worker_bytes = Vec::new();
+    let spawn_worker = |worker_bytes: &mut Vec<Arc<AtomicU64>>| {
+        let mut http = worker_http(client, cfg);
+        let provider = client.provider.clone();
         let mut bytes_per_req = cfg.download_bytes_per_req;
         let stop2 = stop.clone();
         let total2 = total.clone();
+        let worker_total = Arc::new(AtomicU64::new(0));
+        worker_bytes.push(worker_total.clone());
         let errors2 = errors.clone();
+        let responses2 = responses.clone();
+        let truncated2 = truncated.clone();
+        let request_speeds2 = request_speeds.clone();
+        let plan_done2 = plan_done.clone();
+        let mut ladder = cfg
+            .website_parity
+            .then(|| Ladder::new(crate::parity::DOWNLOAD_PLAN));
+        let mut retry = WorkerRetry::new(restarts.clone(), connections.clone());
+        let worker_cfg = cfg.clone();
         let ev_dl = event_tx.clone();
 
-        handles.push(tokio::spawn(async move {
+        tokio::spawn(async move {
             while !stop2.load(Ordering::Relaxed) {
-                let mut url = base_url.clone();
-                url.query_pairs_mut()
-                    .append_pair("measId", &meas_id)
-                    .append_pair("bytes", &bytes_per_req.to_string());
+                if let Some(ladder) = &ladder {
+                    let Some(bytes) = ladder.next_bytes() else {
+                        plan_done2.store(true, Ordering::Relaxed);
+                        break;
+                    };
+                    bytes_per_req = bytes;
+                }
+                let url = provider.download_url(bytes_per_req);
 
                 let resp = match http.get(url).send().await {
                     Ok(r) => r,
                     Err(_) => {
                         errors2.fetch_add(1, Ordering::Relaxed);
+                        retry.failed(&mut http, &worker_cfg).await;
                         continue;
                     }
                 };
@@ -115,28 +388,56 @@ pub async fn run_download_with_loaded_latency(
                                 .await;
                         }
                     }
-                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    retry.failed(&mut http, &worker_cfg).await;
                     continue;
                 }
 
-                let mut stream = resp.bytes_stream();
-                while let Some(chunk) = stream.next().await {
-                    let Ok(b) = chunk else { break };
-                    total2.fetch_add(b.len() as u64, Ordering::Relaxed);
-                    if stop2.load(Ordering::Relaxed) {
-                        break;
+                let transferred = AtomicU64::new(0);
+                let body_start = Instant::now();
+                let body = read_body(resp.bytes_stream(), bytes_per_req, &stop2, |len| {
+                    total2.fetch_add(len, Ordering::Relaxed);
+                    worker_total.fetch_add(len, Ordering::Relaxed);
+                    transferred.fetch_add(len, Ordering::Relaxed);
+                });
+                match enforce_min_rate(body, &transferred, worker_cfg.min_rate_bytes_per_sec).await
+                {
+                    Some(Some(short)) => {
+                        responses2.fetch_add(1, Ordering::Relaxed);
+                        if short {
+                            truncated2.fetch_add(1, Ordering::Relaxed);
+                        }
+                        if let Some(ladder) = &mut ladder {
+                            let duration = body_start.elapsed();
+                            let bytes = transferred.load(Ordering::Relaxed);
+                            if let Some(mbps) = crate::parity::request_mbps(bytes, duration) {
+                                request_speeds2.lock().unwrap().push(mbps);
+                            }
+                            ladder.record(duration);
+                        }
+                        retry.succeeded()
+                    }
+                    Some(None) => retry.failed(&mut http, &worker_cfg).await,
+                    // Too slow: count it as a failed request
+                    None => {
+                        errors2.fetch_add(1, Ordering::Relaxed);
+                        retry.failed(&mut http, &worker_cfg).await;
                     }
                 }
             }
-        }));
+        })
+    };
+    let mut ramp = Ramp::new(cfg.adaptive_concurrency, cfg.download_streams());
+    for _ in 0..ramp.streams {
+        handles.push(spawn_worker(&mut worker_bytes));
     }
 
-    // Loaded latency task (during download).
+    // Loaded latency task (during download), stopped together with the phase
     let (lat_tx, mut lat_rx) = mpsc::channel::<LatencySummary>(1);
     let client2 = client.clone();
     let ev2 = event_tx.clone();
     let paused2 = paused.clone();
-    let cancel2 = cancel.clone();
+    let lat_stop = Arc::new(AtomicBool::new(false));
+    let lat_stop2 = lat_stop.clone();
     let cfg2 = cfg.clone();
     let lat_handle = tokio::spawn(async move {
         let res = run_latency_probes(
@@ -148,7 +449,7 @@ pub async fn run_download_with_loaded_latency(
             cfg2.probe_timeout_ms,
             &ev2,
             paused2,
-            cancel2,
+            lat_stop2,
         )
         .await
         .unwrap_or_else(|_| LatencySummary::failed());
@@ -185,10 +486,33 @@ pub async fn run_download_with_loaded_latency(
             .await
             .ok();
 
+        if ramp.add_stream(&mbps_samples) {
+            handles.push(spawn_worker(&mut worker_bytes));
+        }
+        if plan_done.load(Ordering::Relaxed) && start.elapsed() >= crate::parity::MIN_PHASE {
+            break;
+        }
+
+        if let Some(link) = cfg.stop_at_link_speed_mbps {
+            if start.elapsed() >= MIN_EARLY_STOP_DURATION && link_saturated(&mbps_samples, link) {
+                event_tx
+                    .send(TestEvent::Info {
+                        message: format!(
+                            "Download: limited by {} NIC, ending phase early",
+                            crate::network::format_link_speed(link)
+                        ),
+                    })
+                    .await
+                    .ok();
+                break;
+            }
+        }
+
         tokio::time::sleep(Duration::from_millis(200)).await;
     }
 
     stop.store(true, Ordering::Relaxed);
+    lat_stop.store(true, Ordering::Relaxed);
     for h in handles {
         let _ = h.await;
     }
@@ -196,17 +520,55 @@ pub async fn run_download_with_loaded_latency(
     let duration = start.elapsed();
     let bytes_total = total.load(Ordering::Relaxed);
     let error_count = errors.load(Ordering::Relaxed);
+    let restart_count = restarts.load(Ordering::Relaxed);
     if error_count > 0 {
         event_tx
             .send(TestEvent::Info {
-                message: format!("Download: {} request(s) failed", error_count),
+                message: format!(
+                    "Download: {} request(s) failed, {} worker restart(s)",
+                    error_count, restart_count
+                ),
             })
             .await
             .ok();
     }
     let (bytes, window) =
         estimate_steady_window(&samples, duration).unwrap_or((bytes_total, duration));
-    let dl = throughput_summary(bytes, window, &mbps_samples);
+    let mut dl = throughput_summary(bytes, window, &mbps_samples);
+    if let Some(mbps) = crate::parity::bandwidth_mbps(&request_speeds.lock().unwrap()) {
+        dl.mbps = mbps;
+    }
+    dl.bytes_total = bytes_total;
+    dl.worker_restarts = restart_count;
+    dl.connections = connections.load(Ordering::Relaxed);
+    dl.stream_mbps = stream_mbps(dl.mbps, &worker_bytes);
+    let (responses, truncated) = (
+        responses.load(Ordering::Relaxed),
+        truncated.load(Ordering::Relaxed),
+    );
+    dl.responses = Some(responses);
+    dl.truncated_responses = Some(truncated);
+    if truncated > 0 {
+        event_tx
+            .send(TestEvent::Info {
+                message: format!(
+                    "Download: {} of {} response(s) ended before the bytes requested",
+                    truncated, responses
+                ),
+            })
+            .await
+            .ok();
+    }
+    dl.saturation_streams = ramp.saturation_streams();
+    dl.ramp_unfinished = ramp.unfinished();
+    if let Some(streams) = dl.saturation_streams {
+        let message = if dl.ramp_unfinished {
+            format!("Download: the ramp-up reached {streams} stream(s) before the phase ended")
+        } else {
+            format!("Download: throughput stopped rising at {streams} stream(s)")
+        };
+        event_tx.send(TestEvent::Info { message }).await.ok();
+    }
 
     // Wait for latency results with a timeout to prevent indefinite hangs
     let loaded_latency = tokio::time::timeout(Duration::from_secs(30), lat_rx.recv())
@@ -220,29 +582,71 @@ pub async fn run_download_with_loaded_latency(
     Ok((dl, loaded_latency))
 }
 
+/// Measure download over a single stream, for comparison with the
+/// multi-stream download phase. Its progress isn't reported, so it doesn't
+/// show up as part of that phase.
+pub async fn run_single_stream_download(
+    client: &CloudflareClient,
+    cfg: &RunConfig,
+    paused: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+) -> Result<ThroughputSummary> {
+    let cfg = RunConfig {
+        single_connection: true,
+        ..cfg.clone()
+    };
+    let (tx, _) = mpsc::channel(1);
+    let quiet = EventSender::new(tx, &cfg.meas_id);
+    let (download, _) =
+        run_download_with_loaded_latency(client, &cfg, &quiet, paused, cancel).await?;
+    Ok(download)
+}
+
 pub async fn run_upload_with_loaded_latency(
     client: &CloudflareClient,
     cfg: &RunConfig,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
     paused: Arc<AtomicBool>,
     cancel: Arc<AtomicBool>,
 ) -> Result<(ThroughputSummary, LatencySummary)> {
     let stop = Arc::new(AtomicBool::new(false));
     let total = Arc::new(AtomicU64::new(0));
     let errors = Arc::new(AtomicU64::new(0));
+    let restarts = Arc::new(AtomicU64::new(0));
+    let connections = Arc::new(AtomicU64::new(0));
+    // With `website_parity`, speeds of the requests and whether the plan is done
+    let request_speeds = Arc::new(Mutex::new(Vec::new()));
+    let plan_done = Arc::new(AtomicBool::new(false));
 
     let mut handles = Vec::new();
-    for _ in 0..cfg.concurrency {
-        let http = client.http.clone();
-        let mut url = client.up_url();
-        url.query_pairs_mut().append_pair("measId", &client.meas_id);
+    let mut worker_bytes = Vec::new();
+    let spawn_worker = |worker_bytes: &mut Vec<Arc<AtomicU64>>| {
+        let mut http = worker_http(client, cfg);
+        let url = client.provider.upload_url();
         let stop2 = stop.clone();
         let total2 = total.clone();
+        let worker_total = Arc::new(AtomicU64::new(0));
+        worker_bytes.push(worker_total.clone());
         let errors2 = errors.clone();
-        let bytes_per_req = cfg.upload_bytes_per_req;
-
-        handles.push(tokio::spawn(async move {
+        let mut retry = WorkerRetry::new(restarts.clone(), connections.clone());
+        let worker_cfg = cfg.clone();
+        let mut bytes_per_req = cfg.upload_bytes_per_req;
+        let pattern = cfg.upload_pattern;
+        let request_speeds2 = request_speeds.clone();
+        let plan_done2 = plan_done.clone();
+        let mut ladder = cfg
+            .website_parity
+            .then(|| Ladder::new(crate::parity::UPLOAD_PLAN));
+
+        tokio::spawn(async move {
             while !stop2.load(Ordering::Relaxed) {
+                if let Some(ladder) = &ladder {
+                    let Some(bytes) = ladder.next_bytes() else {
+                        plan_done2.store(true, Ordering::Relaxed);
+                        break;
+                    };
+                    bytes_per_req = bytes;
+                }
                 // Generate upload body as a bounded stream of bytes.
                 // We count bytes as we *produce* chunks for reqwest. This is a close approximation
                 // of bytes put on the wire and produces stable realtime Mbps for the UI.
@@ -255,7 +659,7 @@ pub async fn run_upload_with_loaded_latency(
                 let chunk_full = chunk.clone();
                 let s_full = stream::iter(0..full).map(move |_| {
                     total2a.fetch_add(UPLOAD_CHUNK_SIZE, Ordering::Relaxed);
-                    Ok::<Bytes, std::io::Error>(chunk_full.clone())
+                    Ok::<Bytes, std::io::Error>(upload_chunk(pattern, &chunk_full))
                 });
 
                 let body_stream = if tail == 0 {
@@ -265,25 +669,62 @@ pub async fn run_upload_with_loaded_latency(
                     let chunk_tail = chunk.slice(..tail as usize);
                     let s_tail = stream::once(async move {
                         total2b.fetch_add(tail, Ordering::Relaxed);
-                        Ok::<Bytes, std::io::Error>(chunk_tail)
+                        Ok::<Bytes, std::io::Error>(upload_chunk(pattern, &chunk_tail))
                     });
                     s_full.chain(s_tail).boxed()
                 };
 
+                let transferred = Arc::new(AtomicU64::new(0));
+                let transferred2 = transferred.clone();
+                let worker_total2 = worker_total.clone();
+                let body_stream = body_stream.inspect(move |chunk| {
+                    if let Ok(b) = chunk {
+                        transferred2.fetch_add(b.len() as u64, Ordering::Relaxed);
+                        worker_total2.fetch_add(b.len() as u64, Ordering::Relaxed);
+                    }
+                });
+
                 let body = reqwest::Body::wrap_stream(body_stream);
-                if http.post(url.clone()).body(body).send().await.is_err() {
-                    errors2.fetch_add(1, Ordering::Relaxed);
+                let request_start = Instant::now();
+                let request = http.post(url.clone()).body(body).send();
+                match enforce_min_rate(request, &transferred, worker_cfg.min_rate_bytes_per_sec)
+                    .await
+                {
+                    Some(Ok(resp)) => {
+                        // The body is sent once the response starts
+                        if let Some(ladder) = &mut ladder {
+                            let duration = request_start.elapsed();
+                            let transfer =
+                                duration.saturating_sub(crate::parity::server_time(resp.headers()));
+                            if let Some(mbps) = crate::parity::request_mbps(bytes_per_req, transfer)
+                            {
+                                request_speeds2.lock().unwrap().push(mbps);
+                            }
+                            ladder.record(duration);
+                        }
+                        retry.succeeded()
+                    }
+                    // Failed, or abandoned for being too slow
+                    Some(Err(_)) | None => {
+                        errors2.fetch_add(1, Ordering::Relaxed);
+                        retry.failed(&mut http, &worker_cfg).await;
+                    }
                 }
             }
-        }));
+        })
+    };
+    let mut ramp = Ramp::new(cfg.adaptive_concurrency, cfg.upload_streams());
+    for _ in 0..ramp.streams {
+        handles.push(spawn_worker(&mut worker_bytes));
     }
 
-    // Loaded latency task (during upload).
+    // Loaded latency task (during upload), stopped together with the phase
     let (lat_tx, mut lat_rx) = mpsc::channel::<LatencySummary>(1);
     let client2 = client.clone();
     let ev2 = event_tx.clone();
     let paused2 = paused.clone();
-    let cancel2 = cancel.clone();
+    let lat_stop = Arc::new(AtomicBool::new(false));
+    let lat_stop2 = lat_stop.clone();
     let cfg2 = cfg.clone();
     let lat_handle = tokio::spawn(async move {
         let res = run_latency_probes(
@@ -295,7 +736,7 @@ pub async fn run_upload_with_loaded_latency(
             cfg2.probe_timeout_ms,
             &ev2,
             paused2,
-            cancel2,
+            lat_stop2,
         )
         .await
         .unwrap_or_else(|_| LatencySummary::failed());
@@ -332,10 +773,33 @@ pub async fn run_upload_with_loaded_latency(
             .await
             .ok();
 
+        if ramp.add_stream(&mbps_samples) {
+            handles.push(spawn_worker(&mut worker_bytes));
+        }
+        if plan_done.load(Ordering::Relaxed) && start.elapsed() >= crate::parity::MIN_PHASE {
+            break;
+        }
+
+        if let Some(link) = cfg.stop_at_link_speed_mbps {
+            if start.elapsed() >= MIN_EARLY_STOP_DURATION && link_saturated(&mbps_samples, link) {
+                event_tx
+                    .send(TestEvent::Info {
+                        message: format!(
+                            "Upload: limited by {} NIC, ending phase early",
+                            crate::network::format_link_speed(link)
+                        ),
+                    })
+                    .await
+                    .ok();
+                break;
+            }
+        }
+
         tokio::time::sleep(Duration::from_millis(200)).await;
     }
 
     stop.store(true, Ordering::Relaxed);
+    lat_stop.store(true, Ordering::Relaxed);
     for h in handles {
         let _ = h.await;
     }
@@ -343,17 +807,38 @@ pub async fn run_upload_with_loaded_latency(
     let duration = start.elapsed();
     let bytes_total = total.load(Ordering::Relaxed);
     let error_count = errors.load(Ordering::Relaxed);
+    let restart_count = restarts.load(Ordering::Relaxed);
     if error_count > 0 {
         event_tx
             .send(TestEvent::Info {
-                message: format!("Upload: {} request(s) failed", error_count),
+                message: format!(
+                    "Upload: {} request(s) failed, {} worker restart(s)",
+                    error_count, restart_count
+                ),
             })
             .await
             .ok();
     }
     let (bytes, window) =
         estimate_steady_window(&samples, duration).unwrap_or((bytes_total, duration));
-    let up = throughput_summary(bytes, window, &mbps_samples);
+    let mut up = throughput_summary(bytes, window, &mbps_samples);
+    if let Some(mbps) = crate::parity::bandwidth_mbps(&request_speeds.lock().unwrap()) {
+        up.mbps = mbps;
+    }
+    up.bytes_total = bytes_total;
+    up.worker_restarts = restart_count;
+    up.connections = connections.load(Ordering::Relaxed);
+    up.stream_mbps = stream_mbps(up.mbps, &worker_bytes);
+    up.saturation_streams = ramp.saturation_streams();
+    up.ramp_unfinished = ramp.unfinished();
+    if let Some(streams) = up.saturation_streams {
+        let message = if up.ramp_unfinished {
+            format!("Upload: the ramp-up reached {streams} stream(s) before the phase ended")
+        } else {
+            format!("Upload: throughput stopped rising at {streams} stream(s)")
+        };
+        event_tx.send(TestEvent::Info { message }).await.ok();
+    }
 
     // Wait for latency results with a timeout to prevent indefinite hangs
     let loaded_latency = tokio::time::timeout(Duration::from_secs(30), lat_rx.recv())
@@ -366,3 +851,84 @@ pub async fn run_upload_with_loaded_latency(
 
     Ok((up, loaded_latency))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_mbps() {
+        let workers = |bytes: &[u64]| -> Vec<Arc<AtomicU64>> {
+            bytes.iter().map(|&b| Arc::new(AtomicU64::new(b))).collect()
+        };
+        assert_eq!(stream_mbps(100.0, &workers(&[300, 100])), vec![75.0, 25.0]);
+        assert!(stream_mbps(100.0, &workers(&[400])).is_empty());
+        assert!(stream_mbps(100.0, &workers(&[0, 0])).is_empty());
+    }
+
+    #[test]
+    fn test_adaptive_ramp() {
+        let mut ramp = Ramp::new(true, 4);
+        assert_eq!(ramp.streams, 1);
+        let mut samples = vec![100.0; RAMP_STEP_TICKS - 1];
+        assert!(!ramp.add_stream(&samples));
+        samples.push(100.0);
+        assert!(ramp.add_stream(&samples));
+        // A second stream doubles throughput, a third adds little
+        samples.extend([200.0; RAMP_STEP_TICKS]);
+        assert!(ramp.add_stream(&samples));
+        samples.extend([205.0; RAMP_STEP_TICKS]);
+        assert!(!ramp.add_stream(&samples));
+        assert_eq!(ramp.saturation_streams(), Some(2));
+
+        // A phase that ends mid ramp-up reports the streams reached
+        let mut ramp = Ramp::new(true, 4);
+        assert!(ramp.add_stream(&[100.0; RAMP_STEP_TICKS]));
+        assert!(ramp.unfinished());
+        assert_eq!(ramp.saturation_streams(), Some(2));
+
+        let fixed = Ramp::new(false, 4);
+        assert_eq!((fixed.streams, fixed.saturation_streams()), (4, None));
+        assert!(!fixed.unfinished());
+    }
+
+    #[tokio::test]
+    async fn test_read_body_truncation() {
+        let chunks = |sizes: &[usize]| {
+            let chunks: Vec<Result<Bytes, ()>> = sizes
+                .iter()
+                .map(|&n| Ok(Bytes::from(vec![0u8; n])))
+                .collect();
+            stream::iter(chunks)
+        };
+        let stop = AtomicBool::new(false);
+        let mut received = 0;
+        // A body the server ended early
+        let short = read_body(chunks(&[400, 300]), 1000, &stop, |len| received += len).await;
+        assert_eq!((short, received), (Some(true), 700));
+        assert_eq!(
+            read_body(chunks(&[600, 400]), 1000, &stop, |_| {}).await,
+            Some(false)
+        );
+        let failed = stream::iter(vec![Ok(Bytes::from_static(b"abc")), Err(())]);
+        assert_eq!(read_body(failed, 1000, &stop, |_| {}).await, None);
+        // Stopping mid-body isn't a truncation
+        stop.store(true, Ordering::Relaxed);
+        assert_eq!(
+            read_body(chunks(&[100]), 1000, &stop, |_| {}).await,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_upload_chunk_patterns() {
+        let zeros = Bytes::from(vec![0u8; 4096]);
+        assert_eq!(upload_chunk(UploadPattern::Zeros, &zeros), zeros);
+        let random = upload_chunk(UploadPattern::Random, &zeros);
+        assert_eq!(random.len(), zeros.len());
+        assert_ne!(random, upload_chunk(UploadPattern::Random, &zeros));
+        let letters = upload_chunk(UploadPattern::Compressible, &zeros);
+        assert_eq!(letters.len(), zeros.len());
+        assert!(letters.iter().all(|b| (b'a'..=b'p').contains(b)));
+    }
+}
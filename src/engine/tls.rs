@@ -9,7 +9,7 @@ use tokio::net::TcpStream;
 use tokio_rustls::TlsConnector;
 
 /// Install the ring crypto provider if not already installed.
-fn ensure_crypto_provider() {
+pub fn ensure_crypto_provider() {
     // Install the ring provider as the default crypto provider.
     // This is safe to call multiple times - it will be a no-op if already installed.
     let _ = rustls::crypto::ring::default_provider().install_default();
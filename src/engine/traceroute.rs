@@ -4,6 +4,7 @@
 //! Uses raw ICMP sockets when available (requires CAP_NET_RAW or root),
 //! with fallback to system traceroute command.
 
+use crate::engine::events::EventSender;
 use crate::model::{TestEvent, TracerouteHop, TracerouteSummary};
 use anyhow::{Context, Result};
 use pnet_packet::icmp::IcmpTypes;
@@ -13,7 +14,6 @@ use std::mem::MaybeUninit;
 use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::process::Command;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
 
 /// Number of probes per hop
 const PROBES_PER_HOP: usize = 3;
@@ -27,7 +27,7 @@ const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 pub async fn run_traceroute(
     destination: &str,
     max_hops: u8,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
 ) -> Result<TracerouteSummary> {
     // Resolve destination to IP
     let ip = resolve_destination(destination)?;
@@ -70,7 +70,7 @@ fn resolve_destination(destination: &str) -> Result<IpAddr> {
 async fn run_icmp_traceroute(
     destination: &IpAddr,
     max_hops: u8,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
 ) -> Result<TracerouteSummary> {
     // Check if we're dealing with IPv4 - IPv6 traceroute is more complex
     let dest_v4 = match destination {
@@ -241,7 +241,7 @@ async fn run_system_traceroute(
     destination: &str,
     destination_ip: &IpAddr,
     max_hops: u8,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
 ) -> Result<TracerouteSummary> {
     // Clone strings to avoid lifetime issues with spawn_blocking
     let dest = destination.to_string();
@@ -295,7 +295,7 @@ async fn run_system_traceroute(
 /// Parse traceroute command output into hop structures.
 async fn parse_traceroute_output(
     output: &str,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
 ) -> Vec<TracerouteHop> {
     let mut hops = Vec::new();
 
@@ -1,5 +1,6 @@
+use crate::engine::events::EventSender;
 use crate::engine::network_bind;
-use crate::model::{ExperimentalUdpSummary, RunConfig, TestEvent, TurnInfo};
+use crate::model::{PacketLossSummary, RunConfig, TestEvent, TurnInfo};
 use crate::stats::{latency_summary_from_samples, OnlineStats};
 use anyhow::{Context, Result};
 use rand::RngCore;
@@ -7,7 +8,6 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::net::UdpSocket;
-use tokio::sync::mpsc;
 
 /// Calculate Mean Opinion Score (MOS) using simplified ITU-T G.107 E-model.
 /// (this is lifted from Claude I haven't verified it yet)
@@ -136,12 +136,18 @@ fn parse_host_port(url: &str) -> Result<(String, u16)> {
     Ok((host.to_string(), port))
 }
 
+/// Host and port of the server the probe talks to.
+pub fn stun_target(turn: &TurnInfo) -> Result<(String, u16)> {
+    let target_url = pick_stun_target(turn).context("no stun/turn url in /__turn")?;
+    parse_host_port(&target_url)
+}
+
 pub async fn run_udp_like_loss_probe(
     turn: &TurnInfo,
     cfg: &RunConfig,
-    event_tx: &mpsc::Sender<TestEvent>,
+    event_tx: &EventSender,
     pre_resolved: Option<SocketAddr>,
-) -> Result<ExperimentalUdpSummary> {
+) -> Result<PacketLossSummary> {
     let target_url = pick_stun_target(turn).context("no stun/turn url in /__turn")?;
     let (host, port) = parse_host_port(&target_url)?;
 
@@ -149,13 +155,18 @@ pub async fn run_udp_like_loss_probe(
         a
     } else {
         let mut addrs = tokio::net::lookup_host((host.as_str(), port)).await?;
-        addrs.next().context("dns returned no addresses")?
+        addrs
+            .find(|a| cfg.allows_ip(a.ip()))
+            .context("dns returned no addresses")?
     };
 
     // Bind UDP socket to interface or source IP if specified
     let sock = if cfg.interface.is_some() || cfg.source_ip.is_some() {
-        let bind_addr =
-            network_bind::resolve_bind_address(cfg.interface.as_ref(), cfg.source_ip.as_ref())?;
+        let bind_addr = network_bind::resolve_bind_address(
+            cfg.interface.as_ref(),
+            cfg.source_ip.as_ref(),
+            cfg.ipv6_address,
+        )?;
 
         if let Some(addr) = bind_addr {
             // Create socket using socket2 for binding
@@ -176,21 +187,27 @@ pub async fn run_udp_like_loss_probe(
                     std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid interface name")
                 })?;
 
-                unsafe {
-                    if libc::setsockopt(
+                let rc = unsafe {
+                    libc::setsockopt(
                         socket.as_raw_fd(),
                         libc::SOL_SOCKET,
                         libc::SO_BINDTODEVICE,
                         ifname.as_ptr() as *const libc::c_void,
                         ifname.as_bytes().len() as libc::socklen_t,
-                    ) != 0
-                    {
-                        return Err(anyhow::anyhow!(
-                            "Failed to bind to interface {}: {}",
-                            iface,
-                            std::io::Error::last_os_error()
-                        ));
-                    }
+                    )
+                };
+                if rc != 0 {
+                    // Some kernels and sandboxes refuse this without CAP_NET_RAW;
+                    // the socket is still bound to the interface's address
+                    let err = std::io::Error::last_os_error();
+                    event_tx
+                        .send(TestEvent::Info {
+                            message: format!(
+                                "Could not bind UDP probe to interface {iface} ({err}); using its address only"
+                            ),
+                        })
+                        .await
+                        .ok();
                 }
             }
 
@@ -307,7 +324,7 @@ pub async fn run_udp_like_loss_probe(
 
     let label = quality_label(loss_pct);
 
-    Ok(ExperimentalUdpSummary {
+    Ok(PacketLossSummary {
         target: Some(target_url),
         latency,
         out_of_order,
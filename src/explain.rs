@@ -0,0 +1,403 @@
+//! `explain` subcommand: the triage experienced users apply to a result by
+//! hand. Each check of a saved run that points somewhere adds weight to a
+//! bottleneck category, and the heaviest category is reported as the most
+//! likely one.
+
+use crate::config::Config;
+use crate::metrics::median;
+use crate::model::RunResult;
+use anyhow::Result;
+
+/// Where a result's limit most likely sits, from the client outward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bottleneck {
+    Client,
+    WiFi,
+    Lan,
+    IspAccess,
+    Peering,
+    Server,
+}
+
+impl Bottleneck {
+    pub fn label(self) -> &'static str {
+        match self {
+            Bottleneck::Client => "client device",
+            Bottleneck::WiFi => "Wi-Fi",
+            Bottleneck::Lan => "LAN",
+            Bottleneck::IspAccess => "ISP access",
+            Bottleneck::Peering => "peering/routing",
+            Bottleneck::Server => "test server",
+        }
+    }
+}
+
+/// One observation about a run, pointing at `category` with `weight`, or
+/// just informative.
+#[derive(Debug)]
+pub struct Finding {
+    pub category: Option<(Bottleneck, u32)>,
+    pub text: String,
+}
+
+fn finding(category: Bottleneck, weight: u32, text: String) -> Finding {
+    Finding {
+        category: Some((category, weight)),
+        text,
+    }
+}
+
+fn note(text: String) -> Finding {
+    Finding {
+        category: None,
+        text,
+    }
+}
+
+/// Loss above which a run is considered lossy (fraction of probes)
+const LOSSY: f64 = 0.01;
+/// Loaded latency increase over idle that points to full queues (ms)
+const BUFFERBLOAT_MS: f64 = 60.0;
+/// Idle latency to the edge above what its distance explains (ms)
+const DETOUR_MS: f64 = 25.0;
+/// First-hop round trip that points to the local network (ms)
+const SLOW_FIRST_HOP_MS: f64 = 10.0;
+/// Round-trip increase between two traceroute hops worth pointing out (ms)
+const HOP_JUMP_MS: f64 = 20.0;
+
+/// Findings about `run`, in the order the checks go from the client outward.
+pub fn findings(run: &RunResult) -> Vec<Finding> {
+    let mut out = Vec::new();
+    let wireless = run.is_wireless == Some(true);
+    let local = if wireless {
+        Bottleneck::WiFi
+    } else {
+        Bottleneck::Lan
+    };
+
+    // Client
+    if let Some(thermal) = run.thermal.as_ref().filter(|t| t.throttled) {
+        out.push(finding(
+            Bottleneck::Client,
+            3,
+            format!(
+                "The CPU was throttled during the test ({}), so the client may not have kept up",
+                thermal.describe()
+            ),
+        ));
+    }
+    if run.power.as_ref().is_some_and(|p| p.on_battery) {
+        out.push(finding(
+            Bottleneck::Client,
+            1,
+            "The machine ran on battery; power saving can slow its network adapter".into(),
+        ));
+    }
+
+    // Local link
+    if let Some(link) = run.link_speed_mbps {
+        let fastest = run.download.mbps.max(run.upload.mbps);
+        if run.download.link_limited
+            || run.upload.link_limited
+            || crate::network::saturates_link(fastest, link)
+        {
+            out.push(finding(
+                Bottleneck::Lan,
+                4,
+                format!(
+                    "{:.0} Mbps fills the {} link of the network interface; the connection may be faster",
+                    fastest,
+                    crate::network::format_link_speed(link)
+                ),
+            ));
+        } else {
+            out.push(note(format!(
+                "The {} link of the network interface leaves headroom ({:.0}% used)",
+                crate::network::format_link_speed(link),
+                fastest * 100.0 / link as f64
+            )));
+        }
+    }
+    if wireless {
+        out.push(finding(
+            Bottleneck::WiFi,
+            1,
+            "The test ran over Wi-Fi, which often limits throughput and adds latency; compare with a wired run".into(),
+        ));
+    }
+    if let Some(cellular) = run.cellular.as_ref() {
+        out.push(finding(
+            Bottleneck::IspAccess,
+            1,
+            format!(
+                "The test ran over a cellular connection ({}), where radio conditions set the limit",
+                cellular.describe()
+            ),
+        ));
+    }
+    if let Some(first) = run
+        .traceroute
+        .as_ref()
+        .and_then(|tr| tr.hops.first())
+        .and_then(|hop| median(&hop.rtt_ms))
+        .filter(|&ms| ms > SLOW_FIRST_HOP_MS)
+    {
+        out.push(finding(
+            local,
+            2,
+            format!(
+                "The first hop (the router) takes {first:.1} ms to answer; on a healthy {} it takes 1-2 ms",
+                if wireless { "Wi-Fi" } else { "LAN" }
+            ),
+        ));
+    }
+
+    // Queues and loss
+    if let (Some(idle), Some(score)) = (run.idle_latency.median_ms, run.bufferbloat_score()) {
+        let loaded = [
+            run.loaded_latency_download.median_ms,
+            run.loaded_latency_upload.median_ms,
+        ]
+        .into_iter()
+        .flatten()
+        .fold(idle, f64::max);
+        let grade = crate::metrics::BUFFERBLOAT_GRADES[score];
+        if loaded - idle > BUFFERBLOAT_MS {
+            out.push(finding(
+                Bottleneck::IspAccess,
+                2,
+                format!(
+                    "Latency rises from {idle:.0} ms idle to {loaded:.0} ms under load (bufferbloat {grade}): \
+                     queues fill at the slowest link, usually the access line; SQM on the router helps"
+                ),
+            ));
+            if wireless {
+                out.push(finding(
+                    Bottleneck::WiFi,
+                    1,
+                    "Wi-Fi buffers a lot too, which adds to the latency under load".into(),
+                ));
+            }
+        } else {
+            out.push(note(format!(
+                "Latency stays close to idle under load (bufferbloat {grade})"
+            )));
+        }
+    }
+    let loss = run
+        .packet_loss
+        .as_ref()
+        .map(|p| p.latency.loss)
+        .unwrap_or(0.0)
+        .max(run.idle_latency.loss);
+    if loss > LOSSY {
+        let category = if wireless {
+            Bottleneck::WiFi
+        } else {
+            Bottleneck::IspAccess
+        };
+        out.push(finding(
+            category,
+            2,
+            format!(
+                "{:.1}% of probes were lost, which also holds back each TCP stream",
+                loss * 100.0
+            ),
+        ));
+    }
+
+    // Path to the edge
+    if let (Some(idle), Some(km)) = (
+        run.idle_latency.median_ms,
+        run.geo.as_ref().and_then(|g| g.distance_km),
+    ) {
+        // Light in fibre covers about 100 km per ms of round trip, plus
+        // a few ms of access latency
+        let expected = km / 100.0 + 5.0;
+        if idle > expected + DETOUR_MS {
+            out.push(finding(
+                Bottleneck::Peering,
+                2,
+                format!(
+                    "Idle latency of {idle:.0} ms is well above the ~{expected:.0} ms the {km:.0} km to the edge explain: \
+                     traffic takes a detour on the way"
+                ),
+            ));
+        }
+    }
+    if let Some(hops) = run.traceroute.as_ref().map(|tr| &tr.hops) {
+        let rtts: Vec<(u8, f64)> = hops
+            .iter()
+            .filter_map(|hop| Some((hop.hop_number, median(&hop.rtt_ms)?)))
+            .collect();
+        let jump = rtts
+            .windows(2)
+            .map(|w| (w[1].0, w[1].1 - w[0].1))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, jump)| *jump > HOP_JUMP_MS);
+        if let Some((hop, jump)) = jump {
+            let category = if hop <= 3 {
+                Bottleneck::IspAccess
+            } else {
+                Bottleneck::Peering
+            };
+            out.push(finding(
+                category,
+                1,
+                format!("Round-trip time jumps by {jump:.0} ms at traceroute hop {hop}"),
+            ));
+        }
+    }
+    if let Some(ratio) = run.stream_ratio().filter(|&r| r > 2.0) {
+        out.push(finding(
+            Bottleneck::Peering,
+            1,
+            format!(
+                "Many streams get {ratio:.1}x one stream's throughput: each flow is held back by loss or latency on the path"
+            ),
+        ));
+    }
+    if let Some(cmp) = run.ip_comparison.as_ref() {
+        if let (Some(v4), Some(v6)) = (
+            cmp.ipv4_result.as_ref().filter(|r| r.available),
+            cmp.ipv6_result.as_ref().filter(|r| r.available),
+        ) {
+            let (slow, fast) = if v4.download_mbps < v6.download_mbps {
+                ("IPv4", "IPv6")
+            } else {
+                ("IPv6", "IPv4")
+            };
+            let (low, high) = (
+                v4.download_mbps.min(v6.download_mbps),
+                v4.download_mbps.max(v6.download_mbps),
+            );
+            if high > low * 1.3 {
+                out.push(finding(
+                    Bottleneck::Peering,
+                    1,
+                    format!(
+                        "{slow} downloads much slower than {fast}: the two are routed differently"
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Server
+    if let Some(pct) = run.download.truncated_pct() {
+        out.push(finding(
+            Bottleneck::Server,
+            2,
+            format!("{pct:.1}% of download responses ended early on the server's side"),
+        ));
+    }
+    let restarts = run.download.worker_restarts + run.upload.worker_restarts;
+    if restarts > 0 {
+        out.push(finding(
+            Bottleneck::Server,
+            1,
+            format!("Workers had to reconnect {restarts} time(s) after failed requests"),
+        ));
+    }
+
+    // Asymmetry is normal for most access technologies
+    if run.download.mbps > 0.0 && run.upload.mbps > 0.0 {
+        out.push(note(format!(
+            "Download is {:.1}x upload",
+            run.download.mbps / run.upload.mbps
+        )));
+    }
+    out
+}
+
+/// The category the findings weigh most towards, if any.
+pub fn verdict(findings: &[Finding]) -> Option<Bottleneck> {
+    let mut weights = std::collections::BTreeMap::new();
+    for (category, weight) in findings.iter().filter_map(|f| f.category) {
+        *weights.entry(category).or_insert(0) += weight;
+    }
+    // Ties go to the category closest to the client
+    weights
+        .into_iter()
+        .rev()
+        .max_by_key(|&(_, weight)| weight)
+        .map(|(category, _)| category)
+}
+
+/// Print the breakdown of the saved run `meas_id`, or of the latest run.
+pub fn explain(config: &Config, meas_id: Option<&str>) -> Result<()> {
+    let run = match meas_id {
+        Some(meas_id) => crate::storage::find_run(meas_id)?,
+        None => crate::storage::load_recent(1)?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("no saved runs yet"))?,
+    };
+    println!(
+        "Run {} ({}){}",
+        run.timestamp_utc,
+        run.meas_id,
+        config
+            .network_label(&run)
+            .map(|n| format!(" on {n}"))
+            .unwrap_or_default()
+    );
+    println!(
+        "Download {:.1} Mbps, upload {:.1} Mbps, idle latency {}",
+        run.download.mbps,
+        run.upload.mbps,
+        run.idle_latency
+            .median_ms
+            .map_or_else(|| "-".to_string(), |ms| format!("{ms:.1} ms"))
+    );
+    if let Some(reason) = run.invalid.as_deref() {
+        println!("Warning: this run is invalid ({reason}), so its figures may mislead");
+    }
+    println!();
+    let findings = findings(&run);
+    for f in &findings {
+        let tag = f.category.map_or("", |(category, _)| category.label());
+        println!("  {:<17} {}", tag, f.text);
+    }
+    println!();
+    match verdict(&findings) {
+        Some(category) => println!("Most likely bottleneck: {}", category.label()),
+        None => println!("No bottleneck stands out in this run"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run() -> RunResult {
+        let mut r = test_run(94.0, 40.0);
+        r.idle_latency.median_ms = Some(10.0);
+        r.loaded_latency_download.median_ms = Some(15.0);
+        r.loaded_latency_upload.median_ms = Some(14.0);
+        r
+    }
+
+    #[test]
+    fn test_explain_verdict() {
+        let healthy = run();
+        assert_eq!(verdict(&findings(&healthy)), None);
+
+        let mut fast_ethernet = run();
+        fast_ethernet.link_speed_mbps = Some(100);
+        assert_eq!(verdict(&findings(&fast_ethernet)), Some(Bottleneck::Lan));
+
+        let mut bloated_wifi = run();
+        bloated_wifi.is_wireless = Some(true);
+        bloated_wifi.loaded_latency_upload.median_ms = Some(250.0);
+        bloated_wifi.idle_latency.loss = 0.03;
+        assert_eq!(verdict(&findings(&bloated_wifi)), Some(Bottleneck::WiFi));
+
+        bloated_wifi.is_wireless = Some(false);
+        assert_eq!(
+            verdict(&findings(&bloated_wifi)),
+            Some(Bottleneck::IspAccess)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Combined report over the history of several probe machines
+//! (`fleet-report` subcommand).
+//!
+//! Each probe is a data directory (or its `runs` directory) copied or synced
+//! from a machine running monitor mode. Runs are broken down per probe and
+//! network, with the usual statistics, weekly download medians as the trend
+//! and gaps in the schedule as outages.
+
+use crate::config::Config;
+use crate::model::RunResult;
+use crate::progress::Progress;
+use crate::report::{by_week, metric_line, unix_timestamp};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A gap between runs longer than this many times the usual interval counts
+/// as an outage: the probe couldn't test, or the network was down.
+const OUTAGE_FACTOR: i64 = 3;
+/// Number of most recent weeks shown as the trend.
+const TREND_WEEKS: usize = 6;
+/// Number of longest outages listed per network.
+const OUTAGES_SHOWN: usize = 3;
+
+/// Runs directory of a probe's data directory, or the directory itself.
+fn runs_dir(dir: &Path) -> PathBuf {
+    let runs = dir.join("runs");
+    if runs.is_dir() {
+        runs
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// Name a probe by its data directory.
+fn probe_name(dir: &Path) -> String {
+    let dir = if dir.ends_with("runs") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    };
+    dir.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+/// Gaps between consecutive run times (Unix seconds, oldest first) longer
+/// than `OUTAGE_FACTOR` times the median interval, as (start, length).
+fn outages(times: &[i64]) -> Vec<(i64, i64)> {
+    let gaps: Vec<(i64, i64)> = times.windows(2).map(|w| (w[0], w[1] - w[0])).collect();
+    if gaps.len() < 2 {
+        return Vec::new();
+    }
+    let mut lengths: Vec<i64> = gaps.iter().map(|(_, gap)| *gap).collect();
+    lengths.sort_unstable();
+    let usual = lengths[lengths.len() / 2];
+    if usual <= 0 {
+        return Vec::new();
+    }
+    gaps.into_iter()
+        .filter(|(_, gap)| *gap > usual * OUTAGE_FACTOR)
+        .collect()
+}
+
+fn format_secs(secs: i64) -> String {
+    // Whole minutes are precise enough for gaps between scheduled runs
+    let secs = secs.max(0) as u64 / 60 * 60;
+    humantime::format_duration(Duration::from_secs(secs)).to_string()
+}
+
+fn format_time(secs: i64) -> String {
+    time::OffsetDateTime::from_unix_timestamp(secs)
+        .ok()
+        .and_then(|t| {
+            t.format(&time::format_description::well_known::Rfc3339)
+                .ok()
+        })
+        .unwrap_or_else(|| secs.to_string())
+}
+
+/// Print the report for one network of one probe; `runs` are newest first.
+fn print_network(network: &str, runs: &[&RunResult]) {
+    let (valid, invalid): (Vec<&RunResult>, Vec<&RunResult>) =
+        runs.iter().partition(|r| r.invalid.is_none());
+    let newest = &runs[0].timestamp_utc;
+    let oldest = &runs[runs.len() - 1].timestamp_utc;
+    println!("  {network} ({} runs, {oldest} .. {newest})", runs.len());
+    if !invalid.is_empty() {
+        println!("    Invalid runs left out: {}", invalid.len());
+    }
+
+    let collect = |f: fn(&RunResult) -> Option<f64>| -> Vec<f64> {
+        valid.iter().filter_map(|r| f(r)).collect()
+    };
+    println!(
+        "    {}",
+        metric_line("Download:", "Mbps", &collect(|r| Some(r.download.mbps)))
+    );
+    println!(
+        "    {}",
+        metric_line("Upload:", "Mbps", &collect(|r| Some(r.upload.mbps)))
+    );
+    println!(
+        "    {}",
+        metric_line(
+            "Idle latency:",
+            "ms",
+            &collect(|r| r.idle_latency.median_ms)
+        )
+    );
+
+    let weeks = by_week(valid.iter().copied(), |r| r.download.mbps);
+    if weeks.len() > 1 {
+        let medians: Vec<String> = weeks
+            .iter()
+            .skip(weeks.len().saturating_sub(TREND_WEEKS))
+            .map(|(_, values)| {
+                let mut values = values.clone();
+                values.sort_by(|a, b| a.total_cmp(b));
+                format!("{:.0}", values[values.len() / 2])
+            })
+            .collect();
+        println!("    Weekly download median: {} Mbps", medians.join(" -> "));
+    }
+
+    let mut times: Vec<i64> = runs
+        .iter()
+        .filter_map(|r| unix_timestamp(&r.timestamp_utc))
+        .collect();
+    times.sort_unstable();
+    let mut gaps = outages(&times);
+    if gaps.is_empty() {
+        println!("    Outages: none");
+    } else {
+        let total: i64 = gaps.iter().map(|(_, gap)| gap).sum();
+        println!(
+            "    Outages: {} totalling {}",
+            gaps.len(),
+            format_secs(total)
+        );
+        gaps.sort_by_key(|(_, gap)| std::cmp::Reverse(*gap));
+        for (start, gap) in gaps.iter().take(OUTAGES_SHOWN) {
+            println!("      {} for {}", format_time(*start), format_secs(*gap));
+        }
+    }
+}
+
+/// Print a report over the history in `dirs`, one per probe, loading the
+/// newest `limit` runs of each.
+pub fn print_fleet_report(config: &Config, dirs: &[PathBuf], limit: usize) -> Result<()> {
+    let mut total = 0;
+    for dir in dirs {
+        let mut runs = crate::storage::load_recent_in(
+            &runs_dir(dir),
+            limit,
+            &mut Progress::new("Loading runs"),
+        )?;
+        // File times may not have survived copying between machines
+        runs.sort_by(|a, b| b.timestamp_utc.cmp(&a.timestamp_utc));
+        total += runs.len();
+        println!("{} ({} runs)", probe_name(dir), runs.len());
+
+        let mut networks: BTreeMap<&str, Vec<&RunResult>> = BTreeMap::new();
+        for run in &runs {
+            let network = config.network_label(run).unwrap_or("(unknown network)");
+            networks.entry(network).or_default().push(run);
+        }
+        for (network, runs) in networks {
+            print_network(network, &runs);
+        }
+        println!();
+    }
+    println!("Probes: {}, runs: {total}", dirs.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outages() {
+        // Every 30 minutes, with one two-hour gap
+        let half_hour = 1800;
+        let mut times: Vec<i64> = (0..10).map(|i| i * half_hour).collect();
+        times.extend((0..5).map(|i| 9 * half_hour + 4 * half_hour + i * half_hour));
+        assert_eq!(outages(&times), [(9 * half_hour, 4 * half_hour)]);
+        assert!(outages(&times[..10]).is_empty());
+        assert!(outages(&[0, 10]).is_empty());
+
+        assert_eq!(
+            probe_name(Path::new("/srv/probes/office-pi/runs")),
+            "office-pi"
+        );
+        assert_eq!(probe_name(Path::new("/srv/probes/office-pi")), "office-pi");
+    }
+}
@@ -0,0 +1,141 @@
+//! Approximate locations of the client and the Cloudflare colo serving the
+//! test, and the distance between them.
+
+use crate::model::GeoSummary;
+use anyhow::Result;
+use serde_json::Value;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Mean radius of the Earth in km
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Where the client is, according to Cloudflare or a GeoIP database.
+pub struct ClientLocation {
+    pub city: Option<String>,
+    pub country: Option<String>,
+    /// (latitude, longitude) in degrees
+    pub coordinates: Option<(f64, f64)>,
+    pub source: &'static str,
+}
+
+/// Great-circle (haversine) distance in km between two (latitude, longitude)
+/// points given in degrees.
+pub fn great_circle_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// Read a field that may be a JSON number or a numeric string.
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn string(value: Option<&Value>) -> Option<String> {
+    value?.as_str().map(str::to_string)
+}
+
+fn coordinates(lat: Option<&Value>, lon: Option<&Value>) -> Option<(f64, f64)> {
+    Some((number(lat)?, number(lon)?))
+}
+
+/// Client location from the Cloudflare meta fields.
+pub fn client_from_meta(meta: &Value) -> ClientLocation {
+    ClientLocation {
+        city: string(meta.get("city")),
+        country: string(meta.get("country")),
+        coordinates: coordinates(meta.get("latitude"), meta.get("longitude")),
+        source: "cloudflare",
+    }
+}
+
+/// Client location from a MaxMind/GeoLite2 City database.
+#[cfg(feature = "geoip")]
+pub fn client_from_geoip_db(db: &Path, ip: IpAddr) -> Result<ClientLocation> {
+    use anyhow::Context;
+    use maxminddb::geoip2;
+
+    let reader = maxminddb::Reader::open_readfile(db)
+        .with_context(|| format!("open GeoIP database {}", db.display()))?;
+    let record: geoip2::City = reader
+        .lookup(ip)
+        .with_context(|| format!("look up {ip} in GeoIP database"))?;
+    let city = record
+        .city
+        .and_then(|c| c.names)
+        .and_then(|names| names.get("en").map(|name| name.to_string()));
+    let country = record.country.and_then(|c| c.iso_code).map(str::to_string);
+    let coordinates = record
+        .location
+        .and_then(|l| Some((l.latitude?, l.longitude?)));
+    Ok(ClientLocation {
+        city,
+        country,
+        coordinates,
+        source: "geoip-db",
+    })
+}
+
+#[cfg(not(feature = "geoip"))]
+pub fn client_from_geoip_db(_db: &Path, _ip: IpAddr) -> Result<ClientLocation> {
+    anyhow::bail!("GeoIP database support requires building with the `geoip` feature")
+}
+
+/// Combine the client location with the colo's entry in the /locations data.
+pub fn summarize(
+    client: ClientLocation,
+    colo: Option<&str>,
+    colo_entry: Option<&Value>,
+) -> Option<GeoSummary> {
+    let colo_coordinates =
+        colo_entry.and_then(|entry| coordinates(entry.get("lat"), entry.get("lon")));
+    if client.city.is_none() && client.coordinates.is_none() && colo_coordinates.is_none() {
+        return None;
+    }
+    let distance_km = client
+        .coordinates
+        .zip(colo_coordinates)
+        .map(|(from, to)| great_circle_km(from, to));
+    Some(GeoSummary {
+        client_city: client.city,
+        client_country: client.country,
+        client_latitude: client.coordinates.map(|c| c.0),
+        client_longitude: client.coordinates.map(|c| c.1),
+        client_source: client.source.to_string(),
+        colo: colo.map(str::to_string),
+        colo_city: colo_entry.and_then(|e| string(e.get("city"))),
+        colo_latitude: colo_coordinates.map(|c| c.0),
+        colo_longitude: colo_coordinates.map(|c| c.1),
+        distance_km,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_great_circle_and_summary() {
+        // London Heathrow to New York JFK is about 5540 km
+        let km = great_circle_km((51.47, -0.4543), (40.6413, -73.7781));
+        assert!((km - 5540.0).abs() < 20.0, "{km}");
+        assert_eq!(great_circle_km((10.0, 20.0), (10.0, 20.0)), 0.0);
+
+        let meta = serde_json::json!({
+            "city": "Slough", "country": "GB", "latitude": "51.51", "longitude": "-0.59"
+        });
+        let colo =
+            serde_json::json!({"iata": "LHR", "lat": 51.47, "lon": -0.4543, "city": "London"});
+        let geo = summarize(client_from_meta(&meta), Some("LHR"), Some(&colo)).unwrap();
+        assert_eq!(geo.colo_city.as_deref(), Some("London"));
+        assert!(geo.distance_km.unwrap() < 15.0);
+        assert!(summarize(client_from_meta(&serde_json::json!({})), None, None).is_none());
+    }
+}
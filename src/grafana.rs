@@ -0,0 +1,217 @@
+//! Grafana dashboard generator (`grafana-dashboard` subcommand).
+//!
+//! Metrics are named after `MEASUREMENT` and the field names below, so the
+//! dashboard reads what this tool exports: the InfluxDB measurement of
+//! `--influx-url` / `--export-influx`, with one field per metric, the
+//! `<measurement>_<field>` gauges of `--export-prometheus`, or the objects of
+//! `history export --format grafana-json` read by the Infinity data source.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// InfluxDB measurement / Prometheus metric prefix of exported results.
+pub const MEASUREMENT: &str = "cloudflare_speed";
+
+pub const FIELD_DOWNLOAD_MBPS: &str = "download_mbps";
+pub const FIELD_UPLOAD_MBPS: &str = "upload_mbps";
+pub const FIELD_IDLE_LATENCY_MS: &str = "idle_latency_ms";
+pub const FIELD_LOADED_LATENCY_DOWNLOAD_MS: &str = "loaded_latency_download_ms";
+pub const FIELD_LOADED_LATENCY_UPLOAD_MS: &str = "loaded_latency_upload_ms";
+pub const FIELD_IDLE_LOSS_PCT: &str = "idle_loss_pct";
+pub const FIELD_PACKET_LOSS_PCT: &str = "packet_loss_pct";
+pub const FIELD_BUFFERBLOAT_SCORE: &str = "bufferbloat_score";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Datasource {
+    Influx,
+    Prometheus,
+    /// The Infinity plugin, reading a `grafana-json` history export from a
+    /// URL
+    Infinity,
+}
+
+impl Datasource {
+    fn plugin_id(self) -> &'static str {
+        match self {
+            Datasource::Influx => "influxdb",
+            Datasource::Prometheus => "prometheus",
+            Datasource::Infinity => "yesoreyeram-infinity-datasource",
+        }
+    }
+
+    /// The data source of the configured exporter: `influx` is whether
+    /// InfluxDB results are pushed or exported, `prometheus` whether they're
+    /// exported for Prometheus.
+    pub fn configured(influx: bool, prometheus: bool) -> Result<Self> {
+        match (influx, prometheus) {
+            (true, false) => Ok(Datasource::Influx),
+            (false, true) => Ok(Datasource::Prometheus),
+            (true, true) => anyhow::bail!(
+                "both InfluxDB and Prometheus exports are configured; pick one with --datasource"
+            ),
+            (false, false) => anyhow::bail!(
+                "no InfluxDB or Prometheus export is configured; pick a data source with --datasource"
+            ),
+        }
+    }
+}
+
+/// Build a query target for one metric field.
+fn target(datasource: Datasource, ref_id: &str, field: &str, legend: &str) -> Value {
+    match datasource {
+        Datasource::Influx => json!({
+            "refId": ref_id,
+            "query": format!(
+                "from(bucket: \"${{bucket}}\")\n  |> range(start: v.timeRangeStart, stop: v.timeRangeStop)\n  |> filter(fn: (r) => r._measurement == \"{MEASUREMENT}\" and r._field == \"{field}\")\n  |> keep(columns: [\"_time\", \"_value\"])\n  |> set(key: \"_field\", value: \"{legend}\")"
+            ),
+        }),
+        Datasource::Prometheus => json!({
+            "refId": ref_id,
+            "expr": format!("{MEASUREMENT}_{field}"),
+            "legendFormat": legend,
+        }),
+        Datasource::Infinity => json!({
+            "refId": ref_id,
+            "type": "json",
+            "source": "url",
+            "format": "timeseries",
+            "url": "${url}",
+            "url_options": { "method": "GET" },
+            "root_selector": "",
+            "columns": [
+                { "selector": "time", "text": "Time", "type": "timestamp_epoch" },
+                { "selector": field, "text": legend, "type": "number" },
+            ],
+        }),
+    }
+}
+
+/// Build a panel at the given grid position with one target per `(field, legend)`.
+fn panel(
+    datasource: Datasource,
+    id: u32,
+    (kind, title, unit): (&str, &str, &str),
+    grid: (u32, u32, u32, u32),
+    fields: &[(&str, &str)],
+) -> Value {
+    let (x, y, w, h) = grid;
+    let targets: Vec<Value> = fields
+        .iter()
+        .zip(["A", "B", "C", "D"])
+        .map(|((field, legend), ref_id)| target(datasource, ref_id, field, legend))
+        .collect();
+    json!({
+        "id": id,
+        "type": kind,
+        "title": title,
+        "gridPos": { "x": x, "y": y, "w": w, "h": h },
+        "datasource": { "type": datasource.plugin_id(), "uid": "${datasource}" },
+        "fieldConfig": { "defaults": { "unit": unit }, "overrides": [] },
+        "targets": targets,
+    })
+}
+
+/// Build the dashboard JSON for the given datasource type.
+pub fn dashboard(datasource: Datasource) -> Value {
+    let mut grade_panel = panel(
+        datasource,
+        5,
+        ("stat", "Bufferbloat grade", "none"),
+        (16, 16, 8, 8),
+        &[(FIELD_BUFFERBLOAT_SCORE, "Grade")],
+    );
+    let mappings: Vec<Value> = crate::metrics::BUFFERBLOAT_GRADES
+        .iter()
+        .enumerate()
+        .map(|(score, grade)| {
+            json!({
+                "type": "value",
+                "options": { score.to_string(): { "text": grade } },
+            })
+        })
+        .collect();
+    grade_panel["fieldConfig"]["defaults"]["mappings"] = Value::Array(mappings);
+
+    let panels = vec![
+        panel(
+            datasource,
+            1,
+            ("timeseries", "Throughput", "Mbits"),
+            (0, 0, 24, 8),
+            &[
+                (FIELD_DOWNLOAD_MBPS, "Download"),
+                (FIELD_UPLOAD_MBPS, "Upload"),
+            ],
+        ),
+        panel(
+            datasource,
+            2,
+            ("timeseries", "Latency", "ms"),
+            (0, 8, 24, 8),
+            &[
+                (FIELD_IDLE_LATENCY_MS, "Idle"),
+                (FIELD_LOADED_LATENCY_DOWNLOAD_MS, "Loaded (download)"),
+                (FIELD_LOADED_LATENCY_UPLOAD_MS, "Loaded (upload)"),
+            ],
+        ),
+        panel(
+            datasource,
+            3,
+            ("timeseries", "Packet loss", "percent"),
+            (0, 16, 8, 8),
+            &[
+                (FIELD_IDLE_LOSS_PCT, "Latency probes"),
+                (FIELD_PACKET_LOSS_PCT, "UDP"),
+            ],
+        ),
+        panel(
+            datasource,
+            4,
+            ("timeseries", "Bufferbloat score", "none"),
+            (8, 16, 8, 8),
+            &[(FIELD_BUFFERBLOAT_SCORE, "Score (5 = A+, 0 = F)")],
+        ),
+        grade_panel,
+    ];
+
+    let mut variables = vec![json!({
+        "name": "datasource",
+        "label": "Data source",
+        "type": "datasource",
+        "query": datasource.plugin_id(),
+    })];
+    match datasource {
+        Datasource::Influx => variables.push(json!({
+            "name": "bucket",
+            "label": "Bucket",
+            "type": "textbox",
+            "query": "speedtest",
+            "current": { "text": "speedtest", "value": "speedtest" },
+        })),
+        Datasource::Infinity => variables.push(json!({
+            "name": "url",
+            "label": "Export URL",
+            "type": "textbox",
+            "query": "http://localhost:8000/runs.json",
+            "current": {
+                "text": "http://localhost:8000/runs.json",
+                "value": "http://localhost:8000/runs.json",
+            },
+        })),
+        Datasource::Prometheus => {}
+    }
+
+    json!({
+        "title": "Cloudflare Speed Test",
+        "uid": "cloudflare-speed-cli",
+        "tags": ["cloudflare-speed-cli"],
+        "schemaVersion": 39,
+        "version": 1,
+        "editable": true,
+        "time": { "from": "now-7d", "to": "now" },
+        "refresh": "5m",
+        "templating": { "list": variables },
+        "panels": panels,
+    })
+}
@@ -0,0 +1,674 @@
+//! gRPC control and event streaming API (`grpc-serve` subcommand, `grpc`
+//! feature).
+//!
+//! A long-lived headless instance starts and cancels runs on request and
+//! streams their events, for integrations that want typed streaming rather
+//! than polling the history. The API is defined in
+//! `proto/cloudflare_speed/v1/speedtest.proto`, from which `build.rs`
+//! generates the messages, the client and the server trait.
+//! Finished runs are saved, exported and pushed like monitor mode runs.
+//!
+//! `agent` instances on other machines connect out to the server over
+//! `Connect` and run the tests it routes to them by name, streaming their
+//! events back; the server relays those events to its clients and saves
+//! the agents' results with its own.
+//!
+//! Threat model: whoever reaches the server can start runs (spending its
+//! bandwidth and data cap), read the history (network names and addresses,
+//! unless redacted) and connect as an agent to receive the runs routed to
+//! a name. So the server only listens on loopback unless a token is set,
+//! and then every RPC, `Connect` included, must carry it. The token is one
+//! shared secret: there are no per-client roles, and an agent is only
+//! authenticated by it, so a token holder can take any name that isn't
+//! connected. Over plain `http` the token and the results cross the network
+//! readable; beyond a trusted network, serve with `--tls-cert` and
+//! `--tls-key` (or behind a TLS proxy) and give agents an `https` URL.
+//! Agents trust their server to decide when they run tests, but it can't
+//! make them do anything else.
+
+#[cfg(feature = "grpc")]
+mod server {
+    use crate::cli::{build_config, finalize_run, lock_policy, Cli};
+    use crate::config::Config;
+    use crate::html::Timeline;
+    use crate::model::{RunResult, TestEvent};
+    use crate::network::NetworkInfo;
+    use crate::orchestrator::{Controller, ControllerEvent};
+    use anyhow::{Context, Result};
+    use ring::digest::{digest, Digest, SHA256};
+    use std::collections::{BTreeMap, VecDeque};
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::{broadcast, mpsc, oneshot};
+    use tonic::codegen::BoxStream;
+    use tonic::metadata::{Ascii, MetadataValue};
+    use tonic::service::Interceptor;
+    use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity, ServerTlsConfig};
+    use tonic::{Status, Streaming};
+
+    /// Messages, client and server generated from the proto by `build.rs`.
+    mod pb {
+        tonic::include_proto!("cloudflare_speed.v1");
+    }
+
+    use pb::speed_test_client::SpeedTestClient;
+    use pb::speed_test_server::{SpeedTest, SpeedTestServer};
+    use pb::*;
+
+    /// Runs returned by `ListHistory` without a limit.
+    const DEFAULT_HISTORY: usize = 20;
+    /// Events buffered per client; a client further behind misses events.
+    const EVENT_BUFFER: usize = 1024;
+    /// Wait before an agent reconnects to its server.
+    const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+    fn run_summary(config: &Config, result: &RunResult) -> RunSummary {
+        RunSummary {
+            meas_id: result.meas_id.clone(),
+            timestamp_utc: result.timestamp_utc.clone(),
+            network_name: config.network_label(result).map(str::to_string),
+            server: result.server.clone(),
+            download_mbps: result.download.mbps,
+            upload_mbps: result.upload.mbps,
+            idle_latency_ms: result.idle_latency.median_ms,
+            invalid: result.invalid.clone(),
+            result_json: serde_json::to_string(result).unwrap_or_default(),
+        }
+    }
+
+    fn event_kind(event: &TestEvent) -> event::Kind {
+        match event {
+            TestEvent::PhaseStarted { phase } => event::Kind::PhaseStarted(format!("{phase:?}")),
+            TestEvent::ThroughputTick {
+                phase,
+                bytes_total,
+                bps_instant,
+            } => event::Kind::Throughput(Throughput {
+                phase: format!("{phase:?}"),
+                bytes_total: *bytes_total,
+                mbps: bps_instant * 8.0 / 1_000_000.0,
+            }),
+            TestEvent::LatencySample {
+                phase,
+                during,
+                rtt_ms,
+                ok,
+            } => event::Kind::Latency(Latency {
+                phase: format!("{phase:?}"),
+                during: during.map(|p| format!("{p:?}")),
+                rtt_ms: *rtt_ms,
+                ok: *ok,
+            }),
+            TestEvent::Info { message } => event::Kind::Info(message.clone()),
+            other => event::Kind::OtherJson(serde_json::to_string(other).unwrap_or_default()),
+        }
+    }
+
+    /// Requests from the RPC handlers to the task driving the controller.
+    enum Request {
+        Start(oneshot::Sender<u32>),
+        Cancel(oneshot::Sender<bool>),
+    }
+
+    /// Own the controller: queue and cancel runs on request, finish runs like
+    /// monitor mode and broadcast their events.
+    async fn drive(
+        args: Cli,
+        config: Config,
+        mut requests: mpsc::Receiver<Request>,
+        events: broadcast::Sender<Event>,
+    ) {
+        let mut controller = Controller::new()
+            .with_cooldown(Duration::from(args.cooldown))
+            .with_lock_policy(lock_policy(&args));
+        // Network details of the active and queued runs, in queue order
+        let mut network_infos: VecDeque<NetworkInfo> = VecDeque::new();
+        // Time series of the active run, for --export-html
+        let mut timeline = Timeline::default();
+        let send = |kind: event::Kind| {
+            events
+                .send(Event {
+                    kind: Some(kind),
+                    agent: String::new(),
+                })
+                .ok();
+        };
+
+        loop {
+            tokio::select! {
+                request = requests.recv() => match request {
+                    Some(Request::Start(reply)) => {
+                        let gather_args = args.clone();
+                        let network_info = tokio::task::spawn_blocking(move || {
+                            crate::network::gather_network_info(&gather_args)
+                        })
+                        .await
+                        .unwrap_or_default();
+                        if let Some(warning) = crate::network::check_route(&network_info, &args) {
+                            send(event::Kind::Info(format!("Warning: {warning}")));
+                        }
+                        reply.send(network_infos.len() as u32).ok();
+                        network_infos.push_back(network_info);
+                        controller.enqueue(build_config(&args));
+                    }
+                    Some(Request::Cancel(reply)) => {
+                        let active = controller.state().is_active();
+                        if active {
+                            // A cancelled run has no RunFinished event
+                            controller.cancel_active().await;
+                            network_infos.pop_front();
+                        }
+                        reply.send(active).ok();
+                    }
+                    None => break,
+                },
+                event = controller.next_event() => match event {
+                    ControllerEvent::StateChanged(state) => send(event::Kind::State(state.to_string())),
+                    ControllerEvent::RunStarted { .. } => timeline = Timeline::default(),
+                    ControllerEvent::Engine(envelope) => {
+                        timeline.record(&envelope.event);
+                        send(event_kind(&envelope.event));
+                    }
+                    ControllerEvent::RunFinished { result } => {
+                        let network_info = network_infos.pop_front().unwrap_or_default();
+                        match result {
+                            Ok(result) => {
+                                let result = finalize_run(&args, &config, &result, &network_info);
+                                if let Err(e) =
+                                    crate::monitor::finish_run(&args, &config, &result, &timeline)
+                                        .await
+                                {
+                                    eprintln!("Failed to save results: {e:#}");
+                                }
+                                send(event::Kind::Finished(run_summary(&config, &result)));
+                            }
+                            Err(e) => send(event::Kind::Failed(format!("{e:#}"))),
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct SpeedTestService {
+        requests: mpsc::Sender<Request>,
+        events: broadcast::Sender<Event>,
+        /// Connected agents by name
+        agents: Arc<Mutex<BTreeMap<String, mpsc::Sender<AgentCommand>>>>,
+        /// Save the results agents send back
+        auto_save: bool,
+        config: Arc<Config>,
+    }
+
+    impl SpeedTestService {
+        async fn request<T>(
+            &self,
+            request: impl FnOnce(oneshot::Sender<T>) -> Request,
+        ) -> Result<T, Status> {
+            let (reply, replied) = oneshot::channel();
+            let stopped = || Status::unavailable("server is shutting down");
+            self.requests
+                .send(request(reply))
+                .await
+                .map_err(|_| stopped())?;
+            replied.await.map_err(|_| stopped())
+        }
+
+        /// Pass `command` on to the connected agent `name`.
+        async fn to_agent(&self, name: &str, command: agent_command::Kind) -> Result<(), Status> {
+            let agent = self.agents.lock().unwrap().get(name).cloned();
+            let agent =
+                agent.ok_or_else(|| Status::not_found(format!("no agent {name} is connected")))?;
+            agent
+                .send(AgentCommand {
+                    kind: Some(command),
+                })
+                .await
+                .map_err(|_| Status::unavailable(format!("agent {name} disconnected")))
+        }
+
+        fn info(&self, message: String) {
+            let event = Event {
+                kind: Some(event::Kind::Info(message)),
+                agent: String::new(),
+            };
+            self.events.send(event).ok();
+        }
+
+        async fn save_agent_result(&self, name: &str, result_json: &str) {
+            if !self.auto_save {
+                return;
+            }
+            let saved = serde_json::from_str::<RunResult>(result_json)
+                .map_err(anyhow::Error::from)
+                .map(|result| {
+                    tokio::task::spawn_blocking(move || crate::storage::save_run(&result))
+                });
+            let saved = match saved {
+                Ok(task) => task.await.map_err(anyhow::Error::from).and_then(|r| r),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = saved {
+                eprintln!("Failed to save the result of agent {name}: {e:#}");
+            }
+        }
+    }
+
+    #[tonic::async_trait]
+    impl SpeedTest for SpeedTestService {
+        async fn start_run(
+            &self,
+            request: tonic::Request<StartRunRequest>,
+        ) -> Result<tonic::Response<StartRunResponse>, Status> {
+            let request = request.into_inner();
+            if !request.agent.is_empty() {
+                let name = request.agent.clone();
+                self.to_agent(&name, agent_command::Kind::StartRun(request))
+                    .await?;
+                return Ok(tonic::Response::new(StartRunResponse { queued_behind: 0 }));
+            }
+            let queued_behind = self.request(Request::Start).await?;
+            Ok(tonic::Response::new(StartRunResponse { queued_behind }))
+        }
+
+        async fn cancel_run(
+            &self,
+            request: tonic::Request<CancelRunRequest>,
+        ) -> Result<tonic::Response<CancelRunResponse>, Status> {
+            let request = request.into_inner();
+            if !request.agent.is_empty() {
+                let name = request.agent.clone();
+                self.to_agent(&name, agent_command::Kind::CancelRun(request))
+                    .await?;
+                return Ok(tonic::Response::new(CancelRunResponse { cancelled: true }));
+            }
+            let cancelled = self.request(Request::Cancel).await?;
+            Ok(tonic::Response::new(CancelRunResponse { cancelled }))
+        }
+
+        type StreamEventsStream = BoxStream<Event>;
+
+        async fn stream_events(
+            &self,
+            _: tonic::Request<StreamEventsRequest>,
+        ) -> Result<tonic::Response<Self::StreamEventsStream>, Status> {
+            let events = self.events.subscribe();
+            let stream = futures::stream::unfold(events, |mut events| async move {
+                loop {
+                    match events.recv().await {
+                        Ok(event) => return Some((Ok(event), events)),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            });
+            Ok(tonic::Response::new(Box::pin(stream)))
+        }
+
+        async fn list_history(
+            &self,
+            request: tonic::Request<ListHistoryRequest>,
+        ) -> Result<tonic::Response<ListHistoryResponse>, Status> {
+            let limit = match request.into_inner().limit {
+                0 => DEFAULT_HISTORY,
+                limit => limit as usize,
+            };
+            let runs = tokio::task::spawn_blocking(move || crate::storage::load_recent(limit))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| Status::internal(format!("{e:#}")))?;
+            Ok(tonic::Response::new(ListHistoryResponse {
+                runs: runs.iter().map(|r| run_summary(&self.config, r)).collect(),
+            }))
+        }
+
+        async fn list_agents(
+            &self,
+            _: tonic::Request<ListAgentsRequest>,
+        ) -> Result<tonic::Response<ListAgentsResponse>, Status> {
+            let agents = self.agents.lock().unwrap().keys().cloned().collect();
+            Ok(tonic::Response::new(ListAgentsResponse { agents }))
+        }
+
+        type ConnectStream = BoxStream<AgentCommand>;
+
+        /// Register the agent connecting with `request`, relay its events
+        /// until it disconnects, and stream the commands routed to it.
+        async fn connect(
+            &self,
+            request: tonic::Request<Streaming<AgentMessage>>,
+        ) -> Result<tonic::Response<Self::ConnectStream>, Status> {
+            let mut incoming = request.into_inner();
+            let name = match incoming.message().await? {
+                Some(AgentMessage {
+                    kind: Some(agent_message::Kind::Hello(name)),
+                }) if !name.is_empty() => name,
+                _ => return Err(Status::invalid_argument("an agent first sends its name")),
+            };
+            let (commands, commands_rx) = mpsc::channel(16);
+            {
+                let mut agents = self.agents.lock().unwrap();
+                if agents.contains_key(&name) {
+                    return Err(Status::already_exists(format!(
+                        "an agent named {name} is already connected"
+                    )));
+                }
+                agents.insert(name.clone(), commands.clone());
+            }
+            self.info(format!("Agent {name} connected"));
+
+            let service = self.clone();
+            tokio::spawn(async move {
+                while let Ok(Some(message)) = incoming.message().await {
+                    let Some(agent_message::Kind::Event(mut event)) = message.kind else {
+                        continue;
+                    };
+                    event.agent = name.clone();
+                    if let Some(event::Kind::Finished(summary)) = &event.kind {
+                        service.save_agent_result(&name, &summary.result_json).await;
+                    }
+                    service.events.send(event).ok();
+                }
+                let mut agents = service.agents.lock().unwrap();
+                // A reconnected agent of the same name keeps its entry
+                if agents.get(&name).is_some_and(|c| c.same_channel(&commands)) {
+                    agents.remove(&name);
+                }
+                drop(agents);
+                service.info(format!("Agent {name} disconnected"));
+            });
+
+            let commands = futures::stream::unfold(commands_rx, |mut commands| async move {
+                Some((Ok(commands.recv().await?), commands))
+            });
+            Ok(tonic::Response::new(Box::pin(commands)))
+        }
+    }
+
+    /// Lets a request through if it carries `authorization: Bearer <token>`,
+    /// or if no token is set. Digests are compared, so the time taken doesn't
+    /// tell how much of a guessed token was right.
+    #[derive(Clone)]
+    struct Authorize(Option<Digest>);
+
+    impl Interceptor for Authorize {
+        fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+            let Some(token) = &self.0 else {
+                return Ok(request);
+            };
+            let presented = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            match presented {
+                Some(presented)
+                    if digest(&SHA256, presented.as_bytes()).as_ref() == token.as_ref() =>
+                {
+                    Ok(request)
+                }
+                _ => Err(Status::unauthenticated("missing or wrong token")),
+            }
+        }
+    }
+
+    pub async fn serve(
+        args: Cli,
+        config: Config,
+        listen: SocketAddr,
+        token: Option<String>,
+        tls_cert: Option<PathBuf>,
+        tls_key: Option<PathBuf>,
+    ) -> Result<()> {
+        if token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            anyhow::bail!("The gRPC token must not be empty");
+        }
+        if token.is_none() && !listen.ip().is_loopback() {
+            anyhow::bail!(
+                "Listening on {listen} lets other machines run tests and read the history; \
+                 set a token with --token or `token` in the [grpc] section of the config file"
+            );
+        }
+        let mut server = tonic::transport::Server::builder();
+        match (tls_cert, tls_key) {
+            (Some(cert), Some(key)) => {
+                crate::engine::tls::ensure_crypto_provider();
+                let cert = std::fs::read(&cert)
+                    .with_context(|| format!("read TLS certificate {}", cert.display()))?;
+                let key = std::fs::read(&key)
+                    .with_context(|| format!("read TLS key {}", key.display()))?;
+                server = server
+                    .tls_config(ServerTlsConfig::new().identity(Identity::from_pem(cert, key)))
+                    .context("invalid TLS certificate or key")?;
+            }
+            (None, None) if !listen.ip().is_loopback() && !args.silent => eprintln!(
+                "Warning: without --tls-cert and --tls-key the token and results cross \
+                 the network unencrypted"
+            ),
+            (None, None) => {}
+            _ => anyhow::bail!("--tls-cert and --tls-key go together"),
+        }
+        let token = token.map(|token| digest(&SHA256, token.as_bytes()));
+        let (requests, requests_rx) = mpsc::channel(16);
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        if !args.silent {
+            eprintln!("Serving gRPC on {listen} (Ctrl-C to stop)");
+        }
+        let auto_save = args.auto_save;
+        tokio::spawn(drive(args, config.clone(), requests_rx, events.clone()));
+
+        server
+            .add_service(SpeedTestServer::with_interceptor(
+                SpeedTestService {
+                    requests,
+                    events,
+                    agents: Arc::default(),
+                    auto_save,
+                    config: Arc::new(config),
+                },
+                Authorize(token),
+            ))
+            .serve_with_shutdown(listen, async {
+                tokio::signal::ctrl_c().await.ok();
+            })
+            .await
+            .with_context(|| format!("gRPC server on {listen} failed"))
+    }
+
+    /// Run tests for the `grpc-serve` instance at `server` as agent `name`:
+    /// connect out to it, run what it routes here and stream the events
+    /// back, reconnecting whenever the connection drops. An `https` server
+    /// is verified against the web PKI roots, or against `ca_cert` if given.
+    pub async fn agent(
+        args: Cli,
+        config: Config,
+        server: String,
+        name: String,
+        token: Option<String>,
+        ca_cert: Option<PathBuf>,
+    ) -> Result<()> {
+        let mut endpoint = Endpoint::from_shared(server.clone()).context("invalid server URL")?;
+        if server.starts_with("https://") {
+            crate::engine::tls::ensure_crypto_provider();
+            let tls = match ca_cert {
+                Some(path) => {
+                    let pem = std::fs::read(&path)
+                        .with_context(|| format!("read CA certificate {}", path.display()))?;
+                    ClientTlsConfig::new().ca_certificate(Certificate::from_pem(pem))
+                }
+                None => ClientTlsConfig::new().with_webpki_roots(),
+            };
+            endpoint = endpoint.tls_config(tls).context("invalid TLS settings")?;
+        } else if ca_cert.is_some() {
+            anyhow::bail!("--ca-cert needs an https:// server URL");
+        }
+        let authorization = token
+            .map(|token| format!("Bearer {token}").parse::<MetadataValue<Ascii>>())
+            .transpose()
+            .context("the token must be printable ASCII")?;
+
+        let (requests, requests_rx) = mpsc::channel(16);
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        tokio::spawn(drive(args.clone(), config, requests_rx, events.clone()));
+        loop {
+            let connected =
+                connect_agent(&endpoint, authorization.as_ref(), &name, &requests, &events);
+            match connected.await {
+                Ok(()) => eprintln!("Disconnected from {server}"),
+                Err(e) => eprintln!("Connection to {server} failed: {e:#}"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+        }
+    }
+
+    async fn connect_agent(
+        endpoint: &Endpoint,
+        authorization: Option<&MetadataValue<Ascii>>,
+        name: &str,
+        requests: &mpsc::Sender<Request>,
+        events: &broadcast::Sender<Event>,
+    ) -> Result<()> {
+        let mut client = SpeedTestClient::new(endpoint.connect().await?);
+
+        let hello = AgentMessage {
+            kind: Some(agent_message::Kind::Hello(name.to_string())),
+        };
+        let relayed = futures::stream::unfold(events.subscribe(), |mut events| async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let message = AgentMessage {
+                            kind: Some(agent_message::Kind::Event(event)),
+                        };
+                        return Some((message, events));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        let outgoing = futures::StreamExt::chain(futures::stream::iter([hello]), relayed);
+        let mut request = tonic::Request::new(outgoing);
+        if let Some(authorization) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+        let mut commands = client
+            .connect(request)
+            .await
+            .map_err(|status| anyhow::anyhow!("{}: {}", status.code(), status.message()))?
+            .into_inner();
+        eprintln!("Connected to {} as agent {name}", endpoint.uri());
+
+        while let Some(command) = commands.message().await? {
+            // Replies go to the server's clients as events
+            let request = match command.kind {
+                Some(agent_command::Kind::StartRun(_)) => Request::Start(oneshot::channel().0),
+                Some(agent_command::Kind::CancelRun(_)) => Request::Cancel(oneshot::channel().0),
+                None => continue,
+            };
+            requests
+                .send(request)
+                .await
+                .context("the test runner stopped")?;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::model::Phase;
+        use prost::Message;
+
+        #[test]
+        fn test_event_messages() {
+            let tick = TestEvent::ThroughputTick {
+                phase: Phase::Download,
+                bytes_total: 1_000_000,
+                bps_instant: 12_500_000.0,
+            };
+            let event = Event {
+                kind: Some(event_kind(&tick)),
+                agent: String::new(),
+            };
+            let decoded = Event::decode(event.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(
+                decoded.kind,
+                Some(event::Kind::Throughput(Throughput {
+                    phase: "Download".into(),
+                    bytes_total: 1_000_000,
+                    mbps: 100.0,
+                }))
+            );
+
+            let other = event_kind(&TestEvent::UdpLossProgress {
+                sent: 3,
+                received: 2,
+                total: 10,
+                rtt_ms: None,
+            });
+            let event::Kind::OtherJson(json) = other else {
+                panic!("expected JSON, got {other:?}");
+            };
+            assert!(json.contains(r#""type":"udp_loss_progress""#));
+        }
+
+        #[test]
+        fn test_authorize() {
+            let request = |authorization: Option<&str>| {
+                let mut request = tonic::Request::new(());
+                if let Some(value) = authorization {
+                    request
+                        .metadata_mut()
+                        .insert("authorization", value.parse().unwrap());
+                }
+                request
+            };
+            let mut open = Authorize(None);
+            let mut authorize = Authorize(Some(digest(&SHA256, b"s3cret")));
+
+            assert!(open.call(request(None)).is_ok());
+            assert!(authorize.call(request(Some("Bearer s3cret"))).is_ok());
+            for rejected in [None, Some("Bearer s3cre"), Some("s3cret")] {
+                let status = authorize.call(request(rejected)).unwrap_err();
+                assert_eq!(status.code(), tonic::Code::Unauthenticated);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+pub use server::{agent, serve};
+
+#[cfg(not(feature = "grpc"))]
+pub async fn serve(
+    _args: crate::cli::Cli,
+    _config: crate::config::Config,
+    _listen: std::net::SocketAddr,
+    _token: Option<String>,
+    _tls_cert: Option<std::path::PathBuf>,
+    _tls_key: Option<std::path::PathBuf>,
+) -> anyhow::Result<()> {
+    anyhow::bail!("The grpc-serve subcommand requires building with the `grpc` feature")
+}
+
+#[cfg(not(feature = "grpc"))]
+pub async fn agent(
+    _args: crate::cli::Cli,
+    _config: crate::config::Config,
+    _server: String,
+    _name: String,
+    _token: Option<String>,
+    _ca_cert: Option<std::path::PathBuf>,
+) -> anyhow::Result<()> {
+    anyhow::bail!("The agent subcommand requires building with the `grpc` feature")
+}
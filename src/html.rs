@@ -0,0 +1,361 @@
+//! Standalone HTML report of a run (`--export-html`): its figures and the
+//! throughput and latency time series captured while it ran, drawn by a
+//! small embedded script so the file opens anywhere without a network
+//! connection. Saved runs can be added as a history table and chart.
+
+use crate::config::Config;
+use crate::model::{Phase, RunResult, TestEvent};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// Time series of a run as it happened: seconds since the first event and
+/// the value (Mbps, or ms for latency).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timeline {
+    #[serde(skip)]
+    start: Option<Instant>,
+    pub download: Vec<(f64, f64)>,
+    pub upload: Vec<(f64, f64)>,
+    pub idle_latency: Vec<(f64, f64)>,
+    pub loaded_latency_download: Vec<(f64, f64)>,
+    pub loaded_latency_upload: Vec<(f64, f64)>,
+}
+
+impl Timeline {
+    /// Add the throughput or latency sample `event` carries, if any.
+    pub fn record(&mut self, event: &TestEvent) {
+        let t = self
+            .start
+            .get_or_insert_with(Instant::now)
+            .elapsed()
+            .as_secs_f64();
+        match *event {
+            TestEvent::ThroughputTick {
+                phase, bps_instant, ..
+            } => {
+                let mbps = (bps_instant * 8.0 / 1_000_000.0).max(0.0);
+                match phase {
+                    Phase::Download => self.download.push((t, mbps)),
+                    Phase::Upload => self.upload.push((t, mbps)),
+                    _ => {}
+                }
+            }
+            TestEvent::LatencySample {
+                phase,
+                during,
+                rtt_ms: Some(ms),
+                ok: true,
+            } => match (phase, during) {
+                (Phase::IdleLatency, _) => self.idle_latency.push((t, ms)),
+                (Phase::Download, Some(Phase::Download)) => {
+                    self.loaded_latency_download.push((t, ms))
+                }
+                (Phase::Upload, Some(Phase::Upload)) => self.loaded_latency_upload.push((t, ms)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Escape text for HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `value` as JSON that can sit inside a `<script>` element.
+fn script_json(value: &impl Serialize) -> Result<String> {
+    Ok(serde_json::to_string(value)?.replace("</", "<\\/"))
+}
+
+fn mbps(value: f64) -> String {
+    format!("{value:.2} Mbps")
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |ms| format!("{ms:.1} ms"))
+}
+
+/// Rows of the run's summary table.
+fn summary_rows(config: &Config, run: &RunResult) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("Time (UTC)", run.timestamp_utc.clone()),
+        ("Download", mbps(run.download.mbps)),
+        ("Upload", mbps(run.upload.mbps)),
+        (
+            "Idle latency",
+            format!(
+                "{} (jitter {})",
+                ms(run.idle_latency.median_ms),
+                ms(run.idle_latency.jitter_ms)
+            ),
+        ),
+        (
+            "Loaded latency (download)",
+            ms(run.loaded_latency_download.median_ms),
+        ),
+        (
+            "Loaded latency (upload)",
+            ms(run.loaded_latency_upload.median_ms),
+        ),
+    ];
+    if let Some(loss) = &run.packet_loss {
+        rows.push(("Packet loss", format!("{:.2}%", loss.latency.loss * 100.0)));
+    }
+    if let Some(server) = run.server.as_deref().or(run.colo.as_deref()) {
+        rows.push(("Server", server.to_string()));
+    }
+    if let Some(network) = config.network_label(run) {
+        rows.push(("Network", network.to_string()));
+    }
+    if let Some(interface) = run.interface_name.as_deref() {
+        rows.push(("Interface", interface.to_string()));
+    }
+    if let Some(profile) = crate::report::config_profile(run) {
+        rows.push(("Test", profile));
+    }
+    rows.push(("Measurement ID", run.meas_id.clone()));
+    rows
+}
+
+/// History point of the embedded data: Unix time and throughput.
+#[derive(Serialize)]
+struct HistoryPoint {
+    t: i64,
+    download: f64,
+    upload: f64,
+}
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 960px; color: #222; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.15em; margin-top: 2em; }
+table { border-collapse: collapse; }
+td, th { padding: 0.25em 1em 0.25em 0; text-align: left; }
+th { border-bottom: 1px solid #ccc; }
+canvas { width: 100%; height: 300px; border: 1px solid #eee; }
+.warning { color: #b00; }
+";
+
+/// Draws each `<canvas data-chart>` from `DATA`: `lineChart(canvas, series,
+/// xFormat, unit)` plots [x, y] points with the value axis from zero.
+const SCRIPT: &str = r##"
+const COLORS = { download: "#1f77b4", upload: "#ff7f0e", idle: "#2ca02c" };
+function lineChart(canvas, series, xFormat, unit) {
+  const ratio = window.devicePixelRatio || 1;
+  const w = canvas.clientWidth, h = canvas.clientHeight;
+  canvas.width = w * ratio; canvas.height = h * ratio;
+  const ctx = canvas.getContext("2d");
+  ctx.scale(ratio, ratio);
+  ctx.font = "12px system-ui, sans-serif";
+  const left = 70, right = 20, top = 30, bottom = 30;
+  const points = series.flatMap(s => s.points);
+  if (points.length === 0) {
+    ctx.fillText("No samples", left, top + 20);
+    return;
+  }
+  const xMin = Math.min(...points.map(p => p[0]));
+  const xMax = Math.max(xMin + 1, ...points.map(p => p[0]));
+  const yMax = Math.max(...points.map(p => p[1])) * 1.1 || 1;
+  const x = v => left + (v - xMin) / (xMax - xMin) * (w - left - right);
+  const y = v => h - bottom - v / yMax * (h - top - bottom);
+  ctx.strokeStyle = "#ddd"; ctx.fillStyle = "#555"; ctx.textAlign = "right";
+  for (let i = 0; i <= 4; i++) {
+    const v = yMax * i / 4;
+    ctx.beginPath(); ctx.moveTo(left, y(v)); ctx.lineTo(w - right, y(v)); ctx.stroke();
+    ctx.fillText(v.toFixed(v < 10 ? 1 : 0) + " " + unit, left - 6, y(v) + 4);
+  }
+  ctx.textAlign = "left"; ctx.fillText(xFormat(xMin), left, h - 10);
+  ctx.textAlign = "right"; ctx.fillText(xFormat(xMax), w - right, h - 10);
+  let legend = w - right;
+  for (const s of series.slice().reverse()) {
+    ctx.fillStyle = s.color; ctx.fillText(s.name, legend, 16);
+    legend -= ctx.measureText(s.name).width + 16;
+    ctx.strokeStyle = s.color; ctx.lineWidth = 1.5; ctx.beginPath();
+    s.points.forEach((p, i) => i ? ctx.lineTo(x(p[0]), y(p[1])) : ctx.moveTo(x(p[0]), y(p[1])));
+    ctx.stroke();
+  }
+}
+const seconds = v => v.toFixed(0) + " s";
+const date = v => new Date(v * 1000).toISOString().slice(0, 10);
+const charts = {
+  throughput: () => lineChart(document.getElementById("throughput"), [
+    { name: "Download", color: COLORS.download, points: DATA.timeline.download },
+    { name: "Upload", color: COLORS.upload, points: DATA.timeline.upload },
+  ], seconds, "Mbps"),
+  latency: () => lineChart(document.getElementById("latency"), [
+    { name: "Idle", color: COLORS.idle, points: DATA.timeline.idle_latency },
+    { name: "Loaded (download)", color: COLORS.download, points: DATA.timeline.loaded_latency_download },
+    { name: "Loaded (upload)", color: COLORS.upload, points: DATA.timeline.loaded_latency_upload },
+  ], seconds, "ms"),
+  history: () => lineChart(document.getElementById("history"), [
+    { name: "Download", color: COLORS.download, points: DATA.history.map(r => [r.t, r.download]) },
+    { name: "Upload", color: COLORS.upload, points: DATA.history.map(r => [r.t, r.upload]) },
+  ], date, "Mbps"),
+};
+function draw() {
+  for (const canvas of document.querySelectorAll("canvas[data-chart]")) charts[canvas.dataset.chart]();
+}
+window.addEventListener("resize", draw);
+draw();
+"##;
+
+/// The report of `run`, with the time series of `timeline` (None when the
+/// run wasn't watched, e.g. a saved run) and the saved runs of `history`,
+/// newest first.
+pub fn render(
+    config: &Config,
+    run: &RunResult,
+    timeline: Option<&Timeline>,
+    history: &[RunResult],
+) -> Result<String> {
+    let mut html = String::new();
+    let title = format!("Speed test {}", run.timestamp_utc);
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{}</h1>",
+        escape(&title),
+        escape(&title)
+    );
+    if let Some(reason) = run.invalid.as_deref() {
+        let _ = writeln!(
+            html,
+            "<p class=\"warning\">This run is invalid ({}), so its figures may mislead.</p>",
+            escape(reason)
+        );
+    }
+    let _ = writeln!(html, "<table>");
+    for (label, value) in summary_rows(config, run) {
+        let _ = writeln!(
+            html,
+            "<tr><th scope=\"row\">{label}</th><td>{}</td></tr>",
+            escape(&value)
+        );
+    }
+    let _ = writeln!(html, "</table>");
+
+    if timeline.is_some() {
+        let _ = writeln!(
+            html,
+            "<h2>Throughput</h2>\n<canvas id=\"throughput\" data-chart=\"throughput\"></canvas>\n\
+             <h2>Latency</h2>\n<canvas id=\"latency\" data-chart=\"latency\"></canvas>"
+        );
+    }
+
+    let history: Vec<&RunResult> = history
+        .iter()
+        .filter(|r| r.meas_id != run.meas_id && r.invalid.is_none())
+        .collect();
+    if !history.is_empty() {
+        let _ = writeln!(
+            html,
+            "<h2>History</h2>\n<canvas id=\"history\" data-chart=\"history\"></canvas>\n<table>\n\
+             <tr><th>Time (UTC)</th><th>Network</th><th>Download</th><th>Upload</th>\
+             <th>Idle latency</th></tr>"
+        );
+        for r in &history {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&r.timestamp_utc),
+                escape(config.network_label(r).unwrap_or("-")),
+                mbps(r.download.mbps),
+                mbps(r.upload.mbps),
+                ms(r.idle_latency.median_ms)
+            );
+        }
+        let _ = writeln!(html, "</table>");
+    }
+
+    // Oldest first, as the chart draws them
+    let points: Vec<HistoryPoint> = history
+        .iter()
+        .rev()
+        .chain(std::iter::once(&run))
+        .filter_map(|r| {
+            Some(HistoryPoint {
+                t: crate::report::unix_timestamp(&r.timestamp_utc)?,
+                download: r.download.mbps,
+                upload: r.upload.mbps,
+            })
+        })
+        .collect();
+    let data = serde_json::json!({
+        "timeline": timeline.cloned().unwrap_or_default(),
+        "history": points,
+    });
+    let _ = writeln!(
+        html,
+        "<p><small>cloudflare-speed-cli {}</small></p>\n\
+         <script>\nconst DATA = {};\n{SCRIPT}</script>\n</body>\n</html>",
+        env!("CARGO_PKG_VERSION"),
+        script_json(&data)?
+    );
+    Ok(html)
+}
+
+/// Write the report of `run` to `path` (see `render`).
+pub fn export_html(
+    config: &Config,
+    path: &Path,
+    run: &RunResult,
+    timeline: Option<&Timeline>,
+    history: &[RunResult],
+) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    std::fs::write(path, render(config, run, timeline, history)?).context("write export html")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    #[test]
+    fn test_render_html() {
+        let mut run = test_run(94.0, 40.0);
+        run.timestamp_utc = "2026-10-01T12:00:00Z".to_string();
+        run.meas_id = "42".to_string();
+        run.idle_latency.median_ms = Some(12.0);
+        run.network_name = Some("<script>alert(1)</script>".into());
+
+        let mut timeline = Timeline::default();
+        timeline.record(&TestEvent::ThroughputTick {
+            phase: Phase::Download,
+            bytes_total: 0,
+            bps_instant: 12_500_000.0,
+        });
+        timeline.record(&TestEvent::LatencySample {
+            phase: Phase::Upload,
+            during: Some(Phase::Upload),
+            rtt_ms: Some(30.0),
+            ok: true,
+        });
+        assert_eq!(timeline.download.len(), 1);
+        assert_eq!(timeline.download[0].1, 100.0);
+        assert_eq!(timeline.loaded_latency_upload.len(), 1);
+
+        let html = render(
+            &Config::default(),
+            &run,
+            Some(&timeline),
+            std::slice::from_ref(&run),
+        )
+        .unwrap();
+        assert!(html.contains("<td>94.00 Mbps</td>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("data-chart=\"latency\""));
+        // The run itself isn't repeated as history
+        assert!(!html.contains("<h2>History</h2>"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Localization of user-facing text, using the Fluent catalogs in `locales/`.
+//!
+//! The English (`en-US`) catalog is always loaded and used for any message
+//! missing from the active catalog. To add a translation, copy
+//! `locales/en-US/main.ftl` to `locales/<language>/main.ftl`, translate it and
+//! register it in `CATALOGS`.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Built-in catalogs as (language tag, Fluent source); the first is the fallback.
+const CATALOGS: &[(&str, &str)] = &[("en-US", include_str!("../locales/en-US/main.ftl"))];
+
+/// Translate a message by ID, with optional `name = value` arguments.
+///
+/// `t!("summary-server", server = name)`
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::translate($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($id, Some(&args))
+    }};
+}
+pub(crate) use t;
+
+struct Localizer {
+    active: Option<FluentBundle<FluentResource>>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+fn bundle(tag: &str, source: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = tag.parse().expect("valid catalog language tag");
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Terminals render the Unicode isolation marks around arguments as garbage
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).expect("valid Fluent catalog");
+    bundle
+        .add_resource(resource)
+        .expect("no duplicate message IDs in catalog");
+    bundle
+}
+
+/// Find the catalog for `requested` (e.g. "de", "pt_BR.UTF-8"), preferring an
+/// exact match and then one with the same language.
+fn find_catalog(requested: &str) -> Option<&'static (&'static str, &'static str)> {
+    let requested: LanguageIdentifier = requested
+        .split('.')
+        .next()?
+        .replace('_', "-")
+        .parse()
+        .ok()?;
+    let catalog_langid = |tag: &str| tag.parse::<LanguageIdentifier>().ok();
+    CATALOGS
+        .iter()
+        .find(|(tag, _)| catalog_langid(tag).as_ref() == Some(&requested))
+        .or_else(|| {
+            CATALOGS.iter().find(|(tag, _)| {
+                catalog_langid(tag).is_some_and(|l| l.language == requested.language)
+            })
+        })
+}
+
+/// Whether a translation for `language` is built in.
+pub fn is_available(language: &str) -> bool {
+    find_catalog(language).is_some()
+}
+
+/// Languages with a built-in translation.
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(tag, _)| *tag)
+}
+
+/// The translation used for `language`, or the system locale if None.
+pub fn resolve(language: Option<&str>) -> &'static str {
+    language
+        .map(str::to_string)
+        .or_else(sys_locale::get_locale)
+        .and_then(|requested| find_catalog(&requested))
+        .unwrap_or(&CATALOGS[0])
+        .0
+}
+
+impl Localizer {
+    fn new(language: Option<&str>) -> Self {
+        let (fallback_tag, fallback_source) = CATALOGS[0];
+        let active = language
+            .map(str::to_string)
+            .or_else(sys_locale::get_locale)
+            .and_then(|requested| find_catalog(&requested))
+            .filter(|(tag, _)| *tag != fallback_tag)
+            .map(|(tag, source)| bundle(tag, source));
+        Self {
+            active,
+            fallback: bundle(fallback_tag, fallback_source),
+        }
+    }
+}
+
+/// Select the language for this process. `language` (from the config file)
+/// overrides the system locale. Must be called before the first `t!`.
+pub fn init(language: Option<&str>) {
+    LOCALIZER.get_or_init(|| Localizer::new(language));
+}
+
+/// Look up and format a message, falling back to English and then to the ID.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    let localizer = LOCALIZER.get_or_init(|| Localizer::new(None));
+    for bundle in localizer.active.iter().chain([&localizer.fallback]) {
+        if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+            let mut errors = Vec::new();
+            return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        }
+    }
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(t!("tab-settings"), "Settings");
+        assert_eq!(
+            t!("summary-bufferbloat-grade", grade = "A+"),
+            "Bufferbloat grade: A+"
+        );
+        assert_eq!(t!("no-such-message"), "no-such-message");
+        assert_eq!(find_catalog("en_GB.UTF-8").map(|(tag, _)| *tag), Some("en-US"));
+        assert!(find_catalog("de-DE").is_none());
+    }
+}
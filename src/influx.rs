@@ -0,0 +1,198 @@
+//! InfluxDB line protocol export (`--export-influx`) and push to an
+//! InfluxDB v2 bucket (`--influx-url`).
+//!
+//! A run is one point of the `grafana::MEASUREMENT` measurement, tagged with
+//! the interface, network and colo. Every `ThroughputSummary` and
+//! `LatencySummary` field is written with a prefix naming the phase (e.g.
+//! `download_median_mbps`, `idle_jitter_ms`), alongside the fields the
+//! Grafana dashboard reads.
+
+use crate::config::Config;
+use crate::grafana::{
+    FIELD_BUFFERBLOAT_SCORE, FIELD_IDLE_LATENCY_MS, FIELD_IDLE_LOSS_PCT,
+    FIELD_LOADED_LATENCY_DOWNLOAD_MS, FIELD_LOADED_LATENCY_UPLOAD_MS, FIELD_PACKET_LOSS_PCT,
+    MEASUREMENT,
+};
+use crate::model::RunResult;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// Timeout for writing a point to InfluxDB.
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Escape a tag key or value: commas, equals signs and spaces.
+fn escape_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Append the fields of a serialized summary as `<prefix>_<name>=<value>`:
+/// integers with the `i` suffix, floats and booleans as is, nulls skipped.
+fn push_summary_fields(fields: &mut Vec<String>, prefix: &str, summary: &impl Serialize) {
+    let Ok(serde_json::Value::Object(map)) = serde_json::to_value(summary) else {
+        return;
+    };
+    for (name, value) in map {
+        let value = match value {
+            serde_json::Value::Number(n) if n.is_f64() => n.to_string(),
+            serde_json::Value::Number(n) => format!("{n}i"),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+        fields.push(format!("{prefix}_{name}={value}"));
+    }
+}
+
+/// One line protocol point for `result`.
+pub fn line_protocol(config: &Config, result: &RunResult) -> String {
+    let mut line = MEASUREMENT.to_string();
+    let tags = [
+        ("interface", result.interface_name.as_deref()),
+        ("network", config.network_label(result)),
+        ("colo", result.colo.as_deref()),
+    ];
+    for (key, value) in tags {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            line.push_str(&format!(",{key}={}", escape_tag(value)));
+        }
+    }
+
+    let mut fields = Vec::new();
+    push_summary_fields(&mut fields, "download", &result.download);
+    push_summary_fields(&mut fields, "upload", &result.upload);
+    push_summary_fields(&mut fields, "idle", &result.idle_latency);
+    push_summary_fields(
+        &mut fields,
+        "loaded_download",
+        &result.loaded_latency_download,
+    );
+    push_summary_fields(&mut fields, "loaded_upload", &result.loaded_latency_upload);
+    if let Some(udp) = result.packet_loss.as_ref() {
+        push_summary_fields(&mut fields, "udp", &udp.latency);
+    }
+    // `download_mbps` and `upload_mbps` are written above
+    for field in [
+        FIELD_IDLE_LATENCY_MS,
+        FIELD_LOADED_LATENCY_DOWNLOAD_MS,
+        FIELD_LOADED_LATENCY_UPLOAD_MS,
+        FIELD_IDLE_LOSS_PCT,
+        FIELD_PACKET_LOSS_PCT,
+        FIELD_BUFFERBLOAT_SCORE,
+    ] {
+        let value = crate::storage::column_value(result, field);
+        if let Ok(v) = value.parse::<f64>() {
+            if v.is_finite() {
+                fields.push(format!("{field}={v}"));
+            }
+        }
+    }
+    line.push(' ');
+    line.push_str(&fields.join(","));
+
+    if let Some(secs) = crate::report::unix_timestamp(&result.timestamp_utc) {
+        line.push_str(&format!(" {}", secs as i128 * 1_000_000_000));
+    }
+    line
+}
+
+/// Append `result` to `path` as a line of line protocol, creating the file
+/// if needed, so repeated runs build up one file.
+pub fn export_line_protocol(config: &Config, path: &Path, result: &RunResult) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    writeln!(file, "{}", line_protocol(config, result)).context("write line protocol")?;
+    Ok(())
+}
+
+/// Write `result` to an InfluxDB v2 bucket through its `/api/v2/write` API.
+pub async fn push(
+    config: &Config,
+    url: &str,
+    token: Option<&str>,
+    org: &str,
+    bucket: &str,
+    result: &RunResult,
+) -> Result<()> {
+    // Keep any path prefix of a proxied server, e.g. https://host/influx
+    let mut write_url = reqwest::Url::parse(&format!("{}/", url.trim_end_matches('/')))
+        .and_then(|base| base.join("api/v2/write"))
+        .with_context(|| format!("invalid InfluxDB URL '{url}'"))?;
+    write_url
+        .query_pairs_mut()
+        .append_pair("org", org)
+        .append_pair("bucket", bucket)
+        .append_pair("precision", "ns");
+
+    let mut request = reqwest::Client::new()
+        .post(write_url)
+        .timeout(PUSH_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(line_protocol(config, result));
+    if let Some(token) = token {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("Token {token}"));
+    }
+    let response = request.send().await.context("InfluxDB write failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("InfluxDB write failed: {status} {}", body.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    #[test]
+    fn test_line_protocol() {
+        let mut r = test_run(512.5, 40.0);
+        r.timestamp_utc = "2026-03-15T14:30:45Z".to_string();
+        r.idle_latency.sent = 10;
+        r.idle_latency.received = 9;
+        r.idle_latency.loss = 0.1;
+        r.idle_latency.median_ms = Some(12.5);
+        r.download.bytes = 1000;
+        r.download.duration_ms = 10;
+        r.network_name = Some("Home Wi-Fi, 5G".to_string());
+        r.colo = Some("FRA".to_string());
+
+        let line = line_protocol(&Config::default(), &r);
+        let rest = line
+            .strip_prefix(r"cloudflare_speed,network=Home\ Wi-Fi\,\ 5G,colo=FRA ")
+            .unwrap();
+        let (fields, ts) = rest.rsplit_once(' ').unwrap();
+        assert_eq!(ts, "1773585045000000000");
+        let fields: Vec<&str> = fields.split(',').collect();
+        for expected in [
+            "download_mbps=512.5",
+            "download_bytes=1000i",
+            "download_link_limited=false",
+            "upload_mbps=40.0",
+            "idle_sent=10i",
+            "idle_loss=10.0",
+            "idle_median_ms=12.5",
+            "idle_latency_ms=12.5",
+            "idle_loss_pct=10",
+        ] {
+            assert!(fields.contains(&expected), "{expected} missing: {fields:?}");
+        }
+        assert!(!fields.iter().any(|f| f.starts_with("idle_min_ms")));
+    }
+}
@@ -1,25 +1,92 @@
+mod alert;
+mod bundle;
+mod calibration;
+mod cellular;
 mod cli;
+mod compare;
+mod config;
+mod control;
+mod diff;
+mod email;
 mod engine;
+mod explain;
+mod fleet;
+mod geo;
+mod grafana;
+mod grpc;
+mod html;
+mod i18n;
+mod influx;
 mod metrics;
 mod model;
+mod monitor;
+mod netwatch;
 mod network;
+mod notify;
+mod orchestrator;
+mod parity;
+mod power;
+mod progress;
+mod prometheus;
+mod qos;
+mod query;
+mod repeat;
+mod report;
+mod service;
+mod signing;
 mod stats;
 mod storage;
+mod thermal;
 #[cfg(feature = "tui")]
 mod tui;
+mod units;
 #[cfg(feature = "tui")]
 mod update;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = cli::Cli::parse();
+    let matches = cli::Cli::command().get_matches();
+    let mut args = cli::Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let config = match config::load() {
+        Ok(config) => config,
+        // `config check` reports the error itself
+        Err(_) if matches!(args.command, Some(cli::Command::Config { .. })) => {
+            config::Config::default()
+        }
+        Err(e) => return Err(e.context("fix the config file, or see `config check`")),
+    };
+    config::apply_to_args(&config, &mut args, &matches);
+    if let Some(dir) = args.data_dir.clone() {
+        storage::set_data_dir(dir);
+    }
+    // Monitor mode applies them before each run, as the network may change
+    let runs_test = args.command.is_none() || matches!(args.command, Some(cli::Command::Calibrate));
+    if !config.networks.is_empty() && runs_test && args.monitor.is_none() {
+        let network = network::gather_network_info(&args);
+        if let Some(key) = config::apply_network_settings(&config, &mut args, &matches, &network) {
+            if !args.silent {
+                eprintln!("Using the settings for network \"{key}\"");
+            }
+        }
+    }
+    storage::set_retention(cli::retention(&args));
+    // Catch a bad column list before running a test
+    storage::csv_columns(&args.csv_columns)?;
+    i18n::init(config.language.as_deref());
     let is_silent = args.silent;
-    let is_non_tui = args.silent || args.json || args.text;
+    let is_non_tui = args.silent
+        || args.json
+        || args.json_stream
+        || args.text
+        || args.monitor.is_some()
+        || !args.compare_providers.is_empty()
+        || !args.compare_sources.is_empty()
+        || args.command.is_some();
 
-    match cli::run(args).await {
+    match cli::run(args, config, matches).await {
         Ok(()) => {
             if is_non_tui {
                 std::process::exit(0);
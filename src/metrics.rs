@@ -17,6 +17,18 @@ pub fn compute_metrics(samples: &[f64]) -> Option<(f64, f64, f64, f64)> {
     Some((mean, median, p25, p75))
 }
 
+/// Median of `values`, the mean of the middle two for an even count.
+pub fn median(values: &[f64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n.is_multiple_of(2) => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+        _ => Some(sorted[n / 2]),
+    }
+}
+
 /// Compute jitter (standard deviation) from latency samples.
 pub fn compute_jitter(samples: &[f64]) -> Option<f64> {
     if samples.len() < 2 {
@@ -28,6 +40,68 @@ pub fn compute_jitter(samples: &[f64]) -> Option<f64> {
     Some(variance.sqrt())
 }
 
+/// Split samples into `buckets` equal-width buckets between their minimum and
+/// maximum. Returns the lower bound and sample count of each bucket.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn histogram(samples: &[f64], buckets: usize) -> Vec<(f64, u64)> {
+    let (min, max) = samples
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| {
+            (lo.min(x), hi.max(x))
+        });
+    if samples.is_empty() || buckets == 0 {
+        return Vec::new();
+    }
+    let width = (max - min) / buckets as f64;
+    let mut counts = vec![0u64; buckets];
+    for &x in samples {
+        // All samples land in the first bucket when they're equal
+        let i = if width > 0.0 {
+            (((x - min) / width) as usize).min(buckets - 1)
+        } else {
+            0
+        };
+        counts[i] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| (min + width * i as f64, count))
+        .collect()
+}
+
+/// Empirical cumulative distribution of `samples`: each value in ascending
+/// order with the percentage of samples at or below it.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn cdf(samples: &[f64]) -> Vec<(f64, f64)> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| (x, (i + 1) as f64 * 100.0 / n))
+        .collect()
+}
+
+/// Bufferbloat grades from worst to best; the index doubles as a numeric score.
+pub const BUFFERBLOAT_GRADES: [&str; 6] = ["F", "D", "C", "B", "A", "A+"];
+
+/// Grade bufferbloat from the latency increase under load, using the
+/// thresholds of the Waveform bufferbloat test. Returns an index into
+/// `BUFFERBLOAT_GRADES`.
+pub fn bufferbloat_score(idle_ms: f64, loaded_ms: f64) -> usize {
+    let increase = (loaded_ms - idle_ms).max(0.0);
+    match increase {
+        x if x < 5.0 => 5,
+        x if x < 30.0 => 4,
+        x if x < 60.0 => 3,
+        x if x < 200.0 => 2,
+        x if x < 400.0 => 1,
+        _ => 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,6 +141,13 @@ mod tests {
         assert!((p75 - 4.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(median(&[]), None);
+    }
+
     #[test]
     fn test_compute_jitter_basic() {
         // samples: [1, 2, 3, 4, 5], mean = 3, variance = 10/4 = 2.5, stddev = sqrt(2.5) ≈ 1.58
@@ -80,4 +161,37 @@ mod tests {
         assert!(compute_jitter(&[1.0]).is_none());
         assert!(compute_jitter(&[]).is_none());
     }
+
+    #[test]
+    fn test_histogram() {
+        // Mostly ~15 ms with occasional 300 ms spikes
+        let samples = [14.0, 15.0, 15.0, 16.0, 300.0, 15.0, 298.0];
+        let buckets = histogram(&samples, 4);
+        let counts: Vec<u64> = buckets.iter().map(|(_, c)| *c).collect();
+        assert_eq!(counts, [5, 0, 0, 2]);
+        assert_eq!(buckets[0].0, 14.0);
+        assert_eq!(buckets[2].0, 157.0);
+        assert_eq!(histogram(&[20.0, 20.0], 3)[0], (20.0, 2));
+        assert!(histogram(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_cdf() {
+        let points = cdf(&[300.0, 100.0, 200.0, 400.0]);
+        assert_eq!(
+            points,
+            [(100.0, 25.0), (200.0, 50.0), (300.0, 75.0), (400.0, 100.0)]
+        );
+        assert!(cdf(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_bufferbloat_score() {
+        assert_eq!(BUFFERBLOAT_GRADES[bufferbloat_score(20.0, 22.0)], "A+");
+        assert_eq!(BUFFERBLOAT_GRADES[bufferbloat_score(20.0, 45.0)], "A");
+        assert_eq!(BUFFERBLOAT_GRADES[bufferbloat_score(20.0, 150.0)], "C");
+        assert_eq!(BUFFERBLOAT_GRADES[bufferbloat_score(20.0, 900.0)], "F");
+        // Loaded latency below idle is not penalised
+        assert_eq!(BUFFERBLOAT_GRADES[bufferbloat_score(20.0, 15.0)], "A+");
+    }
 }
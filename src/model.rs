@@ -1,3 +1,5 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -21,32 +23,108 @@ mod loss_percent_serde {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct RunConfig {
     pub base_url: String,
+    /// Backend `base_url` speaks
+    #[serde(default)]
+    pub provider: ProviderKind,
+    /// Shortened test of `--quick`
+    #[serde(default)]
+    pub quick: bool,
+    /// URL latency probes go to instead of `base_url`
+    #[serde(default)]
+    pub latency_url: Option<String>,
     pub meas_id: String,
     #[serde(default)]
     pub comments: Option<String>,
     pub download_bytes_per_req: u64,
     pub upload_bytes_per_req: u64,
+    /// Content of the upload bodies
+    #[serde(default)]
+    pub upload_pattern: UploadPattern,
     pub concurrency: usize,
+    /// Download streams instead of `concurrency`
+    #[serde(default)]
+    pub download_concurrency: Option<usize>,
+    /// Upload streams instead of `concurrency`
+    #[serde(default)]
+    pub upload_concurrency: Option<usize>,
+    /// Start with one stream and add streams while throughput rises, up to
+    /// the phase's stream count
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
+    /// Run a single download/upload worker to measure one flow
+    #[serde(default)]
+    pub single_connection: bool,
+    /// Follow speed.cloudflare.com's plan of request sizes and report its
+    /// percentile of request speeds (see `parity`)
+    #[serde(default)]
+    pub website_parity: bool,
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub idle_latency_duration: Duration,
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub download_duration: Duration,
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub upload_duration: Duration,
     pub probe_interval_ms: u64,
     pub probe_timeout_ms: u64,
+    /// Timeout for each HTTP request, including its body
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub request_timeout: Duration,
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub connect_timeout: Duration,
+    /// Throughput requests transferring fewer bytes per second are abandoned
+    pub min_rate_bytes_per_sec: Option<u64>,
+    /// End a throughput phase early once it saturates this link speed (Mbps)
+    pub stop_at_link_speed_mbps: Option<u64>,
+    /// MaxMind/GeoLite2 City database used to locate the client
+    pub geoip_db: Option<std::path::PathBuf>,
     pub user_agent: String,
     pub experimental: bool,
     pub interface: Option<String>,
     pub source_ip: Option<String>,
+    /// Which IPv6 address of `interface` to bind to
+    #[serde(default)]
+    pub ipv6_address: Option<Ipv6AddressChoice>,
     pub proxy: Option<String>,
     pub certificate_path: Option<std::path::PathBuf>,
+    /// HTTP version to use instead of negotiating one
+    #[serde(default)]
+    pub http_version: Option<HttpVersion>,
+    /// Resolver looking up the test server instead of the system one
+    #[serde(default)]
+    pub resolver: Option<Resolver>,
+    /// Credentials for a private test server; never serialized, so they
+    /// stay out of saved results
+    #[serde(skip)]
+    pub auth: Option<Auth>,
+    /// Cloudflare Access service token for a test server behind Access;
+    /// never serialized either
+    #[serde(skip)]
+    pub access_token: Option<AccessToken>,
+    /// ID sent in a header with every request, to find the test's flows in
+    /// firewall or flow logs
+    #[serde(default)]
+    pub correlation_id: Option<CorrelationId>,
     // Diagnostic options
     pub measure_dns: bool,
+    /// Also time the DNS lookup against 1.1.1.1 and 8.8.8.8
+    #[serde(default)]
+    pub compare_resolvers: bool,
     pub measure_tls: bool,
     pub compare_ip_versions: bool,
+    /// Also measure download over a single stream, to compare with `concurrency`
+    #[serde(default)]
+    pub compare_streams: bool,
+    /// Record CPU frequency and thermal throttling during the test
+    #[serde(default)]
+    pub thermal: bool,
     pub traceroute: bool,
     pub traceroute_max_hops: u8,
     pub ipv4_only: bool,
@@ -54,6 +132,271 @@ pub struct RunConfig {
     pub udp_packets: u64,
 }
 
+impl RunConfig {
+    /// Whether requests to the test server carry credentials.
+    pub fn has_credentials(&self) -> bool {
+        self.auth.is_some() || self.access_token.is_some()
+    }
+
+    /// Whether connections may use `ip`, given `ipv4_only`/`ipv6_only`.
+    pub fn allows_ip(&self, ip: std::net::IpAddr) -> bool {
+        !(self.ipv4_only && ip.is_ipv6() || self.ipv6_only && ip.is_ipv4())
+    }
+
+    /// Streams used by the download and upload phases, unless overridden
+    /// per phase.
+    pub fn streams(&self) -> usize {
+        if self.single_connection {
+            1
+        } else {
+            self.concurrency
+        }
+    }
+
+    /// Streams used by the download phase.
+    pub fn download_streams(&self) -> usize {
+        match self.download_concurrency {
+            Some(streams) if !self.single_connection => streams,
+            _ => self.streams(),
+        }
+    }
+
+    /// Streams used by the upload phase.
+    pub fn upload_streams(&self) -> usize {
+        match self.upload_concurrency {
+            Some(streams) if !self.single_connection => streams,
+            _ => self.streams(),
+        }
+    }
+
+    /// Short description of the test, e.g. "10s×6" for 10 second phases over
+    /// 6 streams, or "10s×8/2" for 8 download and 2 upload streams.
+    /// Adaptive concurrency marks the stream cap with "≤", e.g. "10s×≤16";
+    /// runs with `website_parity` are "website".
+    pub fn label(&self) -> String {
+        if self.website_parity {
+            return "website".to_string();
+        }
+        let (down, up) = (self.download_streams(), self.upload_streams());
+        let cap = if self.adaptive_concurrency { "≤" } else { "" };
+        if down == up {
+            format!("{}s×{cap}{down}", self.download_duration.as_secs())
+        } else {
+            format!("{}s×{cap}{down}/{up}", self.download_duration.as_secs())
+        }
+    }
+
+    /// This configuration as stored with a result: without credentials in
+    /// the proxy URL, which is otherwise kept so the run can be repeated.
+    pub fn for_storage(&self) -> Self {
+        let proxy = self.proxy.as_deref().map(|proxy| {
+            let (scheme, rest) = proxy.split_once("://").unwrap_or(("", proxy));
+            match rest.rsplit_once('@') {
+                Some((_, host)) if scheme.is_empty() => host.to_string(),
+                Some((_, host)) => format!("{scheme}://{host}"),
+                None => proxy.to_string(),
+            }
+        });
+        Self {
+            proxy,
+            ..self.clone()
+        }
+    }
+}
+
+/// Credentials sent with every request to the test server (`--auth-bearer`,
+/// `--auth-basic`).
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    Bearer(String),
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+}
+
+impl Auth {
+    /// Value of the `Authorization` header.
+    pub fn header_value(&self) -> String {
+        use base64::Engine;
+        match self {
+            Auth::Bearer(token) => format!("Bearer {token}"),
+            Auth::Basic { username, password } => {
+                let credentials = format!("{username}:{}", password.as_deref().unwrap_or(""));
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(credentials)
+                )
+            }
+        }
+    }
+}
+
+// Keep secrets out of debug output
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Auth::Bearer(_) => f.write_str("Bearer(..)"),
+            Auth::Basic { username, .. } => write!(f, "Basic({username}, ..)"),
+        }
+    }
+}
+
+/// Cloudflare Access service token, sent as the `CF-Access-Client-Id` and
+/// `CF-Access-Client-Secret` headers.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl std::fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AccessToken({}, ..)", self.client_id)
+    }
+}
+
+/// HTTP version forced with `--http-version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum HttpVersion {
+    #[serde(rename = "1.1")]
+    #[value(name = "1.1")]
+    Http11,
+    #[serde(rename = "2")]
+    #[value(name = "2")]
+    Http2,
+}
+
+/// Speed test backend chosen with `--provider`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// speed.cloudflare.com, or a server with the same API
+    #[default]
+    Cloudflare,
+    /// A LibreSpeed server
+    Librespeed,
+}
+
+impl ProviderKind {
+    /// Server used when none is given; LibreSpeed has no canonical one.
+    pub fn default_url(self) -> Option<&'static str> {
+        match self {
+            Self::Cloudflare => Some("https://speed.cloudflare.com"),
+            Self::Librespeed => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Cloudflare => "cloudflare",
+            Self::Librespeed => "librespeed",
+        })
+    }
+}
+
+/// IPv6 address of the interface chosen with `--ipv6-address`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Ipv6AddressChoice {
+    /// A temporary (privacy) address, which the system rotates
+    Temporary,
+    /// The stable address (EUI-64 or stable-privacy), which doesn't rotate
+    Stable,
+    /// The address within this prefix
+    Prefix {
+        network: std::net::Ipv6Addr,
+        len: u8,
+    },
+}
+
+impl Ipv6AddressChoice {
+    /// Whether `ip` is the kind of address chosen, given whether the system
+    /// flags it as temporary.
+    pub fn matches(&self, ip: std::net::Ipv6Addr, temporary: bool) -> bool {
+        match *self {
+            Ipv6AddressChoice::Temporary => temporary,
+            Ipv6AddressChoice::Stable => !temporary,
+            Ipv6AddressChoice::Prefix { network, len } => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(len)).unwrap_or(0);
+                u128::from(ip) & mask == u128::from(network) & mask
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Ipv6AddressChoice {
+    type Err = String;
+
+    /// `temporary`, `stable` or a prefix such as `2001:db8:1:2::/64`.
+    fn from_str(value: &str) -> Result<Self, String> {
+        match value {
+            "temporary" => Ok(Ipv6AddressChoice::Temporary),
+            "stable" => Ok(Ipv6AddressChoice::Stable),
+            _ => {
+                let (network, len) = value.split_once('/').ok_or_else(|| {
+                    format!("expected temporary, stable or an IPv6 prefix, got {value:?}")
+                })?;
+                let network = network
+                    .parse()
+                    .map_err(|e| format!("invalid IPv6 prefix {value:?}: {e}"))?;
+                let len = len
+                    .parse()
+                    .ok()
+                    .filter(|&len| len <= 128)
+                    .ok_or_else(|| format!("invalid prefix length in {value:?}"))?;
+                Ok(Ipv6AddressChoice::Prefix { network, len })
+            }
+        }
+    }
+}
+
+/// Correlation ID of `--correlation-id`, and the header carrying it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CorrelationId {
+    pub header: String,
+    pub id: String,
+}
+
+/// Upload body content chosen with `--upload-pattern`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum UploadPattern {
+    /// Zero bytes, which middleboxes may compress or deduplicate
+    #[default]
+    Zeros,
+    /// Random bytes, which don't compress
+    Random,
+    /// Random letters, which compress about 2:1 like text
+    Compressible,
+}
+
+/// DNS server (`--resolver`) or DNS-over-HTTPS endpoint (`--doh`) used
+/// instead of the system resolver.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Resolver {
+    Dns(std::net::IpAddr),
+    Doh(String),
+}
+
+impl std::fmt::Display for Resolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resolver::Dns(ip) => write!(f, "{ip}"),
+            Resolver::Doh(url) => f.write_str(url),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Phase {
     IdleLatency,
@@ -74,7 +417,12 @@ impl Phase {
     }
 }
 
+/// Progress event emitted by the test engine.
+///
+/// Serialized internally tagged, e.g. `{"type": "phase_started", "phase": "Download"}`.
+/// Variant and field names are part of the public event format; only add to them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum TestEvent {
     PhaseStarted {
         phase: Phase,
@@ -123,13 +471,32 @@ pub enum TestEvent {
         ipv4: Option<String>,
         ipv6: Option<String>,
     },
+    GeoInfo {
+        geo: Box<GeoSummary>,
+    },
+}
+
+/// A `TestEvent` stamped with the run it belongs to and its position in that run.
+///
+/// `seq` starts at 0 for each run and increases by exactly one per event, so
+/// consumers can detect gaps. `run_id` equals the run's `meas_id`. The event is
+/// flattened: `{"seq": 3, "run_id": "...", "type": "phase_started", ...}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub seq: u64,
+    pub run_id: String,
+    #[serde(flatten)]
+    pub event: TestEvent,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct LatencySummary {
     pub sent: u64,
     pub received: u64,
+    /// Packet loss, serialized as a percentage (0-100)
     #[serde(with = "loss_percent_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "f64"))]
     pub loss: f64,
     pub min_ms: Option<f64>,
     pub mean_ms: Option<f64>,
@@ -168,25 +535,189 @@ impl LatencySummary {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct ThroughputSummary {
     pub bytes: u64,
+    /// Total bytes transferred during the phase, including the ramp-up excluded from `bytes`
+    #[serde(default)]
+    pub bytes_total: u64,
     pub duration_ms: u64,
     pub mbps: f64,
     pub mean_mbps: Option<f64>,
     pub median_mbps: Option<f64>,
     pub p25_mbps: Option<f64>,
     pub p75_mbps: Option<f64>,
+    /// Times a worker replaced its connection after repeated failed requests
+    #[serde(default)]
+    pub worker_restarts: u64,
+    /// Connections that completed a request, including replacements after restarts
+    #[serde(default)]
+    pub connections: u64,
+    /// Throughput reached the interface's link speed, so the NIC was the bottleneck
+    #[serde(default)]
+    pub link_limited: bool,
+    /// Each stream's share of `mbps`, when the phase ran more than one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stream_mbps: Vec<f64>,
+    /// With adaptive concurrency, the streams after which another one no
+    /// longer raised throughput (or the stream cap, if every one did)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturation_streams: Option<u64>,
+    /// The adaptive ramp-up was still adding streams when the phase ended:
+    /// `saturation_streams` is the streams it reached, not where
+    /// throughput stopped rising
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ramp_unfinished: bool,
+    /// Download responses read to the end; None for uploads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responses: Option<u64>,
+    /// Download responses the server ended, without an error, before
+    /// sending the bytes requested; they otherwise just look like lower
+    /// throughput. None for uploads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated_responses: Option<u64>,
+}
+
+impl ThroughputSummary {
+    /// Share of the responses that were truncated, in percent.
+    pub fn truncated_pct(&self) -> Option<f64> {
+        let (responses, truncated) = (self.responses?, self.truncated_responses?);
+        (responses > 0 && truncated > 0).then(|| truncated as f64 * 100.0 / responses as f64)
+    }
 }
 
+/// Download over a single stream, measured after the multi-stream download
+/// phase. A multi-stream result well above the single-stream one points to a
+/// path limited per flow by loss or latency; similar results point to a
+/// shaper or policer limiting the connection as a whole.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StreamComparison {
+    /// Streams used by the download phase
+    pub streams: u64,
+    pub single_stream: ThroughputSummary,
+}
+
+/// Cellular connection details for runs over a mobile modem.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CellularSummary {
+    pub carrier: Option<String>,
+    /// Radio access technology, e.g. "LTE" or "NR" (5G)
+    pub technology: Option<String>,
+    /// Signal quality reported by the modem, 0-100
+    pub signal_quality_percent: Option<u8>,
+    pub rsrp_dbm: Option<f64>,
+    pub rsrq_db: Option<f64>,
+    pub sinr_db: Option<f64>,
+    pub rssi_dbm: Option<f64>,
+    /// Where the details came from: "android", "termux-api" or "modemmanager"
+    pub source: String,
+}
+
+impl CellularSummary {
+    /// One-line description, e.g. "LTE, Example Mobile, RSRP -95 dBm, SINR 8 dB".
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        parts.extend(self.technology.clone());
+        parts.extend(self.carrier.clone());
+        if let Some(rsrp) = self.rsrp_dbm {
+            parts.push(format!("RSRP {rsrp:.0} dBm"));
+        } else if let Some(q) = self.signal_quality_percent {
+            parts.push(format!("signal {q}%"));
+        }
+        if let Some(sinr) = self.sinr_db {
+            parts.push(format!("SINR {sinr:.0} dB"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Power source of the client machine when a run started; CPU governors
+/// on battery can hold back throughput on some machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PowerSummary {
+    /// Running on battery rather than mains power
+    pub on_battery: bool,
+    /// Battery charge, 0-100
+    pub battery_percent: Option<u8>,
+}
+
+/// CPU frequency and thermal state of the client machine during the download
+/// and upload phases (`--thermal`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ThermalSummary {
+    /// Mean frequency of the fastest core (MHz)
+    pub cpu_mhz: Option<f64>,
+    /// Rated maximum CPU frequency (MHz)
+    pub cpu_max_mhz: Option<f64>,
+    /// Hottest temperature reported by a thermal zone (°C)
+    pub max_temperature_c: Option<f64>,
+    /// Thermal throttling events counted by the CPU during the test
+    pub throttle_events: Option<u64>,
+    /// Lowest CPU speed limit set by the OS (percent)
+    pub cpu_speed_limit_pct: Option<u8>,
+    /// The CPU was throttled, so throughput may have been limited by the
+    /// client rather than the network
+    pub throttled: bool,
+}
+
+impl ThermalSummary {
+    /// One-line description, e.g. "throttled, 1.8 of 3.4 GHz, 96°C".
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if self.throttled {
+            parts.push("throttled".to_string());
+        }
+        match (self.cpu_mhz, self.cpu_max_mhz) {
+            (Some(mhz), Some(max)) => {
+                parts.push(format!("{:.1} of {:.1} GHz", mhz / 1000.0, max / 1000.0))
+            }
+            (Some(mhz), None) => parts.push(format!("{:.1} GHz", mhz / 1000.0)),
+            _ => {}
+        }
+        if let Some(limit) = self.cpu_speed_limit_pct.filter(|l| *l < 100) {
+            parts.push(format!("speed limit {limit}%"));
+        }
+        if let Some(temp) = self.max_temperature_c {
+            parts.push(format!("{temp:.0}°C"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Approximate locations of the client and the Cloudflare colo serving the test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct GeoSummary {
+    pub client_city: Option<String>,
+    pub client_country: Option<String>,
+    pub client_latitude: Option<f64>,
+    pub client_longitude: Option<f64>,
+    /// Where the client location came from: "cloudflare" or "geoip-db"
+    pub client_source: String,
+    pub colo: Option<String>,
+    pub colo_city: Option<String>,
+    pub colo_latitude: Option<f64>,
+    pub colo_longitude: Option<f64>,
+    /// Great-circle distance from the client to the colo in km
+    pub distance_km: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct TurnInfo {
     pub urls: Vec<String>,
     pub username: Option<String>,
     pub credential: Option<String>,
 }
 
+/// Result of the UDP packet loss phase.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExperimentalUdpSummary {
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PacketLossSummary {
     pub target: Option<String>,
     pub latency: LatencySummary,
     /// Count of out-of-order packets received
@@ -204,27 +735,45 @@ pub struct ExperimentalUdpSummary {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct RunResult {
     #[serde(default)]
     pub version: Option<String>,
     #[serde(default)]
     pub timestamp_utc: String,
     pub base_url: String,
+    /// Where latency was measured, when not `base_url`
+    #[serde(default)]
+    pub latency_url: Option<String>,
     pub meas_id: String,
     #[serde(default)]
     pub comments: Option<String>,
     pub meta: Option<serde_json::Value>,
+    /// Backend the test ran against, e.g. "cloudflare"
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Identity of the server that ran the test, as the provider names it
+    /// (a colo code for Cloudflare)
+    #[serde(default)]
+    pub server_id: Option<String>,
     #[serde(default)]
     pub server: Option<String>,
+    /// Age in seconds of the cached locations list used to resolve `server`,
+    /// if it wasn't fetched for this run
+    #[serde(default)]
+    pub locations_age_secs: Option<u64>,
     pub idle_latency: LatencySummary,
     pub download: ThroughputSummary,
     pub upload: ThroughputSummary,
     pub loaded_latency_download: LatencySummary,
     pub loaded_latency_upload: LatencySummary,
     pub turn: Option<TurnInfo>,
-    pub experimental_udp: Option<ExperimentalUdpSummary>,
+    /// Packet loss phase; saved as `experimental_udp` by earlier versions
+    #[serde(alias = "experimental_udp")]
+    pub packet_loss: Option<PacketLossSummary>,
     /// Error message when TURN fetch or UDP probe failed (for UI display)
     #[serde(skip, default)]
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
     pub udp_error: Option<String>,
     // Network information
     #[serde(default)]
@@ -243,10 +792,32 @@ pub struct RunResult {
     pub is_wireless: Option<bool>,
     #[serde(default)]
     pub interface_mac: Option<String>,
+    /// Negotiated link speed of the (wired) network interface in Mbps
+    #[serde(default)]
+    pub link_speed_mbps: Option<u64>,
+    /// Interface of the default route when the test ran
+    #[serde(default)]
+    pub route_interface: Option<String>,
+    /// Radio technology of a cellular connection, e.g. "LTE" or "NR"; the
+    /// same as `cellular.technology`, kept for readers of earlier results
+    #[serde(default)]
+    pub cellular_type: Option<String>,
+    #[serde(default)]
+    pub cellular: Option<CellularSummary>,
+    /// Power source when the run started, on machines with a battery
+    #[serde(default)]
+    pub power: Option<PowerSummary>,
+    /// CPU frequency and throttling during the test, with `--thermal`
+    #[serde(default)]
+    pub thermal: Option<ThermalSummary>,
     #[serde(default)]
     pub local_ipv4: Option<String>,
     #[serde(default)]
     pub local_ipv6: Option<String>,
+    /// Local address the test's connections were bound to, with
+    /// `--interface` or `--source`
+    #[serde(default)]
+    pub source_address: Option<String>,
     #[serde(default)]
     pub external_ipv4: Option<String>,
     #[serde(default)]
@@ -259,7 +830,233 @@ pub struct RunResult {
     #[serde(default)]
     pub ip_comparison: Option<IpVersionComparison>,
     #[serde(default)]
+    pub stream_comparison: Option<StreamComparison>,
+    #[serde(default)]
     pub traceroute: Option<TracerouteSummary>,
+    #[serde(default)]
+    pub geo: Option<GeoSummary>,
+    #[serde(default)]
+    pub client: Option<ClientInfo>,
+    /// Configuration the run was made with; missing in results saved before
+    /// it was stored
+    #[serde(default)]
+    pub config: Option<RunConfig>,
+    /// Why the results can't be trusted, e.g. the system slept during the
+    /// run. Invalid runs stay in history but are left out of statistics and
+    /// charts.
+    #[serde(default)]
+    pub invalid: Option<String>,
+    /// What a full run would have measured, for a `--quick` run on a
+    /// network calibrated with the `calibrate` command
+    #[serde(default)]
+    pub normalized: Option<NormalizedEstimate>,
+}
+
+/// Throughput of a quick run scaled by the network's calibration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NormalizedEstimate {
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    /// Configuration of the full runs it estimates, e.g. "10s×6"
+    pub reference_profile: String,
+    /// Calibration runs the correction is the median of
+    pub calibration_runs: usize,
+}
+
+/// The build that produced a result and how it talked to the test server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ClientInfo {
+    pub version: String,
+    /// Cargo features the binary was built with
+    pub features: Vec<String>,
+    pub os: String,
+    pub arch: String,
+    /// HTTP client and TLS stack
+    pub transport: String,
+    /// HTTP version negotiated with the test server, e.g. "HTTP/2.0"
+    pub http_version: Option<String>,
+    /// IP version of the connection to the test server, "IPv4" or "IPv6"
+    #[serde(default)]
+    pub ip_version: Option<String>,
+}
+
+impl ClientInfo {
+    /// Describe this build, with the HTTP and IP versions seen for the run.
+    pub fn current(http_version: Option<String>, ip_version: Option<String>) -> Self {
+        let features = [
+            ("tui", cfg!(feature = "tui")),
+            ("netinfo", cfg!(feature = "netinfo")),
+            ("geoip", cfg!(feature = "geoip")),
+            ("xlsx", cfg!(feature = "xlsx")),
+            ("bundle", cfg!(feature = "bundle")),
+            ("parquet", cfg!(feature = "parquet")),
+            ("sql", cfg!(feature = "sql")),
+            ("grpc", cfg!(feature = "grpc")),
+            ("notify", cfg!(feature = "notify")),
+            ("email", cfg!(feature = "email")),
+            ("schema", cfg!(feature = "schema")),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_string())
+        .collect();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            transport: "reqwest (rustls, ring)".to_string(),
+            http_version,
+            ip_version,
+        }
+    }
+}
+
+impl RunResult {
+    /// Download and upload throughput, as a full run would have measured
+    /// them for a calibrated quick run.
+    pub fn comparable_mbps(&self) -> (f64, f64) {
+        match &self.normalized {
+            Some(n) => (n.download_mbps, n.upload_mbps),
+            None => (self.download.mbps, self.upload.mbps),
+        }
+    }
+
+    /// JSON Schema for the result format, tagged with the crate version that produced it.
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> anyhow::Result<serde_json::Value> {
+        let version = env!("CARGO_PKG_VERSION");
+        let mut schema = schemars::schema_for!(RunResult);
+        schema.insert(
+            "$id".into(),
+            format!("urn:cloudflare-speed-cli:run-result:{version}").into(),
+        );
+        schema.insert("version".into(), version.into());
+        Ok(schema.to_value())
+    }
+
+    #[cfg(not(feature = "schema"))]
+    pub fn json_schema() -> anyhow::Result<serde_json::Value> {
+        anyhow::bail!("The schema subcommand requires building with the `schema` feature")
+    }
+
+    /// Total bytes transferred by the download and upload phases.
+    pub fn data_used_bytes(&self) -> u64 {
+        // Older results predate `bytes_total`; fall back to the steady-window bytes
+        self.download.bytes_total.max(self.download.bytes)
+            + self.upload.bytes_total.max(self.upload.bytes)
+            + self
+                .stream_comparison
+                .as_ref()
+                .map_or(0, |c| c.single_stream.bytes_total)
+    }
+
+    /// Multi-stream over single-stream download throughput, when measured.
+    pub fn stream_ratio(&self) -> Option<f64> {
+        let single = self.stream_comparison.as_ref()?.single_stream.mbps;
+        (single > 0.0).then(|| self.download.mbps / single)
+    }
+
+    /// Bufferbloat score (index into `metrics::BUFFERBLOAT_GRADES`) from the idle
+    /// median latency and the worse of the loaded download/upload medians.
+    pub fn bufferbloat_score(&self) -> Option<usize> {
+        let idle = self.idle_latency.median_ms?;
+        let loaded = match (
+            self.loaded_latency_download.median_ms,
+            self.loaded_latency_upload.median_ms,
+        ) {
+            (Some(dl), Some(ul)) => dl.max(ul),
+            (Some(v), None) | (None, Some(v)) => v,
+            (None, None) => return None,
+        };
+        Some(crate::metrics::bufferbloat_score(idle, loaded))
+    }
+
+    /// Bufferbloat letter grade (A+ .. F), if latency data is available.
+    pub fn bufferbloat_grade(&self) -> Option<&'static str> {
+        self.bufferbloat_score()
+            .map(|score| crate::metrics::BUFFERBLOAT_GRADES[score])
+    }
+
+    /// Short one-line summary of the main results, for sharing.
+    #[cfg(feature = "tui")]
+    pub fn compact_summary(&self) -> String {
+        let ms = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{v:.0}"));
+        // Minute precision is plenty and keeps QR codes small
+        let time = self.timestamp_utc.get(..16).unwrap_or(&self.timestamp_utc);
+        let mut summary = format!(
+            "Speed test {time} UTC: DL {:.1} UL {:.1} Mbps, idle {} ms, loaded {}/{} ms",
+            self.download.mbps,
+            self.upload.mbps,
+            ms(self.idle_latency.median_ms),
+            ms(self.loaded_latency_download.median_ms),
+            ms(self.loaded_latency_upload.median_ms),
+        );
+        if let Some(grade) = self.bufferbloat_grade() {
+            summary.push_str(&format!(", bufferbloat {grade}"));
+        }
+        summary
+    }
+
+    /// Strip identifying details: IP addresses, the interface MAC address and
+    /// the Wi-Fi network name. The network name is replaced by a stable
+    /// pseudonym so runs can still be grouped by network.
+    pub fn redact(&mut self) {
+        self.ip = None;
+        self.interface_mac = None;
+        self.local_ipv4 = None;
+        self.local_ipv6 = None;
+        self.source_address = None;
+        self.external_ipv4 = None;
+        self.external_ipv6 = None;
+        if let Some(config) = self.config.as_mut() {
+            config.source_ip = None;
+            // A prefix identifies the subscriber as much as an address
+            if matches!(config.ipv6_address, Some(Ipv6AddressChoice::Prefix { .. })) {
+                config.ipv6_address = None;
+            }
+        }
+        if let Some(meta) = self.meta.as_mut().and_then(|m| m.as_object_mut()) {
+            for key in [
+                "clientIp",
+                "ip",
+                "clientIP",
+                "latitude",
+                "longitude",
+                "postalCode",
+            ] {
+                meta.remove(key);
+            }
+        }
+        if let Some(geo) = self.geo.as_mut() {
+            geo.client_latitude = None;
+            geo.client_longitude = None;
+        }
+        if let Some(name) = self.network_name.as_mut() {
+            // FNV-1a, so the pseudonym stays the same across builds
+            let hash = name.bytes().fold(0x811c9dc5u32, |h, b| {
+                (h ^ b as u32).wrapping_mul(0x01000193)
+            });
+            *name = format!("network-{hash:08x}");
+        }
+    }
+}
+
+/// A run with nothing measured but the given throughputs, to build test
+/// fixtures from.
+#[cfg(test)]
+pub fn test_run(download_mbps: f64, upload_mbps: f64) -> RunResult {
+    serde_json::from_value(serde_json::json!({
+        "base_url": "", "meas_id": "", "meta": null, "turn": null, "packet_loss": null,
+        "idle_latency": {"sent": 0, "received": 0, "loss": 0.0},
+        "loaded_latency_download": {"sent": 0, "received": 0, "loss": 0.0},
+        "loaded_latency_upload": {"sent": 0, "received": 0, "loss": 0.0},
+        "download": {"bytes": 0, "duration_ms": 0, "mbps": download_mbps},
+        "upload": {"bytes": 0, "duration_ms": 0, "mbps": upload_mbps}
+    }))
+    .unwrap()
 }
 
 // ============================================================================
@@ -268,6 +1065,7 @@ pub struct RunResult {
 
 /// Summary of DNS resolution time measurement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct DnsSummary {
     pub hostname: String,
     pub resolution_time_ms: f64,
@@ -277,10 +1075,27 @@ pub struct DnsSummary {
     /// System DNS servers used for resolution
     #[serde(default)]
     pub dns_servers: Vec<String>,
+    /// Resolver used instead of the system one (`--resolver`, `--doh`)
+    #[serde(default)]
+    pub resolver: Option<String>,
+    /// The same lookup against public resolvers, with `--dns-compare`
+    #[serde(default)]
+    pub resolvers: Vec<ResolverTiming>,
+}
+
+/// Time for one public resolver to answer a query for the test hostname.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ResolverTiming {
+    pub server: String,
+    pub time_ms: Option<f64>,
+    /// Why the resolver didn't answer, when it didn't
+    pub error: Option<String>,
 }
 
 /// Summary of TLS handshake time measurement
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct TlsSummary {
     pub handshake_time_ms: f64,
     pub protocol_version: Option<String>,
@@ -289,6 +1104,7 @@ pub struct TlsSummary {
 
 /// Comparison of IPv4 vs IPv6 performance
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct IpVersionComparison {
     pub ipv4_result: Option<IpVersionResult>,
     pub ipv6_result: Option<IpVersionResult>,
@@ -296,6 +1112,7 @@ pub struct IpVersionComparison {
 
 /// Result for a single IP version test
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct IpVersionResult {
     pub ip_address: String,
     pub download_mbps: f64,
@@ -307,6 +1124,7 @@ pub struct IpVersionResult {
 
 /// Summary of traceroute results
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct TracerouteSummary {
     pub destination: String,
     pub hops: Vec<TracerouteHop>,
@@ -315,6 +1133,7 @@ pub struct TracerouteSummary {
 
 /// A single hop in a traceroute
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct TracerouteHop {
     pub hop_number: u8,
     pub ip_address: Option<String>,
@@ -0,0 +1,306 @@
+//! Headless monitor mode: run tests repeatedly on a fixed interval.
+//!
+//! Each run is saved like a normal headless run. An optional random start
+//! jitter spreads runs from many machines apart, and an optional monthly data
+//! cap shortens or skips runs as the budget runs out (LTE, satellite, ...).
+//! With `--watch-network`, a network change starts the next run early, and
+//! with `--min-battery` runs are skipped while a laptop's battery runs low.
+//! A `run` over the control socket also starts the next run early, and
+//! `--bell-on-fail`/`--alert-sound` alert when a run fails or misses the
+//! configured thresholds, as does `--email-to`, which also sends a weekly
+//! report.
+
+use crate::cli::{build_config, lock_policy, publish_run, run_headless_with, Cli};
+use crate::config::{apply_network_settings, Config};
+use crate::html::Timeline;
+use crate::model::{RunConfig, RunResult};
+use crate::netwatch::NetworkWatcher;
+use crate::orchestrator::controller::RunState;
+use crate::orchestrator::Controller;
+use crate::units::format_bytes;
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Fraction of the monthly cap after which tests are shortened.
+const SHRINK_THRESHOLD: f64 = 0.8;
+/// Shortest download/upload phase used when shrinking a test.
+const MIN_SHRUNK_PHASE: Duration = Duration::from_secs(3);
+
+/// What to do with the next scheduled run given the remaining data budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BudgetDecision {
+    Full,
+    Shrink,
+    Skip,
+}
+
+/// Decide whether the next run fits into the monthly data budget.
+/// `full_run_bytes` is the data used by the last full-length run, if known.
+fn budget_decision(used: u64, cap: u64, full_run_bytes: Option<u64>) -> BudgetDecision {
+    if used >= cap {
+        return BudgetDecision::Skip;
+    }
+    let remaining = cap - used;
+    if let Some(estimate) = full_run_bytes {
+        // A shrunk run transfers roughly half as much as a full one
+        if estimate / 2 > remaining {
+            return BudgetDecision::Skip;
+        }
+        if estimate > remaining {
+            return BudgetDecision::Shrink;
+        }
+    }
+    if used as f64 >= cap as f64 * SHRINK_THRESHOLD {
+        BudgetDecision::Shrink
+    } else {
+        BudgetDecision::Full
+    }
+}
+
+/// Halve the download and upload phases to save data.
+fn shrink_config(cfg: &mut RunConfig) {
+    cfg.download_duration = (cfg.download_duration / 2).max(MIN_SHRUNK_PHASE);
+    cfg.upload_duration = (cfg.upload_duration / 2).max(MIN_SHRUNK_PHASE);
+}
+
+/// Sleep for `duration`, returning true if interrupted by Ctrl-C.
+async fn sleep_or_interrupt(duration: Duration) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => false,
+        _ = tokio::signal::ctrl_c() => true,
+    }
+}
+
+pub async fn run(args: Cli, config: Config, matches: &ArgMatches) -> Result<()> {
+    let interval = Duration::from(args.monitor.context("monitor interval not set")?);
+    let jitter = args.monitor_jitter.map(Duration::from).unwrap_or_default();
+    let mut full_run_bytes: Option<u64> = None;
+    let mut watcher = args.watch_network.then(|| NetworkWatcher::spawn(&args));
+    // Test a new network, or on request, without the start jitter
+    let mut test_now = false;
+    let mut control = crate::control::ControlSocket::bind();
+    let mut controller = Controller::new()
+        .with_cooldown(Duration::from(args.cooldown))
+        .with_lock_policy(lock_policy(&args));
+
+    let log = |message: String| {
+        if !args.silent {
+            eprintln!("{message}");
+        }
+    };
+    log(format!(
+        "Monitor mode: testing every {} (Ctrl-C to stop)",
+        humantime::format_duration(interval)
+    ));
+    if watcher.is_some() {
+        log("Testing again whenever the network changes".into());
+    }
+    let email = |subject: String, body: String| {
+        let (args, config) = (&args, &config);
+        async move {
+            if args.email_to.is_empty() {
+                return false;
+            }
+            let sent = crate::email::send(&config.email, &args.email_to, &subject, &body).await;
+            if let Err(e) = &sent {
+                eprintln!("Failed to send email: {e:#}");
+            }
+            sent.is_ok()
+        }
+    };
+    let mut next_report = Instant::now() + crate::email::REPORT_PERIOD;
+    let mut current_network: Option<String> = None;
+
+    loop {
+        let cycle_start = Instant::now();
+
+        if !jitter.is_zero() && !std::mem::take(&mut test_now) {
+            let delay = Duration::from_secs(jitter.mul_f64(rand::random::<f64>()).as_secs());
+            log(format!(
+                "Waiting {} before next test",
+                humantime::format_duration(delay)
+            ));
+            if sleep_or_interrupt(delay).await {
+                break;
+            }
+        }
+
+        let low_battery = args
+            .min_battery
+            .and_then(|min| crate::power::low_battery(crate::power::power_status().as_ref(), min));
+        let mut run_args = args.clone();
+        if !config.networks.is_empty() {
+            let gather_args = args.clone();
+            let network = tokio::task::spawn_blocking(move || {
+                crate::network::gather_network_info(&gather_args)
+            })
+            .await
+            .context("gathering network info failed")?;
+            let key = apply_network_settings(&config, &mut run_args, matches, &network);
+            if key != current_network.as_deref() {
+                if let Some(key) = key {
+                    log(format!("Using the settings for network \"{key}\""));
+                }
+                current_network = key.map(str::to_string);
+            }
+        }
+        let mut cfg = build_config(&run_args);
+        let decision = match run_args.monthly_data_cap {
+            Some(cap) => {
+                let used = crate::storage::data_usage_this_month();
+                let decision = budget_decision(used, cap, full_run_bytes);
+                if decision != BudgetDecision::Full {
+                    log(format!(
+                        "Data budget: {} of {} used this month",
+                        format_bytes(used),
+                        format_bytes(cap)
+                    ));
+                }
+                decision
+            }
+            None => BudgetDecision::Full,
+        };
+
+        match (low_battery, decision) {
+            (Some(reason), _) => log(format!("Skipping test: {reason}")),
+            (None, BudgetDecision::Skip) => {
+                log("Skipping test: monthly data cap nearly exhausted".into())
+            }
+            (None, BudgetDecision::Full | BudgetDecision::Shrink) => {
+                let shrunk = decision == BudgetDecision::Shrink;
+                if shrunk {
+                    shrink_config(&mut cfg);
+                    log("Running a shortened test to save data".into());
+                }
+                if let Some(control) = &control {
+                    control.set_state(RunState::Running { phase: None });
+                }
+                let mut timeline = Timeline::default();
+                match run_headless_with(&mut controller, &run_args, &config, cfg, &mut timeline)
+                    .await
+                {
+                    Ok(result) => {
+                        let used = result.data_used_bytes();
+                        full_run_bytes = Some(if shrunk { used * 2 } else { used });
+                        if let Some(control) = &control {
+                            control.set_last_result(&result);
+                        }
+                        finish_run(&run_args, &config, &result, &timeline).await?;
+                        let breaches = config.thresholds_for(&result).breaches(&result);
+                        if !breaches.is_empty() {
+                            log(format!("Below thresholds: {}", breaches.join(", ")));
+                            crate::alert::raise(&args);
+                            let (subject, body) =
+                                crate::email::alert_message(&config, Ok(&result), &breaches);
+                            email(subject, body).await;
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Test failed: {e:#}");
+                        crate::alert::raise(&args);
+                        let (subject, body) = crate::email::alert_message(&config, Err(&e), &[]);
+                        email(subject, body).await;
+                    }
+                }
+            }
+        }
+
+        if !args.email_to.is_empty() && Instant::now() >= next_report {
+            next_report += crate::email::REPORT_PERIOD;
+            match crate::email::recent_runs(crate::email::REPORT_PERIOD) {
+                Ok(runs) => {
+                    let (subject, body) =
+                        crate::email::report_message(&runs, crate::email::REPORT_PERIOD);
+                    if email(subject, body).await {
+                        log(format!(
+                            "Emailed the weekly report over {} run(s)",
+                            runs.len()
+                        ));
+                    }
+                }
+                Err(e) => eprintln!("Failed to load runs for the weekly report: {e:#}"),
+            }
+        }
+
+        let elapsed = cycle_start.elapsed();
+        controller.schedule(cycle_start + interval);
+        if let Some(control) = &control {
+            control.set_state(controller.state());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval.saturating_sub(elapsed)) => {}
+            network = crate::netwatch::next_change(&mut watcher) => {
+                log(format!("Network changed to {}, testing now", network.describe()));
+                test_now = true;
+            }
+            _ = crate::control::run_requested(&mut control) => {
+                log("Test requested over the control socket".into());
+                test_now = true;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    log("Monitor mode stopped".into());
+    Ok(())
+}
+
+/// Export, push, save and print a completed monitor run.
+pub async fn finish_run(
+    args: &Cli,
+    config: &Config,
+    result: &RunResult,
+    timeline: &Timeline,
+) -> Result<()> {
+    publish_run(args, config, result, timeline).await?;
+    if args.silent {
+        return Ok(());
+    }
+    if args.json {
+        println!("{}", serde_json::to_string(result)?);
+    } else {
+        let idle = result
+            .idle_latency
+            .median_ms
+            .map_or_else(|| "-".to_string(), |ms| format!("{ms:.1}"));
+        println!(
+            "{}  DL {:.2} Mbps  UL {:.2} Mbps  idle {idle} ms  data {}",
+            result.timestamp_utc,
+            result.download.mbps,
+            result.upload.mbps,
+            format_bytes(result.data_used_bytes())
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_decision() {
+        let gb = 1_000_000_000;
+        assert_eq!(budget_decision(0, 50 * gb, None), BudgetDecision::Full);
+        assert_eq!(
+            budget_decision(45 * gb, 50 * gb, None),
+            BudgetDecision::Shrink
+        );
+        assert_eq!(
+            budget_decision(50 * gb, 50 * gb, None),
+            BudgetDecision::Skip
+        );
+        // Remaining budget fits a shrunk run but not a full one
+        assert_eq!(
+            budget_decision(49 * gb, 50 * gb, Some(gb + gb / 2)),
+            BudgetDecision::Shrink
+        );
+        // Not even a shrunk run fits
+        assert_eq!(
+            budget_decision(49 * gb, 50 * gb, Some(3 * gb)),
+            BudgetDecision::Skip
+        );
+    }
+}
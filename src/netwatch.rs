@@ -0,0 +1,139 @@
+//! Network change detection (`--watch-network`).
+//!
+//! The interface, default route and Wi-Fi network are polled in the
+//! background. Once a change has held for a few polls (Wi-Fi roaming and
+//! docking briefly pass through intermediate states) and a network is up,
+//! the new network is reported so monitor mode and the TUI can test it right
+//! away.
+
+use crate::cli::Cli;
+use crate::network::NetworkIdentity;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often the network is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Consecutive polls a new network must be seen on before it is reported.
+const SETTLE_POLLS: u32 = 2;
+
+/// Decides from successive observations when the network has changed.
+#[derive(Debug, Default)]
+struct ChangeDetector {
+    /// The settled network; None until the first observation
+    current: Option<NetworkIdentity>,
+    /// A different network and the number of polls it has been seen on
+    candidate: Option<(NetworkIdentity, u32)>,
+}
+
+impl ChangeDetector {
+    /// Record an observation, returning the new network once a change has
+    /// settled. Losing the network settles silently, so coming back (even to
+    /// the same network) counts as a change.
+    fn observe(&mut self, identity: NetworkIdentity) -> Option<NetworkIdentity> {
+        let Some(current) = self.current.as_ref() else {
+            self.current = Some(identity);
+            return None;
+        };
+        if *current == identity {
+            self.candidate = None;
+            return None;
+        }
+        let polls = match &self.candidate {
+            Some((candidate, polls)) if *candidate == identity => polls + 1,
+            _ => 1,
+        };
+        if polls < SETTLE_POLLS {
+            self.candidate = Some((identity, polls));
+            return None;
+        }
+        self.candidate = None;
+        self.current = Some(identity.clone());
+        identity.is_connected().then_some(identity)
+    }
+}
+
+/// Polls the network on a background task and passes on settled changes.
+pub struct NetworkWatcher {
+    rx: mpsc::UnboundedReceiver<NetworkIdentity>,
+}
+
+impl NetworkWatcher {
+    /// Start watching; the network at the time of the first poll is the
+    /// baseline. The task stops when the watcher is dropped.
+    pub fn spawn(args: &Cli) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let args = args.clone();
+        tokio::spawn(async move {
+            let mut detector = ChangeDetector::default();
+            loop {
+                let args = args.clone();
+                let identity =
+                    tokio::task::spawn_blocking(move || crate::network::network_identity(&args))
+                        .await
+                        .unwrap_or_default();
+                if let Some(changed) = detector.observe(identity) {
+                    if tx.send(changed).is_err() {
+                        return;
+                    }
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+        Self { rx }
+    }
+
+    /// Wait for the next network change. Cancel-safe.
+    pub async fn next_change(&mut self) -> NetworkIdentity {
+        match self.rx.recv().await {
+            Some(identity) => identity,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// `NetworkWatcher::next_change` when watching, otherwise never resolves, so
+/// it can be polled in `tokio::select!` either way.
+pub async fn next_change(watcher: &mut Option<NetworkWatcher>) -> NetworkIdentity {
+    match watcher {
+        Some(watcher) => watcher.next_change().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(iface: Option<&str>, ssid: Option<&str>) -> NetworkIdentity {
+        NetworkIdentity {
+            interface_name: iface.map(String::from),
+            route_interface: iface.map(String::from),
+            network_name: ssid.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_change_detector() {
+        let wifi = network(Some("wlan0"), Some("Home"));
+        let ethernet = network(Some("eth0"), None);
+        let mut detector = ChangeDetector::default();
+        assert_eq!(detector.observe(wifi.clone()), None);
+        assert_eq!(detector.observe(wifi.clone()), None);
+
+        // Reported once the new network has held for SETTLE_POLLS polls
+        assert_eq!(detector.observe(ethernet.clone()), None);
+        assert_eq!(detector.observe(ethernet.clone()), Some(ethernet.clone()));
+        assert_eq!(detector.observe(ethernet.clone()), None);
+
+        // A blip back to Wi-Fi for one poll is ignored
+        assert_eq!(detector.observe(wifi.clone()), None);
+        assert_eq!(detector.observe(ethernet.clone()), None);
+
+        // Going offline isn't reported, reconnecting is
+        let offline = network(None, None);
+        assert_eq!(detector.observe(offline.clone()), None);
+        assert_eq!(detector.observe(offline), None);
+        assert_eq!(detector.observe(ethernet.clone()), None);
+        assert_eq!(detector.observe(ethernet.clone()), Some(ethernet));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::cli::Cli;
-use crate::model::RunResult;
+use crate::model::{CellularSummary, RunResult};
 use serde_json::Value;
 use std::process::Command;
 
@@ -47,67 +47,257 @@ pub fn extract_metadata(meta: &Value) -> ExtractedMetadata {
 }
 
 /// Network information gathered from the system
+#[derive(Clone, Default)]
 pub struct NetworkInfo {
     pub interface_name: Option<String>,
     pub network_name: Option<String>,
     pub is_wireless: Option<bool>,
     pub interface_mac: Option<String>,
+    pub link_speed_mbps: Option<u64>,
     pub local_ipv4: Option<String>,
     pub local_ipv6: Option<String>,
+    /// Interface of the default route, which may differ from `interface_name`
+    /// when the test is bound with `--interface`
+    pub route_interface: Option<String>,
+    /// Carrier, technology and signal of a cellular interface
+    pub cellular: Option<CellularSummary>,
+}
+
+/// Share of the link speed at which throughput counts as limited by the NIC
+const LINK_SATURATION: f64 = 0.9;
+
+/// Whether `mbps` is close enough to the interface's link speed that the NIC,
+/// rather than the network, is the bottleneck.
+pub fn saturates_link(mbps: f64, link_speed_mbps: u64) -> bool {
+    mbps >= link_speed_mbps as f64 * LINK_SATURATION
+}
+
+/// Format a link speed, e.g. "1 Gbps", "2.5 Gbps" or "100 Mbps".
+pub fn format_link_speed(mbps: u64) -> String {
+    if mbps >= 1000 {
+        format!("{} Gbps", mbps as f64 / 1000.0)
+    } else {
+        format!("{mbps} Mbps")
+    }
+}
+
+/// Link speed of the interface the test will use, from `--interface` or the
+/// default route.
+pub fn link_speed_mbps(args: &Cli) -> Option<u64> {
+    if !cfg!(feature = "netinfo") {
+        return None;
+    }
+    let iface = args.interface.clone().or_else(get_default_interface)?;
+    get_link_speed_mbps(&iface)
+}
+
+/// Warn when the default route doesn't go through the interface the test is
+/// bound to, or moved to another interface since the previous run (e.g. a
+/// silent dual-WAN failover).
+pub fn route_warning(
+    route: Option<&str>,
+    bound: Option<&str>,
+    previous_route: Option<&str>,
+) -> Option<String> {
+    let route = route?;
+    match (bound, previous_route) {
+        (Some(bound), _) if bound != route => Some(format!(
+            "Default route uses {route}, but the test is bound to {bound}"
+        )),
+        (_, Some(previous)) if previous != route => Some(format!(
+            "Default route moved from {previous} to {route} since the last run"
+        )),
+        _ => None,
+    }
+}
+
+/// Route warning for a run about to start with `info`, compared with the last
+/// saved run.
+pub fn check_route(info: &NetworkInfo, args: &Cli) -> Option<String> {
+    let previous = crate::storage::load_recent(1)
+        .ok()
+        .and_then(|runs| runs.into_iter().next())
+        .and_then(|r| r.route_interface);
+    route_warning(
+        info.route_interface.as_deref(),
+        args.interface.as_deref(),
+        previous.as_deref(),
+    )
 }
 
 /// Gather network interface information based on CLI arguments
+/// (nothing without the `netinfo` feature).
 pub fn gather_network_info(args: &Cli) -> NetworkInfo {
-    let (interface_name, network_name, is_wireless, interface_mac) =
-        if let Some(ref iface) = args.interface {
-            // Use the specified interface
-            let is_wireless = check_if_wireless(iface);
-            let network_name = if is_wireless.unwrap_or(false) {
-                get_wireless_ssid(iface)
-            } else {
-                None
-            };
-            let mac = get_interface_mac(iface);
-            (Some(iface.clone()), network_name, is_wireless, mac)
-        } else {
-            // Auto-detect default interface
-            gather_default_network_info()
-        };
-
-    let (local_ipv4, local_ipv6) = get_interface_ips(interface_name.as_deref());
-
-    NetworkInfo {
-        interface_name,
-        network_name,
-        is_wireless,
-        interface_mac,
-        local_ipv4,
-        local_ipv6,
-    }
+    gather_network_info_with(args, |_| {})
 }
 
-/// Gather network interface information for the default interface
-fn gather_default_network_info() -> (Option<String>, Option<String>, Option<bool>, Option<String>) {
-    // Get default interface by trying to connect to a remote address
-    let interface_name = get_default_interface();
-
-    if let Some(ref iface) = interface_name {
-        let is_wireless = check_if_wireless(iface);
-        let network_name = if is_wireless.unwrap_or(false) {
-            get_wireless_ssid(iface)
-        } else {
-            None
-        };
-        let mac = get_interface_mac(iface);
-        (Some(iface.clone()), network_name, is_wireless, mac)
+/// `gather_network_info`, calling `progress` with what is known so far after
+/// each step so a UI can show the interface before the slower lookups (SSID,
+/// link speed, ModemManager) finish.
+pub fn gather_network_info_with(args: &Cli, mut progress: impl FnMut(&NetworkInfo)) -> NetworkInfo {
+    let mut info = NetworkInfo::default();
+    if !cfg!(feature = "netinfo") {
+        return info;
+    }
+    // Use the specified interface or auto-detect the default one
+    let interface_name = args.interface.clone().or_else(get_default_interface);
+    info.route_interface = if args.interface.is_some() {
+        get_default_interface()
     } else {
-        (None, None, None, None)
+        interface_name.clone()
+    };
+    info.interface_name = interface_name.clone();
+    progress(&info);
+
+    if let Some(iface) = interface_name.as_deref() {
+        info.is_wireless = check_if_wireless(iface);
+        info.interface_mac = get_interface_mac(iface);
+        progress(&info);
+        if info.is_wireless.unwrap_or(false) {
+            info.network_name = get_wireless_ssid(iface);
+            progress(&info);
+        }
     }
+
+    (info.local_ipv4, info.local_ipv6) = get_interface_ips(interface_name.as_deref());
+    info.link_speed_mbps = interface_name.as_deref().and_then(get_link_speed_mbps);
+    progress(&info);
+
+    info.cellular = interface_name
+        .as_deref()
+        .and_then(crate::cellular::cellular_info);
+    // Group cellular runs by carrier, like Wi-Fi runs by SSID
+    if info.network_name.is_none() {
+        info.network_name = info.cellular.as_ref().and_then(|c| c.carrier.clone());
+    }
+    info
+}
+
+/// What identifies the network a test runs on, for `--watch-network`: the
+/// interface, the default route and the Wi-Fi network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkIdentity {
+    pub interface_name: Option<String>,
+    pub route_interface: Option<String>,
+    pub network_name: Option<String>,
+}
+
+impl NetworkIdentity {
+    /// Whether there is a network to test on (an interface is up).
+    pub fn is_connected(&self) -> bool {
+        self.interface_name.is_some() || self.route_interface.is_some()
+    }
+
+    /// E.g. "HomeWifi (wlan0)", "eth0" or "no network".
+    pub fn describe(&self) -> String {
+        let iface = self
+            .interface_name
+            .as_ref()
+            .or(self.route_interface.as_ref());
+        match (self.network_name.as_ref(), iface) {
+            (Some(name), Some(iface)) => format!("{name} ({iface})"),
+            (Some(name), None) => name.clone(),
+            (None, Some(iface)) => iface.clone(),
+            (None, None) => "no network".into(),
+        }
+    }
+}
+
+/// The current `NetworkIdentity`. Only the interface, route and SSID lookups
+/// of `gather_network_info` are made, so it is cheap enough to poll.
+pub fn network_identity(args: &Cli) -> NetworkIdentity {
+    let mut identity = NetworkIdentity::default();
+    if !cfg!(feature = "netinfo") {
+        return identity;
+    }
+    identity.route_interface = get_default_interface();
+    identity.interface_name = args
+        .interface
+        .clone()
+        .or_else(|| identity.route_interface.clone());
+    if let Some(iface) = identity.interface_name.as_deref() {
+        if check_if_wireless(iface).unwrap_or(false) {
+            identity.network_name = get_wireless_ssid(iface);
+        }
+    }
+    identity
+}
+
+/// Interface carrying traffic on Android, where apps can't read the routing
+/// table: Wi-Fi if it has an address, otherwise the cellular interface.
+#[cfg(target_os = "android")]
+fn android_default_interface() -> Option<String> {
+    let interfaces = if_addrs::get_if_addrs().ok()?;
+    let names: Vec<&str> = interfaces
+        .iter()
+        .filter(|i| match i.ip() {
+            std::net::IpAddr::V4(ip) => !ip.is_loopback(),
+            std::net::IpAddr::V6(ip) => !ip.is_loopback() && !is_link_local_v6(&ip),
+        })
+        .map(|i| i.name.as_str())
+        .collect();
+    names
+        .iter()
+        .find(|name| name.starts_with("wlan"))
+        .or_else(|| {
+            names
+                .iter()
+                .find(|name| crate::cellular::is_cellular_interface(name))
+        })
+        .or_else(|| names.first())
+        .map(|name| name.to_string())
+}
+
+/// Interface of the lowest-metric default route in the kernel's route tables
+/// (`/proc/net/route` and `/proc/net/ipv6_route` contents). Reading these
+/// doesn't depend on `ip` being installed, which minimal systems may lack.
+#[cfg_attr(windows, allow(dead_code))]
+fn default_route_from_proc(route: &str, ipv6_route: &str) -> Option<String> {
+    const RTF_UP: u32 = 0x0001;
+    const RTF_REJECT: u32 = 0x0200;
+    let usable = |flags: &str| {
+        u32::from_str_radix(flags, 16)
+            .map(|f| f & RTF_UP != 0 && f & RTF_REJECT == 0)
+            .unwrap_or(false)
+    };
+
+    // Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+    let v4 = route.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let is_default = fields.len() >= 8 && fields[1] == "00000000" && fields[7] == "00000000";
+        if !is_default || !usable(fields[3]) {
+            return None;
+        }
+        Some((fields[6].parse::<u32>().ok()?, fields[0]))
+    });
+    // Destination PrefixLen Source PrefixLen NextHop Metric RefCnt Use Flags Iface
+    let v6 = ipv6_route.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let is_default =
+            fields.len() >= 10 && fields[0].bytes().all(|b| b == b'0') && fields[1] == "00";
+        if !is_default || !usable(fields[8]) || fields[9] == "lo" {
+            return None;
+        }
+        Some((u32::from_str_radix(fields[5], 16).ok()?, fields[9]))
+    });
+    v4.min()
+        .or_else(|| v6.min())
+        .map(|(_, iface)| iface.to_string())
 }
 
 /// Get the default network interface name
 #[cfg(not(windows))]
 fn get_default_interface() -> Option<String> {
+    let route = std::fs::read_to_string("/proc/net/route").unwrap_or_default();
+    let ipv6_route = std::fs::read_to_string("/proc/net/ipv6_route").unwrap_or_default();
+    if let Some(iface) = default_route_from_proc(&route, &ipv6_route) {
+        return Some(iface);
+    }
+    #[cfg(target_os = "android")]
+    if let Some(iface) = android_default_interface() {
+        return Some(iface);
+    }
+
     // Try to get interface from default route
     if let Ok(output) = Command::new("ip")
         .args(&["route", "show", "default"])
@@ -183,6 +373,9 @@ fn get_default_interface() -> Option<String> {
 /// Check if interface is wireless
 #[cfg(not(windows))]
 fn check_if_wireless(iface: &str) -> Option<bool> {
+    if crate::cellular::is_cellular_interface(iface) {
+        return Some(true);
+    }
     // Check if /sys/class/net/<iface>/wireless exists
     let wireless_path = format!("/sys/class/net/{}/wireless", iface);
     Some(std::path::Path::new(&wireless_path).exists())
@@ -205,6 +398,20 @@ fn check_if_wireless(iface: &str) -> Option<bool> {
 /// Get wireless SSID for an interface
 #[cfg(not(windows))]
 fn get_wireless_ssid(iface: &str) -> Option<String> {
+    // Termux can only see the SSID through the Termux:API app
+    #[cfg(target_os = "android")]
+    if let Ok(output) = Command::new("termux-wifi-connectioninfo").output() {
+        let info: Option<Value> = serde_json::from_slice(&output.stdout).ok();
+        let ssid = info
+            .as_ref()
+            .and_then(|i| i.get("ssid"))
+            .and_then(|s| s.as_str());
+        // Reported without location permission
+        if let Some(ssid) = ssid.filter(|s| !s.is_empty() && *s != "<unknown ssid>") {
+            return Some(ssid.to_string());
+        }
+    }
+
     // Try iwgetid first (most reliable)
     if let Ok(output) = Command::new("iwgetid").arg("-r").arg(iface).output() {
         if let Ok(ssid) = String::from_utf8(output.stdout) {
@@ -269,7 +476,8 @@ fn get_interface_mac(iface: &str) -> Option<String> {
     std::fs::read_to_string(mac_path)
         .ok()
         .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
+        // Android hides the real MAC from apps behind this placeholder
+        .filter(|s| !s.is_empty() && s != "02:00:00:00:00:00")
 }
 
 #[cfg(windows)]
@@ -292,6 +500,38 @@ fn get_interface_mac(iface: &str) -> Option<String> {
     None
 }
 
+/// Get the negotiated link speed of an interface in Mbps
+#[cfg(not(windows))]
+fn get_link_speed_mbps(iface: &str) -> Option<u64> {
+    // Wireless and virtual interfaces report -1 or can't be read
+    let speed_path = format!("/sys/class/net/{}/speed", iface);
+    let speed: i64 = std::fs::read_to_string(speed_path)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    u64::try_from(speed).ok().filter(|&s| s > 0)
+}
+
+#[cfg(windows)]
+fn get_link_speed_mbps(iface: &str) -> Option<u64> {
+    let output = Command::new("powershell")
+        .args(&[
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-NetAdapter -Name '{}').Speed", iface),
+        ])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        // Reported in bits per second
+        let bps: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        return Some(bps / 1_000_000).filter(|&s| s > 0);
+    }
+    None
+}
+
 /// Get IPv4 and IPv6 addresses for an interface
 fn get_interface_ips(interface_name: Option<&str>) -> (Option<String>, Option<String>) {
     let Ok(interfaces) = if_addrs::get_if_addrs() else {
@@ -350,8 +590,19 @@ pub fn enrich_result(result: &RunResult, network_info: &NetworkInfo) -> RunResul
     enriched.network_name = network_info.network_name.clone();
     enriched.is_wireless = network_info.is_wireless;
     enriched.interface_mac = network_info.interface_mac.clone();
+    enriched.link_speed_mbps = network_info.link_speed_mbps;
+    if let Some(link) = network_info.link_speed_mbps {
+        enriched.download.link_limited = saturates_link(enriched.download.mbps, link);
+        enriched.upload.link_limited = saturates_link(enriched.upload.mbps, link);
+    }
     enriched.local_ipv4 = network_info.local_ipv4.clone();
     enriched.local_ipv6 = network_info.local_ipv6.clone();
+    enriched.route_interface = network_info.route_interface.clone();
+    enriched.cellular_type = network_info
+        .cellular
+        .as_ref()
+        .and_then(|c| c.technology.clone());
+    enriched.cellular = network_info.cellular.clone();
 
     // Extract metadata from result.meta if available
     if let Some(meta) = result.meta.as_ref() {
@@ -367,3 +618,28 @@ pub fn enrich_result(result: &RunResult, network_info: &NetworkInfo) -> RunResul
 
     enriched
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_route_from_proc() {
+        let route =
+            "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask\tMTU\tWindow\tIRTT\n\
+            wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+            eth0\t00000000\t0102A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+            eth0\t0002A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n";
+        assert_eq!(default_route_from_proc(route, "").as_deref(), Some("eth0"));
+
+        // IPv6-only, ignoring the loopback's unreachable default route
+        let ipv6_route = "\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200 lo\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003 wwan0\n";
+        assert_eq!(
+            default_route_from_proc("", ipv6_route).as_deref(),
+            Some("wwan0")
+        );
+        assert_eq!(default_route_from_proc("", ""), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! Desktop notification when a run finishes (`--notify`), through D-Bus on
+//! Linux and BSD, Notification Center on macOS and toasts on Windows.
+
+use crate::config::Config;
+use crate::model::RunResult;
+use anyhow::Result;
+
+/// Title and body of the notification for `result`.
+#[cfg_attr(not(feature = "notify"), allow(dead_code))]
+fn message(config: &Config, result: &RunResult) -> (String, String) {
+    let title = if result.invalid.is_some() {
+        "Speed test finished (invalid)"
+    } else {
+        "Speed test finished"
+    };
+    let mut body = format!(
+        "↓ {:.1} Mbps  ↑ {:.1} Mbps",
+        result.download.mbps, result.upload.mbps
+    );
+    if let Some(ms) = result.idle_latency.median_ms {
+        body.push_str(&format!("  {ms:.1} ms"));
+    }
+    if let Some(network) = config.network_label(result) {
+        body.push_str(&format!("\n{network}"));
+    }
+    (title.to_string(), body)
+}
+
+/// Fail early when `--notify` is given to a build that can't notify.
+pub fn check_available() -> Result<()> {
+    #[cfg(not(feature = "notify"))]
+    anyhow::bail!("--notify requires building with the `notify` feature");
+    #[cfg(feature = "notify")]
+    Ok(())
+}
+
+/// Show the notification for `result` in the background. Failures, such as
+/// no notification daemon running, are ignored.
+#[cfg(feature = "notify")]
+pub fn run_finished(config: &Config, result: &RunResult) {
+    let (title, body) = message(config, result);
+    tokio::task::spawn_blocking(move || {
+        notify_rust::Notification::new()
+            .appname(env!("CARGO_PKG_NAME"))
+            .summary(&title)
+            .body(&body)
+            .show()
+            .ok();
+    });
+}
+
+#[cfg(not(feature = "notify"))]
+pub fn run_finished(_config: &Config, _result: &RunResult) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    #[test]
+    fn test_notification_message() {
+        let mut result = test_run(250.0, 20.56);
+        result.idle_latency.median_ms = Some(12.34);
+        result.network_name = Some("Home".into());
+        assert_eq!(
+            message(&Config::default(), &result),
+            (
+                "Speed test finished".to_string(),
+                "↓ 250.0 Mbps  ↑ 20.6 Mbps  12.3 ms\nHome".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Run controller: a queue of pending run configurations and the active run.
+//!
+//! Front ends enqueue `RunConfig`s and poll [`Controller::next_event`], which
+//! starts queued runs back to back and forwards their engine events. Where
+//! the controller is in a run's lifecycle is a [`RunState`], announced with
+//! `StateChanged` events and queryable with [`Controller::state`].
+
+use crate::engine::{EngineControl, TestEngine};
+use crate::model::{EventEnvelope, Phase, RunConfig, RunResult, TestEvent};
+use crate::storage::RunLock;
+use anyhow::{anyhow, Result};
+use futures::future;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Lifecycle state of the controller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub enum RunState {
+    /// Nothing running or scheduled
+    #[default]
+    Idle,
+    /// The next run is due at `next_at`, e.g. monitor mode's next interval
+    Scheduled { next_at: Instant },
+    /// Waiting out the cooldown after a run
+    Cooldown { until: Instant },
+    /// Another instance holds the run lock; the next run starts once it's released
+    WaitingForLock,
+    /// A run is active, in `phase` (None while it fetches metadata and runs
+    /// diagnostics)
+    Running { phase: Option<Phase> },
+    /// The active run was cancelled and is stopping
+    Cancelling,
+}
+
+impl RunState {
+    /// Whether a run is active (running or stopping).
+    pub fn is_active(self) -> bool {
+        matches!(self, RunState::Running { .. } | RunState::Cancelling)
+    }
+}
+
+impl std::fmt::Display for RunState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            RunState::Idle => "Idle",
+            RunState::Scheduled { .. } => "Scheduled",
+            RunState::Cooldown { .. } => "Cooling down",
+            RunState::WaitingForLock => "Waiting for lock",
+            RunState::Running { .. } => "Running",
+            RunState::Cancelling => "Cancelling",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub enum ControllerEvent {
+    /// The lifecycle state changed; the specific events below carry the
+    /// details front ends show.
+    StateChanged(RunState),
+    /// A queued run's place in the queue; `position` is 1-based out of `pending`.
+    QueuePosition { position: usize, pending: usize },
+    /// Waiting out the cooldown before the next queued run, counting down in seconds.
+    Cooldown { remaining_secs: u64 },
+    /// Another instance holds the run lock; the next run starts once it's released.
+    WaitingForLock { holder: Option<u32> },
+    /// A queued run has started; `pending` runs are still waiting behind it.
+    RunStarted { pending: usize },
+    /// Progress event from the active run.
+    Engine(EventEnvelope),
+    /// The active run ended, successfully or not.
+    RunFinished { result: Result<Box<RunResult>> },
+}
+
+/// What to do when another instance is already running a test.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Fail the run
+    #[default]
+    Fail,
+    /// Wait until the other instance's test finishes
+    Wait,
+    /// Run anyway, without taking the lock
+    Force,
+}
+
+/// Outcome of trying to take the run lock before starting a run.
+enum LockAttempt {
+    /// Start the run, holding the lock if one was taken
+    Ready(Option<RunLock>),
+    /// Another instance holds the lock; try again later
+    Waiting,
+}
+
+/// How often to retry the run lock while waiting for another instance.
+pub const LOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error for a run refused because `holder` is already running a test.
+pub fn lock_busy_error(holder: Option<u32>) -> anyhow::Error {
+    let who = holder.map(|pid| format!(" (pid {pid})")).unwrap_or_default();
+    anyhow!(
+        "another instance{who} is running a speed test; use --wait to run after it or --force to run anyway"
+    )
+}
+
+struct ActiveRun {
+    _lock: Option<RunLock>,
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    ctrl_tx: mpsc::Sender<EngineControl>,
+    event_rx: mpsc::Receiver<EventEnvelope>,
+    handle: JoinHandle<Result<RunResult>>,
+}
+
+#[derive(Default)]
+pub struct Controller {
+    queue: VecDeque<RunConfig>,
+    active: Option<ActiveRun>,
+    notices: VecDeque<ControllerEvent>,
+    /// Minimum idle time between the end of one run and the start of the next
+    cooldown: Duration,
+    cooldown_until: Option<Instant>,
+    announced_cooldown_secs: Option<u64>,
+    lock_policy: LockPolicy,
+    state: RunState,
+}
+
+impl Controller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enforce `cooldown` between consecutive runs.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Choose what happens when another instance holds the run lock.
+    pub fn with_lock_policy(mut self, policy: LockPolicy) -> Self {
+        self.lock_policy = policy;
+        self
+    }
+
+    /// Queue a run behind any pending ones.
+    pub fn enqueue(&mut self, cfg: RunConfig) {
+        self.queue.push_back(cfg);
+        // Runs queued while idle start right away and have no position to report
+        if self.active.is_some() {
+            let pending = self.queue.len();
+            self.notices.push_back(ControllerEvent::QueuePosition {
+                position: pending,
+                pending,
+            });
+        }
+    }
+
+    /// Queue a run ahead of all pending ones.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub fn enqueue_front(&mut self, cfg: RunConfig) {
+        self.queue.push_front(cfg);
+        if self.active.is_some() {
+            self.notify_queue_positions();
+        }
+    }
+
+    /// The current lifecycle state; `state().is_active()` while a run is
+    /// active.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub fn state(&self) -> RunState {
+        match self.state {
+            RunState::Cooldown { until } if until <= Instant::now() => RunState::Idle,
+            state => state,
+        }
+    }
+
+    /// Announce that the next run is due at `next_at`, for front ends that
+    /// schedule runs themselves. Ignored while a run is active.
+    pub fn schedule(&mut self, next_at: Instant) {
+        if !self.state.is_active() {
+            self.set_state(RunState::Scheduled { next_at });
+        }
+    }
+
+    fn set_state(&mut self, state: RunState) {
+        if self.state != state {
+            self.state = state;
+            self.notices.push_back(ControllerEvent::StateChanged(state));
+        }
+    }
+
+    /// State after a run ends: cooling down, or idle without a cooldown.
+    fn after_run_state(&self) -> RunState {
+        match self.cooldown_until {
+            Some(until) => RunState::Cooldown { until },
+            None => RunState::Idle,
+        }
+    }
+
+    /// Send a control message to the active run, if any.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub async fn control(&self, ctrl: EngineControl) {
+        if let Some(active) = &self.active {
+            active.ctrl_tx.send(ctrl).await.ok();
+        }
+    }
+
+    /// Cancel the active run and wait for it to stop. Its result is discarded.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub async fn cancel_active(&mut self) {
+        if let Some(active) = self.active.take() {
+            self.set_state(RunState::Cancelling);
+            active.ctrl_tx.send(EngineControl::Cancel).await.ok();
+            let _ = active.handle.await;
+            self.start_cooldown();
+            self.set_state(self.after_run_state());
+        }
+    }
+
+    /// Wait for the next event, starting the next queued run whenever idle.
+    ///
+    /// Never resolves while idle with an empty queue, so it can be polled in
+    /// `tokio::select!`. Cancel-safe: no event is lost if the future is dropped.
+    pub async fn next_event(&mut self) -> ControllerEvent {
+        loop {
+            if let Some(notice) = self.notices.pop_front() {
+                return notice;
+            }
+            let Some(active) = self.active.as_mut() else {
+                if self.queue.is_empty() {
+                    // Announce the end of a cooldown with nothing queued behind it
+                    if let RunState::Cooldown { until } = self.state {
+                        tokio::time::sleep_until(until).await;
+                        self.set_state(RunState::Idle);
+                        continue;
+                    }
+                    return future::pending().await;
+                }
+                if let Some(remaining_secs) = self.wait_for_cooldown().await {
+                    return ControllerEvent::Cooldown { remaining_secs };
+                }
+                let lock = match self.acquire_lock().await {
+                    Ok(LockAttempt::Ready(lock)) => lock,
+                    Ok(LockAttempt::Waiting) => continue,
+                    Err(e) => {
+                        self.queue.pop_front();
+                        return ControllerEvent::RunFinished { result: Err(e) };
+                    }
+                };
+                match self.queue.pop_front() {
+                    Some(cfg) => {
+                        self.start(cfg, lock);
+                        continue;
+                    }
+                    None => return future::pending().await,
+                }
+            };
+            if let Some(envelope) = active.event_rx.recv().await {
+                if let TestEvent::PhaseStarted { phase } = envelope.event {
+                    self.set_state(RunState::Running { phase: Some(phase) });
+                }
+                return ControllerEvent::Engine(envelope);
+            }
+            // Event channel closed: the engine is done, collect its result
+            let joined = (&mut active.handle).await;
+            self.active = None;
+            self.start_cooldown();
+            self.set_state(self.after_run_state());
+            let result = match joined {
+                Ok(result) => result.map(Box::new),
+                Err(e) => Err(anyhow!("run join failed: {e}")),
+            };
+            return ControllerEvent::RunFinished { result };
+        }
+    }
+
+    fn start_cooldown(&mut self) {
+        if !self.cooldown.is_zero() {
+            self.cooldown_until = Some(Instant::now() + self.cooldown);
+            self.announced_cooldown_secs = None;
+        }
+    }
+
+    /// Sleep through the cooldown one second at a time. Returns the remaining
+    /// whole seconds whenever a new countdown value should be announced, and
+    /// `None` once the cooldown is over.
+    async fn wait_for_cooldown(&mut self) -> Option<u64> {
+        let until = self.cooldown_until?;
+        loop {
+            let remaining = until.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                self.cooldown_until = None;
+                return None;
+            }
+            let secs = remaining.as_secs_f64().ceil() as u64;
+            if self.announced_cooldown_secs != Some(secs) {
+                self.announced_cooldown_secs = Some(secs);
+                return Some(secs);
+            }
+            // Sleep until the countdown reaches the next whole second
+            tokio::time::sleep(remaining.saturating_sub(Duration::from_secs(secs - 1))).await;
+        }
+    }
+
+    /// Take the run lock according to the lock policy. Queues a
+    /// `WaitingForLock` notice the first time the lock is busy, and fails if
+    /// the policy doesn't allow waiting.
+    async fn acquire_lock(&mut self) -> Result<LockAttempt> {
+        if self.lock_policy == LockPolicy::Force {
+            return Ok(LockAttempt::Ready(None));
+        }
+        match crate::storage::try_lock_run() {
+            Ok(Some(lock)) => Ok(LockAttempt::Ready(Some(lock))),
+            Ok(None) => {
+                let holder = crate::storage::run_lock_holder();
+                if self.lock_policy == LockPolicy::Fail {
+                    return Err(lock_busy_error(holder));
+                }
+                if self.state == RunState::WaitingForLock {
+                    tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+                } else {
+                    self.set_state(RunState::WaitingForLock);
+                    self.notices
+                        .push_back(ControllerEvent::WaitingForLock { holder });
+                }
+                Ok(LockAttempt::Waiting)
+            }
+            // An unusable data dir (e.g. read-only home) shouldn't stop tests
+            Err(_) => Ok(LockAttempt::Ready(None)),
+        }
+    }
+
+    fn start(&mut self, cfg: RunConfig, lock: Option<RunLock>) {
+        let (event_tx, event_rx) = mpsc::channel::<EventEnvelope>(4096);
+        let (ctrl_tx, ctrl_rx) = mpsc::channel::<EngineControl>(32);
+        let engine = TestEngine::new(cfg);
+        let handle = tokio::spawn(async move { engine.run(event_tx, ctrl_rx).await });
+        self.active = Some(ActiveRun {
+            _lock: lock,
+            ctrl_tx,
+            event_rx,
+            handle,
+        });
+        self.set_state(RunState::Running { phase: None });
+        self.notices.push_back(ControllerEvent::RunStarted {
+            pending: self.queue.len(),
+        });
+        self.notify_queue_positions();
+    }
+
+    fn notify_queue_positions(&mut self) {
+        let pending = self.queue.len();
+        for position in 1..=pending {
+            self.notices
+                .push_back(ControllerEvent::QueuePosition { position, pending });
+        }
+    }
+}
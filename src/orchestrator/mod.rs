@@ -0,0 +1,5 @@
+//! Run orchestration shared by the front ends.
+
+pub mod controller;
+
+pub use controller::{Controller, ControllerEvent, LockPolicy};
@@ -0,0 +1,171 @@
+//! `--website-parity`: speed.cloudflare.com's measurement plan and
+//! aggregation, so results compare with the website's.
+//!
+//! The website (Cloudflare's `@cloudflare/speedtest` library) makes one
+//! request at a time rather than running parallel streams, through a fixed
+//! plan of request sizes:
+//!
+//! - download: 100 kB ×10, 1 MB ×8, 10 MB ×6, 25 MB ×4, 100 MB ×3, 250 MB ×2
+//! - upload: 100 kB ×8, 1 MB ×6, 10 MB ×4, 25 MB ×4, 50 MB ×3
+//!
+//! Once a request of a size takes longer than a second, the larger sizes
+//! are skipped. A request's speed is its bytes over its transfer time: for
+//! a download from the first byte of the response to the last, for an
+//! upload until the response starts, less the server's processing time
+//! from `Server-Timing`. Requests shorter than 10 ms are left out, and the
+//! reported speed is the 90th percentile of the rest. Loaded latency is
+//! probed at most every 400 ms; latency is the median of the probes.
+
+use std::time::Duration;
+
+/// Download request sizes and how many requests of each.
+pub const DOWNLOAD_PLAN: &[(u64, usize)] = &[
+    (100_000, 10),
+    (1_000_000, 8),
+    (10_000_000, 6),
+    (25_000_000, 4),
+    (100_000_000, 3),
+    (250_000_000, 2),
+];
+
+/// Upload request sizes and how many requests of each.
+pub const UPLOAD_PLAN: &[(u64, usize)] = &[
+    (100_000, 8),
+    (1_000_000, 6),
+    (10_000_000, 4),
+    (25_000_000, 4),
+    (50_000_000, 3),
+];
+
+/// A request taking longer ends the plan after its size.
+const FINISH_REQUEST_DURATION: Duration = Duration::from_secs(1);
+
+/// Shorter requests don't count towards the speed.
+const MIN_REQUEST_DURATION: Duration = Duration::from_millis(10);
+
+/// Percentile of request speeds reported.
+const BANDWIDTH_PERCENTILE: f64 = 0.9;
+
+/// Shortest interval between loaded latency probes.
+pub const LOADED_LATENCY_THROTTLE_MS: u64 = 400;
+
+/// Longest download or upload phase; the website has no time limit, its
+/// plan ends each phase.
+pub const PHASE_LIMIT: Duration = Duration::from_secs(30);
+
+/// Shortest download or upload phase, leaving time for loaded latency
+/// probes and throughput samples when the plan is done sooner.
+pub const MIN_PHASE: Duration = Duration::from_secs(2);
+
+/// Progress through a phase's plan of request sizes.
+pub struct Ladder {
+    plan: &'static [(u64, usize)],
+    step: usize,
+    /// Requests made of the current size
+    done: usize,
+    /// A request took longer than `FINISH_REQUEST_DURATION`
+    finishing: bool,
+}
+
+impl Ladder {
+    pub fn new(plan: &'static [(u64, usize)]) -> Self {
+        Self {
+            plan,
+            step: 0,
+            done: 0,
+            finishing: false,
+        }
+    }
+
+    /// Size of the next request, or `None` once the plan is done.
+    pub fn next_bytes(&self) -> Option<u64> {
+        self.plan.get(self.step).map(|&(bytes, _)| bytes)
+    }
+
+    /// Record a finished request that took `duration`.
+    pub fn record(&mut self, duration: Duration) {
+        let Some(&(_, count)) = self.plan.get(self.step) else {
+            return;
+        };
+        self.finishing |= duration > FINISH_REQUEST_DURATION;
+        self.done += 1;
+        if self.done >= count {
+            self.done = 0;
+            self.step = if self.finishing {
+                self.plan.len()
+            } else {
+                self.step + 1
+            };
+        }
+    }
+}
+
+/// Speed in Mbps of a request of `bytes` transferred in `duration`, unless
+/// it was too short to count.
+pub fn request_mbps(bytes: u64, duration: Duration) -> Option<f64> {
+    (duration >= MIN_REQUEST_DURATION)
+        .then(|| bytes as f64 * 8.0 / duration.as_secs_f64() / 1_000_000.0)
+}
+
+/// The server's processing time from a `Server-Timing` header, e.g.
+/// `cfRequestDuration;dur=12.3`.
+pub fn server_time(headers: &reqwest::header::HeaderMap) -> Duration {
+    headers
+        .get_all("server-timing")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split([',', ';']))
+        .find_map(|param| param.trim().strip_prefix("dur=")?.parse::<f64>().ok())
+        .filter(|ms| ms.is_finite() && *ms >= 0.0)
+        .map_or(Duration::ZERO, |ms| Duration::from_secs_f64(ms / 1000.0))
+}
+
+/// The website's result of a phase: the 90th percentile of its request
+/// speeds, interpolated between the nearest ones.
+pub fn bandwidth_mbps(speeds: &[f64]) -> Option<f64> {
+    let mut sorted = speeds.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let last = sorted.len().checked_sub(1)?;
+    let position = last as f64 * BANDWIDTH_PERCENTILE;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    Some(sorted[below] + (sorted[above] - sorted[below]) * position.fract())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_website_parity() {
+        let mut ladder = Ladder::new(UPLOAD_PLAN);
+        for _ in 0..8 {
+            assert_eq!(ladder.next_bytes(), Some(100_000));
+            ladder.record(Duration::from_millis(50));
+        }
+        assert_eq!(ladder.next_bytes(), Some(1_000_000));
+        // A slow request ends the plan once its size is done
+        ladder.record(Duration::from_millis(1500));
+        for _ in 0..5 {
+            assert_eq!(ladder.next_bytes(), Some(1_000_000));
+            ladder.record(Duration::from_millis(900));
+        }
+        assert_eq!(ladder.next_bytes(), None);
+
+        assert_eq!(
+            request_mbps(1_000_000, Duration::from_millis(80)),
+            Some(100.0)
+        );
+        assert_eq!(request_mbps(100_000, Duration::from_millis(5)), None);
+        let speeds: Vec<f64> = (1..=11).map(|i| i as f64 * 10.0).collect();
+        assert_eq!(bandwidth_mbps(&speeds), Some(100.0));
+        assert_eq!(bandwidth_mbps(&[10.0, 20.0]), Some(19.0));
+        assert_eq!(bandwidth_mbps(&[]), None);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "server-timing",
+            "cfRequestDuration;dur=12.5".parse().unwrap(),
+        );
+        assert_eq!(server_time(&headers), Duration::from_micros(12_500));
+    }
+}
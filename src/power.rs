@@ -0,0 +1,160 @@
+//! Power source of the client machine: on battery or mains, and the battery
+//! charge. Read from `/sys/class/power_supply` on Linux and Android and from
+//! `pmset` on macOS; machines without a battery report nothing.
+
+use crate::model::PowerSummary;
+
+/// One entry of `/sys/class/power_supply`.
+#[cfg_attr(any(windows, target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Default)]
+struct PowerSupply {
+    /// "Battery", "Mains", "USB", ...
+    kind: String,
+    /// Whether an external supply is connected
+    online: Option<bool>,
+    /// Battery state: "Charging", "Discharging", "Full", "Not charging"
+    status: Option<String>,
+    /// Battery charge, 0-100
+    capacity: Option<u8>,
+    /// Battery of a peripheral (mouse, headset) rather than the machine
+    device_scope: bool,
+}
+
+/// The machine's power status from its power supplies; None without a battery.
+#[cfg_attr(any(windows, target_os = "macos"), allow(dead_code))]
+fn summarize_supplies(supplies: &[PowerSupply]) -> Option<PowerSummary> {
+    let batteries: Vec<&PowerSupply> = supplies
+        .iter()
+        .filter(|s| s.kind == "Battery" && !s.device_scope)
+        .collect();
+    if batteries.is_empty() {
+        return None;
+    }
+    let external = supplies
+        .iter()
+        .any(|s| s.kind != "Battery" && s.online == Some(true));
+    let discharging = batteries
+        .iter()
+        .any(|b| b.status.as_deref() == Some("Discharging"));
+    let capacities: Vec<u32> = batteries
+        .iter()
+        .filter_map(|b| b.capacity.map(u32::from))
+        .collect();
+    let battery_percent = (!capacities.is_empty())
+        .then(|| (capacities.iter().sum::<u32>() / capacities.len() as u32) as u8);
+    Some(PowerSummary {
+        on_battery: discharging && !external,
+        battery_percent,
+    })
+}
+
+/// Power status from `pmset -g batt` output, e.g.
+/// "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1)\t85%; discharging; ...".
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> Option<PowerSummary> {
+    let battery = output.lines().find(|l| l.contains("InternalBattery"))?;
+    let battery_percent = battery
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .find_map(|token| token.strip_suffix('%')?.parse().ok());
+    Some(PowerSummary {
+        on_battery: output.contains("'Battery Power'"),
+        battery_percent,
+    })
+}
+
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn query() -> Option<PowerSummary> {
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .ok()
+            .map(|v| v.trim().to_string())
+    };
+    let supplies: Vec<PowerSupply> = std::fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let path = entry.path();
+            PowerSupply {
+                kind: read(&path, "type").unwrap_or_default(),
+                online: read(&path, "online").map(|v| v == "1"),
+                status: read(&path, "status"),
+                capacity: read(&path, "capacity").and_then(|v| v.parse().ok()),
+                device_scope: read(&path, "scope").as_deref() == Some("Device"),
+            }
+        })
+        .collect();
+    summarize_supplies(&supplies)
+}
+
+#[cfg(target_os = "macos")]
+fn query() -> Option<PowerSummary> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    parse_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+fn query() -> Option<PowerSummary> {
+    None
+}
+
+/// The current power status, if the machine has a battery.
+pub fn power_status() -> Option<PowerSummary> {
+    query()
+}
+
+/// Why a scheduled run should be skipped with `--min-battery`: on battery
+/// with less charge than `min_percent`.
+pub fn low_battery(status: Option<&PowerSummary>, min_percent: u8) -> Option<String> {
+    let status = status.filter(|s| s.on_battery)?;
+    let percent = status.battery_percent?;
+    (percent < min_percent).then(|| format!("on battery at {percent}%"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_status() {
+        let battery = |status: &str, capacity| PowerSupply {
+            kind: "Battery".into(),
+            status: Some(status.into()),
+            capacity: Some(capacity),
+            ..Default::default()
+        };
+        let mains = |online| PowerSupply {
+            kind: "Mains".into(),
+            online: Some(online),
+            ..Default::default()
+        };
+        let mouse = PowerSupply {
+            device_scope: true,
+            ..battery("Discharging", 10)
+        };
+
+        let status =
+            summarize_supplies(&[mains(false), battery("Discharging", 40), mouse]).unwrap();
+        assert!(status.on_battery);
+        assert_eq!(status.battery_percent, Some(40));
+        assert_eq!(
+            low_battery(Some(&status), 50).as_deref(),
+            Some("on battery at 40%")
+        );
+        assert_eq!(low_battery(Some(&status), 30), None);
+
+        let status = summarize_supplies(&[mains(true), battery("Charging", 40)]).unwrap();
+        assert!(!status.on_battery);
+        assert_eq!(low_battery(Some(&status), 50), None);
+        // Desktops have no battery
+        assert!(summarize_supplies(&[mains(true)]).is_none());
+
+        let pmset = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t85%; discharging; 4:20 remaining present: true\n";
+        let status = parse_pmset(pmset).unwrap();
+        assert!(status.on_battery);
+        assert_eq!(status.battery_percent, Some(85));
+        assert!(parse_pmset("Now drawing from 'AC Power'\n").is_none());
+    }
+}
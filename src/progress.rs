@@ -0,0 +1,128 @@
+//! Progress display and Ctrl-C cancellation for bulk operations over saved
+//! runs (history export, rename-network, fleet-report), which take a while on
+//! thousands of runs.
+
+use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Set by the first Ctrl-C after `catch_interrupt`.
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Shortest time between two redraws of the progress line.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Make the first Ctrl-C cancel the bulk operation at the next item, so it
+/// stops between files instead of halfway through one. A second Ctrl-C exits
+/// right away. Lasts until the returned guard is dropped.
+#[must_use = "Ctrl-C is only caught while the guard is alive"]
+pub fn catch_interrupt() -> InterruptGuard {
+    CANCELLED.store(false, Ordering::SeqCst);
+    InterruptGuard(tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            CANCELLED.store(true, Ordering::SeqCst);
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    }))
+}
+
+/// Stops catching Ctrl-C when dropped (see `catch_interrupt`).
+pub struct InterruptGuard(tokio::task::JoinHandle<()>);
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+        CANCELLED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// "label: done/total" on stderr, redrawn in place while stderr is a
+/// terminal, or passed to a `report` callback.
+pub struct Progress {
+    label: &'static str,
+    total: usize,
+    done: usize,
+    visible: bool,
+    cancellable: bool,
+    last_draw: Option<Instant>,
+    report: Option<Box<dyn FnMut(String) + Send>>,
+}
+
+impl Progress {
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            total: 0,
+            done: 0,
+            visible: std::io::stderr().is_terminal(),
+            cancellable: true,
+            last_draw: None,
+            report: None,
+        }
+    }
+
+    /// Progress passed to `report` as "label: done/total" instead of drawn,
+    /// e.g. for the TUI's status line. Not cancellable, as Ctrl-C is a key
+    /// press there.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub fn reporting(label: &'static str, report: impl FnMut(String) + Send + 'static) -> Self {
+        let mut progress = Self::hidden();
+        progress.label = label;
+        progress.report = Some(Box::new(report));
+        progress
+    }
+
+    /// Progress that is neither shown nor cancellable, for callers that
+    /// aren't bulk operations.
+    pub fn hidden() -> Self {
+        let mut progress = Self::new("");
+        progress.visible = false;
+        progress.cancellable = false;
+        progress
+    }
+
+    pub fn set_total(&mut self, total: usize) {
+        self.total = total;
+    }
+
+    /// Count the next item, failing instead once the user pressed Ctrl-C.
+    pub fn tick(&mut self) -> Result<()> {
+        if self.cancellable && CANCELLED.load(Ordering::SeqCst) {
+            self.clear();
+            anyhow::bail!("cancelled after {} of {}", self.done, self.total);
+        }
+        self.done += 1;
+        if (self.visible || self.report.is_some())
+            && self
+                .last_draw
+                .is_none_or(|t| t.elapsed() >= REDRAW_INTERVAL)
+        {
+            let line = format!("{}: {}/{}", self.label, self.done, self.total);
+            match &mut self.report {
+                Some(report) => report(line),
+                None => {
+                    eprint!("\r{line}");
+                    std::io::stderr().flush().ok();
+                }
+            }
+            self.last_draw = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        if self.last_draw.take().is_some() && self.report.is_none() {
+            eprint!("\r\x1b[K");
+            std::io::stderr().flush().ok();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,133 @@
+//! Prometheus export (`--export-prometheus`): the latest run as gauges in
+//! the text exposition format, for node_exporter's textfile collector.
+//!
+//! Each metric the Grafana dashboard reads is a gauge named
+//! `<grafana::MEASUREMENT>_<field>` (e.g. `cloudflare_speed_download_mbps`),
+//! labelled with the interface, network and colo like the InfluxDB tags.
+//! The textfile collector doesn't accept sample timestamps, so the run's
+//! time is a gauge of its own.
+
+use crate::config::Config;
+use crate::grafana::{
+    FIELD_BUFFERBLOAT_SCORE, FIELD_DOWNLOAD_MBPS, FIELD_IDLE_LATENCY_MS, FIELD_IDLE_LOSS_PCT,
+    FIELD_LOADED_LATENCY_DOWNLOAD_MS, FIELD_LOADED_LATENCY_UPLOAD_MS, FIELD_PACKET_LOSS_PCT,
+    FIELD_UPLOAD_MBPS, MEASUREMENT,
+};
+use crate::model::RunResult;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Exported fields with their help text.
+const METRICS: &[(&str, &str)] = &[
+    (FIELD_DOWNLOAD_MBPS, "Download throughput in Mbps"),
+    (FIELD_UPLOAD_MBPS, "Upload throughput in Mbps"),
+    (FIELD_IDLE_LATENCY_MS, "Median idle latency in milliseconds"),
+    (
+        FIELD_LOADED_LATENCY_DOWNLOAD_MS,
+        "Median latency during the download in milliseconds",
+    ),
+    (
+        FIELD_LOADED_LATENCY_UPLOAD_MS,
+        "Median latency during the upload in milliseconds",
+    ),
+    (FIELD_IDLE_LOSS_PCT, "Idle latency probes lost, in percent"),
+    (FIELD_PACKET_LOSS_PCT, "UDP packets lost, in percent"),
+    (
+        FIELD_BUFFERBLOAT_SCORE,
+        "Bufferbloat grade as a number, from 0 (F) to 5 (A+)",
+    ),
+];
+
+/// Escape a label value: backslashes, double quotes and newlines.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+/// The metrics of `result` in the text exposition format.
+pub fn exposition(config: &Config, result: &RunResult) -> String {
+    let labels: Vec<String> = [
+        ("interface", result.interface_name.as_deref()),
+        ("network", config.network_label(result)),
+        ("colo", result.colo.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| {
+        let value = value.filter(|v| !v.is_empty())?;
+        Some(format!("{key}=\"{}\"", escape_label(value)))
+    })
+    .collect();
+    let labels = if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    };
+
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: f64| {
+        let _ = writeln!(out, "# HELP {MEASUREMENT}_{name} {help}");
+        let _ = writeln!(out, "# TYPE {MEASUREMENT}_{name} gauge");
+        let _ = writeln!(out, "{MEASUREMENT}_{name}{labels} {value}");
+    };
+    for (field, help) in METRICS {
+        let value = crate::storage::column_value(result, field);
+        if let Some(v) = value.parse::<f64>().ok().filter(|v| v.is_finite()) {
+            gauge(field, help, v);
+        }
+    }
+    if let Some(secs) = crate::report::unix_timestamp(&result.timestamp_utc) {
+        gauge(
+            "last_run_timestamp_seconds",
+            "When the run was made, in seconds since the epoch",
+            secs as f64,
+        );
+    }
+    out
+}
+
+/// Replace `path` with the metrics of `result`. The file is written next to
+/// it and renamed into place, so the collector never reads half of it.
+pub fn export_textfile(config: &Config, path: &Path, result: &RunResult) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".tmp");
+    std::fs::write(&partial, exposition(config, result))
+        .with_context(|| format!("write {}", Path::new(&partial).display()))?;
+    std::fs::rename(&partial, path).with_context(|| format!("replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    #[test]
+    fn test_exposition() {
+        let mut r = test_run(512.5, 40.0);
+        r.timestamp_utc = "2026-03-15T14:30:45Z".to_string();
+        r.idle_latency.median_ms = Some(12.5);
+        r.network_name = Some("Home \"fibre\"".to_string());
+        r.colo = Some("FRA".to_string());
+
+        let text = exposition(&Config::default(), &r);
+        for expected in [
+            "# TYPE cloudflare_speed_download_mbps gauge",
+            r#"cloudflare_speed_download_mbps{network="Home \"fibre\"",colo="FRA"} 512.5"#,
+            r#"cloudflare_speed_idle_latency_ms{network="Home \"fibre\"",colo="FRA"} 12.5"#,
+            r#"cloudflare_speed_last_run_timestamp_seconds{network="Home \"fibre\"",colo="FRA"} 1773585045"#,
+        ] {
+            assert!(
+                text.lines().any(|l| l == expected),
+                "{expected} missing:\n{text}"
+            );
+        }
+        // No UDP probes, so no packet loss
+        assert!(!text.contains("packet_loss_pct{"));
+    }
+}
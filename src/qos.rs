@@ -0,0 +1,301 @@
+//! QoS validation of `--compare-sources`: the same test from several source
+//! addresses of this machine in turn, e.g. one per VLAN or per address a
+//! router's per-device rules apply to, checked against the limits those
+//! rules are meant to enforce.
+
+use crate::model::RunResult;
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// How far a shaped rate may stray from its limit and still count as
+/// shaped as configured (fraction of the limit).
+const TOLERANCE: f64 = 0.15;
+
+/// Where a test leaves from: a local address, or an interface (such as a
+/// VLAN interface, e.g. eth0.20) whose address is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Address(IpAddr),
+    Interface(String),
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Address(ip) => write!(f, "{ip}"),
+            Source::Interface(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Source {
+    /// Fail unless the source exists on this machine, so its run would
+    /// actually leave from it.
+    pub fn check(&self) -> anyhow::Result<()> {
+        use crate::engine::network_bind;
+        match self {
+            Source::Address(ip) => network_bind::check_local_address(*ip),
+            Source::Interface(name) => network_bind::get_interface_ip(name).map(|_| ()),
+        }
+    }
+}
+
+/// One entry of `--compare-sources`: a source and the limits the router is
+/// meant to hold it to, in Mbps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceTarget {
+    pub source: Source,
+    pub download_limit_mbps: Option<f64>,
+    pub upload_limit_mbps: Option<f64>,
+}
+
+/// Parse `SOURCE[=DOWN[/UP]]`, e.g. `192.168.20.5=20/5` or `eth0.30=/2`.
+pub fn parse_target(value: &str) -> Result<SourceTarget, String> {
+    let (source, limits) = match value.split_once('=') {
+        Some((source, limits)) => (source.trim(), Some(limits)),
+        None => (value.trim(), None),
+    };
+    if source.is_empty() {
+        return Err(format!("no source address or interface in {value:?}"));
+    }
+    let source = match source.parse() {
+        Ok(ip) => Source::Address(ip),
+        Err(_) => Source::Interface(source.to_string()),
+    };
+    let limit = |text: &str| -> Result<Option<f64>, String> {
+        match text.trim() {
+            "" => Ok(None),
+            text => match text.parse::<f64>() {
+                Ok(mbps) if mbps.is_finite() && mbps > 0.0 => Ok(Some(mbps)),
+                _ => Err(format!("invalid limit {text:?}, expected Mbps such as 20")),
+            },
+        }
+    };
+    let (download_limit_mbps, upload_limit_mbps) = match limits {
+        Some(limits) => match limits.split_once('/') {
+            Some((down, up)) => (limit(down)?, limit(up)?),
+            None => (limit(limits)?, None),
+        },
+        None => (None, None),
+    };
+    Ok(SourceTarget {
+        source,
+        download_limit_mbps,
+        upload_limit_mbps,
+    })
+}
+
+/// How a measured rate compares with its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shaping {
+    /// Within `TOLERANCE` of the limit: the rule holds
+    AsConfigured,
+    /// Well above the limit: the rule isn't applied
+    NotShaped,
+    /// Well below the limit: shaped harder than configured, or the line is
+    /// slower than the limit
+    BelowLimit,
+}
+
+impl Shaping {
+    pub fn of(measured_mbps: f64, limit_mbps: f64) -> Self {
+        if measured_mbps > limit_mbps * (1.0 + TOLERANCE) {
+            Shaping::NotShaped
+        } else if measured_mbps < limit_mbps * (1.0 - TOLERANCE) {
+            Shaping::BelowLimit
+        } else {
+            Shaping::AsConfigured
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Shaping::AsConfigured => "shaped as configured",
+            Shaping::NotShaped => "NOT shaped",
+            Shaping::BelowLimit => "below the limit",
+        }
+    }
+}
+
+/// Outcome of one source's run.
+#[derive(Debug, Serialize)]
+pub struct SourceRun {
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_limit_mbps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_limit_mbps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_shaping: Option<Shaping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_shaping: Option<Shaping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<RunResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SourceRun {
+    pub fn new(target: &SourceTarget, result: anyhow::Result<RunResult>) -> Self {
+        let shaping =
+            |limit: Option<f64>, measured: Option<f64>| Some(Shaping::of(measured?, limit?));
+        let (download, upload) = match &result {
+            Ok(r) => (Some(r.download.mbps), Some(r.upload.mbps)),
+            Err(_) => (None, None),
+        };
+        Self {
+            source: target.source.to_string(),
+            download_limit_mbps: target.download_limit_mbps,
+            upload_limit_mbps: target.upload_limit_mbps,
+            download_shaping: shaping(target.download_limit_mbps, download),
+            upload_shaping: shaping(target.upload_limit_mbps, upload),
+            error: result.as_ref().err().map(|e| format!("{e:#}")),
+            result: result.ok(),
+        }
+    }
+}
+
+/// Whether the limits held, in words. The rules only hold if every limit
+/// was met closely; sources that failed are named and get no verdict.
+pub fn verdict(runs: &[SourceRun]) -> Option<String> {
+    let sources = |matches: &dyn Fn(&SourceRun) -> bool| -> Vec<&str> {
+        runs.iter()
+            .filter(|r| matches(r))
+            .map(|r| r.source.as_str())
+            .collect()
+    };
+    let with = |shaping: Shaping| {
+        sources(&|r| r.download_shaping == Some(shaping) || r.upload_shaping == Some(shaping))
+    };
+    let checked = sources(&|r| r.download_shaping.is_some() || r.upload_shaping.is_some());
+    let failed = sources(&|r| r.result.is_none());
+    if checked.is_empty() && failed.is_empty() {
+        return None;
+    }
+
+    let mut sentences = Vec::new();
+    let unshaped = with(Shaping::NotShaped);
+    if !unshaped.is_empty() {
+        sentences.push(format!(
+            "{} exceeded its limit: the router's QoS rules aren't shaping it as configured.",
+            unshaped.join(", ")
+        ));
+    }
+    let below = with(Shaping::BelowLimit);
+    if !below.is_empty() {
+        sentences.push(format!(
+            "{} stayed well below its limit: shaped harder than configured, or the line is \
+             slower than the limit.",
+            below.join(", ")
+        ));
+    }
+    if !failed.is_empty() {
+        sentences.push(format!(
+            "{} failed, so its limits weren't checked.",
+            failed.join(", ")
+        ));
+    }
+    if sentences.is_empty() {
+        sentences.push(format!(
+            "No source exceeded its limit by more than {:.0}%: the QoS rules hold.",
+            TOLERANCE * 100.0
+        ));
+    }
+    Some(sentences.join(" "))
+}
+
+/// Print the comparison after the runs' own text output.
+pub fn print_comparison(runs: &[SourceRun]) {
+    let rate = |mbps: f64, limit: Option<f64>, shaping: Option<Shaping>| match (limit, shaping) {
+        (Some(limit), Some(shaping)) => {
+            format!("{mbps:.1} Mbps (limit {limit}: {})", shaping.describe())
+        }
+        _ => format!("{mbps:.1} Mbps"),
+    };
+    println!();
+    println!("== Source comparison ==");
+    for run in runs {
+        match (&run.result, &run.error) {
+            (Some(r), _) => {
+                println!("{}", run.source);
+                println!(
+                    "  Download: {}",
+                    rate(
+                        r.download.mbps,
+                        run.download_limit_mbps,
+                        run.download_shaping
+                    )
+                );
+                println!(
+                    "  Upload:   {}",
+                    rate(r.upload.mbps, run.upload_limit_mbps, run.upload_shaping)
+                );
+            }
+            (None, error) => println!(
+                "{}: failed: {}",
+                run.source,
+                error.as_deref().unwrap_or("-")
+            ),
+        }
+    }
+    if let Some(verdict) = verdict(runs) {
+        println!();
+        println!("{verdict}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(target: &str, download: f64, upload: f64) -> SourceRun {
+        SourceRun::new(
+            &parse_target(target).unwrap(),
+            Ok(test_run(download, upload)),
+        )
+    }
+
+    #[test]
+    fn test_compare_sources() {
+        assert_eq!(
+            parse_target("192.168.20.5=20/5").unwrap(),
+            SourceTarget {
+                source: Source::Address("192.168.20.5".parse().unwrap()),
+                download_limit_mbps: Some(20.0),
+                upload_limit_mbps: Some(5.0)
+            }
+        );
+        let vlan = parse_target("eth0.30=/2").unwrap();
+        assert_eq!(vlan.source, Source::Interface("eth0.30".into()));
+        assert_eq!(
+            (vlan.download_limit_mbps, vlan.upload_limit_mbps),
+            (None, Some(2.0))
+        );
+        assert!(parse_target("10.0.0.2=fast").is_err());
+
+        let shaped = run("10.0.0.2=20/5", 19.0, 5.3);
+        assert_eq!(shaped.download_shaping, Some(Shaping::AsConfigured));
+        assert_eq!(shaped.upload_shaping, Some(Shaping::AsConfigured));
+        assert!(verdict(&[shaped, run("10.0.0.3", 300.0, 50.0)])
+            .unwrap()
+            .starts_with("No source exceeded its limit"));
+        assert!(verdict(&[run("10.0.0.2=20/5", 95.0, 5.0)])
+            .unwrap()
+            .starts_with("10.0.0.2 exceeded its limit"));
+        assert_eq!(verdict(&[run("10.0.0.3", 300.0, 50.0)]), None);
+
+        // Far below the limit isn't the rule holding, and a failed source
+        // gets no verdict
+        let failed = SourceRun::new(
+            &parse_target("10.0.0.4=20").unwrap(),
+            Err(anyhow::anyhow!("no such address")),
+        );
+        assert_eq!(
+            verdict(&[run("10.0.0.2=20/5", 19.0, 1.0), failed]).unwrap(),
+            "10.0.0.2 stayed well below its limit: shaped harder than configured, or the line \
+             is slower than the limit. 10.0.0.4 failed, so its limits weren't checked."
+        );
+    }
+}
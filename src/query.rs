@@ -0,0 +1,241 @@
+//! SQL over saved run history (`query` subcommand).
+//!
+//! Runs are loaded into an in-memory SQLite table `runs` with one column per
+//! export column (see `storage::csv_columns`), plus `ts` (the run timestamp)
+//! and `json` (the whole result, for `json_extract`). DuckDB-style `median`,
+//! `quantile_cont` and `date_trunc` are available so queries carry over.
+
+/// Truncate an RFC 3339 timestamp to the start of a year, month, day, hour
+/// or minute, formatted as SQLite date/time text.
+#[cfg_attr(not(feature = "sql"), allow(dead_code))]
+fn date_trunc(part: &str, ts: &str) -> Option<String> {
+    let date = ts.get(..10)?;
+    let time = ts.get(11..19).unwrap_or("00:00:00");
+    let truncated = match part.to_ascii_lowercase().as_str() {
+        "year" => format!("{}-01-01", &date[..4]),
+        "month" => format!("{}-01", &date[..7]),
+        "day" => date.to_string(),
+        "hour" => format!("{date} {}:00:00", time.get(..2)?),
+        "minute" => format!("{date} {}:00", time.get(..5)?),
+        _ => return None,
+    };
+    Some(truncated)
+}
+
+/// Continuous quantile (linear interpolation between the closest ranks),
+/// as DuckDB's `quantile_cont` and `median`.
+#[cfg_attr(not(feature = "sql"), allow(dead_code))]
+fn quantile_cont(values: &mut [f64], q: f64) -> Option<f64> {
+    if values.is_empty() || !(0.0..=1.0).contains(&q) {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = q * (values.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    let weight = rank - lower as f64;
+    Some(values[lower] + (values[upper] - values[lower]) * weight)
+}
+
+/// Format a number like a SQL shell: whole numbers without decimals, others
+/// with up to three.
+#[cfg_attr(not(feature = "sql"), allow(dead_code))]
+fn format_real(v: f64) -> String {
+    let s = format!("{v:.3}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(feature = "sql")]
+mod engine {
+    use super::{date_trunc, format_real, quantile_cont};
+    use crate::storage::{column_kind, column_value, ColumnKind};
+    use anyhow::{Context, Result};
+    use rusqlite::functions::{Aggregate, Context as FnContext, FunctionFlags};
+    use rusqlite::types::Value;
+    use rusqlite::Connection;
+
+    /// Collects the values of `median` / `quantile_cont`; the quantile is
+    /// fixed for `median` and read from the second argument otherwise.
+    struct Quantile(Option<f64>);
+
+    impl Aggregate<(Vec<f64>, f64), Option<f64>> for Quantile {
+        fn init(&self, _ctx: &mut FnContext<'_>) -> rusqlite::Result<(Vec<f64>, f64)> {
+            Ok((Vec::new(), self.0.unwrap_or(0.5)))
+        }
+
+        fn step(&self, ctx: &mut FnContext<'_>, acc: &mut (Vec<f64>, f64)) -> rusqlite::Result<()> {
+            if self.0.is_none() {
+                acc.1 = ctx.get(1)?;
+            }
+            if let Some(v) = ctx.get::<Option<f64>>(0)? {
+                acc.0.push(v);
+            }
+            Ok(())
+        }
+
+        fn finalize(
+            &self,
+            _ctx: &mut FnContext<'_>,
+            acc: Option<(Vec<f64>, f64)>,
+        ) -> rusqlite::Result<Option<f64>> {
+            Ok(acc.and_then(|(mut values, q)| quantile_cont(&mut values, q)))
+        }
+    }
+
+    fn register_functions(db: &Connection) -> rusqlite::Result<()> {
+        let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+        db.create_aggregate_function("median", 1, flags, Quantile(Some(0.5)))?;
+        db.create_aggregate_function("quantile_cont", 2, flags, Quantile(None))?;
+        db.create_scalar_function("date_trunc", 2, flags, |ctx| {
+            let part: String = ctx.get(0)?;
+            let ts: Option<String> = ctx.get(1)?;
+            Ok(ts.and_then(|ts| date_trunc(&part, &ts)))
+        })
+    }
+
+    /// Open an in-memory database with the `runs` table filled from history.
+    fn load_runs(limit: usize) -> Result<Connection> {
+        let db = Connection::open_in_memory().context("open sqlite")?;
+        register_functions(&db).context("register sql functions")?;
+        let columns = crate::storage::csv_columns("full")?;
+        let definitions: Vec<String> = columns
+            .iter()
+            .map(|c| {
+                let sql_type = match column_kind(c.name) {
+                    ColumnKind::Text => "TEXT",
+                    ColumnKind::Float => "REAL",
+                    ColumnKind::Int | ColumnKind::Bool => "INTEGER",
+                };
+                format!("{} {sql_type}", c.name)
+            })
+            .collect();
+        db.execute(
+            &format!(
+                "CREATE TABLE runs (ts TEXT, {}, json TEXT)",
+                definitions.join(", ")
+            ),
+            [],
+        )
+        .context("create runs table")?;
+
+        let placeholders = vec!["?"; columns.len() + 2].join(", ");
+        let mut insert = db.prepare(&format!("INSERT INTO runs VALUES ({placeholders})"))?;
+        for run in crate::storage::load_recent(limit)? {
+            let mut row = vec![Value::Text(run.timestamp_utc.clone())];
+            for column in &columns {
+                let value = column_value(&run, column.name);
+                row.push(match column_kind(column.name) {
+                    _ if value.is_empty() => Value::Null,
+                    ColumnKind::Text => Value::Text(value),
+                    ColumnKind::Float => value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|v| v.is_finite())
+                        .map_or(Value::Null, Value::Real),
+                    ColumnKind::Int => value.parse().map_or(Value::Null, Value::Integer),
+                    ColumnKind::Bool => Value::Integer((value == "true") as i64),
+                });
+            }
+            row.push(Value::Text(serde_json::to_string(&run)?));
+            insert.execute(rusqlite::params_from_iter(row))?;
+        }
+        drop(insert);
+        Ok(db)
+    }
+
+    /// Run `sql` against the most recent `limit` runs and print the result as
+    /// a table.
+    pub fn run_query(sql: &str, limit: usize) -> Result<()> {
+        let db = load_runs(limit)?;
+        let mut stmt = db.prepare(sql).context("prepare query")?;
+        let names: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+        let mut rows: Vec<Vec<(String, bool)>> = Vec::new();
+        let mut result = stmt.query([])?;
+        while let Some(row) = result.next()? {
+            let cells = (0..names.len())
+                .map(|i| {
+                    // Numbers are right-aligned
+                    Ok(match row.get::<_, Value>(i)? {
+                        Value::Null => ("NULL".to_string(), false),
+                        Value::Integer(v) => (v.to_string(), true),
+                        Value::Real(v) => (format_real(v), true),
+                        Value::Text(v) => (v, false),
+                        Value::Blob(v) => (format!("<{} bytes>", v.len()), false),
+                    })
+                })
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.push(cells);
+        }
+
+        let widths: Vec<usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                rows.iter()
+                    .map(|r| r[i].0.chars().count())
+                    .chain([name.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let header: Vec<String> = names
+            .iter()
+            .zip(&widths)
+            .map(|(name, w)| format!("{name:<w$}"))
+            .collect();
+        println!("{}", header.join("  ").trim_end());
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        println!("{}", rule.join("  "));
+        for row in &rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|((cell, numeric), w)| {
+                    if *numeric {
+                        format!("{cell:>w$}")
+                    } else {
+                        format!("{cell:<w$}")
+                    }
+                })
+                .collect();
+            println!("{}", cells.join("  ").trim_end());
+        }
+        println!(
+            "({} row{})",
+            rows.len(),
+            if rows.len() == 1 { "" } else { "s" }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sql")]
+pub use engine::run_query;
+
+#[cfg(not(feature = "sql"))]
+pub fn run_query(_sql: &str, _limit: usize) -> anyhow::Result<()> {
+    anyhow::bail!("The query subcommand requires building with the `sql` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duckdb_style_functions() {
+        let ts = "2026-03-15T14:30:45Z";
+        assert_eq!(date_trunc("day", ts).as_deref(), Some("2026-03-15"));
+        assert_eq!(date_trunc("Month", ts).as_deref(), Some("2026-03-01"));
+        assert_eq!(
+            date_trunc("hour", ts).as_deref(),
+            Some("2026-03-15 14:00:00")
+        );
+        assert_eq!(date_trunc("week", ts), None);
+
+        assert_eq!(quantile_cont(&mut [4.0, 1.0, 3.0, 2.0], 0.5), Some(2.5));
+        assert_eq!(quantile_cont(&mut [5.0, 1.0, 3.0], 0.5), Some(3.0));
+        assert_eq!(quantile_cont(&mut [1.0, 2.0, 3.0, 4.0], 0.75), Some(3.25));
+        assert_eq!(quantile_cont(&mut [], 0.5), None);
+        assert_eq!(format_real(512.300), "512.3");
+        assert_eq!(format_real(40.0), "40");
+    }
+}
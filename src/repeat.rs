@@ -0,0 +1,137 @@
+//! Aggregate of the back-to-back runs of `--count`, also used for the
+//! emailed weekly report.
+
+use crate::model::{LatencySummary, RunResult};
+use serde::Serialize;
+
+/// Median, minimum and maximum of a metric across runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Spread {
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    fn of(values: &[f64]) -> Option<Self> {
+        Some(Self {
+            median: crate::metrics::median(values)?,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+/// Throughput and latency across runs. Latencies are taken from each run's
+/// median, so `median` is the median of medians.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepeatSummary {
+    pub runs: usize,
+    /// Runs that failed, left out of the spreads
+    pub failed: usize,
+    pub download_mbps: Option<Spread>,
+    pub upload_mbps: Option<Spread>,
+    pub idle_latency_ms: Option<Spread>,
+    pub loaded_latency_download_ms: Option<Spread>,
+    pub loaded_latency_upload_ms: Option<Spread>,
+}
+
+pub fn summarize(runs: &[RunResult]) -> RepeatSummary {
+    let throughput = |f: fn(&RunResult) -> f64| Spread::of(&runs.iter().map(f).collect::<Vec<_>>());
+    let latency = |f: fn(&RunResult) -> &LatencySummary| {
+        Spread::of(
+            &runs
+                .iter()
+                .filter_map(|r| f(r).median_ms)
+                .collect::<Vec<_>>(),
+        )
+    };
+    RepeatSummary {
+        runs: runs.len(),
+        failed: 0,
+        download_mbps: throughput(|r| r.download.mbps),
+        upload_mbps: throughput(|r| r.upload.mbps),
+        idle_latency_ms: latency(|r| &r.idle_latency),
+        loaded_latency_download_ms: latency(|r| &r.loaded_latency_download),
+        loaded_latency_upload_ms: latency(|r| &r.loaded_latency_upload),
+    }
+}
+
+fn spread_line(label: &str, unit: &str, spread: Option<&Spread>) -> String {
+    match spread {
+        Some(s) => format!(
+            "{label:<26} med {:.2} min {:.2} max {:.2} {unit}",
+            s.median, s.min, s.max
+        ),
+        None => format!("{label:<26} -"),
+    }
+}
+
+/// One line per metric of `summary`.
+pub fn summary_lines(summary: &RepeatSummary) -> Vec<String> {
+    let lines = [
+        ("Download:", "Mbps", &summary.download_mbps),
+        ("Upload:", "Mbps", &summary.upload_mbps),
+        ("Idle latency:", "ms", &summary.idle_latency_ms),
+        (
+            "Loaded latency (download):",
+            "ms",
+            &summary.loaded_latency_download_ms,
+        ),
+        (
+            "Loaded latency (upload):",
+            "ms",
+            &summary.loaded_latency_upload_ms,
+        ),
+    ];
+    lines
+        .into_iter()
+        .map(|(label, unit, spread)| spread_line(label, unit, spread.as_ref()))
+        .collect()
+}
+
+/// Print `summary` after the runs' own text output.
+pub fn print_summary(summary: &RepeatSummary) {
+    println!();
+    println!("== Summary of {} runs ==", summary.runs);
+    if summary.failed > 0 {
+        println!("{} more run(s) failed and are left out", summary.failed);
+    }
+    for line in summary_lines(summary) {
+        println!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(download: f64, idle_ms: Option<f64>) -> RunResult {
+        let mut r = test_run(download, 10.0);
+        r.idle_latency.median_ms = idle_ms;
+        r
+    }
+
+    #[test]
+    fn test_repeat_summary() {
+        let runs = [
+            run(100.0, Some(12.0)),
+            run(80.0, Some(20.0)),
+            run(120.0, None),
+        ];
+        let summary = summarize(&runs);
+        assert_eq!(summary.runs, 3);
+        assert_eq!(
+            summary.download_mbps,
+            Some(Spread {
+                median: 100.0,
+                min: 80.0,
+                max: 120.0
+            })
+        );
+        let idle = summary.idle_latency_ms.unwrap();
+        assert_eq!((idle.median, idle.min, idle.max), (16.0, 12.0, 20.0));
+        assert!(summary.loaded_latency_upload_ms.is_none());
+    }
+}
@@ -0,0 +1,667 @@
+//! Plain-text reports over saved run history (`stats` and `history list`/
+//! `history show` subcommands).
+
+use crate::config::Config;
+use crate::model::RunResult;
+use crate::units::format_bytes;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of months of data usage shown by `stats`.
+const USAGE_MONTHS_SHOWN: usize = 6;
+/// Number of most recent AS changes listed by `stats`.
+const AS_CHANGES_SHOWN: usize = 10;
+
+/// Format `avg / median / p25 / p75` of a metric across runs.
+pub fn metric_line(label: &str, unit: &str, values: &[f64]) -> String {
+    match crate::metrics::compute_metrics(values) {
+        Some((mean, median, p25, p75)) => {
+            format!("{label:<14} avg {mean:.2} med {median:.2} p25 {p25:.2} p75 {p75:.2} {unit}")
+        }
+        None => format!("{label:<14} -"),
+    }
+}
+
+/// A run whose upstream AS differs from the previous run on the same network,
+/// e.g. after ISP rerouting or a failover to a backup WAN.
+pub struct AsnChange<'a> {
+    pub run: &'a RunResult,
+    pub from: String,
+    pub to: String,
+}
+
+/// The upstream AS number of a run and its label, e.g. "AS13335 (Cloudflare)".
+pub fn as_label(run: &RunResult) -> Option<(String, String)> {
+    let asn = run.asn.clone().or_else(|| {
+        run.meta
+            .as_ref()
+            .and_then(|m| crate::network::extract_metadata(m).asn)
+    })?;
+    let label = match run.as_org.as_deref() {
+        Some(org) => format!("AS{asn} ({org})"),
+        None => format!("AS{asn}"),
+    };
+    Some((asn, label))
+}
+
+/// Find AS changes in `runs`, given newest first as history is stored.
+/// Changes are returned oldest first.
+pub fn asn_changes(runs: &[RunResult]) -> Vec<AsnChange<'_>> {
+    let mut last_seen: HashMap<Option<&str>, (String, String)> = HashMap::new();
+    let mut changes = Vec::new();
+    for run in runs.iter().rev() {
+        let Some((asn, label)) = as_label(run) else {
+            continue;
+        };
+        let previous = last_seen.insert(run.network_name.as_deref(), (asn.clone(), label.clone()));
+        if let Some((_, from)) = previous.filter(|(previous, _)| *previous != asn) {
+            changes.push(AsnChange {
+                run,
+                from,
+                to: label,
+            });
+        }
+    }
+    changes
+}
+
+/// Test server of the default configuration, left out of `config_profile`.
+const DEFAULT_SERVER: &str = "speed.cloudflare.com";
+
+/// The test configuration that makes a run's results comparable with
+/// another's: phase duration, streams, test server and latency target, e.g.
+/// "10s×6". None for runs saved without their configuration.
+pub fn config_profile(run: &RunResult) -> Option<String> {
+    let config = run.config.as_ref()?;
+    let host = |url: &str| {
+        url.trim_start_matches("https://")
+            .trim_end_matches('/')
+            .to_string()
+    };
+    let server = host(&config.base_url);
+    let mut profile = if server == DEFAULT_SERVER {
+        config.label()
+    } else {
+        format!("{} @{server}", config.label())
+    };
+    if let Some(latency_url) = &config.latency_url {
+        profile += &format!(", latency @{}", host(latency_url));
+    }
+    Some(profile)
+}
+
+/// The test configurations of `runs` and how many runs used each, most used
+/// first. Runs without a stored configuration are left out.
+pub fn config_profiles<'a>(runs: impl IntoIterator<Item = &'a RunResult>) -> Vec<(String, usize)> {
+    rank_profiles(runs.into_iter().filter_map(config_profile))
+}
+
+/// Each of `profiles` with its count, most common first.
+fn rank_profiles(profiles: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for profile in profiles {
+        *counts.entry(profile).or_default() += 1;
+    }
+    let mut profiles: Vec<_> = counts.into_iter().collect();
+    profiles.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    profiles
+}
+
+/// Describe `profiles` (from `config_profiles`), e.g. "10s×6 (40 runs), 5s×1 (3 runs)".
+pub fn describe_profiles(profiles: &[(String, usize)]) -> String {
+    profiles
+        .iter()
+        .map(|(profile, count)| {
+            format!(
+                "{profile} ({count} run{})",
+                if *count == 1 { "" } else { "s" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Seconds since the Unix epoch of an RFC 3339 UTC run timestamp.
+pub fn unix_timestamp(timestamp_utc: &str) -> Option<i64> {
+    let part = |range: std::ops::Range<usize>| timestamp_utc.get(range)?.parse::<u8>().ok();
+    let year = timestamp_utc.get(0..4)?.parse().ok()?;
+    let date =
+        time::Date::from_calendar_date(year, part(5..7)?.try_into().ok()?, part(8..10)?).ok()?;
+    let time = time::Time::from_hms(part(11..13)?, part(14..16)?, part(17..19)?).ok()?;
+    Some(
+        time::PrimitiveDateTime::new(date, time)
+            .assume_utc()
+            .unix_timestamp(),
+    )
+}
+
+/// The Monday starting the (UTC) week of an RFC 3339 run timestamp.
+pub fn week_start(timestamp_utc: &str) -> Option<time::Date> {
+    let year = timestamp_utc.get(0..4)?.parse().ok()?;
+    let month = timestamp_utc.get(5..7)?.parse::<u8>().ok()?;
+    let day = timestamp_utc.get(8..10)?.parse().ok()?;
+    let date = time::Date::from_calendar_date(year, month.try_into().ok()?, day).ok()?;
+    Some(date - time::Duration::days(date.weekday().number_days_from_monday().into()))
+}
+
+/// Group a metric of `runs` by the week they ran in, oldest week first.
+pub fn by_week<'a>(
+    runs: impl IntoIterator<Item = &'a RunResult>,
+    value: fn(&RunResult) -> f64,
+) -> Vec<(time::Date, Vec<f64>)> {
+    let mut weeks: BTreeMap<time::Date, Vec<f64>> = BTreeMap::new();
+    for run in runs {
+        let v = value(run);
+        if let Some(week) = week_start(&run.timestamp_utc).filter(|_| v.is_finite()) {
+            weeks.entry(week).or_default().push(v);
+        }
+    }
+    weeks.into_iter().collect()
+}
+
+/// Fewest runs a trend is fitted to.
+const MIN_TREND_RUNS: usize = 10;
+/// Shortest time span a trend is fitted over (days).
+const MIN_TREND_DAYS: f64 = 14.0;
+/// Weekly change, in percent, from which a worsening trend is warned about.
+const TREND_WARN_PCT_PER_WEEK: f64 = 1.0;
+/// Weeks a worsening trend is extrapolated over in its warning.
+const TREND_FORECAST_WEEKS: f64 = 13.0;
+
+const SECS_PER_WEEK: f64 = 7.0 * 86400.0;
+
+/// Least-squares linear trend of a metric over time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    /// Change per week relative to the metric's mean, in percent
+    pub pct_per_week: f64,
+    /// Share of the variation the trend explains
+    pub r_squared: f64,
+    /// Slope over its standard error; how unlikely the trend is to be noise
+    pub t_value: f64,
+    pub runs: usize,
+    pub weeks: f64,
+}
+
+impl Trend {
+    /// How much the trend can be relied on: "high", "medium" or "low".
+    pub fn confidence(&self) -> &'static str {
+        match self.t_value.abs() {
+            t if t >= 4.0 && self.runs >= 2 * MIN_TREND_RUNS => "high",
+            t if t >= 2.0 => "medium",
+            _ => "low",
+        }
+    }
+}
+
+/// Fit a linear trend to `value` of `runs` over their timestamps. None when
+/// there are too few runs, or they span too short a time, for a trend to
+/// mean anything.
+pub fn trend(runs: &[&RunResult], value: impl Fn(&RunResult) -> Option<f64>) -> Option<Trend> {
+    let points: Vec<(f64, f64)> = runs
+        .iter()
+        .filter_map(|r| {
+            let t = unix_timestamp(&r.timestamp_utc)? as f64 / SECS_PER_WEEK;
+            Some((t, value(r).filter(|v| v.is_finite())?))
+        })
+        .collect();
+    let n = points.len() as f64;
+    let (t_min, t_max) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(lo, hi), (t, _)| {
+            (lo.min(*t), hi.max(*t))
+        });
+    let weeks = t_max - t_min;
+    if points.len() < MIN_TREND_RUNS || weeks * 7.0 < MIN_TREND_DAYS {
+        return None;
+    }
+    let t_mean = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let y_mean = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(t, _)| (t - t_mean).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(t, y)| (t - t_mean) * (y - y_mean))
+        .sum();
+    let syy: f64 = points.iter().map(|(_, y)| (y - y_mean).powi(2)).sum();
+    if y_mean == 0.0 || sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    let sse = (syy - slope * sxy).max(0.0);
+    let r_squared = if syy > 0.0 { 1.0 - sse / syy } else { 0.0 };
+    let std_error = (sse / (n - 2.0) / sxx).sqrt();
+    let t_value = if std_error > 0.0 {
+        slope / std_error
+    } else {
+        f64::INFINITY.copysign(slope)
+    };
+    Some(Trend {
+        pct_per_week: slope / y_mean * 100.0,
+        r_squared,
+        t_value,
+        runs: points.len(),
+        weeks,
+    })
+}
+
+type Metric = fn(&RunResult) -> Option<f64>;
+
+/// Metrics `stats` fits trends to, and whether a rise is the bad direction.
+const TREND_METRICS: [(&str, Metric, bool); 3] = [
+    ("download", |r| Some(r.comparable_mbps().0), false),
+    ("upload", |r| Some(r.comparable_mbps().1), false),
+    ("idle latency", |r| r.idle_latency.median_ms, true),
+];
+
+/// Configuration a run counts as in trends: a calibrated quick run counts
+/// as the full runs it was normalized to.
+fn trend_profile(run: &RunResult) -> Option<String> {
+    match &run.normalized {
+        Some(normalized) => Some(normalized.reference_profile.clone()),
+        None => config_profile(run),
+    }
+}
+
+/// Print the trend of each metric per network, and a warning for those
+/// steadily getting worse. Only each network's most common test
+/// configuration is used, as others aren't comparable; calibrated quick
+/// runs join it with their normalized throughput.
+fn print_trends(config: &Config, runs: &[RunResult]) {
+    let mut networks: BTreeMap<&str, Vec<&RunResult>> = BTreeMap::new();
+    for run in runs {
+        networks
+            .entry(config.network_label(run).unwrap_or("-"))
+            .or_default()
+            .push(run);
+    }
+    let mut lines = Vec::new();
+    let mut warnings = Vec::new();
+    for (network, runs) in networks {
+        let profile = rank_profiles(runs.iter().filter_map(|r| trend_profile(r)))
+            .into_iter()
+            .next()
+            .map(|(profile, _)| profile);
+        let runs: Vec<&RunResult> = runs
+            .into_iter()
+            .filter(|r| profile.is_none() || trend_profile(r) == profile)
+            .collect();
+        let mut figures = Vec::new();
+        for (metric, value, rise_is_worse) in TREND_METRICS {
+            let Some(trend) = trend(&runs, value) else {
+                continue;
+            };
+            figures.push(format!("{metric} {:+.1}%/week", trend.pct_per_week));
+            let worsening = if rise_is_worse {
+                trend.pct_per_week
+            } else {
+                -trend.pct_per_week
+            };
+            if worsening >= TREND_WARN_PCT_PER_WEEK && trend.confidence() != "low" {
+                warnings.push(format!(
+                    "Warning: {network} {metric} has been getting worse by {:.1}% per week \
+                     ({} confidence: R² {:.2}, {} runs over {:.1} weeks); {:.0}% worse in 3 months at this rate",
+                    worsening,
+                    trend.confidence(),
+                    trend.r_squared,
+                    trend.runs,
+                    trend.weeks,
+                    worsening * TREND_FORECAST_WEEKS
+                ));
+            }
+        }
+        if !figures.is_empty() {
+            lines.push(format!("  {network}: {}", figures.join(", ")));
+        }
+    }
+    if lines.is_empty() {
+        return;
+    }
+    println!();
+    println!("Trends:");
+    for line in lines.iter().chain(&warnings) {
+        println!("{line}");
+    }
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |ms| format!("{ms:.1} ms"))
+}
+
+/// Print one line per run, in the order given (`history list`).
+pub fn print_run_list(config: &Config, runs: &[RunResult]) {
+    if runs.is_empty() {
+        println!("No saved runs");
+        return;
+    }
+    println!(
+        "{:<32} {:<20} {:<16} {:>10} {:>10} {:>9}",
+        "Time", "Measurement ID", "Network", "Down Mbps", "Up Mbps", "Idle"
+    );
+    for run in runs {
+        let mut line = format!(
+            "{:<32} {:<20} {:<16} {:>10.2} {:>10.2} {:>9}",
+            run.timestamp_utc,
+            run.meas_id,
+            config.network_label(run).unwrap_or("-"),
+            run.download.mbps,
+            run.upload.mbps,
+            ms(run.idle_latency.median_ms)
+        );
+        if run.invalid.is_some() {
+            line.push_str("  invalid");
+        }
+        println!("{line}");
+    }
+}
+
+/// Print the main figures of a saved run (`history show`); `--json` prints
+/// all of it.
+pub fn print_run(config: &Config, run: &RunResult) {
+    let mut lines = vec![
+        ("Time", run.timestamp_utc.clone()),
+        ("Measurement ID", run.meas_id.clone()),
+        (
+            "Network",
+            config.network_label(run).unwrap_or("-").to_string(),
+        ),
+    ];
+    if let Some(interface) = run.interface_name.as_deref() {
+        lines.push(("Interface", interface.to_string()));
+    }
+    if let Some((_, label)) = as_label(run) {
+        lines.push(("Upstream", label));
+    }
+    let server = [run.server.as_deref(), run.colo.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if !server.is_empty() {
+        lines.push(("Server", server.join(", ")));
+    }
+    if let Some(provider) = run.provider.as_deref() {
+        lines.push(("Provider", provider.to_string()));
+    }
+    if let Some(config) = run.config.as_ref() {
+        lines.push(("Configuration", config.label()));
+    }
+    lines.push((
+        "Download",
+        format!(
+            "{:.2} Mbps ({})",
+            run.download.mbps,
+            format_bytes(run.download.bytes)
+        ),
+    ));
+    lines.push((
+        "Upload",
+        format!(
+            "{:.2} Mbps ({})",
+            run.upload.mbps,
+            format_bytes(run.upload.bytes)
+        ),
+    ));
+    if let Some(normalized) = run.normalized.as_ref() {
+        lines.push((
+            "Normalized",
+            format!(
+                "{:.2} down, {:.2} up Mbps as a {} run",
+                normalized.download_mbps, normalized.upload_mbps, normalized.reference_profile
+            ),
+        ));
+    }
+    for (label, latency) in [
+        ("Idle latency", &run.idle_latency),
+        ("Loaded (down)", &run.loaded_latency_download),
+        ("Loaded (up)", &run.loaded_latency_upload),
+    ] {
+        lines.push((
+            label,
+            format!(
+                "{} median, {} jitter, {:.1}% loss",
+                ms(latency.median_ms),
+                ms(latency.jitter_ms),
+                latency.loss * 100.0
+            ),
+        ));
+    }
+    if let Some(grade) = run.bufferbloat_grade() {
+        lines.push(("Bufferbloat", grade.to_string()));
+    }
+    if let Some(comments) = run.comments.as_deref().filter(|c| !c.trim().is_empty()) {
+        lines.push(("Comments", comments.to_string()));
+    }
+    if let Some(reason) = run.invalid.as_deref() {
+        lines.push(("Invalid", reason.to_string()));
+    }
+    for (label, value) in lines {
+        println!("{:<16}{value}", format!("{label}:"));
+    }
+}
+
+/// Print summary statistics over the most recent `limit` runs and the
+/// monthly data usage, relative to `data_cap` when one is given.
+pub fn print_stats(config: &Config, limit: usize, data_cap: Option<u64>) -> Result<()> {
+    // Runs that slept mid-test would skew every figure
+    let (runs, invalid): (Vec<_>, Vec<_>) = crate::storage::load_recent(limit)?
+        .into_iter()
+        .partition(|r| r.invalid.is_none());
+    if runs.is_empty() {
+        println!("Runs: 0");
+    } else {
+        let newest = &runs[0].timestamp_utc;
+        let oldest = &runs[runs.len() - 1].timestamp_utc;
+        println!("Runs: {} ({} .. {})", runs.len(), oldest, newest);
+        if !invalid.is_empty() {
+            println!("Invalid runs left out: {}", invalid.len());
+        }
+
+        let collect =
+            |f: fn(&RunResult) -> Option<f64>| -> Vec<f64> { runs.iter().filter_map(f).collect() };
+        println!(
+            "{}",
+            metric_line("Download:", "Mbps", &collect(|r| Some(r.download.mbps)))
+        );
+        println!(
+            "{}",
+            metric_line("Upload:", "Mbps", &collect(|r| Some(r.upload.mbps)))
+        );
+        println!(
+            "{}",
+            metric_line(
+                "Idle latency:",
+                "ms",
+                &collect(|r| r.idle_latency.median_ms)
+            )
+        );
+
+        // Results of different test configurations aren't comparable, so
+        // break them down per configuration as well
+        let profiles = config_profiles(&runs);
+        if profiles.len() > 1 {
+            println!();
+            println!(
+                "Test configurations differ: {}",
+                describe_profiles(&profiles)
+            );
+            for (profile, _) in &profiles {
+                let matching: Vec<&RunResult> = runs
+                    .iter()
+                    .filter(|r| config_profile(r).as_ref() == Some(profile))
+                    .collect();
+                let values = |f: fn(&RunResult) -> f64| -> Vec<f64> {
+                    matching.iter().map(|r| f(r)).collect()
+                };
+                println!("  {profile}:");
+                println!(
+                    "    {}",
+                    metric_line("Download:", "Mbps", &values(|r| r.download.mbps))
+                );
+                println!(
+                    "    {}",
+                    metric_line("Upload:", "Mbps", &values(|r| r.upload.mbps))
+                );
+            }
+        }
+
+        print_trends(config, &runs);
+
+        let changes = asn_changes(&runs);
+        if !changes.is_empty() {
+            println!();
+            println!("AS changes: {}", changes.len());
+            for change in changes.iter().rev().take(AS_CHANGES_SHOWN) {
+                println!(
+                    "  {} {}: {} -> {}",
+                    change.run.timestamp_utc,
+                    config.network_label(change.run).unwrap_or("-"),
+                    change.from,
+                    change.to
+                );
+            }
+        }
+    }
+
+    println!();
+    let month = crate::storage::current_month_key();
+    let usage = crate::storage::load_data_usage()?;
+    let used = usage.get(&month).copied().unwrap_or(0);
+    match data_cap {
+        Some(cap) if cap > 0 => println!(
+            "Data usage ({month}): {} of {} ({:.1}%), {} remaining",
+            format_bytes(used),
+            format_bytes(cap),
+            used as f64 * 100.0 / cap as f64,
+            format_bytes(cap.saturating_sub(used))
+        ),
+        _ => println!("Data usage ({month}): {}", format_bytes(used)),
+    }
+    let previous = usage.iter().rev().filter(|(key, _)| **key != month);
+    for (key, bytes) in previous.take(USAGE_MONTHS_SHOWN - 1) {
+        println!("  {key}: {}", format_bytes(*bytes));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn run(network: &str, asn: &str) -> RunResult {
+        let mut r = test_run(0.0, 0.0);
+        r.network_name = Some(network.to_string());
+        r.asn = Some(asn.to_string());
+        r
+    }
+
+    #[test]
+    fn test_asn_changes_per_network() {
+        // Newest first, as loaded from history
+        let runs = vec![
+            run("home", "100"),
+            run("office", "300"),
+            run("home", "200"),
+            run("office", "300"),
+            run("home", "100"),
+        ];
+        let changes = asn_changes(&runs);
+        let found: Vec<_> = changes
+            .iter()
+            .map(|c| (c.from.as_str(), c.to.as_str()))
+            .collect();
+        assert_eq!(found, [("AS100", "AS200"), ("AS200", "AS100")]);
+        assert!(std::ptr::eq(changes[1].run, &runs[0]));
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        use clap::Parser;
+        let with_args = |args: &[&str]| {
+            let mut r = run("home", "100");
+            let cli = crate::cli::Cli::parse_from(["cloudflare-speed-cli"].iter().chain(args));
+            r.config = Some(crate::cli::build_config(&cli));
+            r
+        };
+        let runs = vec![
+            with_args(&[]),
+            with_args(&["--download-duration", "5s", "--single-connection"]),
+            with_args(&[]),
+            with_args(&["--base-url", "https://speed.example.com/"]),
+            run("home", "100"),
+            with_args(&["--latency-url", "https://ping.example.com/"]),
+        ];
+        assert_eq!(
+            config_profiles(&runs),
+            [
+                ("10s×6".to_string(), 2),
+                ("10s×6 @speed.example.com".to_string(), 1),
+                ("10s×6, latency @ping.example.com".to_string(), 1),
+                ("5s×1".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            describe_profiles(&config_profiles(&runs[..2])),
+            "10s×6 (1 run), 5s×1 (1 run)"
+        );
+    }
+
+    #[test]
+    fn test_by_week() {
+        let runs: Vec<RunResult> = [
+            ("2026-03-16T08:00:00Z", 300.0), // Monday
+            ("2026-03-11T20:00:00Z", 100.0),
+            ("2026-03-15T23:59:59Z", 200.0), // Sunday, same week as the 11th
+            ("2026-03-09T00:00:00Z", f64::NAN),
+        ]
+        .into_iter()
+        .map(|(ts, mbps)| {
+            let mut r = run("home", "100");
+            r.timestamp_utc = ts.to_string();
+            r.download.mbps = mbps;
+            r
+        })
+        .collect();
+        let weeks = by_week(&runs, |r| r.download.mbps);
+        let found: Vec<_> = weeks
+            .iter()
+            .map(|(week, values)| (week.to_string(), values.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("2026-03-09".to_string(), vec![100.0, 200.0]),
+                ("2026-03-16".to_string(), vec![300.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trend() {
+        // Two runs a day for four weeks, upload losing 2 Mbps (2%) a week
+        let start = time::macros::datetime!(2026-03-02 00:00 UTC);
+        let runs: Vec<RunResult> = (0..56)
+            .map(|i| {
+                let mut r = run("home", "100");
+                let at = start + time::Duration::hours(12 * i);
+                r.timestamp_utc = at
+                    .format(&time::format_description::well_known::Rfc3339)
+                    .unwrap();
+                let jitter = if i % 2 == 0 { 0.5 } else { -0.5 };
+                r.upload.mbps = 100.0 - 2.0 * i as f64 / 14.0 + jitter;
+                r.download.mbps = 500.0 + jitter;
+                r
+            })
+            .collect();
+        let refs: Vec<&RunResult> = runs.iter().collect();
+
+        let upload = trend(&refs, |r| Some(r.upload.mbps)).unwrap();
+        assert!((upload.pct_per_week + 2.0).abs() < 0.1, "{upload:?}");
+        assert_eq!(upload.confidence(), "high");
+        let download = trend(&refs, |r| Some(r.download.mbps)).unwrap();
+        assert!(download.pct_per_week.abs() < 0.1);
+        assert_eq!(download.confidence(), "low");
+
+        // Too short a span
+        assert!(trend(&refs[..20], |r| Some(r.upload.mbps)).is_none());
+    }
+}
@@ -0,0 +1,257 @@
+//! Background monitor mode: a Scheduled Task that starts at logon on Windows,
+//! a LaunchAgent on macOS.
+
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+
+/// Name of the Scheduled Task unless `--name` is given.
+pub const DEFAULT_TASK_NAME: &str = "cloudflare-speed-cli monitor";
+
+/// Longest command line `schtasks /TR` accepts.
+#[cfg_attr(not(windows), allow(dead_code))]
+const MAX_TASK_COMMAND_LEN: usize = 261;
+
+/// Command line the task runs: silent monitor mode with the given interval
+/// and data directory.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn task_command(exe: &Path, interval: Duration, data_dir: Option<&Path>) -> String {
+    let mut command = format!(
+        "\"{}\" --monitor {} --json --silent",
+        exe.display(),
+        humantime::format_duration(interval)
+    );
+    if let Some(dir) = data_dir {
+        command.push_str(&format!(" --data-dir \"{}\"", dir.display()));
+    }
+    command
+}
+
+/// Register a Scheduled Task that runs monitor mode at logon, replacing any
+/// existing task with the same name.
+#[cfg(windows)]
+pub fn install(name: &str, interval: Duration, data_dir: Option<&Path>) -> Result<()> {
+    use anyhow::Context;
+
+    let exe = std::env::current_exe().context("locate cloudflare-speed-cli executable")?;
+    // The task may start in another working directory
+    let data_dir = data_dir
+        .map(std::path::absolute)
+        .transpose()
+        .context("resolve data directory")?;
+    let command = task_command(&exe, interval, data_dir.as_deref());
+    if command.len() > MAX_TASK_COMMAND_LEN {
+        anyhow::bail!(
+            "task command is longer than {MAX_TASK_COMMAND_LEN} characters; \
+             move the executable or data directory to a shorter path"
+        );
+    }
+    schtasks(&[
+        "/Create",
+        "/F",
+        "/SC",
+        "ONLOGON",
+        "/TN",
+        name,
+        "/TR",
+        command.as_str(),
+    ])?;
+    println!("Installed scheduled task \"{name}\": {command}");
+    println!("It starts at your next logon; run `schtasks /Run /TN \"{name}\"` to start it now.");
+    Ok(())
+}
+
+/// Remove the Scheduled Task created by `install`.
+#[cfg(windows)]
+pub fn uninstall(name: &str) -> Result<()> {
+    // Stop a running instance first; fails harmlessly if it isn't running
+    schtasks(&["/End", "/TN", name]).ok();
+    schtasks(&["/Delete", "/F", "/TN", name])?;
+    println!("Removed scheduled task \"{name}\"");
+    Ok(())
+}
+
+#[cfg(windows)]
+fn schtasks(args: &[&str]) -> Result<()> {
+    use anyhow::Context;
+
+    let output = std::process::Command::new("schtasks")
+        .args(args)
+        .output()
+        .context("run schtasks")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "schtasks {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn install(_name: &str, _interval: Duration, _data_dir: Option<&Path>) -> Result<()> {
+    anyhow::bail!("service-install is only available on Windows (use launchd-install on macOS)")
+}
+
+#[cfg(not(windows))]
+pub fn uninstall(_name: &str) -> Result<()> {
+    anyhow::bail!("service-uninstall is only available on Windows")
+}
+
+/// Label of the LaunchAgent, also its plist file name.
+pub const LAUNCHD_LABEL: &str = "com.github.kavehtehrani.cloudflare-speed-cli";
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// LaunchAgent plist that keeps silent monitor mode running, logging errors
+/// to `log`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launchd_plist(exe: &Path, interval: Duration, data_dir: Option<&Path>, log: &Path) -> String {
+    let mut args = vec![
+        exe.display().to_string(),
+        "--monitor".to_string(),
+        humantime::format_duration(interval).to_string(),
+        "--json".to_string(),
+        "--silent".to_string(),
+    ];
+    if let Some(dir) = data_dir {
+        args.push("--data-dir".to_string());
+        args.push(dir.display().to_string());
+    }
+    let args: String = args
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+        .collect();
+    let log = xml_escape(&log.display().to_string());
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// Write the LaunchAgent plist and load it, replacing a previously installed one.
+#[cfg(target_os = "macos")]
+pub fn launchd_install(interval: Duration, data_dir: Option<&Path>) -> Result<()> {
+    use anyhow::Context;
+
+    let home = dirs::home_dir().context("locate home directory")?;
+    let exe = std::env::current_exe().context("locate cloudflare-speed-cli executable")?;
+    let data_dir = data_dir
+        .map(std::path::absolute)
+        .transpose()
+        .context("resolve data directory")?;
+    let log = home.join("Library/Logs/cloudflare-speed-cli.log");
+    let agents = home.join("Library/LaunchAgents");
+    std::fs::create_dir_all(&agents).context("create LaunchAgents dir")?;
+    let plist = agents.join(format!("{LAUNCHD_LABEL}.plist"));
+
+    // Unload the old agent so the new plist takes effect
+    if plist.exists() {
+        launchctl(&["unload", &plist.display().to_string()]).ok();
+    }
+    std::fs::write(
+        &plist,
+        launchd_plist(&exe, interval, data_dir.as_deref(), &log),
+    )
+    .with_context(|| format!("write {}", plist.display()))?;
+    launchctl(&["load", "-w", &plist.display().to_string()])?;
+    println!("Installed and started LaunchAgent {}", plist.display());
+    println!("Errors are logged to {}", log.display());
+    Ok(())
+}
+
+/// Unload and delete the LaunchAgent written by `launchd_install`.
+#[cfg(target_os = "macos")]
+pub fn launchd_uninstall() -> Result<()> {
+    use anyhow::Context;
+
+    let home = dirs::home_dir().context("locate home directory")?;
+    let plist = home
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist"));
+    if !plist.exists() {
+        anyhow::bail!("LaunchAgent {} is not installed", plist.display());
+    }
+    launchctl(&["unload", "-w", &plist.display().to_string()]).ok();
+    std::fs::remove_file(&plist).with_context(|| format!("remove {}", plist.display()))?;
+    println!("Removed LaunchAgent {}", plist.display());
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn launchctl(args: &[&str]) -> Result<()> {
+    use anyhow::Context;
+
+    let output = std::process::Command::new("launchctl")
+        .args(args)
+        .output()
+        .context("run launchctl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "launchctl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn launchd_install(_interval: Duration, _data_dir: Option<&Path>) -> Result<()> {
+    anyhow::bail!("launchd-install is only available on macOS")
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn launchd_uninstall() -> Result<()> {
+    anyhow::bail!("launchd-uninstall is only available on macOS")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_command() {
+        let command = task_command(
+            Path::new(r"C:\Tools\cloudflare-speed-cli.exe"),
+            Duration::from_secs(30 * 60),
+            Some(Path::new(r"D:\speed data")),
+        );
+        assert_eq!(
+            command,
+            r#""C:\Tools\cloudflare-speed-cli.exe" --monitor 30m --json --silent --data-dir "D:\speed data""#
+        );
+
+        let plist = launchd_plist(
+            Path::new("/usr/local/bin/cloudflare-speed-cli"),
+            Duration::from_secs(3600),
+            Some(Path::new("/Users/me/R&D")),
+            Path::new("/Users/me/Library/Logs/cloudflare-speed-cli.log"),
+        );
+        assert!(plist.contains("        <string>--monitor</string>\n        <string>1h</string>\n"));
+        assert!(plist.contains("<string>/Users/me/R&amp;D</string>"));
+    }
+}
@@ -0,0 +1,154 @@
+//! Ed25519 signatures over exported result files (`--sign`) and their
+//! verification (`verify`).
+//!
+//! The key is generated on first use and kept in the data directory. Each
+//! signed file gets a detached `<file>.sig` holding the signature and the
+//! public key; a valid signature shows the file hasn't changed since this
+//! machine wrote it, as far as the reader trusts the public key.
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const ALGORITHM: &str = "ed25519";
+
+/// Contents of a `.sig` file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Signature {
+    pub algorithm: String,
+    /// Signer's public key, base64
+    pub public_key: String,
+    /// Signature over the file's bytes, base64
+    pub signature: String,
+}
+
+fn sign_bytes(key: &Ed25519KeyPair, data: &[u8]) -> Signature {
+    Signature {
+        algorithm: ALGORITHM.to_string(),
+        public_key: STANDARD.encode(key.public_key().as_ref()),
+        signature: STANDARD.encode(key.sign(data).as_ref()),
+    }
+}
+
+/// Check `signature` against `data`, and against `public_key` when the
+/// signer's key is known.
+fn verify_bytes(signature: &Signature, data: &[u8], public_key: Option<&str>) -> Result<()> {
+    anyhow::ensure!(
+        signature.algorithm == ALGORITHM,
+        "unsupported signature algorithm '{}'",
+        signature.algorithm
+    );
+    if let Some(expected) = public_key {
+        anyhow::ensure!(
+            expected.trim() == signature.public_key,
+            "signed with a different key ({})",
+            signature.public_key
+        );
+    }
+    let key = STANDARD
+        .decode(&signature.public_key)
+        .context("invalid public key")?;
+    let sig = STANDARD
+        .decode(&signature.signature)
+        .context("invalid signature")?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(data, &sig)
+        .map_err(|_| anyhow::anyhow!("signature doesn't match the file"))
+}
+
+/// This machine's signing key, generated on first use.
+fn load_or_create_key() -> Result<Ed25519KeyPair> {
+    let path = crate::storage::signing_key_path();
+    let pkcs8 = match std::fs::read(&path) {
+        Ok(pkcs8) => pkcs8,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|_| anyhow::anyhow!("failed to generate a signing key"))?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).context("create data directory")?;
+            }
+            write_private(&path, pkcs8.as_ref())
+                .with_context(|| format!("write {}", path.display()))?;
+            pkcs8.as_ref().to_vec()
+        }
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
+    };
+    Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|_| anyhow::anyhow!("invalid signing key in {}", path.display()))
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)
+}
+
+/// Path of the detached signature for `path`.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// Sign the file at `path`, writing `<path>.sig`.
+pub fn sign_file(path: &Path) -> Result<()> {
+    let key = load_or_create_key()?;
+    let data = std::fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    let signature = sign_bytes(&key, &data);
+    let sig_path = signature_path(path);
+    std::fs::write(&sig_path, serde_json::to_vec_pretty(&signature)?)
+        .with_context(|| format!("write {}", sig_path.display()))
+}
+
+/// Verify the file at `path` against `<path>.sig`, returning the signer's
+/// public key.
+pub fn verify_file(path: &Path, public_key: Option<&str>) -> Result<String> {
+    let sig_path = signature_path(path);
+    let signature: Signature = serde_json::from_slice(
+        &std::fs::read(&sig_path).with_context(|| format!("read {}", sig_path.display()))?,
+    )
+    .with_context(|| format!("parse {}", sig_path.display()))?;
+    let data = std::fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    verify_bytes(&signature, &data, public_key)?;
+    Ok(signature.public_key)
+}
+
+/// This machine's public key, base64, creating the key if needed.
+pub fn public_key() -> Result<String> {
+    Ok(STANDARD.encode(load_or_create_key()?.public_key().as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let data = br#"{"download":{"mbps":512.5}}"#;
+        let signature = sign_bytes(&key, data);
+
+        assert!(verify_bytes(&signature, data, None).is_ok());
+        assert!(verify_bytes(&signature, data, Some(&signature.public_key)).is_ok());
+        // Edited results or another key fail
+        assert!(verify_bytes(&signature, br#"{"download":{"mbps":912.5}}"#, None).is_err());
+        let other = STANDARD.encode([7u8; 32]);
+        assert!(verify_bytes(&signature, data, Some(&other)).is_err());
+    }
+}
@@ -1,30 +1,126 @@
 use crate::model::RunResult;
+use crate::progress::Progress;
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Data directory chosen with `--data-dir`, if any.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// History kept after saving a run, if limited.
+static RETENTION: OnceLock<Retention> = OnceLock::new();
+
+/// Store application data in `dir` instead of the platform's data directory.
+/// Only the first call has an effect.
+pub fn set_data_dir(dir: PathBuf) {
+    DATA_DIR.set(dir).ok();
+}
+
+/// How much run history to keep (`--history-retention`, `--history-max-runs`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    /// Delete runs saved longer ago than this
+    pub max_age: Option<Duration>,
+    /// Keep at most this many runs, the newest
+    pub max_runs: Option<usize>,
+}
+
+impl Retention {
+    pub fn is_limited(&self) -> bool {
+        self.max_age.is_some() || self.max_runs.is_some()
+    }
+}
+
+/// Prune history to `retention` whenever a run is saved. Only the first call
+/// has an effect.
+pub fn set_retention(retention: Retention) {
+    if retention.is_limited() {
+        RETENTION.set(retention).ok();
+    }
+}
 
 /// Get the base directory for storing application data.
 fn base_dir() -> PathBuf {
+    if let Some(dir) = DATA_DIR.get() {
+        return dir.clone();
+    }
     dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("cloudflare-speed-cli")
 }
 
 /// Get the directory for storing test run results.
-fn runs_dir() -> PathBuf {
+pub fn runs_dir() -> PathBuf {
     base_dir().join("runs")
 }
 
+/// Key used to sign exports with `--sign`.
+pub fn signing_key_path() -> PathBuf {
+    base_dir().join("signing-key.pk8")
+}
+
+/// Control socket of a running TUI or monitor mode instance.
+pub fn control_socket_path() -> PathBuf {
+    base_dir().join("control.sock")
+}
+
 /// Ensure the necessary directories exist for storing data.
 pub fn ensure_dirs() -> Result<()> {
     std::fs::create_dir_all(runs_dir()).context("create runs dir")?;
     Ok(())
 }
 
+/// Exclusive lock on the data directory, held while a speed test runs so that
+/// concurrent instances don't skew each other's measurements. Released on drop
+/// (or by the OS if the process dies).
+pub struct RunLock {
+    _file: File,
+}
+
+fn lock_path() -> PathBuf {
+    base_dir().join("run.lock")
+}
+
+/// Take the run lock without blocking. Returns `None` if another instance
+/// holds it.
+pub fn try_lock_run() -> Result<Option<RunLock>> {
+    std::fs::create_dir_all(base_dir()).context("create data dir")?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path())
+        .context("open run lock file")?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(None),
+        Err(TryLockError::Error(e)) => return Err(e).context("lock run lock file"),
+    }
+    // Record our PID so a blocked instance can say who it's waiting for
+    file.set_len(0).context("truncate run lock file")?;
+    write!(file, "{}", std::process::id()).context("write run lock file")?;
+    Ok(Some(RunLock { _file: file }))
+}
+
+/// PID of the instance holding the run lock, if it can be read.
+pub fn run_lock_holder() -> Option<u32> {
+    std::fs::read_to_string(lock_path()).ok()?.trim().parse().ok()
+}
+
 pub fn save_run(result: &RunResult) -> Result<PathBuf> {
     ensure_dirs()?;
     let path = get_run_path(result)?;
     let data = serde_json::to_vec_pretty(result)?;
     std::fs::write(&path, data).context("write run json")?;
+    if let Some(retention) = RETENTION.get() {
+        // The run is saved either way; a file that can't be deleted now is
+        // pruned on a later save
+        prune(retention, false, &mut Progress::hidden()).ok();
+    }
     Ok(path)
 }
 
@@ -34,6 +130,7 @@ pub fn get_run_path(result: &RunResult) -> Result<PathBuf> {
     Ok(runs_dir().join(format!("run-{safe_ts}-{}.json", result.meas_id)))
 }
 
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
 pub fn delete_run(result: &RunResult) -> Result<()> {
     let path = get_run_path(result)?;
     if path.exists() {
@@ -52,127 +149,376 @@ pub fn export_json(path: &Path, result: &RunResult) -> Result<()> {
     Ok(())
 }
 
-pub fn export_csv(path: &Path, result: &RunResult) -> Result<()> {
+/// CSV layout of the first releases, kept for spreadsheets built on it.
+pub const CSV_COLUMNS_V1: &[&str] = &[
+    "timestamp_utc",
+    "base_url",
+    "meas_id",
+    "comments",
+    "server",
+    "download_mbps",
+    "upload_mbps",
+    "idle_mean_ms",
+    "idle_median_ms",
+    "idle_p25_ms",
+    "idle_p75_ms",
+    "idle_loss",
+    "dl_loaded_mean_ms",
+    "dl_loaded_median_ms",
+    "dl_loaded_p25_ms",
+    "dl_loaded_p75_ms",
+    "dl_loaded_loss",
+    "ul_loaded_mean_ms",
+    "ul_loaded_median_ms",
+    "ul_loaded_p25_ms",
+    "ul_loaded_p75_ms",
+    "ul_loaded_loss",
+    "ip",
+    "colo",
+    "asn",
+    "as_org",
+    "interface_name",
+    "network_name",
+    "is_wireless",
+    "interface_mac",
+    "local_ipv4",
+    "local_ipv6",
+    "external_ipv4",
+    "external_ipv6",
+    "dns_resolution_ms",
+    "dns_ipv4_count",
+    "dns_ipv6_count",
+    "dns_servers",
+    "tls_handshake_ms",
+    "tls_protocol",
+    "tls_cipher",
+    "ipv4_download_mbps",
+    "ipv4_upload_mbps",
+    "ipv4_latency_ms",
+    "ipv6_download_mbps",
+    "ipv6_upload_mbps",
+    "ipv6_latency_ms",
+    "traceroute_hops",
+];
+
+/// Columns added in CSV layout v2, appended after the v1 columns.
+const CSV_COLUMNS_V2_ADDED: &[&str] = &[
+    "udp_loss",
+    "udp_jitter_ms",
+    "udp_mos",
+    "udp_quality",
+    "bufferbloat_grade",
+];
+
+/// Columns outside the versioned layouts, named after the Grafana dashboard
+/// fields (see `grafana`).
+const CSV_COLUMNS_EXTRA: &[&str] = &[
+    "idle_latency_ms",
+    "loaded_latency_download_ms",
+    "loaded_latency_upload_ms",
+    "idle_loss_pct",
+    "packet_loss_pct",
+    "bufferbloat_score",
+    "download_duration_s",
+    "upload_duration_s",
+    "concurrency",
+    "download_concurrency",
+    "upload_concurrency",
+    "on_battery",
+    "battery_pct",
+    "cpu_throttled",
+    "max_temperature_c",
+    "invalid",
+    "latency_url",
+    "correlation_id",
+    "provider",
+    "server_id",
+    "quick",
+    "normalized_download_mbps",
+    "normalized_upload_mbps",
+    "source_address",
+];
+
+/// CSV layout used unless `--csv-columns` or `csv_columns` says otherwise.
+pub const CSV_LAYOUT_LATEST: &str = "v2";
+
+/// Names accepted as column presets by `csv_columns`.
+pub const CSV_PRESETS: &[&str] = &["v1", "v2", "minimal", "full", "grafana-csv"];
+
+fn csv_all_columns() -> impl Iterator<Item = &'static str> {
+    CSV_COLUMNS_V1
+        .iter()
+        .chain(CSV_COLUMNS_V2_ADDED)
+        .chain(CSV_COLUMNS_EXTRA)
+        .copied()
+}
+
+/// Columns of a named preset. `v1` and `v2` are frozen layouts; `full` grows
+/// with every new column.
+fn csv_preset(name: &str) -> Option<Vec<&'static str>> {
+    use crate::grafana::{
+        FIELD_BUFFERBLOAT_SCORE, FIELD_DOWNLOAD_MBPS, FIELD_IDLE_LATENCY_MS, FIELD_IDLE_LOSS_PCT,
+        FIELD_LOADED_LATENCY_DOWNLOAD_MS, FIELD_LOADED_LATENCY_UPLOAD_MS, FIELD_PACKET_LOSS_PCT,
+        FIELD_UPLOAD_MBPS,
+    };
+
+    let columns = match name {
+        "v1" => CSV_COLUMNS_V1.to_vec(),
+        "v2" => CSV_COLUMNS_V1
+            .iter()
+            .chain(CSV_COLUMNS_V2_ADDED)
+            .copied()
+            .collect(),
+        "minimal" => vec![
+            "timestamp_utc",
+            "network_name",
+            "download_mbps",
+            "upload_mbps",
+            "idle_median_ms",
+            "idle_loss",
+        ],
+        "full" => csv_all_columns().collect(),
+        "grafana-csv" => vec![
+            "timestamp_utc",
+            FIELD_DOWNLOAD_MBPS,
+            FIELD_UPLOAD_MBPS,
+            FIELD_IDLE_LATENCY_MS,
+            FIELD_LOADED_LATENCY_DOWNLOAD_MS,
+            FIELD_LOADED_LATENCY_UPLOAD_MS,
+            FIELD_IDLE_LOSS_PCT,
+            FIELD_PACKET_LOSS_PCT,
+            FIELD_BUFFERBLOAT_SCORE,
+        ],
+        _ => return None,
+    };
+    Some(columns)
+}
+
+/// A CSV column and the header it's written under.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumn {
+    pub name: &'static str,
+    pub header: String,
+}
+
+/// Resolve a CSV column spec: a comma-separated list of presets (see
+/// `CSV_PRESETS`) and column names. A column can be given another header
+/// with `name=Header`.
+pub fn csv_columns(spec: &str) -> Result<Vec<CsvColumn>> {
+    let mut columns = Vec::new();
+    for item in spec.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        if let Some(preset) = csv_preset(item) {
+            columns.extend(preset.into_iter().map(|name| CsvColumn {
+                name,
+                header: name.to_string(),
+            }));
+            continue;
+        }
+        let (name, header) = match item.split_once('=') {
+            Some((name, header)) => (name.trim(), header.trim()),
+            None => (item, item),
+        };
+        let name = csv_all_columns().find(|c| *c == name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unknown CSV column or preset: {name} (presets: {})",
+                CSV_PRESETS.join(", ")
+            )
+        })?;
+        columns.push(CsvColumn {
+            name,
+            header: header.to_string(),
+        });
+    }
+    if columns.is_empty() {
+        anyhow::bail!("no CSV columns given");
+    }
+    Ok(columns)
+}
+
+/// Value of one export column (see `csv_columns`) for `result`, unescaped.
+pub fn column_value(result: &RunResult, column: &str) -> String {
+    let text = |v: Option<&str>| v.unwrap_or("").to_string();
+    let ms = |v: Option<f64>| format!("{:.3}", v.unwrap_or(f64::NAN));
+    let opt = |v: Option<f64>| v.map(|v| format!("{:.3}", v)).unwrap_or_default();
+    let comparison = result.ip_comparison.as_ref();
+    let ipv4 = comparison
+        .and_then(|c| c.ipv4_result.as_ref())
+        .filter(|r| r.available);
+    let ipv6 = comparison
+        .and_then(|c| c.ipv6_result.as_ref())
+        .filter(|r| r.available);
+    let tls = result.tls.as_ref();
+    let udp = result.packet_loss.as_ref();
+    let config = result.config.as_ref();
+    match column {
+        "timestamp_utc" => result.timestamp_utc.clone(),
+        "base_url" => result.base_url.clone(),
+        "meas_id" => result.meas_id.clone(),
+        "comments" => text(result.comments.as_deref()),
+        "server" => text(result.server.as_deref()),
+        "download_mbps" => format!("{:.3}", result.download.mbps),
+        "upload_mbps" => format!("{:.3}", result.upload.mbps),
+        "idle_mean_ms" => ms(result.idle_latency.mean_ms),
+        "idle_median_ms" => ms(result.idle_latency.median_ms),
+        "idle_p25_ms" => ms(result.idle_latency.p25_ms),
+        "idle_p75_ms" => ms(result.idle_latency.p75_ms),
+        "idle_loss" => format!("{:.6}", result.idle_latency.loss),
+        "dl_loaded_mean_ms" => ms(result.loaded_latency_download.mean_ms),
+        "dl_loaded_median_ms" => ms(result.loaded_latency_download.median_ms),
+        "dl_loaded_p25_ms" => ms(result.loaded_latency_download.p25_ms),
+        "dl_loaded_p75_ms" => ms(result.loaded_latency_download.p75_ms),
+        "dl_loaded_loss" => format!("{:.6}", result.loaded_latency_download.loss),
+        "ul_loaded_mean_ms" => ms(result.loaded_latency_upload.mean_ms),
+        "ul_loaded_median_ms" => ms(result.loaded_latency_upload.median_ms),
+        "ul_loaded_p25_ms" => ms(result.loaded_latency_upload.p25_ms),
+        "ul_loaded_p75_ms" => ms(result.loaded_latency_upload.p75_ms),
+        "ul_loaded_loss" => format!("{:.6}", result.loaded_latency_upload.loss),
+        "ip" => text(result.ip.as_deref()),
+        "colo" => text(result.colo.as_deref()),
+        "asn" => text(result.asn.as_deref()),
+        "as_org" => text(result.as_org.as_deref()),
+        "interface_name" => text(result.interface_name.as_deref()),
+        "network_name" => text(result.network_name.as_deref()),
+        "is_wireless" => result
+            .is_wireless
+            .map(|w| w.to_string())
+            .unwrap_or_default(),
+        "interface_mac" => text(result.interface_mac.as_deref()),
+        "local_ipv4" => text(result.local_ipv4.as_deref()),
+        "local_ipv6" => text(result.local_ipv6.as_deref()),
+        "external_ipv4" => text(result.external_ipv4.as_deref()),
+        "external_ipv6" => text(result.external_ipv6.as_deref()),
+        "dns_resolution_ms" => opt(result.dns.as_ref().map(|d| d.resolution_time_ms)),
+        "dns_ipv4_count" => result
+            .dns
+            .as_ref()
+            .map(|d| d.ipv4_count.to_string())
+            .unwrap_or_default(),
+        "dns_ipv6_count" => result
+            .dns
+            .as_ref()
+            .map(|d| d.ipv6_count.to_string())
+            .unwrap_or_default(),
+        "dns_servers" => result
+            .dns
+            .as_ref()
+            .map(|d| d.dns_servers.join("; "))
+            .unwrap_or_default(),
+        "tls_handshake_ms" => opt(tls.map(|t| t.handshake_time_ms)),
+        "tls_protocol" => text(tls.and_then(|t| t.protocol_version.as_deref())),
+        "tls_cipher" => text(tls.and_then(|t| t.cipher_suite.as_deref())),
+        "ipv4_download_mbps" => opt(ipv4.map(|r| r.download_mbps)),
+        "ipv4_upload_mbps" => opt(ipv4.map(|r| r.upload_mbps)),
+        "ipv4_latency_ms" => opt(ipv4.map(|r| r.latency_ms)),
+        "ipv6_download_mbps" => opt(ipv6.map(|r| r.download_mbps)),
+        "ipv6_upload_mbps" => opt(ipv6.map(|r| r.upload_mbps)),
+        "ipv6_latency_ms" => opt(ipv6.map(|r| r.latency_ms)),
+        "traceroute_hops" => result
+            .traceroute
+            .as_ref()
+            .map(|t| t.hops.len().to_string())
+            .unwrap_or_default(),
+        "udp_loss" => udp
+            .map(|u| format!("{:.6}", u.latency.loss))
+            .unwrap_or_default(),
+        "udp_jitter_ms" => opt(udp.and_then(|u| u.latency.jitter_ms)),
+        "udp_mos" => udp
+            .and_then(|u| u.mos)
+            .map(|m| format!("{:.2}", m))
+            .unwrap_or_default(),
+        "udp_quality" => text(udp.map(|u| u.quality_label.as_str())),
+        "bufferbloat_grade" => result.bufferbloat_grade().unwrap_or("").to_string(),
+        "idle_latency_ms" => opt(result.idle_latency.median_ms),
+        "loaded_latency_download_ms" => opt(result.loaded_latency_download.median_ms),
+        "loaded_latency_upload_ms" => opt(result.loaded_latency_upload.median_ms),
+        "idle_loss_pct" => format!("{:.3}", result.idle_latency.loss * 100.0),
+        "packet_loss_pct" => udp
+            .map(|u| format!("{:.3}", u.latency.loss * 100.0))
+            .unwrap_or_default(),
+        "bufferbloat_score" => result
+            .bufferbloat_score()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        "download_duration_s" => opt(config.map(|c| c.download_duration.as_secs_f64())),
+        "upload_duration_s" => opt(config.map(|c| c.upload_duration.as_secs_f64())),
+        "concurrency" => config.map(|c| c.streams().to_string()).unwrap_or_default(),
+        "download_concurrency" => config
+            .map(|c| c.download_streams().to_string())
+            .unwrap_or_default(),
+        "upload_concurrency" => config
+            .map(|c| c.upload_streams().to_string())
+            .unwrap_or_default(),
+        "on_battery" => result
+            .power
+            .as_ref()
+            .map(|p| p.on_battery.to_string())
+            .unwrap_or_default(),
+        "battery_pct" => result
+            .power
+            .as_ref()
+            .and_then(|p| p.battery_percent)
+            .map(|p| p.to_string())
+            .unwrap_or_default(),
+        "cpu_throttled" => result
+            .thermal
+            .as_ref()
+            .map(|t| t.throttled.to_string())
+            .unwrap_or_default(),
+        "max_temperature_c" => opt(result.thermal.as_ref().and_then(|t| t.max_temperature_c)),
+        "invalid" => text(result.invalid.as_deref()),
+        "latency_url" => text(result.latency_url.as_deref()),
+        "correlation_id" => text(
+            config
+                .and_then(|c| c.correlation_id.as_ref())
+                .map(|c| c.id.as_str()),
+        ),
+        "provider" => text(result.provider.as_deref()),
+        "server_id" => text(result.server_id.as_deref()),
+        "source_address" => text(result.source_address.as_deref()),
+        "quick" => config.map(|c| c.quick.to_string()).unwrap_or_default(),
+        "normalized_download_mbps" => opt(result.normalized.as_ref().map(|n| n.download_mbps)),
+        "normalized_upload_mbps" => opt(result.normalized.as_ref().map(|n| n.upload_mbps)),
+        _ => String::new(),
+    }
+}
+
+/// Write a header row and one row per result with the given columns (see
+/// `csv_columns`).
+pub fn export_csv(path: &Path, results: &[RunResult], columns: &[CsvColumn]) -> Result<()> {
     // Create parent directories if they don't exist
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).context("create export directory")?;
     }
-    let mut out = String::new();
-    // Header row with all fields including diagnostics
-    out.push_str("timestamp_utc,base_url,meas_id,comments,server,download_mbps,upload_mbps,idle_mean_ms,idle_median_ms,idle_p25_ms,idle_p75_ms,idle_loss,dl_loaded_mean_ms,dl_loaded_median_ms,dl_loaded_p25_ms,dl_loaded_p75_ms,dl_loaded_loss,ul_loaded_mean_ms,ul_loaded_median_ms,ul_loaded_p25_ms,ul_loaded_p75_ms,ul_loaded_loss,ip,colo,asn,as_org,interface_name,network_name,is_wireless,interface_mac,local_ipv4,local_ipv6,external_ipv4,external_ipv6,dns_resolution_ms,dns_ipv4_count,dns_ipv6_count,dns_servers,tls_handshake_ms,tls_protocol,tls_cipher,ipv4_download_mbps,ipv4_upload_mbps,ipv4_latency_ms,ipv6_download_mbps,ipv6_upload_mbps,ipv6_latency_ms,traceroute_hops\n");
-
-    // Extract diagnostic values
-    let dns_resolution_ms = result.dns.as_ref().map(|d| d.resolution_time_ms);
-    let dns_ipv4_count = result.dns.as_ref().map(|d| d.ipv4_count);
-    let dns_ipv6_count = result.dns.as_ref().map(|d| d.ipv6_count);
-    let dns_servers = result
-        .dns
-        .as_ref()
-        .map(|d| d.dns_servers.join("; "))
-        .unwrap_or_default();
-    let tls_handshake_ms = result.tls.as_ref().map(|t| t.handshake_time_ms);
-    let tls_protocol = result.tls.as_ref().and_then(|t| t.protocol_version.clone());
-    let tls_cipher = result.tls.as_ref().and_then(|t| t.cipher_suite.clone());
-
-    // IPv4 results
-    let ipv4_download = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv4_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.download_mbps);
-    let ipv4_upload = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv4_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.upload_mbps);
-    let ipv4_latency = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv4_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.latency_ms);
-
-    // IPv6 results
-    let ipv6_download = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv6_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.download_mbps);
-    let ipv6_upload = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv6_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.upload_mbps);
-    let ipv6_latency = result
-        .ip_comparison
-        .as_ref()
-        .and_then(|c| c.ipv6_result.as_ref())
-        .filter(|r| r.available)
-        .map(|r| r.latency_ms);
-
-    // Traceroute hop count
-    let traceroute_hops = result.traceroute.as_ref().map(|t| t.hops.len());
-
-    out.push_str(&format!(
-        "{},{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.6},{:.3},{:.3},{:.3},{:.3},{:.6},{:.3},{:.3},{:.3},{:.3},{:.6},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
-        csv_escape(&result.timestamp_utc),
-        csv_escape(&result.base_url),
-        csv_escape(&result.meas_id),
-        csv_escape(result.comments.as_deref().unwrap_or("")),
-        csv_escape(result.server.as_deref().unwrap_or("")),
-        result.download.mbps,
-        result.upload.mbps,
-        result.idle_latency.mean_ms.unwrap_or(f64::NAN),
-        result.idle_latency.median_ms.unwrap_or(f64::NAN),
-        result.idle_latency.p25_ms.unwrap_or(f64::NAN),
-        result.idle_latency.p75_ms.unwrap_or(f64::NAN),
-        result.idle_latency.loss,
-        result.loaded_latency_download.mean_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_download.median_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_download.p25_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_download.p75_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_download.loss,
-        result.loaded_latency_upload.mean_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_upload.median_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_upload.p25_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_upload.p75_ms.unwrap_or(f64::NAN),
-        result.loaded_latency_upload.loss,
-        csv_escape(result.ip.as_deref().unwrap_or("")),
-        csv_escape(result.colo.as_deref().unwrap_or("")),
-        csv_escape(result.asn.as_deref().unwrap_or("")),
-        csv_escape(result.as_org.as_deref().unwrap_or("")),
-        csv_escape(result.interface_name.as_deref().unwrap_or("")),
-        csv_escape(result.network_name.as_deref().unwrap_or("")),
-        result.is_wireless.map(|w| if w { "true" } else { "false" }).unwrap_or(""),
-        csv_escape(result.interface_mac.as_deref().unwrap_or("")),
-        csv_escape(result.local_ipv4.as_deref().unwrap_or("")),
-        csv_escape(result.local_ipv6.as_deref().unwrap_or("")),
-        csv_escape(result.external_ipv4.as_deref().unwrap_or("")),
-        csv_escape(result.external_ipv6.as_deref().unwrap_or("")),
-        // Diagnostic fields
-        dns_resolution_ms.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        dns_ipv4_count.map(|v| v.to_string()).unwrap_or_default(),
-        dns_ipv6_count.map(|v| v.to_string()).unwrap_or_default(),
-        csv_escape(&dns_servers),
-        tls_handshake_ms.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        csv_escape(tls_protocol.as_deref().unwrap_or("")),
-        csv_escape(tls_cipher.as_deref().unwrap_or("")),
-        ipv4_download.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        ipv4_upload.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        ipv4_latency.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        ipv6_download.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        ipv6_upload.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        ipv6_latency.map(|v| format!("{:.3}", v)).unwrap_or_default(),
-        traceroute_hops.map(|v| v.to_string()).unwrap_or_default(),
-    ));
-    std::fs::write(path, out).context("write export csv")?;
+    std::fs::write(path, csv_text(results, columns)).context("write export csv")?;
     Ok(())
 }
 
+/// The CSV text of `export_csv`.
+pub fn csv_text(results: &[RunResult], columns: &[CsvColumn]) -> String {
+    let header: Vec<String> = columns.iter().map(|c| csv_escape(&c.header)).collect();
+    let mut out = header.join(",");
+    out.push('\n');
+    for result in results {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|c| csv_escape(&column_value(result, c.name)))
+            .collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Export every saved run to `path` as CSV with all columns (the `full`
+/// preset), oldest first, so runs of any age share one layout. Returns the
+/// number of runs.
+pub fn export_all_csv(path: &Path, progress: &mut Progress) -> Result<usize> {
+    let mut runs = load_recent_with(usize::MAX, progress)?;
+    runs.reverse();
+    export_csv(path, &runs, &csv_columns("full")?)?;
+    Ok(runs.len())
+}
+
 /// Escape a string for CSV format (handles commas, quotes, and newlines).
 fn csv_escape(s: &str) -> String {
     if s.contains(',') || s.contains('"') || s.contains('\n') {
@@ -182,11 +528,295 @@ fn csv_escape(s: &str) -> String {
     }
 }
 
+/// Write `result` as an Excel workbook: a formatted "Summary" sheet and a
+/// "Data" sheet with every export column, numbers stored as numbers.
+#[cfg(feature = "xlsx")]
+pub fn export_xlsx(path: &Path, result: &RunResult) -> Result<()> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    let bold = Format::new().set_bold();
+    let decimal = Format::new().set_num_format("0.00");
+    let mut workbook = Workbook::new();
+
+    let text = |v: Option<&str>| v.unwrap_or("-").to_string();
+    let udp = result.packet_loss.as_ref();
+    let numbers: [(&str, Option<f64>, &str); 10] = [
+        ("Download", Some(result.download.mbps), "Mbps"),
+        ("Upload", Some(result.upload.mbps), "Mbps"),
+        ("Idle latency (median)", result.idle_latency.median_ms, "ms"),
+        ("Idle jitter", result.idle_latency.jitter_ms, "ms"),
+        (
+            "Loaded latency, download (median)",
+            result.loaded_latency_download.median_ms,
+            "ms",
+        ),
+        (
+            "Loaded latency, upload (median)",
+            result.loaded_latency_upload.median_ms,
+            "ms",
+        ),
+        ("Idle loss", Some(result.idle_latency.loss * 100.0), "%"),
+        ("UDP packet loss", udp.map(|u| u.latency.loss * 100.0), "%"),
+        ("UDP jitter", udp.and_then(|u| u.latency.jitter_ms), "ms"),
+        ("MOS", udp.and_then(|u| u.mos), ""),
+    ];
+    let about = [
+        ("Time (UTC)", result.timestamp_utc.clone()),
+        ("Server", text(result.server.as_deref())),
+        ("Colo", text(result.colo.as_deref())),
+        ("Network", text(result.network_name.as_deref())),
+        ("Interface", text(result.interface_name.as_deref())),
+    ];
+    let notes = [
+        ("Bufferbloat grade", text(result.bufferbloat_grade())),
+        ("UDP quality", text(udp.map(|u| u.quality_label.as_str()))),
+        ("Comments", text(result.comments.as_deref())),
+    ];
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("Summary")?;
+    summary.write_string_with_format(0, 0, "Metric", &bold)?;
+    summary.write_string_with_format(0, 1, "Value", &bold)?;
+    summary.write_string_with_format(0, 2, "Unit", &bold)?;
+    let mut row = 1;
+    for (label, value) in &about {
+        summary.write_string(row, 0, *label)?;
+        summary.write_string(row, 1, value)?;
+        row += 1;
+    }
+    for (label, value, unit) in numbers {
+        summary.write_string(row, 0, label)?;
+        match value.filter(|v| v.is_finite()) {
+            Some(v) => summary.write_number_with_format(row, 1, v, &decimal)?,
+            None => summary.write_string(row, 1, "-")?,
+        };
+        summary.write_string(row, 2, unit)?;
+        row += 1;
+    }
+    for (label, value) in &notes {
+        summary.write_string(row, 0, *label)?;
+        summary.write_string(row, 1, value)?;
+        row += 1;
+    }
+    summary.set_column_width(0, 34)?;
+    summary.set_column_width(1, 28)?;
+
+    let data = workbook.add_worksheet();
+    data.set_name("Data")?;
+    let columns = csv_columns("full")?;
+    for (col, column) in columns.iter().enumerate() {
+        let col = col as u16;
+        data.write_string_with_format(0, col, &column.header, &bold)?;
+        // Missing values ("" or NaN in CSV) are left blank
+        let value = column_value(result, column.name);
+        match value.parse::<f64>() {
+            Ok(v) if v.is_finite() => data.write_number(1, col, v)?,
+            Ok(_) => data,
+            Err(_) if value.is_empty() => data,
+            Err(_) => data.write_string(1, col, value)?,
+        };
+    }
+    data.set_freeze_panes(1, 0)?;
+    data.autofit();
+
+    workbook
+        .save(path)
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "xlsx"))]
+pub fn export_xlsx(_path: &Path, _result: &RunResult) -> Result<()> {
+    anyhow::bail!("Excel export requires building with the `xlsx` feature")
+}
+
+/// File format for `history export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+    /// JSON for Grafana's JSON API and Infinity data sources, with the
+    /// `grafana-csv` columns
+    GrafanaJson,
+}
+
+impl ExportFormat {
+    /// Format implied by a file extension, defaulting to CSV. A `.json`
+    /// file could mean the runs' own JSON as well as Grafana's, so it needs
+    /// an explicit format.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("parquet") => Ok(ExportFormat::Parquet),
+            Some(ext) if ext.eq_ignore_ascii_case("json") => anyhow::bail!(
+                "give --format grafana-json to export Grafana JSON to {}",
+                path.display()
+            ),
+            _ => Ok(ExportFormat::Csv),
+        }
+    }
+}
+
+/// Value type of an export column in typed formats (Parquet, SQL, Grafana
+/// JSON).
+pub enum ColumnKind {
+    Text,
+    Float,
+    Int,
+    Bool,
+}
+
+pub fn column_kind(column: &str) -> ColumnKind {
+    match column {
+        "timestamp_utc" | "base_url" | "meas_id" | "comments" | "server" | "ip" | "colo"
+        | "asn" | "as_org" | "interface_name" | "network_name" | "interface_mac" | "local_ipv4"
+        | "local_ipv6" | "external_ipv4" | "external_ipv6" | "dns_servers" | "tls_protocol"
+        | "tls_cipher" | "udp_quality" | "bufferbloat_grade" | "invalid" | "latency_url"
+        | "correlation_id" | "provider" | "server_id" | "source_address" => ColumnKind::Text,
+        "dns_ipv4_count"
+        | "dns_ipv6_count"
+        | "traceroute_hops"
+        | "bufferbloat_score"
+        | "concurrency"
+        | "download_concurrency"
+        | "upload_concurrency"
+        | "battery_pct" => ColumnKind::Int,
+        "is_wireless" | "on_battery" | "cpu_throttled" | "quick" => ColumnKind::Bool,
+        _ => ColumnKind::Float,
+    }
+}
+
+/// A column value as a typed JSON value, null when missing.
+fn json_value(value: String, kind: ColumnKind) -> serde_json::Value {
+    use serde_json::Value;
+
+    match kind {
+        _ if value.is_empty() => Value::Null,
+        ColumnKind::Text => Value::String(value),
+        ColumnKind::Float => value
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map_or(Value::Null, Value::from),
+        ColumnKind::Int => value.parse::<i64>().map_or(Value::Null, Value::from),
+        ColumnKind::Bool => value.parse::<bool>().map_or(Value::Null, Value::from),
+    }
+}
+
+/// Results as Grafana's JSON API and Infinity data sources read them: an
+/// array with one object per result, with `time` in milliseconds since the
+/// epoch and the `grafana-csv` columns, named like the dashboard's fields.
+pub fn grafana_json(results: &[RunResult]) -> Result<serde_json::Value> {
+    let columns = csv_columns("grafana-csv")?;
+    let rows = results
+        .iter()
+        .filter_map(|result| {
+            let time = crate::report::unix_timestamp(&result.timestamp_utc)? * 1000;
+            let mut row = serde_json::Map::new();
+            row.insert("time".into(), time.into());
+            for column in &columns {
+                let value = column_value(result, column.name);
+                row.insert(
+                    column.header.clone(),
+                    json_value(value, column_kind(column.name)),
+                );
+            }
+            Some(serde_json::Value::Object(row))
+        })
+        .collect();
+    Ok(serde_json::Value::Array(rows))
+}
+
+/// Write results to `path` as `grafana_json`.
+pub fn export_grafana_json(path: &Path, results: &[RunResult]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    let data = serde_json::to_vec_pretty(&grafana_json(results)?)?;
+    std::fs::write(path, data).context("write export json")
+}
+
+/// Write results to a Parquet file with the given columns, one row per
+/// result. Missing values are stored as nulls.
+#[cfg(feature = "parquet")]
+pub fn export_parquet(path: &Path, results: &[RunResult], columns: &[CsvColumn]) -> Result<()> {
+    use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("create export directory")?;
+    }
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for column in columns {
+        let values: Vec<String> = results
+            .iter()
+            .map(|r| column_value(r, column.name))
+            .collect();
+        let values = values.iter().map(String::as_str);
+        let (data_type, array): (DataType, ArrayRef) = match column_kind(column.name) {
+            ColumnKind::Text => (
+                DataType::Utf8,
+                Arc::new(StringArray::from_iter(
+                    values.map(|v| Some(v).filter(|v| !v.is_empty())),
+                )),
+            ),
+            ColumnKind::Float => (
+                DataType::Float64,
+                Arc::new(Float64Array::from_iter(
+                    values.map(|v| v.parse::<f64>().ok().filter(|v| v.is_finite())),
+                )),
+            ),
+            ColumnKind::Int => (
+                DataType::Int64,
+                Arc::new(Int64Array::from_iter(values.map(|v| v.parse().ok()))),
+            ),
+            ColumnKind::Bool => (
+                DataType::Boolean,
+                Arc::new(BooleanArray::from_iter(values.map(|v| v.parse().ok()))),
+            ),
+        };
+        fields.push(Field::new(column.header.as_str(), data_type, true));
+        arrays.push(array);
+    }
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays).context("build parquet rows")?;
+    let file = std::fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props)).context("write parquet")?;
+    writer.write(&batch).context("write parquet")?;
+    writer.close().context("write parquet")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn export_parquet(_path: &Path, _results: &[RunResult], _columns: &[CsvColumn]) -> Result<()> {
+    anyhow::bail!("Parquet export requires building with the `parquet` feature")
+}
+
 pub fn load_recent(limit: usize) -> Result<Vec<RunResult>> {
+    load_recent_with(limit, &mut Progress::hidden())
+}
+
+/// `load_recent`, reporting each run loaded to `progress`.
+pub fn load_recent_with(limit: usize, progress: &mut Progress) -> Result<Vec<RunResult>> {
     ensure_dirs()?;
-    let dir = runs_dir();
+    load_recent_in(&runs_dir(), limit, progress)
+}
+
+/// The newest `limit` runs saved in `dir`, newest first; `dir` can be a runs
+/// directory copied from another machine.
+pub fn load_recent_in(dir: &Path, limit: usize, progress: &mut Progress) -> Result<Vec<RunResult>> {
     let mut entries: Vec<(std::time::SystemTime, PathBuf)> = Vec::new();
-    for e in std::fs::read_dir(&dir).context("read runs dir")? {
+    for e in std::fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
         let e = e?;
         let p = e.path();
         if p.extension().and_then(|e| e.to_str()) != Some("json") {
@@ -198,9 +828,12 @@ pub fn load_recent(limit: usize) -> Result<Vec<RunResult>> {
     }
     entries.sort_by_key(|(t, _)| *t);
     entries.reverse();
+    entries.truncate(limit);
 
+    progress.set_total(entries.len());
     let mut out = Vec::new();
-    for (_, p) in entries.into_iter().take(limit) {
+    for (_, p) in entries {
+        progress.tick()?;
         let data = std::fs::read(&p).with_context(|| format!("read {}", p.display()))?;
         let r: RunResult =
             serde_json::from_slice(&data).with_context(|| format!("parse {}", p.display()))?;
@@ -208,3 +841,320 @@ pub fn load_recent(limit: usize) -> Result<Vec<RunResult>> {
     }
     Ok(out)
 }
+
+/// Runs of `entries` (modification time and file, newest first) that
+/// `retention` doesn't keep at `now`.
+fn runs_to_prune(
+    entries: &[(std::time::SystemTime, PathBuf)],
+    retention: &Retention,
+    now: std::time::SystemTime,
+) -> Vec<PathBuf> {
+    let cutoff = retention.max_age.and_then(|age| now.checked_sub(age));
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(i, (modified, _))| {
+            retention.max_runs.is_some_and(|max| *i >= max)
+                || cutoff.is_some_and(|cutoff| *modified < cutoff)
+        })
+        .map(|(_, (_, path))| path.clone())
+        .collect()
+}
+
+/// Delete the saved runs `retention` doesn't keep, or with `dry_run` only
+/// count them. Runs are aged by file modification time, like history is
+/// ordered.
+pub fn prune(retention: &Retention, dry_run: bool, progress: &mut Progress) -> Result<usize> {
+    ensure_dirs()?;
+    let mut entries = Vec::new();
+    for e in std::fs::read_dir(runs_dir()).context("read runs dir")? {
+        let e = e?;
+        let p = e.path();
+        if p.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let modified = e.metadata()?.modified().unwrap_or(std::time::UNIX_EPOCH);
+        entries.push((modified, p));
+    }
+    entries.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    let doomed = runs_to_prune(&entries, retention, std::time::SystemTime::now());
+    if !dry_run {
+        progress.set_total(doomed.len());
+        for (deleted, path) in doomed.iter().enumerate() {
+            if let Err(e) = progress.tick() {
+                anyhow::bail!(
+                    "{e}: {deleted} run(s) already deleted, run again to delete the rest"
+                );
+            }
+            std::fs::remove_file(path).with_context(|| format!("delete {}", path.display()))?;
+        }
+    }
+    Ok(doomed.len())
+}
+
+/// File of the saved run with measurement ID `meas_id`.
+fn find_run_path(meas_id: &str) -> Result<PathBuf> {
+    ensure_dirs()?;
+    let suffix = format!("-{meas_id}.json");
+    for e in std::fs::read_dir(runs_dir()).context("read runs dir")? {
+        let p = e?.path();
+        if p.to_string_lossy().ends_with(&suffix) {
+            return Ok(p);
+        }
+    }
+    anyhow::bail!("no saved run with measurement ID {meas_id}")
+}
+
+/// Load the saved run with measurement ID `meas_id`.
+pub fn find_run(meas_id: &str) -> Result<RunResult> {
+    let p = find_run_path(meas_id)?;
+    let data = std::fs::read(&p).with_context(|| format!("read {}", p.display()))?;
+    serde_json::from_slice(&data).with_context(|| format!("parse {}", p.display()))
+}
+
+/// Delete the saved run with measurement ID `meas_id`, returning its file.
+pub fn delete_run_by_id(meas_id: &str) -> Result<PathBuf> {
+    let p = find_run_path(meas_id)?;
+    std::fs::remove_file(&p).with_context(|| format!("delete {}", p.display()))?;
+    Ok(p)
+}
+
+/// Set `network_name` to `to` in every saved run whose network is one of
+/// `from`, returning the number of runs changed. Files are edited as JSON so
+/// fields unknown to this version are kept, and their modification time is
+/// preserved because history is ordered by it.
+pub fn rename_network(
+    from: &[String],
+    to: &str,
+    dry_run: bool,
+    progress: &mut Progress,
+) -> Result<usize> {
+    ensure_dirs()?;
+    let mut paths = Vec::new();
+    for e in std::fs::read_dir(runs_dir()).context("read runs dir")? {
+        let p = e?.path();
+        if p.extension().and_then(|e| e.to_str()) == Some("json") {
+            paths.push(p);
+        }
+    }
+
+    progress.set_total(paths.len());
+    let mut changed = 0;
+    for p in paths {
+        if let Err(e) = progress.tick() {
+            if dry_run {
+                return Err(e);
+            }
+            // Stopping between files leaves none half written
+            anyhow::bail!(
+                "{e}: {changed} run(s) already moved to \"{to}\", run again to move the rest"
+            );
+        }
+        let data = std::fs::read(&p).with_context(|| format!("read {}", p.display()))?;
+        let mut run: serde_json::Value =
+            serde_json::from_slice(&data).with_context(|| format!("parse {}", p.display()))?;
+        let matches = run
+            .get("network_name")
+            .and_then(|v| v.as_str())
+            .is_some_and(|name| name != to && from.iter().any(|f| f == name));
+        if !matches {
+            continue;
+        }
+        changed += 1;
+        if dry_run {
+            continue;
+        }
+
+        run["network_name"] = to.into();
+        let modified = std::fs::metadata(&p)?.modified()?;
+        std::fs::write(&p, serde_json::to_vec_pretty(&run)?)
+            .with_context(|| format!("write {}", p.display()))?;
+        std::fs::File::options()
+            .write(true)
+            .open(&p)
+            .and_then(|f| f.set_modified(modified))
+            .with_context(|| format!("restore modification time of {}", p.display()))?;
+    }
+    Ok(changed)
+}
+
+/// Quick test calibrations per network, written by `calibrate`.
+pub fn calibration_path() -> PathBuf {
+    base_dir().join("calibration.json")
+}
+
+/// Get the path of the monthly data usage ledger.
+fn usage_path() -> PathBuf {
+    base_dir().join("usage.json")
+}
+
+/// Month key (`YYYY-MM`, UTC) used to bucket data usage.
+pub fn current_month_key() -> String {
+    let now = time::OffsetDateTime::now_utc();
+    format!("{:04}-{:02}", now.year(), u8::from(now.month()))
+}
+
+/// Load the data usage ledger: bytes transferred per month, keyed by `YYYY-MM`.
+pub fn load_data_usage() -> Result<BTreeMap<String, u64>> {
+    let path = usage_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = std::fs::read(&path).context("read usage ledger")?;
+    serde_json::from_slice(&data).context("parse usage ledger")
+}
+
+/// Bytes transferred so far in the current month.
+pub fn data_usage_this_month() -> u64 {
+    load_data_usage()
+        .ok()
+        .and_then(|usage| usage.get(&current_month_key()).copied())
+        .unwrap_or(0)
+}
+
+/// Add `bytes` to the current month's usage and return the new monthly total.
+pub fn record_data_usage(bytes: u64) -> Result<u64> {
+    std::fs::create_dir_all(base_dir()).context("create data dir")?;
+    let mut usage = load_data_usage().unwrap_or_default();
+    let total = usage.entry(current_month_key()).or_insert(0);
+    *total = total.saturating_add(bytes);
+    let total = *total;
+    let data = serde_json::to_vec_pretty(&usage)?;
+    std::fs::write(usage_path(), data).context("write usage ledger")?;
+    Ok(total)
+}
+
+/// How long a cached /locations list is used before it's fetched again.
+pub const LOCATIONS_CACHE_TTL: Duration = Duration::from_secs(24 * 3600);
+/// How long cached /meta data is shown while waiting for a run's own.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub const META_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Get the directory for cached Cloudflare responses.
+fn cache_dir() -> PathBuf {
+    base_dir().join("cache")
+}
+
+/// A cached Cloudflare response.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    /// Unix time (seconds) the response was fetched
+    pub fetched_at: u64,
+    pub value: serde_json::Value,
+}
+
+impl CacheEntry {
+    /// Time since the response was fetched.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Load the cached response `name`, if there is one no older than `max_age`
+/// (any age if `None`).
+pub fn read_cache(name: &str, max_age: Option<Duration>) -> Option<CacheEntry> {
+    let data = std::fs::read(cache_dir().join(format!("{name}.json"))).ok()?;
+    let entry: CacheEntry = serde_json::from_slice(&data).ok()?;
+    match max_age {
+        Some(max_age) if entry.age() > max_age => None,
+        _ => Some(entry),
+    }
+}
+
+/// Replace the cached response `name` with a freshly fetched one.
+pub fn write_cache(name: &str, value: &serde_json::Value) -> Result<()> {
+    std::fs::create_dir_all(cache_dir()).context("create cache dir")?;
+    let entry = CacheEntry {
+        fetched_at: unix_now(),
+        value: value.clone(),
+    };
+    let data = serde_json::to_vec(&entry)?;
+    std::fs::write(cache_dir().join(format!("{name}.json")), data)
+        .with_context(|| format!("write {name} cache"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+
+    fn names(spec: &str) -> Vec<&'static str> {
+        csv_columns(spec).unwrap().iter().map(|c| c.name).collect()
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let v1 = names("v1").join(",");
+        assert!(v1.starts_with("timestamp_utc,base_url,meas_id,"));
+        assert!(v1.ends_with(",ipv6_latency_ms,traceroute_hops"));
+        let v2 = names("v2");
+        assert_eq!(&v2[..CSV_COLUMNS_V1.len()], CSV_COLUMNS_V1);
+        assert_eq!(v2.last(), Some(&"bufferbloat_grade"));
+        assert_eq!(
+            names(" download_mbps, udp_mos "),
+            ["download_mbps", "udp_mos"]
+        );
+        assert!(csv_columns("download_mbps,mos").is_err());
+        assert!(csv_columns(",").is_err());
+
+        // Presets only use known columns
+        for preset in CSV_PRESETS {
+            let columns = csv_preset(preset).unwrap();
+            assert!(columns.iter().all(|c| csv_all_columns().any(|a| a == *c)));
+        }
+        let columns = csv_columns("minimal,udp_mos=MOS").unwrap();
+        assert_eq!(columns.len(), 7);
+        assert_eq!(columns[6].name, "udp_mos");
+        assert_eq!(columns[6].header, "MOS");
+    }
+
+    #[test]
+    fn test_grafana_json() {
+        let mut result = test_run(94.0, 40.0);
+        result.timestamp_utc = "2026-10-01T12:00:00Z".to_string();
+        result.idle_latency.median_ms = Some(12.5);
+        let rows = grafana_json(&[result]).unwrap();
+        let row = &rows[0];
+        assert_eq!(row["time"], 1_790_856_000_000i64);
+        assert_eq!(row[crate::grafana::FIELD_DOWNLOAD_MBPS], 94.0);
+        assert_eq!(row[crate::grafana::FIELD_IDLE_LATENCY_MS], 12.5);
+        assert!(row[crate::grafana::FIELD_LOADED_LATENCY_UPLOAD_MS].is_null());
+        assert!(ExportFormat::from_path(Path::new("runs.JSON")).is_err());
+        assert_eq!(
+            ExportFormat::from_path(Path::new("runs.parquet")).unwrap(),
+            ExportFormat::Parquet
+        );
+    }
+
+    #[test]
+    fn test_runs_to_prune() {
+        let now = std::time::SystemTime::now();
+        let day = Duration::from_secs(86400);
+        let entries: Vec<_> = (0..5)
+            .map(|i| (now - day * i, PathBuf::from(format!("run-{i}.json"))))
+            .collect();
+        let pruned = |max_age: Option<u32>, max_runs| {
+            let retention = Retention {
+                max_age: max_age.map(|days| day * days),
+                max_runs,
+            };
+            runs_to_prune(&entries, &retention, now)
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(pruned(None, None).is_empty());
+        assert_eq!(pruned(None, Some(3)), ["run-3.json", "run-4.json"]);
+        assert_eq!(pruned(Some(2), None), ["run-3.json", "run-4.json"]);
+        // Whichever limit is stricter applies
+        assert_eq!(pruned(Some(3), Some(1)).len(), 4);
+    }
+}
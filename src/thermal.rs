@@ -0,0 +1,215 @@
+//! CPU frequency and thermal throttling of the client machine during the
+//! throughput phases (`--thermal`), separating a slow network from a hot
+//! laptop. Linux reads cpufreq, the CPU's throttle counters and the thermal
+//! zones from sysfs; macOS reads the CPU speed limit from `pmset -g therm`,
+//! since `powermetrics` needs root.
+
+use crate::model::ThermalSummary;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Time between readings.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One reading of the CPU's state.
+#[derive(Debug, Default, Clone)]
+struct ThermalSample {
+    /// Current frequency of the fastest core (MHz)
+    cpu_mhz: Option<f64>,
+    /// Rated maximum frequency (MHz)
+    cpu_max_mhz: Option<f64>,
+    /// Hottest thermal zone (°C)
+    temperature_c: Option<f64>,
+    /// Throttle events counted by the CPU since boot
+    throttle_count: Option<u64>,
+    /// CPU speed limit imposed by the OS (percent)
+    speed_limit_pct: Option<u8>,
+}
+
+/// Summarize the readings taken during a test; None when nothing could be
+/// read on this machine.
+fn summarize(samples: &[ThermalSample]) -> Option<ThermalSummary> {
+    let mhz: Vec<f64> = samples.iter().filter_map(|s| s.cpu_mhz).collect();
+    let cpu_mhz = (!mhz.is_empty()).then(|| mhz.iter().sum::<f64>() / mhz.len() as f64);
+    let cpu_max_mhz = samples
+        .iter()
+        .filter_map(|s| s.cpu_max_mhz)
+        .reduce(f64::max);
+    let max_temperature_c = samples
+        .iter()
+        .filter_map(|s| s.temperature_c)
+        .reduce(f64::max);
+    let counts: Vec<u64> = samples.iter().filter_map(|s| s.throttle_count).collect();
+    let throttle_events = match (counts.first(), counts.last()) {
+        (Some(first), Some(last)) => Some(last.saturating_sub(*first)),
+        _ => None,
+    };
+    let cpu_speed_limit_pct = samples.iter().filter_map(|s| s.speed_limit_pct).min();
+    if cpu_mhz.is_none()
+        && max_temperature_c.is_none()
+        && throttle_events.is_none()
+        && cpu_speed_limit_pct.is_none()
+    {
+        return None;
+    }
+    Some(ThermalSummary {
+        cpu_mhz,
+        cpu_max_mhz,
+        max_temperature_c,
+        throttle_events,
+        cpu_speed_limit_pct,
+        throttled: throttle_events.is_some_and(|n| n > 0)
+            || cpu_speed_limit_pct.is_some_and(|p| p < 100),
+    })
+}
+
+/// CPU speed limit from `pmset -g therm` output ("CPU_Speed_Limit = 80");
+/// 100 when no limit is reported.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset_therm(output: &str) -> u8 {
+    output
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "CPU_Speed_Limit").then(|| value.trim().parse().ok())?
+        })
+        .unwrap_or(100)
+}
+
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn sample() -> ThermalSample {
+    use std::path::Path;
+    let read =
+        |path: &Path| -> Option<f64> { std::fs::read_to_string(path).ok()?.trim().parse().ok() };
+    let entries = |dir: &str, prefix: &'static str| {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(move |p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix(prefix))
+                    .is_some_and(|rest| {
+                        !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_digit())
+                    })
+            })
+    };
+
+    let mut sample = ThermalSample::default();
+    for cpu in entries("/sys/devices/system/cpu", "cpu") {
+        // Frequencies are in kHz
+        if let Some(khz) = read(&cpu.join("cpufreq/scaling_cur_freq")) {
+            sample.cpu_mhz = Some(sample.cpu_mhz.unwrap_or(0.0).max(khz / 1000.0));
+        }
+        if let Some(khz) = read(&cpu.join("cpufreq/cpuinfo_max_freq")) {
+            sample.cpu_max_mhz = Some(sample.cpu_max_mhz.unwrap_or(0.0).max(khz / 1000.0));
+        }
+        // Intel CPUs count thermal throttling per core
+        if let Some(count) = read(&cpu.join("thermal_throttle/core_throttle_count")) {
+            *sample.throttle_count.get_or_insert(0) += count as u64;
+        }
+    }
+    // Millidegrees Celsius
+    for zone in entries("/sys/class/thermal", "thermal_zone") {
+        if let Some(temp) = read(&zone.join("temp")).filter(|t| *t > 0.0) {
+            sample.temperature_c =
+                Some(sample.temperature_c.unwrap_or(f64::MIN).max(temp / 1000.0));
+        }
+    }
+    sample
+}
+
+#[cfg(target_os = "macos")]
+fn sample() -> ThermalSample {
+    let limit = std::process::Command::new("pmset")
+        .args(["-g", "therm"])
+        .output()
+        .ok()
+        .map(|output| parse_pmset_therm(&String::from_utf8_lossy(&output.stdout)));
+    ThermalSample {
+        speed_limit_pct: limit,
+        ..Default::default()
+    }
+}
+
+#[cfg(windows)]
+fn sample() -> ThermalSample {
+    ThermalSample::default()
+}
+
+/// Samples the CPU on a background task until `finish` (or until dropped).
+pub struct ThermalMonitor {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<Vec<ThermalSample>>,
+}
+
+impl ThermalMonitor {
+    pub fn start() -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let handle = tokio::spawn(async move {
+            let mut samples = Vec::new();
+            loop {
+                if let Ok(s) = tokio::task::spawn_blocking(sample).await {
+                    samples.push(s);
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(SAMPLE_INTERVAL) => {}
+                    // Stopped, or the monitor was dropped
+                    _ = &mut stopped => break,
+                }
+            }
+            // A last reading so throttling right at the end is counted
+            if let Ok(s) = tokio::task::spawn_blocking(sample).await {
+                samples.push(s);
+            }
+            samples
+        });
+        Self { stop, handle }
+    }
+
+    /// Stop sampling and summarize the readings.
+    pub async fn finish(self) -> Option<ThermalSummary> {
+        let _ = self.stop.send(());
+        summarize(&self.handle.await.ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thermal_summary() {
+        let sample = |mhz, temp, count| ThermalSample {
+            cpu_mhz: Some(mhz),
+            cpu_max_mhz: Some(3400.0),
+            temperature_c: Some(temp),
+            throttle_count: Some(count),
+            speed_limit_pct: None,
+        };
+        let summary = summarize(&[
+            sample(3000.0, 70.0, 12),
+            sample(2000.0, 95.0, 14),
+            sample(1000.0, 90.0, 15),
+        ])
+        .unwrap();
+        assert_eq!(summary.cpu_mhz, Some(2000.0));
+        assert_eq!(summary.cpu_max_mhz, Some(3400.0));
+        assert_eq!(summary.max_temperature_c, Some(95.0));
+        assert_eq!(summary.throttle_events, Some(3));
+        assert!(summary.throttled);
+
+        let cool = summarize(&[sample(3000.0, 60.0, 12), sample(3000.0, 61.0, 12)]).unwrap();
+        assert!(!cool.throttled);
+        assert!(summarize(&[ThermalSample::default()]).is_none());
+
+        assert_eq!(parse_pmset_therm("CPU_Speed_Limit \t= 80\n"), 80);
+        assert_eq!(
+            parse_pmset_therm("Note: No thermal warning level has been recorded\n"),
+            100
+        );
+    }
+}
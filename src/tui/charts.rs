@@ -2,9 +2,13 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Color,
     style::Style,
+    symbols,
     text::{Line, Span},
     widgets::canvas::Line as CanvasLine,
-    widgets::{canvas::Canvas, Bar, BarChart, BarGroup, Block, Borders, Chart, Dataset, Paragraph},
+    widgets::{
+        canvas::Canvas, Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Dataset, GraphType,
+        Paragraph,
+    },
     Frame,
 };
 use std::collections::HashMap;
@@ -122,6 +126,88 @@ pub fn render_box_plot_with_metrics_inside(
     f.render_widget(block, area);
 }
 
+/// Render a latency histogram with the same metrics line and border as
+/// `render_box_plot_with_metrics_inside`. Bars are labelled with the lower
+/// bound of their bucket in ms.
+pub fn render_histogram_with_metrics_inside(
+    f: &mut Frame,
+    area: Rect,
+    samples: &[f64],
+    title: Line,
+    color: Option<Color>,
+    jitter: Option<f64>,
+    loss: Option<f64>,
+) {
+    const BAR_WIDTH: u16 = 3;
+    const MAX_BUCKETS: usize = 12;
+
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chart_metrics = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+        .split(inner);
+
+    let buckets = ((inner.width + 1) / (BAR_WIDTH + 1)).clamp(2, MAX_BUCKETS as u16);
+    let bar_color = color.unwrap_or(Color::Gray);
+    let bars: Vec<Bar> = crate::metrics::histogram(samples, buckets as usize)
+        .into_iter()
+        .map(|(lower, count)| {
+            Bar::default()
+                .value(count)
+                .label(Line::from(format!("{lower:.0}")))
+                .style(Style::default().fg(bar_color))
+                .value_style(Style::default().fg(Color::Black).bg(bar_color))
+        })
+        .collect();
+    f.render_widget(
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(BAR_WIDTH)
+            .bar_gap(1)
+            .label_style(Style::default().fg(Color::Gray)),
+        chart_metrics[0],
+    );
+
+    if let Some(metrics) = crate::metrics::compute_metrics(samples) {
+        let metrics_text = render_metrics_text(metrics, jitter, loss, color);
+        f.render_widget(
+            Paragraph::new(metrics_text).alignment(Alignment::Center),
+            chart_metrics[1],
+        );
+    }
+}
+
+/// Line dataset for a throughput CDF (see `metrics::cdf`).
+pub fn cdf_dataset(points: &[(f64, f64)], color: Color) -> Dataset<'_> {
+    Dataset::default()
+        .graph_type(GraphType::Line)
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(color))
+        .data(points)
+}
+
+/// Axes for a throughput CDF: throughput up to `x_max` on x and the share of
+/// samples at or below it on y, both labelled since the chart has no time axis.
+pub fn cdf_axes(x_max: f64, unit_label: &str) -> (Axis<'static>, Axis<'static>) {
+    let x_max = (x_max * 1.10).max(10.0);
+    let x_axis = Axis::default()
+        .title(unit_label.to_string())
+        .bounds([0.0, x_max])
+        .labels([
+            "0".to_string(),
+            format!("{:.0}", x_max / 2.0),
+            format!("{x_max:.0}"),
+        ]);
+    let y_axis = Axis::default()
+        .title("%")
+        .bounds([0.0, 100.0])
+        .labels(["0", "50", "100"]);
+    (x_axis, y_axis)
+}
+
 /// Helper function to render metrics text (avg, med, p25, p75, and optionally jitter, loss)
 fn render_metrics_text<'a>(
     metrics: (f64, f64, f64, f64),
@@ -217,7 +303,7 @@ pub fn render_chart_with_metrics_inside(
     f.render_widget(block, area);
 }
 
-pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
+pub fn draw_charts(area: Rect, f: &mut Frame, state: &mut UiState) {
     // Assign consistent colors to networks using a HashMap for reliable lookup
     let network_colors = [
         Color::Green,
@@ -240,23 +326,58 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .map(|(idx, name)| (name.as_str(), network_colors[idx % network_colors.len()]))
         .collect();
 
-    // Filter history by selected network
+    // Filter history by selected network, leaving out invalid runs
     let filtered_data: Vec<&RunResult> = state
         .history
         .iter()
+        .filter(|r| r.invalid.is_none())
         .filter(|r| {
             if let Some(ref filter_network) = state.charts_network_filter {
-                r.network_name.as_ref() == Some(filter_network)
+                state.config.network_label(r) == Some(filter_network.as_str())
             } else {
                 true // Show all
             }
         })
         .collect();
 
-    // Layout: header (2 lines + border) + two charts
+    // Runs where the upstream AS changed, annotated on the charts
+    let asn_changes = crate::report::asn_changes(&state.history);
+    let asn_change_of = |r: &RunResult| asn_changes.iter().find(|c| std::ptr::eq(c.run, r));
+    let latest_asn_change = filtered_data
+        .iter()
+        .find_map(|r| asn_change_of(r))
+        .map(|c| {
+            format!(
+                "AS change (▲): {} {} → {}",
+                c.run.timestamp_utc, c.from, c.to
+            )
+        });
+
+    // Calculate how many bars can fit based on available width
+    // Chart width = area.width - Y-axis label width (6) - borders (2)
+    let available_chart_width = area.width.saturating_sub(8) as usize;
+    // Cap at 200 bars max for performance, but allow wider bars on ultra-wide screens
+    let max_bars = available_chart_width.max(1).min(200);
+
+    // Runs shown with a test configuration other than the most common one are
+    // marked, since their results aren't comparable with the others
+    let profiles = crate::report::config_profiles(filtered_data.iter().take(max_bars).copied());
+    let mixed_configs = (profiles.len() > 1).then(|| {
+        format!(
+            "Mixed test configurations (≠): {}",
+            crate::report::describe_profiles(&profiles)
+        )
+    });
+    let other_config = |r: &RunResult| {
+        mixed_configs.is_some()
+            && crate::report::config_profile(r).is_some_and(|p| p != profiles[0].0)
+    };
+
+    // Layout: header (2-4 lines + border) + two charts
+    let header_height = 3 + latest_asn_change.is_some() as u16 + mixed_configs.is_some() as u16;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .constraints([Constraint::Length(header_height), Constraint::Min(0)].as_ref())
         .split(area);
 
     // Header with network filter info
@@ -276,7 +397,7 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         legend_spans.push(Span::styled(network.as_str(), Style::default().fg(color)));
     }
 
-    let header_text = vec![
+    let mut header_text = vec![
         Line::from(vec![
             Span::raw("Filter: "),
             Span::styled(&filter_display, Style::default().fg(Color::Yellow)),
@@ -301,6 +422,18 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         ]),
         Line::from(legend_spans),
     ];
+    if let Some(change) = latest_asn_change {
+        header_text.push(Line::from(Span::styled(
+            change,
+            Style::default().fg(Color::LightRed),
+        )));
+    }
+    if let Some(mixed) = mixed_configs.as_deref() {
+        header_text.push(Line::from(Span::styled(
+            mixed,
+            Style::default().fg(Color::Yellow),
+        )));
+    }
     let header = Paragraph::new(header_text).block(Block::default().borders(Borders::BOTTOM));
     f.render_widget(header, chunks[0]);
 
@@ -310,12 +443,6 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(chunks[1]);
 
-    // Calculate how many bars can fit based on available width
-    // Chart width = chunks[1].width - Y-axis label width (6) - borders (2)
-    let available_chart_width = chunks[1].width.saturating_sub(8) as usize;
-    // Cap at 200 bars max for performance, but allow wider bars on ultra-wide screens
-    let max_bars = available_chart_width.max(1).min(200);
-
     // Prepare data for charts: take only as many as can fit, then reverse so oldest is on left, newest on right
     let data_points: Vec<_> = filtered_data
         .iter()
@@ -332,6 +459,48 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         return;
     }
 
+    if state.charts_weekly {
+        draw_history_weekly(f, &chart_chunks, &filtered_data);
+        return;
+    }
+
+    // Cursor mode: keep the cursor on a shown bar, then apply the zoom
+    let all_bars = data_points.len();
+    state.charts_cursor.index = state.charts_cursor.index.min(all_bars - 1);
+    let (data_points, zoom_offset) = state.charts_cursor.visible(&data_points);
+    let selected = state
+        .charts_cursor
+        .selected(all_bars)
+        .and_then(|i| i.checked_sub(zoom_offset))
+        .filter(|i| *i < data_points.len());
+    let cursor_label = |value: fn(&RunResult) -> f64| -> String {
+        let Some(r) = selected.map(|i| data_points[i]) else {
+            return String::new();
+        };
+        let mark = if state.charts_cursor.mark.is_some() {
+            " [z: zoom to here]"
+        } else {
+            ""
+        };
+        let as_change = asn_change_of(r)
+            .map(|c| format!(" │ {} → {}", c.from, c.to))
+            .unwrap_or_default();
+        let config = crate::report::config_profile(r)
+            .filter(|_| mixed_configs.is_some())
+            .map(|p| format!(" │ {p}"))
+            .unwrap_or_default();
+        format!(
+            " │ {} {:.1} Mbps{config}{as_change}{mark}",
+            r.timestamp_utc,
+            value(r)
+        )
+    };
+
+    if state.throughput_cdf {
+        draw_history_cdf(f, &chart_chunks, data_points, state, &network_color_map);
+        return;
+    }
+
     let num_bars = data_points.len();
 
     // Calculate max values for scaling
@@ -349,12 +518,16 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
     // Compute colors ONCE for all data points (same color for DL and UL of same test)
     let bar_colors: Vec<Color> = data_points
         .iter()
-        .map(|r| {
-            if state.charts_network_filter.is_none() {
+        .enumerate()
+        .map(|(i, r)| {
+            if selected == Some(i) {
+                Color::White
+            } else if state.charts_network_filter.is_none() {
                 // "All Networks" view - color by network
-                r.network_name
-                    .as_ref()
-                    .and_then(|n| network_color_map.get(n.as_str()).copied())
+                state
+                    .config
+                    .network_label(r)
+                    .and_then(|n| network_color_map.get(n).copied())
                     .unwrap_or(Color::Gray) // Fallback for entries with no network name
             } else {
                 // Single network view - use consistent green
@@ -368,9 +541,16 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .iter()
         .enumerate()
         .map(|(i, r)| {
-            Bar::default()
+            let bar = Bar::default()
                 .value(r.download.mbps as u64)
-                .style(Style::default().fg(bar_colors[i]))
+                .style(Style::default().fg(bar_colors[i]));
+            if asn_change_of(r).is_some() {
+                bar.text_value("▲".to_string())
+            } else if other_config(r) {
+                bar.text_value("≠".to_string())
+            } else {
+                bar
+            }
         })
         .collect();
 
@@ -413,7 +593,11 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Download (max {:.0} Mbps)", max_dl)),
+                .title(format!(
+                    "Download (max {:.0} Mbps){}",
+                    max_dl,
+                    cursor_label(|r| r.download.mbps)
+                )),
         )
         .data(BarGroup::default().bars(&dl_bars))
         .bar_width(dl_bar_width)
@@ -427,9 +611,16 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .iter()
         .enumerate()
         .map(|(i, r)| {
-            Bar::default()
+            let bar = Bar::default()
                 .value(r.upload.mbps as u64)
-                .style(Style::default().fg(bar_colors[i]))
+                .style(Style::default().fg(bar_colors[i]));
+            if asn_change_of(r).is_some() {
+                bar.text_value("▲".to_string())
+            } else if other_config(r) {
+                bar.text_value("≠".to_string())
+            } else {
+                bar
+            }
         })
         .collect();
 
@@ -472,7 +663,11 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Upload (max {:.0} Mbps)", max_ul)),
+                .title(format!(
+                    "Upload (max {:.0} Mbps){}",
+                    max_ul,
+                    cursor_label(|r| r.upload.mbps)
+                )),
         )
         .data(BarGroup::default().bars(&ul_bars))
         .bar_width(ul_bar_width)
@@ -481,3 +676,154 @@ pub fn draw_charts(area: Rect, f: &mut Frame, state: &UiState) {
 
     f.render_widget(ul_chart, ul_layout[1]);
 }
+
+/// Charts tab CDF view ('C'): the distribution of per-run download and upload
+/// throughput, one line per network when showing all networks.
+fn draw_history_cdf(
+    f: &mut Frame,
+    areas: &[Rect],
+    runs: &[&&RunResult],
+    state: &UiState,
+    network_color_map: &HashMap<&str, Color>,
+) {
+    let panels = [
+        ("Download", (|r| r.download.mbps) as fn(&RunResult) -> f64),
+        ("Upload", |r| r.upload.mbps),
+    ];
+    for ((name, value), area) in panels.into_iter().zip(areas) {
+        let mut groups: Vec<(Option<&str>, Vec<f64>)> = Vec::new();
+        for r in runs {
+            let network = state.config.network_label(r);
+            match groups.iter_mut().find(|(n, _)| *n == network) {
+                Some((_, values)) => values.push(value(r)),
+                None => groups.push((network, vec![value(r)])),
+            }
+        }
+        let cdfs: Vec<(Color, Vec<(f64, f64)>)> = groups
+            .into_iter()
+            .map(|(network, values)| {
+                let color = if state.charts_network_filter.is_none() {
+                    network
+                        .and_then(|n| network_color_map.get(n).copied())
+                        .unwrap_or(Color::Gray)
+                } else {
+                    Color::Green
+                };
+                (color, crate::metrics::cdf(&values))
+            })
+            .collect();
+        let datasets = cdfs
+            .iter()
+            .map(|(color, points)| cdf_dataset(points, *color))
+            .collect();
+
+        let values: Vec<f64> = runs.iter().map(|r| value(r)).collect();
+        let max = values.iter().fold(0.0_f64, |a, b| a.max(*b));
+        let (x_axis, y_axis) = cdf_axes(max, "Mbps");
+        render_chart_with_metrics_inside(
+            f,
+            *area,
+            datasets,
+            x_axis,
+            y_axis,
+            Line::from(format!("{name} CDF ({} runs)", runs.len())),
+            crate::metrics::compute_metrics(&values),
+            Color::Green,
+        );
+    }
+}
+
+/// Charts tab weekly view ('w'): a box plot of per-run download and upload
+/// throughput for each week, to show how run-to-run variance changes over time.
+fn draw_history_weekly(f: &mut Frame, areas: &[Rect], runs: &[&RunResult]) {
+    // Room for each week's "MM-DD" label
+    const WEEK_WIDTH: u16 = 6;
+
+    let panels = [
+        ("Download", (|r| r.download.mbps) as fn(&RunResult) -> f64),
+        ("Upload", |r| r.upload.mbps),
+    ];
+    for ((name, value), area) in panels.into_iter().zip(areas) {
+        let block = Block::default().borders(Borders::ALL);
+        let inner = block.inner(*area);
+        let chart_labels = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+            .split(inner);
+
+        let weeks = crate::report::by_week(runs.iter().copied(), value);
+        let shown = (inner.width / WEEK_WIDTH).max(1) as usize;
+        let weeks = &weeks[weeks.len().saturating_sub(shown)..];
+        // Scaled to the range of results rather than from zero, so the
+        // spread within each week stays visible
+        let (min, max) = weeks
+            .iter()
+            .flat_map(|(_, values)| values)
+            .fold((f64::INFINITY, 0.0_f64), |(lo, hi), v| {
+                (lo.min(*v), hi.max(*v))
+            });
+        let pad = ((max - min) * 0.05).max(1.0);
+        let title = format!(
+            "{name} by week ({:.0}-{:.0} Mbps, {} weeks)",
+            min.min(max),
+            max,
+            weeks.len()
+        );
+        f.render_widget(block.title(title), *area);
+        if weeks.is_empty() {
+            continue;
+        }
+
+        // (min, q1, median, q3, max) per week, as in the latency box plots
+        let boxes: Vec<[f64; 5]> = weeks
+            .iter()
+            .map(|(_, values)| {
+                let mut sorted = values.clone();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let n = sorted.len();
+                [
+                    sorted[0],
+                    sorted[n / 4],
+                    sorted[n / 2],
+                    sorted[3 * n / 4],
+                    sorted[n - 1],
+                ]
+            })
+            .collect();
+        // Weeks are spread evenly; the canvas is scaled so box i sits in the
+        // middle of label slot i
+        let slot = (chart_labels[1].width as usize / weeks.len()).max(1);
+        let canvas = Canvas::default()
+            .x_bounds([0.0, chart_labels[0].width as f64 / slot as f64])
+            .y_bounds([min - pad, max + pad])
+            .paint(move |ctx| {
+                for (i, [low, q1, med, q3, high]) in boxes.iter().copied().enumerate() {
+                    let x = i as f64 + 0.5;
+                    let (left, right) = (x - 0.3, x + 0.3);
+                    // Box (Q1 to Q3)
+                    draw_line(ctx, left, q1, right, q1, Color::White);
+                    draw_line(ctx, left, q3, right, q3, Color::White);
+                    draw_line(ctx, left, q1, left, q3, Color::White);
+                    draw_line(ctx, right, q1, right, q3, Color::White);
+                    // Median
+                    draw_line(ctx, left, med, right, med, Color::Yellow);
+                    // Whiskers
+                    draw_line(ctx, x, low, x, q1, Color::White);
+                    draw_line(ctx, x, q3, x, high, Color::White);
+                }
+            });
+        f.render_widget(canvas, chart_labels[0]);
+
+        let labels: String = weeks
+            .iter()
+            .map(|(week, _)| {
+                let label = format!("{:02}-{:02}", u8::from(week.month()), week.day());
+                format!("{label:^slot$}")
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(labels).style(Style::default().fg(Color::Gray)),
+            chart_labels[1],
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use crossterm::event::KeyCode;
+
+/// Crosshair and zoom state for a chart. Positions are indices into the
+/// chart's samples (dashboard) or bars (Charts tab).
+#[derive(Debug, Default, Clone)]
+pub struct ChartCursor {
+    pub active: bool,
+    pub index: usize,
+    /// Start of a zoom region being selected with `z`
+    pub mark: Option<usize>,
+    /// Zoomed range of indices (inclusive)
+    pub zoom: Option<(usize, usize)>,
+}
+
+impl ChartCursor {
+    /// Handle a key press; `len` is the number of samples in the chart.
+    /// Returns false if the key isn't used by the cursor.
+    pub fn handle_key(&mut self, code: KeyCode, len: usize) -> bool {
+        match code {
+            KeyCode::Char('x') => {
+                if self.active {
+                    *self = Self::default();
+                } else if len > 0 {
+                    self.active = true;
+                    self.index = len - 1;
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') if self.active => self.move_by(-1, len),
+            KeyCode::Right | KeyCode::Char('l') if self.active => self.move_by(1, len),
+            KeyCode::Char('z') if self.active => {
+                if self.zoom.is_some() {
+                    self.zoom = None;
+                } else if let Some(mark) = self.mark.take() {
+                    let (start, end) = (mark.min(self.index), mark.max(self.index));
+                    // A single-sample region isn't worth zooming into
+                    if end > start {
+                        self.zoom = Some((start, end));
+                    }
+                } else {
+                    self.mark = Some(self.index);
+                }
+            }
+            KeyCode::Esc if self.active => *self = Self::default(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn move_by(&mut self, delta: isize, len: usize) {
+        let (lo, hi) = self.zoom.unwrap_or((0, len.saturating_sub(1)));
+        self.index = self.index.saturating_add_signed(delta).clamp(lo, hi.max(lo));
+    }
+
+    /// The part of `points` visible under the current zoom, and its offset.
+    pub fn visible<'a, T>(&self, points: &'a [T]) -> (&'a [T], usize) {
+        match self.zoom {
+            Some((start, end)) if start < points.len() => {
+                (&points[start..=end.min(points.len() - 1)], start)
+            }
+            _ => (points, 0),
+        }
+    }
+
+    /// Index of the sample under the cursor, if the cursor is shown.
+    pub fn selected(&self, len: usize) -> Option<usize> {
+        (self.active && len > 0).then(|| self.index.min(len - 1))
+    }
+}
@@ -9,13 +9,175 @@ use ratatui::{
 };
 
 use super::charts;
+use super::settings::throughput_colors;
 use super::state::{push_wrapped_status_kv, UiState};
+use crate::config::{ThroughputUnit, UiConfig};
+
+/// Number of recent UDP probe RTTs in the packet loss row sparkline.
+const UDP_SPARKLINE_WIDTH: u16 = 24;
 
 /// Helper function to get the maximum y value from a series of points
 pub fn max_y(points: &[(f64, f64)]) -> f64 {
     points.iter().map(|(_, y)| *y).fold(0.0, |a, b| a.max(b))
 }
 
+/// Helper function to get the maximum x value from a series of points
+fn max_x(points: &[(f64, f64)]) -> f64 {
+    points.iter().map(|(x, _)| *x).fold(0.0, |a, b| a.max(b))
+}
+
+/// Throughput samples as a CDF in `unit`, for the CDF view ('C').
+fn cdf_points(points: &[(f64, f64)], unit: ThroughputUnit) -> Vec<(f64, f64)> {
+    let values: Vec<f64> = points.iter().map(|(_, y)| unit.convert_mbps(*y)).collect();
+    crate::metrics::cdf(&values)
+}
+
+/// Throughput points as shown on the dashboard: smoothed with a trailing
+/// moving average and converted to the configured unit.
+fn display_points(points: &[(f64, f64)], ui: &UiConfig) -> Vec<(f64, f64)> {
+    let window = ui.chart_smoothing.max(1);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, (x, _))| {
+            let recent = &points[(i + 1).saturating_sub(window)..=i];
+            let avg = recent.iter().map(|(_, y)| y).sum::<f64>() / recent.len() as f64;
+            (*x, ui.units.convert_mbps(avg))
+        })
+        .collect()
+}
+
+/// Convert throughput metrics (mean, median, p25, p75) from Mbps to `unit`.
+fn metrics_in_unit(
+    metrics: Option<(f64, f64, f64, f64)>,
+    unit: ThroughputUnit,
+) -> Option<(f64, f64, f64, f64)> {
+    metrics.map(|(mean, median, p25, p75)| {
+        (
+            unit.convert_mbps(mean),
+            unit.convert_mbps(median),
+            unit.convert_mbps(p25),
+            unit.convert_mbps(p75),
+        )
+    })
+}
+
+/// Red when `value` is on the wrong side of a configured threshold.
+fn threshold_color(color: Color, violated: bool) -> Color {
+    if violated {
+        Color::Red
+    } else {
+        color
+    }
+}
+
+/// Title of the Network Information panel, noting a lookup in progress.
+fn network_info_title(state: &UiState) -> &'static str {
+    if state.network_info_loading {
+        "Network Information (detecting…)"
+    } else {
+        "Network Information"
+    }
+}
+
+/// "Wired", "Wireless" or the cellular technology, e.g. "Cellular LTE".
+fn connection_kind(state: &UiState) -> String {
+    let technology = state.cellular.as_ref().and_then(|c| c.technology.as_ref());
+    match (technology, state.is_wireless) {
+        (Some(kind), _) => format!("Cellular {kind}"),
+        (None, Some(true)) => "Wireless".to_string(),
+        (None, _) => "Wired".to_string(),
+    }
+}
+
+/// " limited by 1 Gbps NIC" when `avg_mbps` reaches the interface's link speed.
+fn link_limit_span(state: &UiState, avg_mbps: f64) -> Option<Span<'static>> {
+    let link = state
+        .link_speed_mbps
+        .filter(|&link| crate::network::saturates_link(avg_mbps, link))?;
+    let speed = crate::network::format_link_speed(link);
+    Some(Span::styled(
+        format!(" limited by {speed} NIC"),
+        Style::default().fg(Color::Yellow),
+    ))
+}
+
+/// Color for a loaded latency sample, by its bufferbloat grade relative to idle.
+fn latency_heat_color(idle_ms: f64, loaded_ms: f64) -> Color {
+    match crate::metrics::bufferbloat_score(idle_ms, loaded_ms) {
+        4..=5 => Color::Green,
+        3 => Color::Yellow,
+        2 => Color::LightRed,
+        _ => Color::Red,
+    }
+}
+
+/// One-row strip colored by the worst loaded latency in each column of the
+/// chart's time range, so latency spikes line up with throughput changes.
+fn latency_heat_strip(
+    lat_points: &[(f64, f64)],
+    (x_min, x_max): (f64, f64),
+    idle_ms: f64,
+    width: usize,
+) -> Line<'static> {
+    let label = "latency ";
+    let cells = width.saturating_sub(label.len());
+    let secs_per_cell = (x_max - x_min).max(f64::EPSILON) / cells.max(1) as f64;
+    let mut spans = vec![Span::styled(label, Style::default().fg(Color::Gray))];
+    let mut last = None;
+    for col in 0..cells {
+        let start = x_min + secs_per_cell * col as f64;
+        let end = start + secs_per_cell;
+        let worst = lat_points
+            .iter()
+            .filter(|(t, _)| *t >= start && (*t < end || col + 1 == cells))
+            .map(|(_, ms)| *ms)
+            .reduce(f64::max);
+        // Carry the last sample over columns without one
+        last = worst.or(last);
+        spans.push(match last {
+            Some(ms) => Span::styled("▀", Style::default().fg(latency_heat_color(idle_ms, ms))),
+            None => Span::raw(" "),
+        });
+    }
+    Line::from(spans)
+}
+
+/// Crosshair line and title label for the dashboard cursor on one throughput
+/// chart. `shown` are the displayed points, starting at index `offset` of `all`.
+fn cursor_overlay(
+    state: &UiState,
+    all: &[(f64, f64)],
+    shown: &[(f64, f64)],
+    offset: usize,
+    y_max: f64,
+    unit: ThroughputUnit,
+) -> Option<(Vec<(f64, f64)>, Span<'static>)> {
+    let cursor = &state.dashboard_cursor;
+    let (x, y) = *shown.get(cursor.selected(all.len())?.checked_sub(offset)?)?;
+    let phase_start = all.first().map(|(x, _)| *x).unwrap_or(x);
+    let mark = if cursor.mark.is_some() {
+        " [z: zoom to here]"
+    } else {
+        ""
+    };
+    Some((
+        vec![(x, 0.0), (x, y_max)],
+        Span::styled(
+            format!(" │ {:.1}s: {:.0} {}{mark}", x - phase_start, y, unit.label()),
+            Style::default().fg(Color::Yellow),
+        ),
+    ))
+}
+
+fn crosshair_dataset(line: &[(f64, f64)]) -> Dataset<'_> {
+    Dataset::default()
+        .graph_type(GraphType::Line)
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(Color::White))
+        .data(line)
+}
+
 fn udp_split_bar(sent: u64, received: u64, width: usize) -> Line<'static> {
     let safe_sent = sent.max(1);
     let safe_received = received.min(safe_sent);
@@ -63,7 +225,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(13), // Throughput charts row with metrics (side-by-side)
+                Constraint::Length(14), // Throughput charts row with metrics and latency strip (side-by-side)
                 Constraint::Length(10), // Latency box plots with metrics below (idle + loaded DL + loaded UL)
                 Constraint::Length(3),  // Packet loss (UDP) row
                 Constraint::Min(0),     // Network Information + Keyboard Shortcuts (side-by-side)
@@ -78,22 +240,72 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(main[0]);
+    // Each side: chart on top, loaded latency heat strip below
+    let split_strip = |area: Rect| {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(area)
+    };
+    let dl_area = split_strip(thr_row[0]);
+    let ul_area = split_strip(thr_row[1]);
+    let idle_median_ms = crate::metrics::compute_metrics(&state.idle_latency_samples)
+        .map(|(_, median, _, _)| median)
+        .unwrap_or(0.0);
+
+    let ui = &state.config.ui;
+    let unit = ui.units;
+    let thresholds = state.config.thresholds_on(
+        state.network_name.as_deref(),
+        state.interface_mac.as_deref(),
+        state.interface_name.as_deref(),
+    );
+    let (dl_color, ul_color) = throughput_colors(ui.theme);
 
     // Download throughput chart (left) - only show when download phase has data
     if state.dl_phase_start.is_some() && !state.dl_points.is_empty() {
         // Calculate x bounds only for download points
-        let dl_x_max = state.dl_points.last().map(|(x, _)| *x).unwrap_or(0.0);
-        let dl_x_min = state.dl_points.first().map(|(x, _)| *x).unwrap_or(0.0);
+        let (dl_visible, dl_offset) = state.dashboard_cursor.visible(&state.dl_points);
+        let dl_x_max = dl_visible.last().map(|(x, _)| *x).unwrap_or(0.0);
+        let dl_x_min = dl_visible.first().map(|(x, _)| *x).unwrap_or(0.0);
 
-        let y_dl_max = max_y(&state.dl_points).max(10.0);
+        let dl_points = display_points(dl_visible, ui);
+        let y_dl_max = max_y(&dl_points).max(10.0);
         let y_dl_max = (y_dl_max * 1.10).min(10_000.0);
 
         // Use all download points (they're already filtered to download phase)
         let dl_ds = Dataset::default()
             .graph_type(GraphType::Line)
             .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Green))
-            .data(&state.dl_points);
+            .style(Style::default().fg(dl_color))
+            .data(&dl_points);
+        let (dl_crosshair, dl_cursor_label) = cursor_overlay(
+            state,
+            &state.dl_points,
+            &dl_points,
+            dl_offset,
+            y_dl_max,
+            unit,
+        )
+        .unzip();
+        let mut dl_datasets = vec![dl_ds];
+        if let Some(line) = &dl_crosshair {
+            dl_datasets.push(crosshair_dataset(line));
+        }
+        let dl_cdf = if state.throughput_cdf {
+            cdf_points(dl_visible, unit)
+        } else {
+            Vec::new()
+        };
+        let (dl_x_axis, dl_y_axis) = if state.throughput_cdf {
+            dl_datasets = vec![charts::cdf_dataset(&dl_cdf, dl_color)];
+            charts::cdf_axes(max_x(&dl_cdf), unit.label())
+        } else {
+            (
+                Axis::default().bounds([dl_x_min, dl_x_max.max(1.0)]),
+                Axis::default().title(unit.label()).bounds([0.0, y_dl_max]),
+            )
+        };
 
         let dl_values: Vec<f64> = state.dl_points.iter().map(|(_, y)| *y).collect();
         let dl_metrics = crate::metrics::compute_metrics(&dl_values);
@@ -101,26 +313,48 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         let dl_avg = dl_metrics
             .map(|(mean, _, _, _)| mean)
             .unwrap_or(state.dl_avg_mbps);
-        let dl_title = Line::from(vec![
+        let dl_avg_color = threshold_color(
+            dl_color,
+            thresholds.min_download_mbps.is_some_and(|min| dl_avg < min),
+        );
+        let mut dl_title = Line::from(vec![
             Span::raw("Download (inst "),
             Span::styled(
-                format!("{:.0}", state.dl_mbps),
-                Style::default().fg(Color::Green),
+                format!("{:.0}", unit.convert_mbps(state.dl_mbps)),
+                Style::default().fg(dl_color),
             ),
             Span::raw(" / avg "),
-            Span::styled(format!("{:.0}", dl_avg), Style::default().fg(Color::Green)),
-            Span::raw(" Mbps)"),
+            Span::styled(
+                format!("{:.0}", unit.convert_mbps(dl_avg)),
+                Style::default().fg(dl_avg_color),
+            ),
+            Span::raw(format!(" {})", unit.label())),
         ]);
+        if let Some(label) = dl_cursor_label {
+            dl_title.push_span(label);
+        }
+        if let Some(span) = link_limit_span(state, dl_avg) {
+            dl_title.push_span(span);
+        }
         charts::render_chart_with_metrics_inside(
             f,
-            thr_row[0],
-            vec![dl_ds],
-            Axis::default().bounds([dl_x_min, dl_x_max.max(1.0)]),
-            Axis::default().title("Mbps").bounds([0.0, y_dl_max]),
+            dl_area[0],
+            dl_datasets,
+            dl_x_axis,
+            dl_y_axis,
             dl_title,
-            dl_metrics,
-            Color::Green,
+            metrics_in_unit(dl_metrics, unit),
+            dl_color,
         );
+        if !state.loaded_dl_lat_points.is_empty() {
+            let strip = latency_heat_strip(
+                &state.loaded_dl_lat_points,
+                (dl_x_min, dl_x_max.max(1.0)),
+                idle_median_ms,
+                dl_area[1].width as usize,
+            );
+            f.render_widget(Paragraph::new(strip), dl_area[1]);
+        }
     } else {
         // Show empty placeholder when download hasn't started
         let empty_chart = Paragraph::new("Waiting for download phase...").block(
@@ -129,35 +363,64 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
                 .title(Line::from(vec![
                     Span::raw("Download (inst "),
                     Span::styled(
-                        format!("{:.0}", state.dl_mbps),
-                        Style::default().fg(Color::Green),
+                        format!("{:.0}", unit.convert_mbps(state.dl_mbps)),
+                        Style::default().fg(dl_color),
                     ),
                     Span::raw(" / avg "),
                     Span::styled(
-                        format!("{:.0}", state.dl_avg_mbps),
-                        Style::default().fg(Color::Green),
+                        format!("{:.0}", unit.convert_mbps(state.dl_avg_mbps)),
+                        Style::default().fg(dl_color),
                     ),
-                    Span::raw(" Mbps)"),
+                    Span::raw(format!(" {})", unit.label())),
                 ])),
         );
-        f.render_widget(empty_chart, thr_row[0]);
+        f.render_widget(empty_chart, dl_area[0]);
     }
 
     // Upload throughput chart (right) - only show when upload phase has data
     if state.ul_phase_start.is_some() && !state.ul_points.is_empty() {
         // Calculate x bounds only for upload points
-        let ul_x_max = state.ul_points.last().map(|(x, _)| *x).unwrap_or(0.0);
-        let ul_x_min = state.ul_points.first().map(|(x, _)| *x).unwrap_or(0.0);
+        let (ul_visible, ul_offset) = state.dashboard_cursor.visible(&state.ul_points);
+        let ul_x_max = ul_visible.last().map(|(x, _)| *x).unwrap_or(0.0);
+        let ul_x_min = ul_visible.first().map(|(x, _)| *x).unwrap_or(0.0);
 
-        let y_ul_max = max_y(&state.ul_points).max(10.0);
+        let ul_points = display_points(ul_visible, ui);
+        let y_ul_max = max_y(&ul_points).max(10.0);
         let y_ul_max = (y_ul_max * 1.10).min(10_000.0);
 
         // Use all upload points (they're already filtered to upload phase)
         let ul_ds = Dataset::default()
             .graph_type(GraphType::Line)
             .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Cyan))
-            .data(&state.ul_points);
+            .style(Style::default().fg(ul_color))
+            .data(&ul_points);
+        let (ul_crosshair, ul_cursor_label) = cursor_overlay(
+            state,
+            &state.ul_points,
+            &ul_points,
+            ul_offset,
+            y_ul_max,
+            unit,
+        )
+        .unzip();
+        let mut ul_datasets = vec![ul_ds];
+        if let Some(line) = &ul_crosshair {
+            ul_datasets.push(crosshair_dataset(line));
+        }
+        let ul_cdf = if state.throughput_cdf {
+            cdf_points(ul_visible, unit)
+        } else {
+            Vec::new()
+        };
+        let (ul_x_axis, ul_y_axis) = if state.throughput_cdf {
+            ul_datasets = vec![charts::cdf_dataset(&ul_cdf, ul_color)];
+            charts::cdf_axes(max_x(&ul_cdf), unit.label())
+        } else {
+            (
+                Axis::default().bounds([ul_x_min, ul_x_max.max(1.0)]),
+                Axis::default().title(unit.label()).bounds([0.0, y_ul_max]),
+            )
+        };
 
         let ul_values: Vec<f64> = state.ul_points.iter().map(|(_, y)| *y).collect();
         let ul_metrics = crate::metrics::compute_metrics(&ul_values);
@@ -165,26 +428,48 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         let ul_avg = ul_metrics
             .map(|(mean, _, _, _)| mean)
             .unwrap_or(state.ul_avg_mbps);
-        let ul_title = Line::from(vec![
+        let ul_avg_color = threshold_color(
+            ul_color,
+            thresholds.min_upload_mbps.is_some_and(|min| ul_avg < min),
+        );
+        let mut ul_title = Line::from(vec![
             Span::raw("Upload (inst "),
             Span::styled(
-                format!("{:.0}", state.ul_mbps),
-                Style::default().fg(Color::Cyan),
+                format!("{:.0}", unit.convert_mbps(state.ul_mbps)),
+                Style::default().fg(ul_color),
             ),
             Span::raw(" / avg "),
-            Span::styled(format!("{:.0}", ul_avg), Style::default().fg(Color::Cyan)),
-            Span::raw(" Mbps)"),
+            Span::styled(
+                format!("{:.0}", unit.convert_mbps(ul_avg)),
+                Style::default().fg(ul_avg_color),
+            ),
+            Span::raw(format!(" {})", unit.label())),
         ]);
+        if let Some(label) = ul_cursor_label {
+            ul_title.push_span(label);
+        }
+        if let Some(span) = link_limit_span(state, ul_avg) {
+            ul_title.push_span(span);
+        }
         charts::render_chart_with_metrics_inside(
             f,
-            thr_row[1],
-            vec![ul_ds],
-            Axis::default().bounds([ul_x_min, ul_x_max.max(1.0)]),
-            Axis::default().title("Mbps").bounds([0.0, y_ul_max]),
+            ul_area[0],
+            ul_datasets,
+            ul_x_axis,
+            ul_y_axis,
             ul_title,
-            ul_metrics,
-            Color::Cyan,
+            metrics_in_unit(ul_metrics, unit),
+            ul_color,
         );
+        if !state.loaded_ul_lat_points.is_empty() {
+            let strip = latency_heat_strip(
+                &state.loaded_ul_lat_points,
+                (ul_x_min, ul_x_max.max(1.0)),
+                idle_median_ms,
+                ul_area[1].width as usize,
+            );
+            f.render_widget(Paragraph::new(strip), ul_area[1]);
+        }
     } else {
         // Show empty placeholder when upload hasn't started
         let empty_chart = Paragraph::new("Waiting for upload phase...").block(
@@ -193,21 +478,26 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
                 .title(Line::from(vec![
                     Span::raw("Upload (inst "),
                     Span::styled(
-                        format!("{:.0}", state.ul_mbps),
-                        Style::default().fg(Color::Cyan),
+                        format!("{:.0}", unit.convert_mbps(state.ul_mbps)),
+                        Style::default().fg(ul_color),
                     ),
                     Span::raw(" / avg "),
                     Span::styled(
-                        format!("{:.0}", state.ul_avg_mbps),
-                        Style::default().fg(Color::Cyan),
+                        format!("{:.0}", unit.convert_mbps(state.ul_avg_mbps)),
+                        Style::default().fg(ul_color),
                     ),
-                    Span::raw(" Mbps)"),
+                    Span::raw(format!(" {})", unit.label())),
                 ])),
         );
-        f.render_widget(empty_chart, thr_row[1]);
+        f.render_widget(empty_chart, ul_area[0]);
     }
 
-    // Latency box plots: Idle, Loaded DL, Loaded UL
+    // Latency box plots (or histograms, 'g' toggles): Idle, Loaded DL, Loaded UL
+    let render_latency = if state.latency_histogram {
+        charts::render_histogram_with_metrics_inside
+    } else {
+        charts::render_box_plot_with_metrics_inside
+    };
     let lat_row = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -228,7 +518,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
             .unwrap_or(f64::NAN);
         let jitter = crate::metrics::compute_jitter(&state.idle_latency_samples);
         let title = Line::from(format!("Idle Latency ({:.0}ms)", median));
-        charts::render_box_plot_with_metrics_inside(
+        render_latency(
             f,
             lat_row[0],
             &state.idle_latency_samples,
@@ -254,11 +544,14 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
             Span::raw("Latency Download ("),
             Span::styled(
                 format!("{:.0}ms", median),
-                Style::default().fg(Color::Green),
+                Style::default().fg(threshold_color(
+                    Color::Green,
+                    thresholds.max_loaded_latency_ms.is_some_and(|max| median > max),
+                )),
             ),
             Span::raw(")"),
         ]);
-        charts::render_box_plot_with_metrics_inside(
+        render_latency(
             f,
             lat_row[1],
             &state.loaded_dl_latency_samples,
@@ -285,10 +578,16 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         let jitter = crate::metrics::compute_jitter(&state.loaded_ul_latency_samples);
         let title = Line::from(vec![
             Span::raw("Latency Upload ("),
-            Span::styled(format!("{:.0}ms", median), Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("{:.0}ms", median),
+                Style::default().fg(threshold_color(
+                    Color::Cyan,
+                    thresholds.max_loaded_latency_ms.is_some_and(|max| median > max),
+                )),
+            ),
             Span::raw(")"),
         ]);
-        charts::render_box_plot_with_metrics_inside(
+        render_latency(
             f,
             lat_row[2],
             &state.loaded_ul_latency_samples,
@@ -317,7 +616,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
     } else if let Some(exp) = state
         .last_result
         .as_ref()
-        .and_then(|r| r.experimental_udp.as_ref())
+        .and_then(|r| r.packet_loss.as_ref())
     {
         (
             exp.latency.sent,
@@ -346,6 +645,36 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
     let udp_inner = udp_block.inner(main[2]);
     f.render_widget(udp_block, main[2]);
 
+    // RTT sparkline of the probes answered so far, on the right of the row
+    let udp_inner = if state.udp_rtt_series.is_empty() {
+        udp_inner
+    } else {
+        let width = UDP_SPARKLINE_WIDTH + 6; // "  rtt " label
+        let udp_row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(width)].as_ref())
+            .split(udp_inner);
+        let spark_row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(6), Constraint::Min(0)].as_ref())
+            .split(udp_row[1]);
+        let skip = state
+            .udp_rtt_series
+            .len()
+            .saturating_sub(UDP_SPARKLINE_WIDTH as usize);
+        f.render_widget(
+            Paragraph::new(Span::styled("  rtt ", Style::default().fg(Color::Gray))),
+            spark_row[0],
+        );
+        f.render_widget(
+            Sparkline::default()
+                .data(&state.udp_rtt_series[skip..])
+                .style(Style::default().fg(Color::Magenta)),
+            spark_row[1],
+        );
+        udp_row[0]
+    };
+
     if let Some(err) = state
         .last_result
         .as_ref()
@@ -372,7 +701,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         let (quality_label, mos_str, jitter_str, reorder_str) = state
             .last_result
             .as_ref()
-            .and_then(|r| r.experimental_udp.as_ref())
+            .and_then(|r| r.packet_loss.as_ref())
             .map(|exp| {
                 let label = exp.quality_label.as_str();
                 let mos = exp.mos.map(|m| format!("MOS {:.1}", m)).unwrap_or_default();
@@ -521,19 +850,19 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
             Span::styled("Interface: ", Style::default().fg(Color::Gray)),
             Span::raw(state.interface_name.as_deref().unwrap_or("-")),
             Span::raw(" ("),
-            Span::raw(if state.is_wireless.unwrap_or(false) {
-                "Wireless"
-            } else {
-                "Wired"
-            }),
+            Span::raw(connection_kind(state)),
             Span::raw(")"),
         ]),
         Line::from(vec![
             Span::styled("Network: ", Style::default().fg(Color::Gray)),
             Span::raw(
                 state
-                    .network_name
-                    .as_deref()
+                    .config
+                    .network_label_for(
+                        state.network_name.as_deref(),
+                        state.interface_mac.as_deref(),
+                        state.interface_name.as_deref(),
+                    )
                     .or_else(|| state.interface_name.as_deref())
                     .unwrap_or("-"),
             ),
@@ -544,6 +873,20 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         ]),
     ];
 
+    if let Some(ref cellular) = state.cellular {
+        network_lines.push(Line::from(vec![
+            Span::styled("Cellular: ", Style::default().fg(Color::Gray)),
+            Span::raw(cellular.describe()),
+        ]));
+    }
+
+    if let Some(ref warning) = state.route_warning {
+        network_lines.push(Line::from(Span::styled(
+            warning.as_str(),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
     // Only show Certificate line if a certificate is set
     if let Some(ref cert_filename) = state.certificate_filename {
         network_lines.push(Line::from(vec![
@@ -565,6 +908,13 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
             Span::styled("Server location: ", Style::default().fg(Color::Gray)),
             Span::raw(state.server.as_deref().unwrap_or("-")),
         ]),
+        Line::from(vec![
+            Span::styled("Distance to edge: ", Style::default().fg(Color::Gray)),
+            Span::raw(match state.geo.as_ref().and_then(|g| g.distance_km) {
+                Some(km) => format!("~{km:.0} km"),
+                None => "-".to_string(),
+            }),
+        ]),
         Line::from(vec![
             Span::styled("Your network: ", Style::default().fg(Color::Gray)),
             Span::raw(match (state.as_org.as_deref(), state.asn.as_deref()) {
@@ -594,15 +944,39 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
         || state.tls_summary.is_some()
         || state.ip_comparison.is_some()
         || state.traceroute_summary.is_some();
+    let stream_comparison = state
+        .last_result
+        .as_ref()
+        .and_then(|r| Some((r, r.stream_comparison.as_ref()?, r.stream_ratio()?)));
+    let thermal = state.last_result.as_ref().and_then(|r| r.thermal.as_ref());
 
-    if has_diagnostics {
+    if has_diagnostics || stream_comparison.is_some() || thermal.is_some() {
         network_lines.push(Line::from("")); // Separator
 
         if let Some(ref dns) = state.dns_summary {
+            let via = dns
+                .resolver
+                .as_deref()
+                .map(|r| format!(" via {r}"))
+                .unwrap_or_default();
             network_lines.push(Line::from(vec![
                 Span::styled("DNS resolution: ", Style::default().fg(Color::Gray)),
-                Span::raw(format!("{:.2}ms", dns.resolution_time_ms)),
+                Span::raw(format!("{:.2}ms{via}", dns.resolution_time_ms)),
             ]));
+            if !dns.resolvers.is_empty() {
+                let resolvers: Vec<String> = dns
+                    .resolvers
+                    .iter()
+                    .map(|r| match r.time_ms {
+                        Some(ms) => format!("{} {:.1}ms", r.server, ms),
+                        None => format!("{} failed", r.server),
+                    })
+                    .collect();
+                network_lines.push(Line::from(vec![
+                    Span::styled("  Public DNS: ", Style::default().fg(Color::Gray)),
+                    Span::raw(resolvers.join(", ")),
+                ]));
+            }
         }
 
         if let Some(ref tls) = state.tls_summary {
@@ -645,6 +1019,28 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
             ]));
         }
 
+        if let Some((result, cmp, ratio)) = stream_comparison {
+            network_lines.push(Line::from(vec![
+                Span::styled("1 vs N streams: ", Style::default().fg(Color::Gray)),
+                Span::raw(format!(
+                    "{:.1} vs {:.1}Mbps ({:.1}x, N={})",
+                    cmp.single_stream.mbps, result.download.mbps, ratio, cmp.streams
+                )),
+            ]));
+        }
+
+        if let Some(thermal) = thermal {
+            let style = if thermal.throttled {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            network_lines.push(Line::from(vec![
+                Span::styled("Client CPU: ", Style::default().fg(Color::Gray)),
+                Span::styled(thermal.describe(), style),
+            ]));
+        }
+
         if let Some(ref tr) = state.traceroute_summary {
             let status = if tr.completed { "complete" } else { "partial" };
             network_lines.push(Line::from(vec![
@@ -668,7 +1064,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
     let network_info = Paragraph::new(network_lines).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Network Information"),
+            .title(network_info_title(state)),
     );
     f.render_widget(network_info, info_row[0]);
 
@@ -721,7 +1117,7 @@ pub fn draw_dashboard(area: Rect, f: &mut Frame, state: &UiState) {
     // Status panel (full width at bottom)
     let mut status_lines = vec![Line::from(vec![
         Span::styled("Phase: ", Style::default().fg(Color::Gray)),
-        Span::raw(format!("{:?}", state.phase)),
+        Span::raw(state.phase_label()),
         Span::raw("   "),
         Span::styled("Paused: ", Style::default().fg(Color::Gray)),
         Span::raw(format!("{}", state.paused)),
@@ -920,7 +1316,7 @@ pub fn draw_dashboard_compact(area: Rect, f: &mut Frame, state: &UiState) {
     let mut meta_lines = vec![
         Line::from(vec![
             Span::styled("Phase: ", Style::default().fg(Color::Gray)),
-            Span::raw(format!("{:?}", state.phase)),
+            Span::raw(state.phase_label()),
             Span::raw("   "),
             Span::styled("Paused: ", Style::default().fg(Color::Gray)),
             Span::raw(format!("{}", state.paused)),
@@ -929,19 +1325,19 @@ pub fn draw_dashboard_compact(area: Rect, f: &mut Frame, state: &UiState) {
             Span::styled("Interface: ", Style::default().fg(Color::Gray)),
             Span::raw(state.interface_name.as_deref().unwrap_or("-")),
             Span::raw(" ("),
-            Span::raw(if state.is_wireless.unwrap_or(false) {
-                "Wireless"
-            } else {
-                "Wired"
-            }),
+            Span::raw(connection_kind(state)),
             Span::raw(")"),
         ]),
         Line::from(vec![
             Span::styled("Network: ", Style::default().fg(Color::Gray)),
             Span::raw(
                 state
-                    .network_name
-                    .as_deref()
+                    .config
+                    .network_label_for(
+                        state.network_name.as_deref(),
+                        state.interface_mac.as_deref(),
+                        state.interface_name.as_deref(),
+                    )
                     .or_else(|| state.interface_name.as_deref())
                     .unwrap_or("-"),
             ),
@@ -999,7 +1395,7 @@ pub fn draw_dashboard_compact(area: Rect, f: &mut Frame, state: &UiState) {
     if let Some(exp) = state
         .last_result
         .as_ref()
-        .and_then(|r| r.experimental_udp.as_ref())
+        .and_then(|r| r.packet_loss.as_ref())
     {
         let label_color = quality_label_color(&exp.quality_label);
         let mos_str = exp.mos.map(|m| format!(" MOS {:.1}", m)).unwrap_or_default();
@@ -1025,7 +1421,7 @@ pub fn draw_dashboard_compact(area: Rect, f: &mut Frame, state: &UiState) {
     let meta = Paragraph::new(meta_lines).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Network Information"),
+            .title(network_info_title(state)),
     );
     f.render_widget(meta, bottom_row[1]);
 }
@@ -0,0 +1,229 @@
+use super::state::UiState;
+use crate::model::{IpVersionResult, RunResult, ThroughputSummary, TracerouteHop};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+fn label(text: &str) -> Span<'static> {
+    Span::styled(format!("{text:<18}"), Style::default().fg(Color::Gray))
+}
+
+fn heading(text: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        text.to_string(),
+        Style::default().fg(Color::Cyan),
+    ))
+}
+
+fn ip_version_line(name: &str, result: Option<&IpVersionResult>) -> Line<'static> {
+    let value = match result {
+        Some(r) if r.available => format!(
+            "{:.1} Mbps down, {:.1} Mbps up, {:.1} ms ({})",
+            r.download_mbps, r.upload_mbps, r.latency_ms, r.ip_address
+        ),
+        Some(r) => format!(
+            "unavailable{}",
+            r.error
+                .as_deref()
+                .map(|e| format!(": {e}"))
+                .unwrap_or_default()
+        ),
+        None => "-".into(),
+    };
+    Line::from(vec![label(name), Span::raw(value)])
+}
+
+fn hop_line(hop: &TracerouteHop) -> Line<'static> {
+    let number = Span::styled(
+        format!("{:>3}  ", hop.hop_number),
+        Style::default().fg(Color::Gray),
+    );
+    if hop.timeout || hop.ip_address.is_none() {
+        return Line::from(vec![number, Span::raw("*")]);
+    }
+    let address = match (&hop.hostname, &hop.ip_address) {
+        (Some(name), Some(ip)) if name != ip => format!("{name} ({ip})"),
+        (_, ip) => ip.clone().unwrap_or_default(),
+    };
+    let rtts: Vec<String> = hop.rtt_ms.iter().map(|ms| format!("{ms:.1} ms")).collect();
+    Line::from(vec![
+        number,
+        Span::raw(format!("{address}  ")),
+        Span::styled(rtts.join("  "), Style::default().fg(Color::Yellow)),
+    ])
+}
+
+fn stream_line(name: &str, stream_mbps: &[f64]) -> Line<'static> {
+    let values: Vec<String> = stream_mbps
+        .iter()
+        .map(|mbps| format!("{mbps:.1}"))
+        .collect();
+    Line::from(vec![
+        label(name),
+        Span::raw(format!("{} Mbps", values.join("  "))),
+    ])
+}
+
+/// Throughput per stream, adaptive ramp-up and truncated responses of
+/// `result`, where there is anything to show.
+fn stream_lines(result: &RunResult) -> Vec<Line<'static>> {
+    let (download, upload) = (&result.download, &result.upload);
+    let mut lines = Vec::new();
+    if !download.stream_mbps.is_empty() {
+        lines.push(stream_line("Download", &download.stream_mbps));
+    }
+    if !upload.stream_mbps.is_empty() {
+        lines.push(stream_line("Upload", &upload.stream_mbps));
+    }
+    if download.saturation_streams.is_some() || upload.saturation_streams.is_some() {
+        let streams = |summary: &ThroughputSummary| match summary.saturation_streams {
+            Some(s) if summary.ramp_unfinished => format!("{s}+"),
+            Some(s) => s.to_string(),
+            None => "-".to_string(),
+        };
+        lines.push(Line::from(vec![
+            label("Saturated at"),
+            Span::raw(format!(
+                "{} down, {} up stream(s)",
+                streams(download),
+                streams(upload)
+            )),
+        ]));
+    }
+    if let Some(pct) = download.truncated_pct() {
+        lines.push(Line::from(vec![
+            label("Truncated"),
+            Span::styled(
+                format!(
+                    "{} of {} download responses ({pct:.1}%)",
+                    download.truncated_responses.unwrap_or_default(),
+                    download.responses.unwrap_or_default()
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+    }
+    lines
+}
+
+/// DNS, TLS and IP version results of the current run, and the streams of
+/// the last one.
+fn connection_lines(state: &UiState) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    if let Some(dns) = &state.dns_summary {
+        lines.push(heading("DNS"));
+        lines.push(Line::from(vec![
+            label("Hostname"),
+            Span::raw(dns.hostname.clone()),
+        ]));
+        let resolver = dns.resolver.as_deref().unwrap_or("System resolver");
+        lines.push(Line::from(vec![
+            label(resolver),
+            Span::raw(format!("{:.2} ms", dns.resolution_time_ms)),
+        ]));
+        if !dns.dns_servers.is_empty() {
+            lines.push(Line::from(vec![
+                label("Servers"),
+                Span::raw(dns.dns_servers.join(", ")),
+            ]));
+        }
+        for resolver in &dns.resolvers {
+            let value = match (resolver.time_ms, &resolver.error) {
+                (Some(ms), _) => format!("{ms:.2} ms"),
+                (None, error) => format!("failed ({})", error.as_deref().unwrap_or("-")),
+            };
+            lines.push(Line::from(vec![label(&resolver.server), Span::raw(value)]));
+        }
+        lines.push(Line::from(vec![
+            label("Addresses"),
+            Span::raw(format!("{} IPv4, {} IPv6", dns.ipv4_count, dns.ipv6_count)),
+        ]));
+        lines.push(Line::from(""));
+    }
+    if let Some(tls) = &state.tls_summary {
+        lines.push(heading("TLS"));
+        lines.push(Line::from(vec![
+            label("Handshake"),
+            Span::raw(format!("{:.2} ms", tls.handshake_time_ms)),
+        ]));
+        lines.push(Line::from(vec![
+            label("Protocol"),
+            Span::raw(tls.protocol_version.clone().unwrap_or_else(|| "-".into())),
+        ]));
+        lines.push(Line::from(vec![
+            label("Cipher suite"),
+            Span::raw(tls.cipher_suite.clone().unwrap_or_else(|| "-".into())),
+        ]));
+        lines.push(Line::from(""));
+    }
+    if let Some(cmp) = &state.ip_comparison {
+        lines.push(heading("IPv4 vs IPv6"));
+        lines.push(ip_version_line("IPv4", cmp.ipv4_result.as_ref()));
+        lines.push(ip_version_line("IPv6", cmp.ipv6_result.as_ref()));
+        lines.push(Line::from(""));
+    }
+    if let Some(result) = state.last_result.as_ref() {
+        let streams = stream_lines(result);
+        if !streams.is_empty() {
+            lines.push(heading("Streams"));
+            lines.extend(streams);
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "DNS and TLS are measured at the start of each run, unless diagnostics are skipped.",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    lines
+}
+
+/// Hops of the current run's traceroute, as they come in.
+fn traceroute_lines(state: &UiState) -> Vec<Line<'static>> {
+    let (hops, status) = match &state.traceroute_summary {
+        Some(tr) => {
+            let status = if tr.completed {
+                format!("reached {}", tr.destination)
+            } else {
+                format!("didn't reach {}", tr.destination)
+            };
+            (&tr.hops, Some(status))
+        }
+        None => (&state.traceroute_hops, None),
+    };
+    if hops.is_empty() {
+        return vec![Line::from(Span::styled(
+            "Start with --traceroute to trace the path to the Cloudflare edge.",
+            Style::default().fg(Color::Gray),
+        ))];
+    }
+    let mut lines: Vec<Line> = hops.iter().map(hop_line).collect();
+    if let Some(status) = status {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            status,
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    lines
+}
+
+pub fn draw_diagnostics(area: Rect, f: &mut Frame, state: &UiState) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+
+    let connection = Paragraph::new(connection_lines(state))
+        .block(Block::default().borders(Borders::ALL).title("Diagnostics"));
+    f.render_widget(connection, columns[0]);
+
+    let traceroute = Paragraph::new(traceroute_lines(state))
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Traceroute"));
+    f.render_widget(traceroute, columns[1]);
+}
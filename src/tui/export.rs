@@ -1,4 +1,5 @@
 use crate::model::RunResult;
+use crate::progress::Progress;
 use anyhow::{Context, Result};
 use std::sync::mpsc as std_mpsc;
 use std::sync::OnceLock;
@@ -9,21 +10,26 @@ use super::state::UiState;
 // Global clipboard manager channel - initialized once on first use
 static CLIPBOARD_SENDER: OnceLock<std_mpsc::Sender<String>> = OnceLock::new();
 
-/// Enrich RunResult with network information from UiState.
-/// This uses the shared enrichment function and then adds TUI-specific state (IP, colo, etc.)
-pub fn enrich_result_with_network_info(r: &RunResult, state: &UiState) -> RunResult {
-    // Create NetworkInfo from UiState
-    let network_info = crate::network::NetworkInfo {
+/// The network details the TUI gathered for the current run.
+pub fn network_info(state: &UiState) -> crate::network::NetworkInfo {
+    crate::network::NetworkInfo {
         interface_name: state.interface_name.clone(),
         network_name: state.network_name.clone(),
         is_wireless: state.is_wireless,
         interface_mac: state.interface_mac.clone(),
+        link_speed_mbps: state.link_speed_mbps,
         local_ipv4: state.local_ipv4.clone(),
         local_ipv6: state.local_ipv6.clone(),
-    };
+        route_interface: state.route_interface.clone(),
+        cellular: state.cellular.clone(),
+    }
+}
 
+/// Enrich RunResult with network information from UiState.
+/// This uses the shared enrichment function and then adds TUI-specific state (IP, colo, etc.)
+pub fn enrich_result_with_network_info(r: &RunResult, state: &UiState) -> RunResult {
     // Use shared enrichment function
-    let mut enriched = crate::network::enrich_result(r, &network_info);
+    let mut enriched = crate::network::enrich_result(r, &network_info(state));
 
     // Override with TUI state values (which may have been updated from meta)
     enriched.ip = state.ip.clone();
@@ -35,6 +41,9 @@ pub fn enrich_result_with_network_info(r: &RunResult, state: &UiState) -> RunRes
     if enriched.server.is_none() {
         enriched.server = state.server.clone();
     }
+    if state.redact {
+        enriched.redact();
+    }
     enriched
 }
 
@@ -85,7 +94,11 @@ pub fn export_result_json(r: &RunResult, state: &UiState) -> Result<std::path::P
 
 /// Export CSV to a user-specified file location.
 /// Returns the absolute path of the exported file.
-pub fn export_result_csv(r: &RunResult, state: &UiState) -> Result<std::path::PathBuf> {
+pub fn export_result_csv(
+    r: &RunResult,
+    state: &UiState,
+    columns: &[crate::storage::CsvColumn],
+) -> Result<std::path::PathBuf> {
     // Generate a default filename based on timestamp
     let default_name = format!(
         "cloudflare-speed-{}-{}.csv",
@@ -97,10 +110,30 @@ pub fn export_result_csv(r: &RunResult, state: &UiState) -> Result<std::path::Pa
     let current_dir = std::env::current_dir().context("get current directory")?;
     let path = current_dir.join(default_name);
     let enriched = enrich_result_with_network_info(r, state);
-    crate::storage::export_csv(&path, &enriched)?;
+    crate::storage::export_csv(&path, std::slice::from_ref(&enriched), columns)?;
     Ok(path)
 }
 
+/// Progress and outcome of `export_history_csv`, for the status line.
+pub enum HistoryExport {
+    Progress(String),
+    Done(Result<(std::path::PathBuf, usize)>),
+}
+
+/// Export every saved run, not only those loaded in the History tab, to one
+/// CSV file in the current directory. Returns the absolute path of the file
+/// and the number of runs.
+pub fn export_history_csv(progress: &mut Progress) -> Result<(std::path::PathBuf, usize)> {
+    let default_name = format!(
+        "cloudflare-speed-history-{}.csv",
+        time::OffsetDateTime::now_utc().date()
+    );
+    let current_dir = std::env::current_dir().context("get current directory")?;
+    let path = current_dir.join(default_name);
+    let count = crate::storage::export_all_csv(&path, progress)?;
+    Ok((path, count))
+}
+
 /// Initialize the clipboard manager thread if not already initialized.
 /// This creates a background thread that processes clipboard operations sequentially,
 /// keeping each clipboard instance alive for a sufficient duration.
@@ -0,0 +1,59 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::model::Phase;
+
+/// Redraw interval with no test running (2 fps).
+const IDLE_FRAME: Duration = Duration::from_millis(500);
+/// Redraw interval during the latency and packet loss phases (10 fps).
+const ACTIVE_FRAME: Duration = Duration::from_millis(100);
+/// Redraw interval while the throughput charts are streaming (20 fps).
+const STREAMING_FRAME: Duration = Duration::from_millis(50);
+
+/// Decides when the TUI redraws. Events only mark the screen as changed and
+/// frames are drawn at a rate that depends on the running phase; a frame with
+/// no changes since the last one is skipped. Input redraws immediately so
+/// keys don't wait for the idle frame rate.
+pub struct FramePacer {
+    dirty: bool,
+    next: Instant,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self {
+            dirty: true,
+            next: Instant::now(),
+        }
+    }
+}
+
+impl FramePacer {
+    /// When the next frame is due.
+    pub fn deadline(&self) -> Instant {
+        self.next
+    }
+
+    /// State shown on screen changed; draw it with the next frame.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Draw as soon as possible, e.g. after a key press or resize.
+    pub fn redraw_now(&mut self) {
+        self.dirty = true;
+        self.next = Instant::now();
+    }
+
+    /// A frame is due: schedule the next one for the running `phase` (None
+    /// when idle) and return whether this one needs drawing.
+    pub fn frame(&mut self, phase: Option<Phase>) -> bool {
+        let interval = match phase {
+            None => IDLE_FRAME,
+            Some(Phase::Download | Phase::Upload) => STREAMING_FRAME,
+            Some(_) => ACTIVE_FRAME,
+        };
+        self.next = Instant::now() + interval;
+        std::mem::take(&mut self.dirty)
+    }
+}
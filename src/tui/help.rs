@@ -37,6 +37,16 @@ pub fn draw_help(area: Rect, f: &mut Frame) {
             Span::styled("a", Style::default().fg(Color::Magenta)),
             Span::raw("           Toggle auto-save"),
         ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("P", Style::default().fg(Color::Magenta)),
+            Span::raw("           Save screen snapshot (ANSI text)"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("S", Style::default().fg(Color::Magenta)),
+            Span::raw("           Share result as a QR code"),
+        ]),
         Line::from(vec![
             Span::raw("  "),
             Span::styled("tab", Style::default().fg(Color::Magenta)),
@@ -48,6 +58,63 @@ pub fn draw_help(area: Rect, f: &mut Frame) {
             Span::raw("           Show this help"),
         ]),
         Line::from(""),
+        Line::from("Start screen (--test-on-launch false):"),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("↑/↓", Style::default().fg(Color::Magenta)),
+            Span::raw(" / "),
+            Span::styled("←/→", Style::default().fg(Color::Magenta)),
+            Span::raw("   Select field / change value"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("Enter", Style::default().fg(Color::Magenta)),
+            Span::raw(" / "),
+            Span::styled("Esc", Style::default().fg(Color::Magenta)),
+            Span::raw(" Start test / skip"),
+        ]),
+        Line::from(""),
+        Line::from("Dashboard and Charts tab:"),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("x", Style::default().fg(Color::Magenta)),
+            Span::raw("           Toggle chart cursor"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("←/→", Style::default().fg(Color::Magenta)),
+            Span::raw("         Move cursor (shows exact time/value)"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("z", Style::default().fg(Color::Magenta)),
+            Span::raw("           Mark zoom start, zoom to region, reset zoom"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("g", Style::default().fg(Color::Magenta)),
+            Span::raw("           Latency box plots / histograms (dashboard)"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("C", Style::default().fg(Color::Magenta)),
+            Span::raw("           Throughput over time / CDF"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("w", Style::default().fg(Color::Magenta)),
+            Span::raw("           Weekly box plots of runs (Charts tab)"),
+        ]),
+        Line::from(""),
+        Line::from("Settings tab:"),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("↑/↓", Style::default().fg(Color::Magenta)),
+            Span::raw(" / "),
+            Span::styled("←/→", Style::default().fg(Color::Magenta)),
+            Span::raw("   Select setting / change value (saved to config file)"),
+        ]),
+        Line::from(""),
         Line::from("History tab:"),
         Line::from(vec![
             Span::raw("  "),
@@ -66,6 +133,11 @@ pub fn draw_help(area: Rect, f: &mut Frame) {
             Span::styled("c", Style::default().fg(Color::Magenta)),
             Span::raw("           Export selected as CSV"),
         ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("X", Style::default().fg(Color::Magenta)),
+            Span::raw("           Export all saved runs as one CSV"),
+        ]),
         Line::from(vec![
             Span::raw("  "),
             Span::styled("y", Style::default().fg(Color::Magenta)),
@@ -76,6 +148,16 @@ pub fn draw_help(area: Rect, f: &mut Frame) {
             Span::styled("d", Style::default().fg(Color::Magenta)),
             Span::raw("           Delete selected"),
         ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("D", Style::default().fg(Color::Magenta)),
+            Span::raw("           Copy a Markdown diff against the previous run"),
+        ]),
+        Line::from(vec![
+            Span::raw("  "),
+            Span::styled("R", Style::default().fg(Color::Magenta)),
+            Span::raw("           Rerun with the selected run's configuration"),
+        ]),
         Line::from(vec![
             Span::raw("  "),
             Span::styled("r", Style::default().fg(Color::Magenta)),
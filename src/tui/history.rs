@@ -9,39 +9,176 @@ use ratatui::{
 };
 
 use super::state::UiState;
+use crate::config::Config;
+
+/// Text of a History tab row that is costly to build every frame, formatted
+/// once when the history changes.
+struct HistoryRow {
+    timestamp: String,
+    loss: String,
+    mos: String,
+    /// Test duration and streams, from the stored configuration, or
+    /// "invalid" for a run that can't be trusted
+    test: String,
+    interface: String,
+    network: String,
+    /// Lowercased fields matched by the history filter, one per line
+    search: String,
+}
+
+impl HistoryRow {
+    fn new(r: &RunResult, config: &Config, offset: Option<time::UtcOffset>) -> Self {
+        let network_label = config.network_label(r);
+        let search = [
+            r.network_name.as_deref(),
+            network_label,
+            r.interface_name.as_deref(),
+            r.cellular.as_ref().and_then(|c| c.technology.as_deref()),
+            r.cellular.as_ref().and_then(|c| c.carrier.as_deref()),
+            r.as_org.as_deref(),
+            r.colo.as_deref(),
+            r.comments.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("\n");
+        Self {
+            timestamp: format_timestamp(&r.timestamp_utc, offset),
+            loss: r
+                .packet_loss
+                .as_ref()
+                .map(|u| format!("{:.1}%", u.latency.loss * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+            mos: r
+                .packet_loss
+                .as_ref()
+                .and_then(|u| u.mos)
+                .map(|m| format!("{:.1}", m))
+                .unwrap_or_else(|| "-".to_string()),
+            test: match (&r.invalid, &r.config) {
+                (Some(_), _) => "invalid".to_string(),
+                (None, Some(c)) => c.label(),
+                (None, None) => "-".to_string(),
+            },
+            interface: r.interface_name.as_deref().unwrap_or("-").to_string(),
+            network: network_label
+                .or(r.interface_name.as_deref())
+                .unwrap_or("-")
+                .to_string(),
+            search,
+        }
+    }
+}
+
+/// Formatted rows for `UiState::history` and the indices of those matching
+/// the history filter. Rows are rebuilt only when the history changes and
+/// the filter is re-applied only when its text changes, so scrolling a large
+/// history stays cheap.
+#[derive(Default)]
+pub struct HistoryCache {
+    rows: Vec<HistoryRow>,
+    filter: String,
+    filtered: Vec<usize>,
+    stale: bool,
+}
+
+impl HistoryCache {
+    /// The history was reloaded or edited; rebuild the rows on the next draw.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    fn refresh(&mut self, history: &[RunResult], config: &Config, filter: &str) {
+        let rebuild = self.stale || self.rows.len() != history.len();
+        if rebuild {
+            // Looked up once per rebuild rather than for every row
+            let offset = time::UtcOffset::current_local_offset().ok();
+            self.rows = history
+                .iter()
+                .map(|r| HistoryRow::new(r, config, offset))
+                .collect();
+            self.stale = false;
+        }
+        if rebuild || self.filter != filter {
+            let filter_lower = filter.to_lowercase();
+            self.filtered = (0..self.rows.len())
+                .filter(|&i| filter.is_empty() || self.rows[i].search.contains(&filter_lower))
+                .collect();
+            self.filter = filter.to_string();
+        }
+    }
+}
+
+/// Format an RFC 3339 UTC timestamp ("2024-01-15T14:30:45Z" or
+/// "2024-01-15T14:30:45+00:00") in the local timezone `offset`, falling back
+/// to UTC when it can't be parsed or the local offset is unknown.
+fn format_timestamp(s: &str, offset: Option<time::UtcOffset>) -> String {
+    if s.len() < 19 || !s.contains('T') {
+        return format!("{} UTC", s);
+    }
+    let date_time: String = s.chars().take(19).collect();
+    let Some(t_pos) = date_time.find('T') else {
+        return format!("{} UTC", s);
+    };
+    let date_part = &date_time[..t_pos];
+    let time_part = &date_time[t_pos + 1..];
+
+    let local = || {
+        let local_offset = offset?;
+        let date = time::Date::from_calendar_date(
+            date_part.get(0..4)?.parse::<i32>().ok()?,
+            time::Month::try_from(date_part.get(5..7)?.parse::<u8>().ok()?).ok()?,
+            date_part.get(8..10)?.parse::<u8>().ok()?,
+        )
+        .ok()?;
+        let time = time::Time::from_hms(
+            time_part.get(0..2)?.parse::<u8>().ok()?,
+            time_part.get(3..5)?.parse::<u8>().ok()?,
+            time_part.get(6..8)?.parse::<u8>().ok()?,
+        )
+        .ok()?;
+        let local_dt = time::PrimitiveDateTime::new(date, time)
+            .assume_utc()
+            .to_offset(local_offset);
+        let local_date = local_dt.date();
+        let local_time = local_dt.time();
+        // Format offset as +HH:MM or -HH:MM
+        let offset_hours = local_offset.whole_hours();
+        let offset_minutes = local_offset.whole_minutes() % 60;
+        let offset_sign = if offset_hours >= 0 { '+' } else { '-' };
+        Some(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}{:02}:{:02}",
+            local_date.year(),
+            local_date.month() as u8,
+            local_date.day(),
+            local_time.hour(),
+            local_time.minute(),
+            local_time.second(),
+            offset_sign,
+            offset_hours.abs(),
+            offset_minutes.abs()
+        ))
+    };
+    local().unwrap_or_else(|| format!("{} {} UTC", date_part, time_part))
+}
 
 pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
     let mut lines: Vec<Line> = Vec::new();
 
     // Filter history based on filter text (case-insensitive search in network_name, interface_name, as_org, colo)
-    let filter_lower = state.history_filter.to_lowercase();
-    let filtered_history: Vec<&RunResult> = if state.history_filter.is_empty() {
-        state.history.iter().collect()
-    } else {
-        state
-            .history
-            .iter()
-            .filter(|r| {
-                let matches_field = |opt: &Option<String>| {
-                    opt.as_ref()
-                        .map(|s| s.to_lowercase().contains(&filter_lower))
-                        .unwrap_or(false)
-                };
-                matches_field(&r.network_name)
-                    || matches_field(&r.interface_name)
-                    || matches_field(&r.as_org)
-                    || matches_field(&r.colo)
-                    || matches_field(&r.comments)
-            })
-            .collect()
-    };
+    state
+        .history_cache
+        .refresh(&state.history, &state.config, &state.history_filter);
+    let cache = &state.history_cache;
 
     // Calculate how many items can fit in the available area
     // Subtract 4 for: controls line, filter line (optional), column headers, borders
     let max_items = (area.height as usize).saturating_sub(4);
 
     // Show total count and current position
-    let total_count = filtered_history.len();
+    let total_count = cache.filtered.len();
     let current_pos = if total_count > 0 {
         state.history_selected.min(total_count.saturating_sub(1)) + 1
     } else {
@@ -59,6 +196,9 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
     if total_count > max_items {
         header_spans.push(Span::raw(format!(", showing {}", max_items)));
     }
+    if state.history_loading {
+        header_spans.push(Span::styled(", loading…", Style::default().fg(Color::Cyan)));
+    }
     header_spans.extend(vec![
         Span::raw(") - "),
         Span::styled("Enter", Style::default().fg(Color::Magenta)),
@@ -73,9 +213,13 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
         Span::raw(": refresh, "),
         Span::styled("d", Style::default().fg(Color::Magenta)),
         Span::raw(": del, "),
+        Span::styled("R", Style::default().fg(Color::Magenta)),
+        Span::raw(": rerun, "),
         Span::styled("e", Style::default().fg(Color::Magenta)),
         Span::raw("/"),
         Span::styled("c", Style::default().fg(Color::Magenta)),
+        Span::raw("/"),
+        Span::styled("X", Style::default().fg(Color::Magenta)),
         Span::raw(": export"),
     ]);
     lines.push(Line::from(header_spans));
@@ -181,20 +325,21 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
         Span::styled("UL        ", Style::default().fg(Color::Cyan)), // 10 chars
         Span::styled("Ping      ", Style::default().fg(Color::Gray)), // 10 chars
         Span::styled("Loss     ", Style::default().fg(Color::Yellow)), // 9 chars
+        Span::styled("MOS   ", Style::default().fg(Color::Yellow)), // 6 chars
+        Span::styled("Bloat ", Style::default().fg(Color::Gray)), // 6 chars
+        Span::styled("Test    ", Style::default().fg(Color::Gray)), // 8 chars
         Span::styled("Interface    ", Style::default().fg(Color::Blue)), // 13 chars
         Span::styled("Network", Style::default().fg(Color::Magenta)),
     ]));
 
     // Clamp selection to filtered history bounds
-    let effective_selected = state
-        .history_selected
-        .min(filtered_history.len().saturating_sub(1));
+    let effective_selected = state.history_selected.min(total_count.saturating_sub(1));
 
     // Auto-adjust scroll to keep selected item visible
     // Only scroll when selection goes off-screen (not before)
     let mut offset = state
         .history_scroll_offset
-        .min(filtered_history.len().saturating_sub(1));
+        .min(total_count.saturating_sub(1));
     if effective_selected < offset {
         offset = effective_selected;
     } else if max_items > 0 && effective_selected >= offset + max_items {
@@ -203,101 +348,18 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
     state.history_scroll_offset = offset;
     let scroll_offset = offset;
 
-    let history_display: Vec<_> = filtered_history
+    for (display_idx, &i) in cache
+        .filtered
         .iter()
         .skip(scroll_offset)
         .take(max_items)
-        .collect();
-
-    for (display_idx, r) in history_display.iter().enumerate() {
+        .enumerate()
+    {
         // Calculate actual index in filtered view (accounting for scroll offset)
         let filtered_idx = scroll_offset + display_idx;
         let is_selected = state.tab == 1 && filtered_idx == effective_selected;
-
-        // Parse and format timestamp to human-readable format in local timezone
-        let timestamp_str: String = {
-            let s = &r.timestamp_utc;
-            // Parse RFC3339 format manually and convert to local time
-            // Format: "2024-01-15T14:30:45Z" or "2024-01-15T14:30:45+00:00"
-            if s.len() >= 19 && s.contains('T') {
-                let date_time: String = s.chars().take(19).collect();
-                if let Some(t_pos) = date_time.find('T') {
-                    let date_part = &date_time[..t_pos];
-                    let time_part = &date_time[t_pos + 1..];
-
-                    // Parse date components
-                    if let (Some(year), Some(month), Some(day)) = (
-                        date_part.get(0..4).and_then(|s| s.parse::<i32>().ok()),
-                        date_part.get(5..7).and_then(|s| s.parse::<u8>().ok()),
-                        date_part.get(8..10).and_then(|s| s.parse::<u8>().ok()),
-                    ) {
-                        // Parse time components
-                        if let (Some(hour), Some(minute), Some(second)) = (
-                            time_part.get(0..2).and_then(|s| s.parse::<u8>().ok()),
-                            time_part.get(3..5).and_then(|s| s.parse::<u8>().ok()),
-                            time_part.get(6..8).and_then(|s| s.parse::<u8>().ok()),
-                        ) {
-                            // Try to create UTC datetime and convert to local
-                            if let Ok(month_enum) = time::Month::try_from(month) {
-                                if let (Ok(date), Ok(time)) = (
-                                    time::Date::from_calendar_date(year, month_enum, day),
-                                    time::Time::from_hms(hour, minute, second),
-                                ) {
-                                    let utc_dt =
-                                        time::PrimitiveDateTime::new(date, time).assume_utc();
-
-                                    // Get local offset and convert
-                                    match time::UtcOffset::current_local_offset() {
-                                        Ok(local_offset) => {
-                                            let local_dt = utc_dt.to_offset(local_offset);
-                                            let local_date = local_dt.date();
-                                            let local_time = local_dt.time();
-                                            // Format offset as +HH:MM or -HH:MM
-                                            let offset_hours = local_offset.whole_hours();
-                                            let offset_minutes = local_offset.whole_minutes() % 60;
-                                            let offset_sign =
-                                                if offset_hours >= 0 { '+' } else { '-' };
-                                            let offset_str = format!(
-                                                "{}{:02}:{:02}",
-                                                offset_sign,
-                                                offset_hours.abs(),
-                                                offset_minutes.abs()
-                                            );
-                                            format!(
-                                                "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}",
-                                                local_date.year(),
-                                                local_date.month() as u8,
-                                                local_date.day(),
-                                                local_time.hour(),
-                                                local_time.minute(),
-                                                local_time.second(),
-                                                offset_str
-                                            )
-                                        }
-                                        Err(_) => {
-                                            // Fallback to UTC if local offset can't be determined
-                                            format!("{} {} UTC", date_part, time_part)
-                                        }
-                                    }
-                                } else {
-                                    format!("{} {} UTC", date_part, time_part)
-                                }
-                            } else {
-                                format!("{} {} UTC", date_part, time_part)
-                            }
-                        } else {
-                            format!("{} {} UTC", date_part, time_part)
-                        }
-                    } else {
-                        format!("{} {} UTC", date_part, time_part)
-                    }
-                } else {
-                    format!("{} UTC", s)
-                }
-            } else {
-                format!("{} UTC", s)
-            }
-        };
+        let r = &state.history[i];
+        let row = &cache.rows[i];
 
         let style = if is_selected {
             Style::default()
@@ -310,19 +372,6 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
         // Line number (1-indexed, newest = 1)
         let line_num = filtered_idx + 1;
 
-        // Format interface and network names, truncating if needed
-        let interface = r.interface_name.as_deref().unwrap_or("-");
-        let network = r
-            .network_name
-            .as_deref()
-            .or_else(|| r.interface_name.as_deref())
-            .unwrap_or("-");
-        let history_loss_text = r
-            .experimental_udp
-            .as_ref()
-            .map(|u| format!("{:.1}%", u.latency.loss * 100.0))
-            .unwrap_or_else(|| "-".to_string());
-
         lines.push(Line::from(vec![
             Span::styled(
                 format!("{:<4}{}", line_num, if is_selected { ">" } else { " " }), // 5 chars total
@@ -333,7 +382,7 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
                 },
             ),
             Span::styled(
-                format!("{:<28}", timestamp_str), // 28 chars
+                format!("{:<28}", row.timestamp), // 28 chars
                 if is_selected {
                     style
                 } else {
@@ -361,7 +410,7 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
                 if is_selected { style } else { Style::default() },
             ),
             Span::styled(
-                format!("{:<9}", history_loss_text), // 9 chars
+                format!("{:<9}", row.loss), // 9 chars
                 if is_selected {
                     style
                 } else {
@@ -369,7 +418,33 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
                 },
             ),
             Span::styled(
-                format!("{:<13}", interface), // 13 chars
+                format!("{:<6}", row.mos), // 6 chars
+                if is_selected {
+                    style
+                } else {
+                    Style::default().fg(Color::Yellow)
+                },
+            ),
+            Span::styled(
+                format!("{:<6}", r.bufferbloat_grade().unwrap_or("-")), // 6 chars
+                if is_selected {
+                    style
+                } else {
+                    Style::default().fg(Color::Gray)
+                },
+            ),
+            Span::styled(
+                format!("{:<8}", row.test), // 8 chars
+                if is_selected {
+                    style
+                } else if r.invalid.is_some() {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default().fg(Color::Gray)
+                },
+            ),
+            Span::styled(
+                format!("{:<13}", row.interface), // 13 chars
                 if is_selected {
                     style
                 } else {
@@ -377,7 +452,7 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
                 },
             ),
             Span::styled(
-                network.to_string(),
+                row.network.as_str(),
                 if is_selected {
                     style
                 } else {
@@ -389,7 +464,7 @@ pub fn show_history(area: Rect, f: &mut Frame, state: &mut UiState) {
 
     if state.history.is_empty() {
         lines.push(Line::from("No history available."));
-    } else if filtered_history.is_empty() && !state.history_filter.is_empty() {
+    } else if total_count == 0 && !state.history_filter.is_empty() {
         lines.push(Line::from(vec![
             Span::styled(
                 "No results match filter: ",
@@ -518,35 +593,16 @@ pub fn draw_history_detail(area: Rect, f: &mut Frame, state: &mut UiState) {
     let mut lines: Vec<Line> = Vec::new();
 
     // Get the filtered history to find the correct selected item
-    let filter_lower = state.history_filter.to_lowercase();
-    let filtered_history: Vec<&RunResult> = if state.history_filter.is_empty() {
-        state.history.iter().collect()
-    } else {
-        state
-            .history
-            .iter()
-            .filter(|r| {
-                let matches_field = |opt: &Option<String>| {
-                    opt.as_ref()
-                        .map(|s| s.to_lowercase().contains(&filter_lower))
-                        .unwrap_or(false)
-                };
-                matches_field(&r.network_name)
-                    || matches_field(&r.interface_name)
-                    || matches_field(&r.as_org)
-                    || matches_field(&r.colo)
-                    || matches_field(&r.comments)
-            })
-            .collect()
-    };
-
-    let effective_selected = state
-        .history_selected
-        .min(filtered_history.len().saturating_sub(1));
+    state
+        .history_cache
+        .refresh(&state.history, &state.config, &state.history_filter);
+    let filtered = &state.history_cache.filtered;
+    let effective_selected = state.history_selected.min(filtered.len().saturating_sub(1));
+    let selected = filtered.get(effective_selected).map(|&i| &state.history[i]);
 
     let mut detail_scroll_info: Option<(usize, usize, usize)> = None;
 
-    if let Some(result) = filtered_history.get(effective_selected) {
+    if let Some(result) = selected {
         // Header with navigation help
         lines.push(Line::from(vec![
             Span::styled("JSON Detail View", Style::default().fg(Color::Cyan)),
@@ -589,7 +645,7 @@ pub fn draw_history_detail(area: Rect, f: &mut Frame, state: &mut UiState) {
         };
         lines.push(Line::from(vec![
             Span::styled(
-                result.network_name.as_deref().unwrap_or("Unknown Network"),
+                state.config.network_label(result).unwrap_or("Unknown Network"),
                 Style::default().fg(Color::Yellow),
             ),
             Span::raw(" - "),
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use super::state::{update_available_networks, UiState};
+use crate::cli::Cli;
+use crate::model::RunResult;
+use crate::network::NetworkInfo;
+
+/// How long to wait for network information before giving up on it.
+const NETWORK_INFO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why history is being loaded, which decides how the runs are merged into
+/// `UiState::history` once they arrive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryLoad {
+    /// `r` on the History tab: replace the history and report what changed
+    Refresh,
+    /// Scrolled near the end of the loaded runs: append the ones not loaded yet
+    More,
+    /// A run finished: replace the history so it includes the new run
+    RunFinished,
+}
+
+/// Loads saved runs on a blocking worker so reading hundreds of JSON files
+/// doesn't stall the render loop. Finished loads are picked up with
+/// `next_loaded` in `tokio::select!`.
+pub struct HistoryLoader {
+    tx: mpsc::UnboundedSender<(HistoryLoad, Result<Vec<RunResult>>)>,
+    rx: mpsc::UnboundedReceiver<(HistoryLoad, Result<Vec<RunResult>>)>,
+    in_flight: Vec<HistoryLoad>,
+}
+
+impl HistoryLoader {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx,
+            in_flight: Vec::new(),
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// Start loading the `limit` most recent runs. A lazy load while another
+    /// is still running is dropped, since scrolling requests one per key press.
+    pub fn request(&mut self, kind: HistoryLoad, limit: usize) {
+        if kind == HistoryLoad::More && self.in_flight.contains(&HistoryLoad::More) {
+            return;
+        }
+        self.in_flight.push(kind);
+        let tx = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send((kind, crate::storage::load_recent(limit)));
+        });
+    }
+
+    /// Wait for the next finished load. Cancel-safe.
+    pub async fn next_loaded(&mut self) -> (HistoryLoad, Result<Vec<RunResult>>) {
+        // The loader holds a sender, so the channel never closes
+        let (kind, result) = self.rx.recv().await.expect("history loader channel closed");
+        if let Some(i) = self.in_flight.iter().position(|k| *k == kind) {
+            self.in_flight.remove(i);
+        }
+        (kind, result)
+    }
+}
+
+/// Merge a finished history load into the UI state.
+pub fn apply_history_load(state: &mut UiState, kind: HistoryLoad, result: Result<Vec<RunResult>>) {
+    match (kind, result) {
+        (HistoryLoad::Refresh, Ok(new_history)) => {
+            let old_count = state.history.len();
+            state.history = new_history;
+            state.history_cache.invalidate();
+            state.history_loaded_count = state.history.len();
+            update_available_networks(state);
+
+            // Adjust selection if needed
+            if state.history_selected >= state.history.len() && !state.history.is_empty() {
+                state.history_selected = state.history.len() - 1;
+            } else if state.history.is_empty() {
+                state.history_selected = 0;
+                state.history_scroll_offset = 0;
+            }
+
+            // Adjust scroll offset if needed
+            if state.history_scroll_offset >= state.history.len() && !state.history.is_empty() {
+                state.history_scroll_offset = state.history.len().saturating_sub(20);
+            }
+
+            let new_count = state.history.len();
+            if new_count > old_count {
+                state.info = format!("Refreshed: {} new run(s)", new_count - old_count);
+            } else if new_count < old_count {
+                state.info = format!("Refreshed: {} run(s) removed", old_count - new_count);
+            } else {
+                state.info = "Refreshed".into();
+            }
+        }
+        (HistoryLoad::Refresh, Err(e)) => {
+            state.info = format!("Refresh failed: {e:#}");
+        }
+        (HistoryLoad::More, Ok(more_history)) => {
+            let existing_ids: std::collections::HashSet<_> =
+                state.history.iter().map(|r| &r.meas_id).collect();
+            let new_items: Vec<_> = more_history
+                .into_iter()
+                .filter(|r| !existing_ids.contains(&r.meas_id))
+                .collect();
+            if !new_items.is_empty() {
+                state.history.extend(new_items);
+                state.history_cache.invalidate();
+                state.history_loaded_count = state.history.len();
+                update_available_networks(state);
+            }
+        }
+        (HistoryLoad::More, Err(_)) => {}
+        (HistoryLoad::RunFinished, result) => {
+            state.history = result.unwrap_or_default();
+            state.history_cache.invalidate();
+            state.history_loaded_count = state.history.len();
+            update_available_networks(state);
+            // Reset selection to show the new test (most recent) if on history tab
+            if state.tab == 1 {
+                state.history_selected = 0;
+                state.history_scroll_offset = 0;
+            }
+        }
+    }
+}
+
+/// Progress of a background network information lookup.
+pub enum NetworkUpdate {
+    /// Part of the information, more to follow
+    Partial(NetworkInfo),
+    /// All of it, with the route warning for the next run
+    Done(NetworkInfo, Option<String>),
+    /// The lookup took longer than `NETWORK_INFO_TIMEOUT`; anything it
+    /// finds later is dropped
+    TimedOut,
+}
+
+/// Gathers network information on a blocking worker, passing on what is
+/// known after each step so the Network Information panel fills in as it
+/// goes instead of startup waiting on slow lookups (VPN adapters, WMI on
+/// Windows).
+pub struct NetworkInfoLoader {
+    tx: mpsc::UnboundedSender<(u64, NetworkUpdate)>,
+    rx: mpsc::UnboundedReceiver<(u64, NetworkUpdate)>,
+    /// Bumped for each lookup, so updates from an older one are ignored
+    generation: u64,
+    deadline: Option<Instant>,
+}
+
+impl NetworkInfoLoader {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx,
+            generation: 0,
+            deadline: None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    /// Start a lookup, superseding any still running.
+    pub fn request(&mut self, args: &Cli) {
+        self.generation += 1;
+        self.deadline = Some(Instant::now() + NETWORK_INFO_TIMEOUT);
+        let (generation, tx, args) = (self.generation, self.tx.clone(), args.clone());
+        tokio::task::spawn_blocking(move || {
+            let info = crate::network::gather_network_info_with(&args, |partial| {
+                let _ = tx.send((generation, NetworkUpdate::Partial(partial.clone())));
+            });
+            let warning = crate::network::check_route(&info, &args);
+            let _ = tx.send((generation, NetworkUpdate::Done(info, warning)));
+        });
+    }
+
+    /// Wait for the next update of the current lookup. Never resolves while
+    /// idle, so it can be polled in `tokio::select!`. Cancel-safe.
+    pub async fn next_update(&mut self) -> NetworkUpdate {
+        loop {
+            let Some(deadline) = self.deadline else {
+                return std::future::pending().await;
+            };
+            tokio::select! {
+                Some((generation, update)) = self.rx.recv() => {
+                    if generation != self.generation {
+                        continue;
+                    }
+                    if matches!(update, NetworkUpdate::Done(..)) {
+                        self.deadline = None;
+                    }
+                    return update;
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    self.deadline = None;
+                    // Drop whatever the timed out lookup sends later
+                    self.generation += 1;
+                    return NetworkUpdate::TimedOut;
+                }
+            }
+        }
+    }
+}
+
+/// Show network information in the UI state.
+pub fn apply_network_info(state: &mut UiState, info: NetworkInfo) {
+    state.interface_name = info.interface_name;
+    state.network_name = info.network_name;
+    state.is_wireless = info.is_wireless;
+    state.interface_mac = info.interface_mac;
+    state.link_speed_mbps = info.link_speed_mbps;
+    state.local_ipv4 = info.local_ipv4;
+    state.local_ipv6 = info.local_ipv6;
+    state.route_interface = info.route_interface;
+    state.cellular = info.cellular;
+}
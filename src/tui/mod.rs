@@ -1,22 +1,36 @@
 mod charts;
+mod cursor;
 mod dashboard;
+mod diagnostics;
 mod export;
+mod frames;
 mod help;
 mod history;
+mod loader;
+mod onboarding;
+mod settings;
+mod share;
+mod snapshot;
+mod split;
+mod start;
 mod state;
 
 pub use state::UiState;
 
-use crate::cli::{build_config, Cli};
-use crate::engine::{EngineControl, TestEngine};
-use crate::model::{Phase, RunResult, TestEvent};
+use crate::cli::{apply_run_config, build_config, Cli};
+use crate::config::Config;
+use crate::engine::EngineControl;
+use crate::i18n::t;
+use crate::model::{Phase, TestEvent};
+use crate::orchestrator::{Controller, ControllerEvent};
+use crate::progress::Progress;
 use anyhow::{Context, Result};
 use crossterm::{
     event::{Event, EventStream, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use futures::{future, StreamExt};
+use futures::StreamExt;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -27,16 +41,27 @@ use ratatui::{
     Terminal,
 };
 use std::{io, time::Duration, time::Instant};
-use tokio::sync::mpsc;
 
 use charts::draw_charts;
 use dashboard::draw_dashboard;
-use export::{copy_to_clipboard, enrich_result_with_network_info, export_result_csv, export_result_json, save_and_show_path};
+use diagnostics::draw_diagnostics;
+use export::{copy_to_clipboard, enrich_result_with_network_info, export_history_csv, export_result_csv, export_result_json, network_info, save_and_show_path, HistoryExport};
+use frames::FramePacer;
 use help::draw_help;
 use history::{show_history, draw_history_detail};
+use loader::{
+    apply_history_load, apply_network_info, HistoryLoad, HistoryLoader, NetworkInfoLoader,
+    NetworkUpdate,
+};
+use onboarding::{draw_onboarding, Onboarding, OnboardingAction};
+use settings::draw_settings;
+use share::{draw_share, SharePopup};
+use split::{draw_split, SplitView};
+use start::{draw_start_form, StartAction, StartForm};
 use state::update_available_networks;
 
-pub async fn run(args: Cli) -> Result<()> {
+pub async fn run(mut args: Cli, config: Config) -> Result<()> {
+    let csv_columns = crate::storage::csv_columns(&args.csv_columns)?;
     enable_raw_mode().context("enable raw mode")?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen).ok();
@@ -56,7 +81,9 @@ pub async fn run(args: Cli) -> Result<()> {
     let mut state = UiState {
         phase: Phase::IdleLatency,
         auto_save: args.auto_save,
+        redact: args.redact,
         comments: args.comments.clone(),
+        config,
         ..Default::default()
     };
     state.initial_history_load_size = initial_load;
@@ -64,14 +91,13 @@ pub async fn run(args: Cli) -> Result<()> {
     state.history_loaded_count = state.history.len();
     update_available_networks(&mut state);
 
-    // Gather network interface information using shared module
-    let network_info = crate::network::gather_network_info(&args);
-    state.interface_name = network_info.interface_name.clone();
-    state.network_name = network_info.network_name.clone();
-    state.is_wireless = network_info.is_wireless;
-    state.interface_mac = network_info.interface_mac.clone();
-    state.local_ipv4 = network_info.local_ipv4.clone();
-    state.local_ipv6 = network_info.local_ipv6.clone();
+    // Filled in by the select loop as the lookup progresses
+    let mut network_loader = NetworkInfoLoader::new();
+    network_loader.request(&args);
+    // Show colo/ASN from the last run until this run's own meta arrives
+    if let Some(cached) = crate::storage::read_cache("meta", Some(crate::storage::META_CACHE_TTL)) {
+        apply_event(&mut state, TestEvent::MetaInfo { meta: cached.value });
+    }
     state.certificate_filename = args
         .certificate
         .as_ref()
@@ -79,6 +105,9 @@ pub async fn run(args: Cli) -> Result<()> {
         .and_then(|n| n.to_str())
         .map(|s| s.to_string());
     state.proxy_url = args.proxy.clone();
+    if !args.split_interfaces.is_empty() {
+        state.split = Some(SplitView::new(&args.split_interfaces, args.split_rounds));
+    }
 
     // Spawn background task to check for updates (non-blocking, silent on error)
     let (update_tx, mut update_rx) = tokio::sync::mpsc::channel::<Option<String>>(1);
@@ -88,26 +117,110 @@ pub async fn run(args: Cli) -> Result<()> {
         }
     });
 
+    // Failed InfluxDB pushes, reported in the status line
+    let (push_tx, mut push_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    // History exports ('X'), which read every saved run off the UI thread
+    let (export_tx, mut export_rx) = tokio::sync::mpsc::unbounded_channel::<HistoryExport>();
+
+    let mut network_watcher = args
+        .watch_network
+        .then(|| crate::netwatch::NetworkWatcher::spawn(&args));
+
+    let mut control = crate::control::ControlSocket::bind();
+
     let mut events = EventStream::new();
-    let mut tick = tokio::time::interval(Duration::from_millis(100));
+    let mut frames = FramePacer::default();
+    let mut history_loader = HistoryLoader::new();
+
+    // Ask how results should be stored before anything is saved
+    if !state.config.onboarded {
+        state.onboarding = Some(Onboarding::default());
+    }
 
-    // Start first run if test_on_launch is enabled
-    let mut run_ctx = if args.test_on_launch {
-        Some(start_run(&args).await?)
+    // Start first run if test_on_launch is enabled (after the onboarding dialog)
+    let mut controller = Controller::new()
+        .with_cooldown(Duration::from(args.cooldown))
+        .with_lock_policy(crate::cli::lock_policy(&args));
+    if args.test_on_launch {
+        if state.onboarding.is_none() {
+            enqueue_test(&mut controller, &mut state, &args, false);
+        }
     } else {
-        None
-    };
+        state.start_form = Some(StartForm::new(&args));
+    }
 
     let res = loop {
         tokio::select! {
-            _ = tick.tick() => {
-                terminal.draw(|f| draw(f.area(), f, &mut state)).ok();
+            _ = tokio::time::sleep_until(frames.deadline()) => {
+                if frames.frame(controller.state().is_active().then_some(state.phase)) {
+                    state.history_loading = history_loader.is_loading();
+                    state.network_info_loading = network_loader.is_loading();
+                    terminal.draw(|f| draw(f.area(), f, &mut state, &args)).ok();
+                }
+            }
+            update = network_loader.next_update() => {
+                match update {
+                    NetworkUpdate::Partial(info) => apply_network_info(&mut state, info),
+                    NetworkUpdate::Done(info, route_warning) => {
+                        apply_network_info(&mut state, info);
+                        state.route_warning = route_warning;
+                    }
+                    NetworkUpdate::TimedOut => {
+                        state.info = "Network information lookup timed out".into();
+                    }
+                }
+                frames.mark_dirty();
+            }
+            (kind, result) = history_loader.next_loaded() => {
+                apply_history_load(&mut state, kind, result);
+                frames.mark_dirty();
             }
             Some(status) = update_rx.recv() => {
                 state.update_status = Some(status);
+                frames.mark_dirty();
+            }
+            Some(error) = push_rx.recv() => {
+                state.info = error;
+                frames.mark_dirty();
+            }
+            Some(export) = export_rx.recv() => {
+                match export {
+                    HistoryExport::Progress(line) => state.info = line,
+                    HistoryExport::Done(Ok((p, count))) => {
+                        state.last_exported_path = Some(p.to_string_lossy().to_string());
+                        state.info = format!("Exported CSV: {} ({count} runs, press 'y' to copy path)", p.display());
+                    }
+                    HistoryExport::Done(Err(e)) => {
+                        state.info = format!("CSV export failed: {e:#}");
+                    }
+                }
+                frames.mark_dirty();
+            }
+            network = crate::netwatch::next_change(&mut network_watcher) => {
+                network_loader.request(&args);
+                // Wait for the storage preferences before the first run
+                if state.onboarding.is_none() {
+                    state.info = format!("Network changed to {}, testing it…", network.describe());
+                    state.start_form = None;
+                    // A run in progress straddles both networks, so restart it
+                    controller.cancel_active().await;
+                    enqueue_test(&mut controller, &mut state, &args, true);
+                }
+                frames.mark_dirty();
+            }
+            _ = crate::control::run_requested(&mut control) => {
+                // Wait for the storage preferences before the first run
+                if state.onboarding.is_none() && !controller.state().is_active() {
+                    state.info = "Test requested over the control socket".into();
+                    state.start_form = None;
+                    enqueue_test(&mut controller, &mut state, &args, false);
+                }
+                frames.mark_dirty();
             }
             maybe_ev = events.next() => {
                 let Some(Ok(ev)) = maybe_ev else { continue };
+                // Key presses and resizes are drawn right away
+                frames.redraw_now();
                 if let Event::Key(k) = ev {
                     if k.kind != KeyEventKind::Press {
                         continue;
@@ -169,107 +282,102 @@ pub async fn run(args: Cli) -> Result<()> {
                         continue;
                     }
 
-                    match (k.modifiers, k.code) {
-                        (_, KeyCode::Char('q')) | (KeyModifiers::CONTROL, KeyCode::Char('c')) => {
-                            if let Some(ref ctx) = run_ctx {
-                                ctx.ctrl_tx.send(EngineControl::Cancel).await.ok();
+                    // Handle the first-run onboarding dialog (captures all keys)
+                    if let Some(onboarding) = state.onboarding.as_mut() {
+                        if k.modifiers == KeyModifiers::CONTROL && k.code == KeyCode::Char('c') {
+                            break Ok(());
+                        }
+                        match onboarding.handle_key(k.code) {
+                            OnboardingAction::None => continue,
+                            OnboardingAction::Confirm(auto_save, redact) => {
+                                state.auto_save = auto_save;
+                                state.redact = redact;
+                                args.auto_save = auto_save;
+                                args.redact = redact;
+                                state.config.auto_save = Some(auto_save);
+                                state.config.redact = Some(redact);
+                                state.config.onboarded = true;
+                                state.info = match crate::config::save(&state.config) {
+                                    Ok(path) => format!("Preferences saved to {}", path.display()),
+                                    Err(e) => format!("Failed to save preferences: {e:#}"),
+                                };
                             }
+                            OnboardingAction::Later => {}
+                            OnboardingAction::Quit => break Ok(()),
+                        }
+                        state.onboarding = None;
+                        if args.test_on_launch && state.start_form.is_none() {
+                            enqueue_test(&mut controller, &mut state, &args, false);
+                        }
+                        continue;
+                    }
+
+                    // Any key closes the share QR code
+                    if state.share_popup.take().is_some() {
+                        continue;
+                    }
+
+                    // Handle the start screen (shown on the dashboard until the first run);
+                    // Tab still switches tabs
+                    let start_form_active = state.tab == 0 && !matches!(k.code, KeyCode::Tab | KeyCode::BackTab);
+                    if let Some(form) = state.start_form.as_mut().filter(|_| start_form_active) {
+                        if k.modifiers == KeyModifiers::CONTROL && k.code == KeyCode::Char('c') {
                             break Ok(());
                         }
-                        (_, KeyCode::Char('p')) => {
-                            if let Some(ref ctx) = run_ctx {
-                                state.paused = !state.paused;
-                                ctx.ctrl_tx.send(EngineControl::Pause(state.paused)).await.ok();
+                        match form.handle_key(k.code) {
+                            StartAction::None => {}
+                            StartAction::Start => {
+                                form.apply(&mut args);
+                                state.start_form = None;
+                                state.comments = args.comments.clone();
+                                network_loader.request(&args);
+                                enqueue_test(&mut controller, &mut state, &args, false);
                             }
+                            StartAction::Dismiss => state.start_form = None,
+                            StartAction::Quit => break Ok(()),
+                        }
+                        continue;
+                    }
+
+                    // Chart cursor mode on the dashboard and Charts tab ('x' toggles)
+                    let cursor = match state.tab {
+                        0 => Some((
+                            &mut state.dashboard_cursor,
+                            state.dl_points.len().max(state.ul_points.len()),
+                        )),
+                        2 => Some((&mut state.charts_cursor, state.history.len())),
+                        _ => None,
+                    };
+                    if let Some((cursor, len)) = cursor {
+                        if cursor.handle_key(k.code, len) {
+                            continue;
+                        }
+                    }
+
+                    // Settings tab: arrow keys select and change settings
+                    if state.tab == 4 && settings::handle_key(&mut state, &mut args, k.code) {
+                        continue;
+                    }
+
+                    match (k.modifiers, k.code) {
+                        (_, KeyCode::Char('q')) | (KeyModifiers::CONTROL, KeyCode::Char('c')) => {
+                            controller.control(EngineControl::Cancel).await;
+                            break Ok(());
+                        }
+                        (_, KeyCode::Char('p')) if controller.state().is_active() => {
+                            state.paused = !state.paused;
+                            controller.control(EngineControl::Pause(state.paused)).await;
                         }
                         (_, KeyCode::Char('r')) => {
                             // Refresh history (only when on history tab)
                             if state.tab == 1 {
                                 let reload_size = state.initial_history_load_size.max(state.history_loaded_count);
-                                match crate::storage::load_recent(reload_size) {
-                                    Ok(new_history) => {
-                                        let old_count = state.history.len();
-                                        state.history = new_history;
-                                        state.history_loaded_count = state.history.len();
-                                        update_available_networks(&mut state);
-
-                                        // Adjust selection if needed
-                                        if state.history_selected >= state.history.len() && !state.history.is_empty() {
-                                            state.history_selected = state.history.len() - 1;
-                                        } else if state.history.is_empty() {
-                                            state.history_selected = 0;
-                                            state.history_scroll_offset = 0;
-                                        }
-
-                                        // Adjust scroll offset if needed
-                                        if state.history_scroll_offset >= state.history.len() && !state.history.is_empty() {
-                                            state.history_scroll_offset = state.history.len().saturating_sub(20).max(0);
-                                        }
-
-                                        let new_count = state.history.len();
-                                        if new_count > old_count {
-                                            state.info = format!("Refreshed: {} new run(s)", new_count - old_count);
-                                        } else if new_count < old_count {
-                                            state.info = format!("Refreshed: {} run(s) removed", old_count - new_count);
-                                        } else {
-                                            state.info = "Refreshed".into();
-                                        }
-                                    }
-                                    Err(e) => {
-                                        state.info = format!("Refresh failed: {e:#}");
-                                    }
-                                }
+                                history_loader.request(HistoryLoad::Refresh, reload_size);
                             } else {
                                 // Rerun (only when NOT on history tab)
                                 state.info = "Restarting…".into();
-                                if let Some(ref mut ctx) = run_ctx {
-                                    ctx.ctrl_tx.send(EngineControl::Cancel).await.ok();
-                                    if let Some(h) = ctx.handle.take() {
-                                        let _ = h.await;
-                                    }
-                                }
-                                state.last_result = None;
-                                state.run_start = Instant::now();
-                                state.dl_series.clear();
-                                state.ul_series.clear();
-                                state.idle_lat_series.clear();
-                                state.loaded_dl_lat_series.clear();
-                                state.loaded_ul_lat_series.clear();
-                                state.dl_points.clear();
-                                state.ul_points.clear();
-                                state.idle_lat_points.clear();
-                                state.loaded_dl_lat_points.clear();
-                                state.loaded_ul_lat_points.clear();
-                                state.dl_mbps = 0.0;
-                                state.ul_mbps = 0.0;
-                                state.dl_avg_mbps = 0.0;
-                                state.ul_avg_mbps = 0.0;
-                                state.dl_bytes_total = 0;
-                                state.ul_bytes_total = 0;
-                                state.dl_phase_start = None;
-                                state.ul_phase_start = None;
-                                state.idle_latency_samples.clear();
-                                state.loaded_dl_latency_samples.clear();
-                                state.loaded_ul_latency_samples.clear();
-                                state.idle_latency_sent = 0;
-                                state.idle_latency_received = 0;
-                                state.loaded_dl_latency_sent = 0;
-                                state.loaded_dl_latency_received = 0;
-                                state.loaded_ul_latency_sent = 0;
-                                state.loaded_ul_latency_received = 0;
-                                state.phase = Phase::IdleLatency;
-                                state.paused = false;
-                                // Clear UDP loss counters
-                                state.udp_loss_sent = 0;
-                                state.udp_loss_received = 0;
-                                state.udp_loss_total = 0;
-                                state.udp_loss_latest_rtt_ms = None;
-                                // Clear diagnostic results
-                                state.dns_summary = None;
-                                state.tls_summary = None;
-                                state.ip_comparison = None;
-                                state.traceroute_summary = None;
-                                run_ctx = Some(start_run(&args).await?);
+                                controller.cancel_active().await;
+                                enqueue_test(&mut controller, &mut state, &args, true);
                             }
                         }
                         (_, KeyCode::Char('s')) => {
@@ -304,7 +412,7 @@ pub async fn run(args: Cli) -> Result<()> {
                             if state.tab == 1 && !state.history.is_empty() {
                                 if state.history_selected < state.history.len() {
                                     let r = &state.history[state.history_selected];
-                                    match export_result_csv(r, &state) {
+                                    match export_result_csv(r, &state, &csv_columns) {
                                         Ok(p) => {
                                             let path_str = p.to_string_lossy().to_string();
                                             state.last_exported_path = Some(path_str.clone());
@@ -317,6 +425,31 @@ pub async fn run(args: Cli) -> Result<()> {
                                 }
                             }
                         }
+                        (_, KeyCode::Char('D')) if state.tab == 1 => {
+                            // Diff the selected run against the one before it, as Markdown
+                            let selected = state.history_selected;
+                            match (state.history.get(selected + 1), state.history.get(selected)) {
+                                (Some(base), Some(other)) => {
+                                    let markdown = crate::diff::diff(&state.config, base, other).markdown();
+                                    state.info = match copy_to_clipboard(&markdown) {
+                                        Ok(_) => format!("✓ Copied the comparison with run {} to clipboard", base.meas_id),
+                                        Err(e) => format!("Clipboard copy failed: {e:#}"),
+                                    };
+                                }
+                                _ => state.info = "No earlier run to compare with.".into(),
+                            }
+                        }
+                        (_, KeyCode::Char('X')) if state.tab == 1 => {
+                            state.info = "Exporting all saved runs…".into();
+                            let tx = export_tx.clone();
+                            tokio::task::spawn_blocking(move || {
+                                let progress_tx = tx.clone();
+                                let mut progress = Progress::reporting("Exporting runs", move |line| {
+                                    let _ = progress_tx.send(HistoryExport::Progress(line));
+                                });
+                                let _ = tx.send(HistoryExport::Done(export_history_csv(&mut progress)));
+                            });
+                        }
                         (_, KeyCode::Char('y')) => {
                             // Copy last exported path to clipboard (yank)
                             if state.tab == 1 {
@@ -340,6 +473,62 @@ pub async fn run(args: Cli) -> Result<()> {
                                 }
                             }
                         }
+                        (_, KeyCode::Char('R')) if state.tab == 1 => {
+                            // Repeat the selected run with its stored configuration
+                            match state.history.get(state.history_selected) {
+                                Some(r) => match r.config.clone() {
+                                    Some(cfg) => {
+                                        state.info = format!("Rerunning like run {}…", r.meas_id);
+                                        apply_run_config(&mut args, &cfg);
+                                        args.comments = None;
+                                        state.comments = None;
+                                        state.start_form = None;
+                                        state.tab = 0;
+                                        network_loader.request(&args);
+                                        controller.cancel_active().await;
+                                        enqueue_test(&mut controller, &mut state, &args, true);
+                                    }
+                                    None => {
+                                        state.info = "This run was saved without its configuration".into();
+                                    }
+                                },
+                                None => state.info = "No run selected.".into(),
+                            }
+                        }
+                        (_, KeyCode::Char('S')) => {
+                            // Share the last result (dashboard) or the selected run (history)
+                            let result = match state.tab {
+                                0 => state.last_result.as_ref(),
+                                1 => state.history.get(state.history_selected),
+                                _ => None,
+                            };
+                            match result.map(SharePopup::new) {
+                                Some(Ok(popup)) => state.share_popup = Some(popup),
+                                Some(Err(e)) => state.info = format!("QR code failed: {e:#}"),
+                                None => state.info = "No result to share yet.".into(),
+                            }
+                        }
+                        (_, KeyCode::Char('P')) => {
+                            let size = terminal.size().unwrap_or_default();
+                            match snapshot::save_snapshot(size, &mut state, &args) {
+                                Ok(p) => {
+                                    state.last_exported_path = Some(p.to_string_lossy().to_string());
+                                    state.info = format!("Snapshot saved: {}", p.display());
+                                }
+                                Err(e) => {
+                                    state.info = format!("Snapshot failed: {e:#}");
+                                }
+                            }
+                        }
+                        (_, KeyCode::Char('g')) if state.tab == 0 => {
+                            state.latency_histogram = !state.latency_histogram;
+                        }
+                        (_, KeyCode::Char('C')) if state.tab == 0 || state.tab == 2 => {
+                            state.throughput_cdf = !state.throughput_cdf;
+                        }
+                        (_, KeyCode::Char('w')) if state.tab == 2 => {
+                            state.charts_weekly = !state.charts_weekly;
+                        }
                         (_, KeyCode::Char('a')) => {
                             state.auto_save = !state.auto_save;
                             state.info = if state.auto_save {
@@ -350,7 +539,7 @@ pub async fn run(args: Cli) -> Result<()> {
                         }
                         (KeyModifiers::SHIFT, KeyCode::BackTab) => {
                             // Shift+Tab cycles backwards
-                            let new_tab = if state.tab == 0 { 3 } else { state.tab - 1 };
+                            let new_tab = if state.tab == 0 { 5 } else { state.tab - 1 };
                             state.tab = new_tab;
                             if new_tab == 1 {
                                 state.history_selected = 0;
@@ -358,7 +547,7 @@ pub async fn run(args: Cli) -> Result<()> {
                             }
                         }
                         (_, KeyCode::Tab) => {
-                            let new_tab = (state.tab + 1) % 4;
+                            let new_tab = (state.tab + 1) % 6;
                             state.tab = new_tab;
                             // Reset history selection when switching to history tab
                             if new_tab == 1 {
@@ -367,7 +556,7 @@ pub async fn run(args: Cli) -> Result<()> {
                             }
                         }
                         (_, KeyCode::Char('?')) => {
-                            state.tab = 3; // help
+                            state.tab = 5; // help
                         }
                         // History navigation and deletion (only when on History tab)
                         (_, KeyCode::Up) | (_, KeyCode::Char('k')) => {
@@ -385,23 +574,8 @@ pub async fn run(args: Cli) -> Result<()> {
                                     // Lazy load: if near end of loaded items, load more
                                     let load_threshold = state.history_loaded_count.saturating_sub(10);
                                     if state.history_selected >= load_threshold && state.history_loaded_count == state.history.len() {
-                                        let current_count = state.history.len();
-                                        let load_more = current_count.max(20);
-                                        if let Ok(more_history) = crate::storage::load_recent(load_more) {
-                                            let existing_ids: std::collections::HashSet<_> = state.history
-                                                .iter()
-                                                .map(|r| &r.meas_id)
-                                                .collect();
-                                            let new_items: Vec<_> = more_history
-                                                .into_iter()
-                                                .filter(|r| !existing_ids.contains(&r.meas_id))
-                                                .collect();
-                                            if !new_items.is_empty() {
-                                                state.history.extend(new_items);
-                                                state.history_loaded_count = state.history.len();
-                                                update_available_networks(&mut state);
-                                            }
-                                        }
+                                        let load_more = state.history.len() + state.history.len().max(20);
+                                        history_loader.request(HistoryLoad::More, load_more);
                                     }
                                 }
                             }
@@ -421,23 +595,8 @@ pub async fn run(args: Cli) -> Result<()> {
                                 // Lazy load if near the end
                                 let load_threshold = state.history_loaded_count.saturating_sub(10);
                                 if state.history_selected >= load_threshold && state.history_loaded_count == state.history.len() {
-                                    let current_count = state.history.len();
-                                    let load_more = current_count.max(20);
-                                    if let Ok(more_history) = crate::storage::load_recent(load_more) {
-                                        let existing_ids: std::collections::HashSet<_> = state.history
-                                            .iter()
-                                            .map(|r| &r.meas_id)
-                                            .collect();
-                                        let new_items: Vec<_> = more_history
-                                            .into_iter()
-                                            .filter(|r| !existing_ids.contains(&r.meas_id))
-                                            .collect();
-                                        if !new_items.is_empty() {
-                                            state.history.extend(new_items);
-                                            state.history_loaded_count = state.history.len();
-                                            update_available_networks(&mut state);
-                                        }
-                                    }
+                                    let load_more = state.history.len() + state.history.len().max(20);
+                                    history_loader.request(HistoryLoad::More, load_more);
                                 }
                             }
                         }
@@ -450,6 +609,7 @@ pub async fn run(args: Cli) -> Result<()> {
                                         state.info = format!("Delete failed: {e:#}");
                                     } else {
                                         state.history.remove(state.history_selected);
+                                        state.history_cache.invalidate();
                                         // Adjust scroll offset if needed
                                         if state.history_scroll_offset >= state.history.len() && !state.history.is_empty() {
                                             state.history_scroll_offset = state.history.len().saturating_sub(20).max(0);
@@ -554,77 +714,152 @@ pub async fn run(args: Cli) -> Result<()> {
                     }
                 }
             }
-            // wrapping in conditional async to avoid spiking cpu usage when run_ctx is None
-            maybe_engine_ev = async {
-                if let Some(ref mut ctx) = run_ctx {
-                    ctx.event_rx.recv().await
-                } else {
-                    future::pending().await
-                }
-            } => {
-                match maybe_engine_ev {
-                    None => {
-                        // engine finished; wait for result
-                        if let Some(ctx) = &mut run_ctx {
-                            if let Some(h) = ctx.handle.take() {
-                            match h.await {
-                                Ok(Ok(r)) => {
-                                    if state.auto_save {
-                                        save_and_show_path(&r, &mut state);
-                                    }
-                                    if let Some(meta) = r.meta.as_ref() {
-                                        let extracted = crate::network::extract_metadata(meta);
-                                        state.ip = extracted.ip;
-                                        state.colo = extracted.colo;
-                                        state.asn = extracted.asn;
-                                        state.as_org = extracted.as_org;
-                                    }
-                                    // Server should be set from RunResult.server
-                                    if r.server.is_some() {
-                                        state.server = r.server.clone();
-                                    }
-                                    // Enrich result with network info before storing
-                                    let enriched = enrich_result_with_network_info(&r, &state);
-                                    state.last_result = Some(enriched.clone());
-
-                                    // Handle command-line export flags
-                                    let mut export_messages = Vec::new();
-                                    if let Some(export_path) = args.export_json.as_deref() {
-                                        match crate::storage::export_json(export_path, &enriched) {
-                                            Ok(_) => export_messages.push(format!("Exported JSON: {}", export_path.display())),
-                                            Err(e) => export_messages.push(format!("Export JSON failed: {e:#}")),
-                                        }
-                                    }
-                                    if let Some(export_path) = args.export_csv.as_deref() {
-                                        match crate::storage::export_csv(export_path, &enriched) {
-                                            Ok(_) => export_messages.push(format!("Exported CSV: {}", export_path.display())),
-                                            Err(e) => export_messages.push(format!("Export CSV failed: {e:#}")),
-                                        }
-                                    }
-                                    if !export_messages.is_empty() {
-                                        state.info = export_messages.join("; ");
-                                    }
+            controller_ev = controller.next_event() => {
+                frames.mark_dirty();
+                match controller_ev {
+                    ControllerEvent::StateChanged(run_state) => {
+                        state.run_state = run_state;
+                        if let Some(control) = &control {
+                            control.set_state(run_state);
+                        }
+                    }
+                    ControllerEvent::Engine(envelope) => apply_event(&mut state, envelope.event),
+                    ControllerEvent::QueuePosition { position, pending } => {
+                        state.info = format!("Run queued ({position} of {pending})");
+                    }
+                    ControllerEvent::Cooldown { remaining_secs } => {
+                        state.info = format!("Cooling down: next run in {remaining_secs}s");
+                    }
+                    ControllerEvent::WaitingForLock { holder } => {
+                        state.info = match holder {
+                            Some(pid) => format!("Waiting for another speed test to finish (pid {pid})"),
+                            None => "Waiting for another speed test to finish".into(),
+                        };
+                    }
+                    ControllerEvent::RunStarted { pending } => {
+                        state.reset_for_new_run();
+                        // Pick up a route change (e.g. WAN failover) since launch
+                        match state.split.as_mut() {
+                            Some(split) => {
+                                let side = split.run_started();
+                                network_loader.request(&split.side_args(&args, side));
+                            }
+                            None => network_loader.request(&args),
+                        }
+                        if pending > 0 {
+                            state.info = format!("Run started ({pending} more queued)");
+                        }
+                    }
+                    ControllerEvent::RunFinished { result: Ok(r) } => {
+                        let r = crate::cli::complete_run(&state.config, &r, &network_info(&state));
+                        if state.auto_save {
+                            save_and_show_path(&r, &mut state);
+                        }
+                        if let Some(meta) = r.meta.as_ref() {
+                            let extracted = crate::network::extract_metadata(meta);
+                            state.ip = extracted.ip;
+                            state.colo = extracted.colo;
+                            state.asn = extracted.asn;
+                            state.as_org = extracted.as_org;
+                        }
+                        // Server should be set from RunResult.server
+                        if r.server.is_some() {
+                            state.server = r.server.clone();
+                        }
+                        // Enrich result with network info before storing
+                        let enriched = enrich_result_with_network_info(&r, &state);
+                        if let Some(mut split) = state.split.take() {
+                            split.run_finished(&state, Some(&enriched));
+                            state.split = Some(split);
+                        }
+                        state.last_result = Some(enriched.clone());
+                        if let Some(control) = &control {
+                            control.set_last_result(&enriched);
+                        }
+                        if args.notify {
+                            crate::notify::run_finished(&state.config, &enriched);
+                        }
 
-                                    // Reload history to include the new test
-                                    // Load at least one more than we had before to ensure the new test is included
-                                    let reload_size = (state.history_loaded_count + 1).max(state.initial_history_load_size);
-                                    state.history = crate::storage::load_recent(reload_size).unwrap_or_default();
-                                    state.history_loaded_count = state.history.len();
-                                    update_available_networks(&mut state);
-                                    // Reset selection to show the new test (most recent) if on history tab
-                                    if state.tab == 1 {
-                                        state.history_selected = 0;
-                                        state.history_scroll_offset = 0;
-                                    }
-                                }
-                                Ok(Err(e)) => state.info = format!("Run failed: {e:#}"),
-                                Err(e) => state.info = format!("Run join failed: {e}"),
+                        // Handle command-line export flags
+                        let mut export_messages = Vec::new();
+                        if let Some(export_path) = args.export_json.as_deref() {
+                            match crate::storage::export_json(export_path, &enriched).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported JSON: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export JSON failed: {e:#}")),
                             }
+                        }
+                        if let Some(export_path) = args.export_csv.as_deref() {
+                            match crate::storage::export_csv(export_path, std::slice::from_ref(&enriched), &csv_columns).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported CSV: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export CSV failed: {e:#}")),
+                            }
+                        }
+                        if let Some(export_path) = args.export_xlsx.as_deref() {
+                            match crate::storage::export_xlsx(export_path, &enriched).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported Excel: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export Excel failed: {e:#}")),
+                            }
+                        }
+                        if let Some(export_path) = args.export_html.as_deref() {
+                            let mut timeline = crate::html::Timeline::default();
+                            timeline.download = state.dl_points.clone();
+                            timeline.upload = state.ul_points.clone();
+                            timeline.idle_latency = state.idle_lat_points.clone();
+                            timeline.loaded_latency_download = state.loaded_dl_lat_points.clone();
+                            timeline.loaded_latency_upload = state.loaded_ul_lat_points.clone();
+                            match crate::cli::export_html(&args, &state.config, export_path, &enriched, &timeline).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported HTML: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export HTML failed: {e:#}")),
+                            }
+                        }
+                        if let Some(export_path) = args.export_influx.as_deref() {
+                            match crate::influx::export_line_protocol(&state.config, export_path, &enriched).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported line protocol: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export line protocol failed: {e:#}")),
+                            }
+                        }
+                        if let Some(export_path) = args.export_prometheus.as_deref() {
+                            match crate::prometheus::export_textfile(&state.config, export_path, &enriched).and_then(|_| crate::cli::sign_export(&args, export_path)) {
+                                Ok(_) => export_messages.push(format!("Exported Prometheus metrics: {}", export_path.display())),
+                                Err(e) => export_messages.push(format!("Export Prometheus metrics failed: {e:#}")),
                             }
-                            run_ctx = None;
                         }
+                        if !export_messages.is_empty() {
+                            state.info = export_messages.join("; ");
+                        }
+                        if let Some(reason) = enriched.invalid.as_deref() {
+                            // Alongside the saved path or export messages, not instead of them
+                            state.info = if state.auto_save || !export_messages.is_empty() {
+                                format!("Results are invalid: {reason}; {}", state.info)
+                            } else {
+                                format!("Results are invalid: {reason}")
+                            };
+                        }
+                        if !state.config.thresholds_for(&enriched).breaches(&enriched).is_empty() {
+                            crate::alert::raise(&args);
+                        }
+                        if args.influx_url.is_some() {
+                            let (args, config, result, tx) = (args.clone(), state.config.clone(), enriched.clone(), push_tx.clone());
+                            tokio::spawn(async move {
+                                if let Err(e) = crate::cli::push_influx(&args, &config, &result).await {
+                                    let _ = tx.send(format!("{e:#}"));
+                                }
+                            });
+                        }
+
+                        // Reload history to include the new test
+                        // Load at least one more than we had before to ensure the new test is included
+                        let reload_size = (state.history_loaded_count + 1).max(state.initial_history_load_size);
+                        history_loader.request(HistoryLoad::RunFinished, reload_size);
+                    }
+                    ControllerEvent::RunFinished { result: Err(e) } => {
+                        state.info = format!("Run failed: {e:#}");
+                        if let Some(mut split) = state.split.take() {
+                            split.run_finished(&state, None);
+                            state.split = Some(split);
+                        }
+                        crate::alert::raise(&args);
                     }
-                    Some(ev) => apply_event(&mut state, ev),
                 }
             }
         }
@@ -637,25 +872,22 @@ pub async fn run(args: Cli) -> Result<()> {
     res
 }
 
-struct RunCtx {
-    ctrl_tx: mpsc::Sender<EngineControl>,
-    event_rx: mpsc::Receiver<TestEvent>,
-    handle: Option<tokio::task::JoinHandle<Result<RunResult>>>,
-}
-
-async fn start_run(args: &Cli) -> Result<RunCtx> {
-    let cfg = build_config(args);
-    let (event_tx, event_rx) = mpsc::channel::<TestEvent>(4096);
-    let (ctrl_tx, ctrl_rx) = mpsc::channel::<EngineControl>(32);
-    let engine = TestEngine::new(cfg);
-    let handle = tokio::spawn(async move { engine.run(event_tx, ctrl_rx).await });
-    Ok(RunCtx {
-        ctrl_tx,
-        event_rx,
-        handle: Some(handle),
-    })
+/// Queue a test: one run, or with `--split-interfaces` a run per interface
+/// and round. `front` queues it ahead of pending runs.
+fn enqueue_test(controller: &mut Controller, state: &mut UiState, args: &Cli, front: bool) {
+    let Some(split) = state.split.as_mut() else {
+        let cfg = build_config(args);
+        return if front { controller.enqueue_front(cfg) } else { controller.enqueue(cfg) };
+    };
+    let runs = split.queue(args, front);
+    if front {
+        runs.into_iter().rev().for_each(|cfg| controller.enqueue_front(cfg));
+    } else {
+        runs.into_iter().for_each(|cfg| controller.enqueue(cfg));
+    }
 }
 
+/// Update the live view with an event from the running test.
 fn apply_event(state: &mut UiState, ev: TestEvent) {
     match ev {
         TestEvent::PhaseStarted { phase } => {
@@ -696,6 +928,7 @@ fn apply_event(state: &mut UiState, ev: TestEvent) {
             }
         }
         TestEvent::Info { message } => state.info = message,
+        TestEvent::GeoInfo { geo } => state.geo = Some(*geo),
         TestEvent::MetaInfo { meta } => {
             // Extract IP, colo, ASN, and org from meta
             let extracted = crate::network::extract_metadata(&meta);
@@ -823,6 +1056,9 @@ fn apply_event(state: &mut UiState, ev: TestEvent) {
             state.udp_loss_received = received;
             state.udp_loss_total = total;
             state.udp_loss_latest_rtt_ms = rtt_ms;
+            if let Some(ms) = rtt_ms {
+                UiState::push_series(&mut state.udp_rtt_series, ms.round() as u64);
+            }
             let loss_pct = if sent == 0 {
                 0.0
             } else {
@@ -885,6 +1121,7 @@ fn apply_event(state: &mut UiState, ev: TestEvent) {
                 .map(|r| format!("{:.1}ms", r))
                 .unwrap_or_else(|| "*".to_string());
             state.info = format!("Traceroute hop {}: {} {}", hop_number, addr, rtt);
+            state.traceroute_hops.push(hop);
         }
         TestEvent::TracerouteComplete { summary } => {
             state.info = format!(
@@ -901,17 +1138,19 @@ fn apply_event(state: &mut UiState, ev: TestEvent) {
     }
 }
 
-fn draw(area: Rect, f: &mut ratatui::Frame, state: &mut UiState) {
+fn draw(area: Rect, f: &mut ratatui::Frame, state: &mut UiState, args: &Cli) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
         .split(area);
 
     let tabs = Tabs::new(vec![
-        Line::from("Dashboard"),
-        Line::from("History"),
-        Line::from("Charts"),
-        Line::from("Help"),
+        Line::from(t!("tab-dashboard")),
+        Line::from(t!("tab-history")),
+        Line::from(t!("tab-charts")),
+        Line::from(t!("tab-diagnostics")),
+        Line::from(t!("tab-settings")),
+        Line::from(t!("tab-help")),
     ])
     .select(state.tab)
     .block(
@@ -930,7 +1169,13 @@ fn draw(area: Rect, f: &mut ratatui::Frame, state: &mut UiState) {
     f.render_widget(tabs, chunks[0]);
 
     match state.tab {
-        0 => draw_dashboard(chunks[1], f, state),
+        0 => match &state.start_form {
+            Some(form) => draw_start_form(chunks[1], f, form),
+            None => match &state.split {
+                Some(split) => draw_split(chunks[1], f, state, split),
+                None => draw_dashboard(chunks[1], f, state),
+            },
+        },
         1 => {
             if state.history_detail_view {
                 draw_history_detail(chunks[1], f, &mut *state)
@@ -939,6 +1184,15 @@ fn draw(area: Rect, f: &mut ratatui::Frame, state: &mut UiState) {
             }
         }
         2 => draw_charts(chunks[1], f, state),
+        3 => draw_diagnostics(chunks[1], f, state),
+        4 => draw_settings(chunks[1], f, state, args),
         _ => draw_help(chunks[1], f),
     }
+
+    if let Some(popup) = &state.share_popup {
+        draw_share(area, f, popup);
+    }
+    if let Some(onboarding) = &state.onboarding {
+        draw_onboarding(area, f, onboarding);
+    }
 }
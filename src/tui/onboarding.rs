@@ -0,0 +1,121 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Storage choices offered on first launch: (label, auto_save, redact).
+const CHOICES: [(&str, bool, bool); 3] = [
+    ("Save results with full network details", true, false),
+    ("Save results without IPs, MAC address and Wi-Fi name", true, true),
+    ("Don't save results automatically", false, false),
+];
+
+/// What the TUI should do after a key press on the onboarding dialog.
+pub enum OnboardingAction {
+    None,
+    /// Store the choice: (auto_save, redact)
+    Confirm(bool, bool),
+    /// Close without storing; the dialog is shown again next launch
+    Later,
+    Quit,
+}
+
+/// First-run dialog explaining what is stored locally.
+#[derive(Default)]
+pub struct Onboarding {
+    selected: usize,
+}
+
+impl Onboarding {
+    pub fn handle_key(&mut self, code: KeyCode) -> OnboardingAction {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = (self.selected + CHOICES.len() - 1) % CHOICES.len();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1) % CHOICES.len();
+            }
+            KeyCode::Enter => {
+                let (_, auto_save, redact) = CHOICES[self.selected];
+                return OnboardingAction::Confirm(auto_save, redact);
+            }
+            KeyCode::Esc => return OnboardingAction::Later,
+            KeyCode::Char('q') => return OnboardingAction::Quit,
+            _ => {}
+        }
+        OnboardingAction::None
+    }
+}
+
+pub fn draw_onboarding(area: Rect, f: &mut Frame, onboarding: &Onboarding) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(18),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(72),
+            Constraint::Min(0),
+        ])
+        .split(rows[1]);
+
+    let mut lines = vec![
+        Line::from("Each completed test is saved on this computer so you can browse your history."),
+        Line::from("Besides the measurements, a saved result includes:"),
+        Line::from("  • your public and local IP addresses"),
+        Line::from("  • the network interface name and its MAC address"),
+        Line::from("  • the Wi-Fi network name (SSID)"),
+        Line::from(vec![
+            Span::raw("Results are stored in "),
+            Span::styled(
+                crate::storage::runs_dir().display().to_string(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(""),
+        Line::from("How should results be saved?"),
+    ];
+    for (idx, (label, _, _)) in CHOICES.iter().enumerate() {
+        if idx == onboarding.selected {
+            lines.push(Line::from(Span::styled(
+                format!("> {label}"),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else {
+            lines.push(Line::from(format!("  {label}")));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("↑/↓", Style::default().fg(Color::Magenta)),
+        Span::raw(" select  "),
+        Span::styled("Enter", Style::default().fg(Color::Magenta)),
+        Span::raw(" confirm  "),
+        Span::styled("Esc", Style::default().fg(Color::Magenta)),
+        Span::raw(" ask me later"),
+    ]));
+    lines.push(Line::from(Span::styled(
+        "You can change this later on the Settings tab.",
+        Style::default().fg(Color::Gray),
+    )));
+
+    let p = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Welcome to cloudflare-speed-cli"),
+    );
+    f.render_widget(Clear, cols[1]);
+    f.render_widget(p, cols[1]);
+}
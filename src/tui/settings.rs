@@ -0,0 +1,246 @@
+use super::state::UiState;
+use crate::cli::Cli;
+use crate::config::{Theme, ThroughputUnit};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+const DURATION_PRESETS_SECS: [u64; 6] = [5, 10, 15, 20, 30, 60];
+const SMOOTHING_PRESETS: [usize; 4] = [1, 3, 5, 10];
+const UNITS: [ThroughputUnit; 2] = [ThroughputUnit::Mbps, ThroughputUnit::MBps];
+const THEMES: [Theme; 3] = [Theme::Default, Theme::HighContrast, Theme::Monochrome];
+const SPEED_THRESHOLD_PRESETS: [Option<f64>; 6] = [
+    None,
+    Some(10.0),
+    Some(25.0),
+    Some(50.0),
+    Some(100.0),
+    Some(500.0),
+];
+const LATENCY_THRESHOLD_PRESETS: [Option<f64>; 5] =
+    [None, Some(50.0), Some(100.0), Some(200.0), Some(500.0)];
+
+#[derive(Clone, Copy)]
+enum Setting {
+    AutoSave,
+    Redact,
+    DownloadDuration,
+    UploadDuration,
+    Units,
+    ChartSmoothing,
+    Theme,
+    MinDownload,
+    MinUpload,
+    MaxLoadedLatency,
+}
+
+const SETTINGS: [Setting; 10] = [
+    Setting::AutoSave,
+    Setting::Redact,
+    Setting::DownloadDuration,
+    Setting::UploadDuration,
+    Setting::Units,
+    Setting::ChartSmoothing,
+    Setting::Theme,
+    Setting::MinDownload,
+    Setting::MinUpload,
+    Setting::MaxLoadedLatency,
+];
+
+/// Download and upload chart colors for a theme.
+pub fn throughput_colors(theme: Theme) -> (Color, Color) {
+    match theme {
+        Theme::Default => (Color::Green, Color::Cyan),
+        Theme::HighContrast => (Color::LightGreen, Color::LightMagenta),
+        Theme::Monochrome => (Color::White, Color::Gray),
+    }
+}
+
+/// Pick the next (or previous) option after `current`, wrapping around.
+fn cycle<T: PartialEq + Copy>(options: &[T], current: T, forward: bool) -> T {
+    let len = options.len();
+    let next = match options.iter().position(|o| *o == current) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None => 0,
+    };
+    options[next]
+}
+
+/// Handle a key press on the Settings tab. Returns false if the key isn't
+/// used by the settings panel.
+pub fn handle_key(state: &mut UiState, args: &mut Cli, code: KeyCode) -> bool {
+    let count = SETTINGS.len();
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            state.settings_selected = (state.settings_selected + count - 1) % count;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            state.settings_selected = (state.settings_selected + 1) % count;
+        }
+        KeyCode::Left | KeyCode::Char('h') => change(state, args, false),
+        KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => change(state, args, true),
+        _ => return false,
+    }
+    true
+}
+
+/// Change the selected setting and persist the configuration.
+fn change(state: &mut UiState, args: &mut Cli, forward: bool) {
+    let config = &mut state.config;
+    match SETTINGS[state.settings_selected] {
+        Setting::AutoSave => {
+            state.auto_save = !state.auto_save;
+            args.auto_save = state.auto_save;
+            config.auto_save = Some(state.auto_save);
+        }
+        Setting::Redact => {
+            state.redact = !state.redact;
+            args.redact = state.redact;
+            config.redact = Some(state.redact);
+        }
+        Setting::DownloadDuration => {
+            let current = Duration::from(args.download_duration).as_secs();
+            let duration = Duration::from_secs(cycle(&DURATION_PRESETS_SECS, current, forward));
+            args.download_duration = duration.into();
+            config.download_duration = Some(duration);
+        }
+        Setting::UploadDuration => {
+            let current = Duration::from(args.upload_duration).as_secs();
+            let duration = Duration::from_secs(cycle(&DURATION_PRESETS_SECS, current, forward));
+            args.upload_duration = duration.into();
+            config.upload_duration = Some(duration);
+        }
+        Setting::Units => config.ui.units = cycle(&UNITS, config.ui.units, forward),
+        Setting::ChartSmoothing => {
+            config.ui.chart_smoothing =
+                cycle(&SMOOTHING_PRESETS, config.ui.chart_smoothing, forward)
+        }
+        Setting::Theme => config.ui.theme = cycle(&THEMES, config.ui.theme, forward),
+        Setting::MinDownload => {
+            config.thresholds.min_download_mbps = cycle(
+                &SPEED_THRESHOLD_PRESETS,
+                config.thresholds.min_download_mbps,
+                forward,
+            )
+        }
+        Setting::MinUpload => {
+            config.thresholds.min_upload_mbps = cycle(
+                &SPEED_THRESHOLD_PRESETS,
+                config.thresholds.min_upload_mbps,
+                forward,
+            )
+        }
+        Setting::MaxLoadedLatency => {
+            config.thresholds.max_loaded_latency_ms = cycle(
+                &LATENCY_THRESHOLD_PRESETS,
+                config.thresholds.max_loaded_latency_ms,
+                forward,
+            )
+        }
+    }
+
+    state.info = match crate::config::save(&state.config) {
+        Ok(path) => format!("Settings saved to {}", path.display()),
+        Err(e) => format!("Failed to save settings: {e:#}"),
+    };
+}
+
+fn setting_label_value(state: &UiState, args: &Cli, setting: Setting) -> (&'static str, String) {
+    let config = &state.config;
+    let threshold = |v: Option<f64>, unit: &str| match v {
+        Some(v) => format!("{v:.0} {unit}"),
+        None => "Off".to_string(),
+    };
+    match setting {
+        Setting::AutoSave => (
+            "Auto-save",
+            if state.auto_save { "On" } else { "Off" }.to_string(),
+        ),
+        Setting::Redact => (
+            "Redact IPs/MAC/SSID",
+            if state.redact { "On" } else { "Off" }.to_string(),
+        ),
+        Setting::DownloadDuration => ("Download duration", args.download_duration.to_string()),
+        Setting::UploadDuration => ("Upload duration", args.upload_duration.to_string()),
+        Setting::Units => ("Throughput units", config.ui.units.label().to_string()),
+        Setting::ChartSmoothing => (
+            "Chart smoothing",
+            match config.ui.chart_smoothing {
+                0 | 1 => "Off".to_string(),
+                n => format!("{n} samples"),
+            },
+        ),
+        Setting::Theme => (
+            "Theme",
+            match config.ui.theme {
+                Theme::Default => "Default",
+                Theme::HighContrast => "High contrast",
+                Theme::Monochrome => "Monochrome",
+            }
+            .to_string(),
+        ),
+        Setting::MinDownload => (
+            "Min download",
+            threshold(config.thresholds.min_download_mbps, "Mbps"),
+        ),
+        Setting::MinUpload => (
+            "Min upload",
+            threshold(config.thresholds.min_upload_mbps, "Mbps"),
+        ),
+        Setting::MaxLoadedLatency => (
+            "Max loaded latency",
+            threshold(config.thresholds.max_loaded_latency_ms, "ms"),
+        ),
+    }
+}
+
+pub fn draw_settings(area: Rect, f: &mut Frame, state: &UiState, args: &Cli) {
+    let mut lines = vec![Line::from("")];
+    for (idx, setting) in SETTINGS.iter().enumerate() {
+        let (label, value) = setting_label_value(state, args, *setting);
+        let selected = state.settings_selected == idx;
+        let (marker, value, value_style) = if selected {
+            (
+                "> ",
+                format!("◀ {value} ▶"),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("  ", value, Style::default())
+        };
+        lines.push(Line::from(vec![
+            Span::raw(marker),
+            Span::styled(format!("{label:<20}"), Style::default().fg(Color::Gray)),
+            Span::styled(value, value_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("↑/↓", Style::default().fg(Color::Magenta)),
+        Span::raw(" select  "),
+        Span::styled("←/→", Style::default().fg(Color::Magenta)),
+        Span::raw(" change (saved immediately)"),
+    ]));
+    lines.push(Line::from(Span::styled(
+        format!("Config file: {}", crate::config::config_path().display()),
+        Style::default().fg(Color::Gray),
+    )));
+    if !state.info.is_empty() {
+        lines.push(Line::from(Span::styled(
+            state.info.clone(),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    let p = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Settings"));
+    f.render_widget(p, area);
+}
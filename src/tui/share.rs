@@ -0,0 +1,104 @@
+use crate::model::RunResult;
+use anyhow::{Context, Result};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::{EcLevel, QrCode};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Popup showing a result summary as a QR code, so it can be grabbed with a
+/// phone from a terminal on a headless box.
+pub struct SharePopup {
+    summary: String,
+    qr_lines: Vec<String>,
+}
+
+impl SharePopup {
+    pub fn new(result: &RunResult) -> Result<Self> {
+        let summary = result.compact_summary();
+        // Low error correction keeps the code small enough for a terminal
+        let code = QrCode::with_error_correction_level(summary.as_bytes(), EcLevel::L)
+            .context("encode QR code")?;
+        let rendered = code.render::<Dense1x2>().quiet_zone(false).build();
+        Ok(Self {
+            summary,
+            qr_lines: rendered.lines().map(str::to_string).collect(),
+        })
+    }
+}
+
+pub fn draw_share(area: Rect, f: &mut Frame, popup: &SharePopup) {
+    let qr_width = popup
+        .qr_lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    // QR code with a light margin (the quiet zone), then the summary text
+    let width = (qr_width + 6).max(40);
+    let summary_lines = (popup.summary.len() as u16).div_ceil(width - 2);
+    let height = popup.qr_lines.len() as u16 + 2 + summary_lines + 3;
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ])
+        .split(rows[1]);
+    let popup_area = cols[1];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Scan to share (any key closes)");
+    let inner = block.inner(popup_area);
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(popup.qr_lines.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    let qr_area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(qr_width + 4),
+            Constraint::Min(0),
+        ])
+        .split(parts[0])[1];
+
+    // Dark modules on a light background, whatever the terminal theme
+    let mut qr_text = vec![Line::from("")];
+    qr_text.extend(
+        popup
+            .qr_lines
+            .iter()
+            .map(|l| Line::from(format!("  {l}  "))),
+    );
+    qr_text.push(Line::from(""));
+    f.render_widget(
+        Paragraph::new(qr_text).style(Style::default().fg(Color::Black).bg(Color::White)),
+        qr_area,
+    );
+    f.render_widget(
+        Paragraph::new(popup.summary.as_str()).wrap(Wrap { trim: true }),
+        parts[1],
+    );
+}
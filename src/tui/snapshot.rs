@@ -0,0 +1,98 @@
+use super::{draw, UiState};
+use crate::cli::Cli;
+use anyhow::{Context, Result};
+use ratatui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::Size,
+    style::{Color, Modifier},
+    Terminal,
+};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// SGR parameters selecting `color` as foreground (or background).
+fn color_sgr(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    match color {
+        Color::Reset => (base + 9).to_string(),
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+        Color::Indexed(i) => format!("{};5;{i}", base + 8),
+    }
+}
+
+/// Convert a rendered buffer to text with ANSI color escape sequences.
+fn buffer_to_ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let mut current = None;
+        for x in area.left()..area.right() {
+            let cell = &buffer[(x, y)];
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(style) {
+                out.push_str("\x1b[0");
+                for (modifier, code) in [
+                    (Modifier::BOLD, 1),
+                    (Modifier::DIM, 2),
+                    (Modifier::ITALIC, 3),
+                    (Modifier::UNDERLINED, 4),
+                    (Modifier::REVERSED, 7),
+                ] {
+                    if cell.modifier.contains(modifier) {
+                        let _ = write!(out, ";{code}");
+                    }
+                }
+                let _ = write!(
+                    out,
+                    ";{};{}m",
+                    color_sgr(cell.fg, false),
+                    color_sgr(cell.bg, true)
+                );
+                current = Some(style);
+            }
+            out.push_str(cell.symbol());
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Render the current screen off-screen at `size` and save it as an ANSI
+/// text file (view with `cat`) in the current directory.
+pub fn save_snapshot(size: Size, state: &mut UiState, args: &Cli) -> Result<PathBuf> {
+    let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))
+        .context("create snapshot terminal")?;
+    terminal
+        .draw(|f| draw(f.area(), f, state, args))
+        .context("render snapshot")?;
+
+    let now = time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+    let stamp = now
+        .format(time::macros::format_description!(
+            "[year]-[month]-[day]_[hour]-[minute]-[second]"
+        ))
+        .context("format snapshot timestamp")?;
+    let path = std::env::current_dir()
+        .context("get current directory")?
+        .join(format!("cloudflare-speed-snapshot-{stamp}.ans"));
+    std::fs::write(&path, buffer_to_ansi(terminal.backend().buffer()))
+        .context("write snapshot")?;
+    Ok(path)
+}
@@ -0,0 +1,442 @@
+//! Split dashboard of `--split-interfaces`: runs on two interfaces, one
+//! after the other, side by side on shared axes, so a slower link (often
+//! the Wi-Fi) stands out without comparing two separate runs.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Wrap},
+    Frame,
+};
+
+use std::collections::VecDeque;
+
+use super::dashboard::max_y;
+use super::settings::throughput_colors;
+use super::state::UiState;
+use crate::cli::{build_config, Cli};
+use crate::model::{RunConfig, RunResult};
+
+/// Sides within this share of each other are reported as alike.
+const ALIKE_RATIO: f64 = 0.9;
+
+/// One interface of the split view.
+pub struct SplitSide {
+    pub interface: String,
+    /// Series of the side's latest run, in seconds since it started
+    dl_points: Vec<(f64, f64)>,
+    ul_points: Vec<(f64, f64)>,
+    idle_lat_points: Vec<(f64, f64)>,
+    loaded_dl_lat_points: Vec<(f64, f64)>,
+    loaded_ul_lat_points: Vec<(f64, f64)>,
+    /// The side's finished runs, oldest first
+    results: Vec<RunResult>,
+}
+
+/// Series of a side as drawn: its live run or its latest finished one.
+struct Series<'a> {
+    dl: &'a [(f64, f64)],
+    ul: &'a [(f64, f64)],
+    idle_lat: &'a [(f64, f64)],
+    loaded_dl_lat: &'a [(f64, f64)],
+    loaded_ul_lat: &'a [(f64, f64)],
+}
+
+pub struct SplitView {
+    pub sides: [SplitSide; 2],
+    /// Runs queued per side
+    rounds: u32,
+    /// Side of the run in progress
+    active: Option<usize>,
+    /// Sides of the queued runs, in queue order
+    pending: VecDeque<usize>,
+}
+
+impl SplitView {
+    pub fn new(interfaces: &[String], rounds: u32) -> Self {
+        let side = |interface: &String| SplitSide {
+            interface: interface.clone(),
+            dl_points: Vec::new(),
+            ul_points: Vec::new(),
+            idle_lat_points: Vec::new(),
+            loaded_dl_lat_points: Vec::new(),
+            loaded_ul_lat_points: Vec::new(),
+            results: Vec::new(),
+        };
+        Self {
+            sides: [side(&interfaces[0]), side(&interfaces[1])],
+            rounds: rounds.max(1),
+            active: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// `args` bound to the interface of `side`.
+    pub fn side_args(&self, args: &Cli, side: usize) -> Cli {
+        Cli {
+            interface: Some(self.sides[side].interface.clone()),
+            ..args.clone()
+        }
+    }
+
+    /// The runs of a comparison: the two interfaces alternating, so both
+    /// see the same conditions over the rounds. `front` when they are
+    /// queued ahead of pending runs.
+    pub fn queue(&mut self, args: &Cli, front: bool) -> Vec<RunConfig> {
+        let sides = (0..self.rounds as usize * 2).map(|i| i % 2);
+        if front {
+            for side in sides.clone().rev() {
+                self.pending.push_front(side);
+            }
+        } else {
+            self.pending.extend(sides.clone());
+        }
+        sides
+            .map(|side| build_config(&self.side_args(args, side)))
+            .collect()
+    }
+
+    /// A queued run started; returns its side.
+    pub fn run_started(&mut self) -> usize {
+        let side = self.pending.pop_front().unwrap_or_default();
+        self.active = Some(side);
+        side
+    }
+
+    /// Keep the series of the run that just ended, and its result.
+    pub fn run_finished(&mut self, state: &UiState, result: Option<&RunResult>) {
+        let interface = result.and_then(|r| r.config.as_ref()?.interface.as_deref());
+        let side = match interface {
+            Some(interface) => self.sides.iter().position(|s| s.interface == interface),
+            None => self.active,
+        };
+        self.active = None;
+        let Some(side) = side.map(|i| &mut self.sides[i]) else {
+            return;
+        };
+        side.dl_points = state.dl_points.clone();
+        side.ul_points = state.ul_points.clone();
+        side.idle_lat_points = state.idle_lat_points.clone();
+        side.loaded_dl_lat_points = state.loaded_dl_lat_points.clone();
+        side.loaded_ul_lat_points = state.loaded_ul_lat_points.clone();
+        side.results.extend(result.cloned());
+        let excess = side.results.len().saturating_sub(self.rounds as usize);
+        side.results.drain(..excess);
+    }
+
+    fn series<'a>(&'a self, side: usize, state: &'a UiState) -> Series<'a> {
+        if self.active == Some(side) {
+            return Series {
+                dl: &state.dl_points,
+                ul: &state.ul_points,
+                idle_lat: &state.idle_lat_points,
+                loaded_dl_lat: &state.loaded_dl_lat_points,
+                loaded_ul_lat: &state.loaded_ul_lat_points,
+            };
+        }
+        let s = &self.sides[side];
+        Series {
+            dl: &s.dl_points,
+            ul: &s.ul_points,
+            idle_lat: &s.idle_lat_points,
+            loaded_dl_lat: &s.loaded_dl_lat_points,
+            loaded_ul_lat: &s.loaded_ul_lat_points,
+        }
+    }
+}
+
+/// Median of `f` over the valid runs of `results`.
+fn median(results: &[RunResult], f: impl Fn(&RunResult) -> Option<f64>) -> Option<f64> {
+    let values: Vec<f64> = results
+        .iter()
+        .filter(|r| r.invalid.is_none())
+        .filter_map(f)
+        .collect();
+    crate::metrics::median(&values)
+}
+
+/// Median download, upload and loaded latency of a side's runs.
+fn side_medians(side: &SplitSide) -> Option<(f64, f64, Option<f64>)> {
+    Some((
+        median(&side.results, |r| Some(r.download.mbps))?,
+        median(&side.results, |r| Some(r.upload.mbps))?,
+        median(&side.results, |r| r.loaded_latency_download.median_ms),
+    ))
+}
+
+/// How the two sides compare, in words, once both have valid runs that
+/// measured some throughput.
+fn verdict(view: &SplitView) -> Option<String> {
+    let [a, b] = &view.sides;
+    let (a_down, a_up, a_lat) = side_medians(a)?;
+    let (b_down, b_up, b_lat) = side_medians(b)?;
+    if [a_down, a_up, b_down, b_up].iter().any(|mbps| *mbps <= 0.0) {
+        return None;
+    }
+    let (slow, fast, ratio_down, ratio_up, extra_lat) = if b_down < a_down {
+        (b, a, b_down / a_down, b_up / a_up, b_lat.zip(a_lat))
+    } else {
+        (a, b, a_down / b_down, a_up / b_up, a_lat.zip(b_lat))
+    };
+    if ratio_down >= ALIKE_RATIO && ratio_up >= ALIKE_RATIO {
+        return Some(format!(
+            "{} and {} measure within {:.0}% of each other: the difference isn't the link.",
+            a.interface,
+            b.interface,
+            (1.0 - ALIKE_RATIO) * 100.0
+        ));
+    }
+    let mut text = format!(
+        "{} reaches {:.0}% of {}'s download and {:.0}% of its upload",
+        slow.interface,
+        ratio_down * 100.0,
+        fast.interface,
+        ratio_up * 100.0
+    );
+    if let Some((slow_ms, fast_ms)) = extra_lat.filter(|(s, f)| s > f) {
+        text.push_str(&format!(
+            ", with {:.0} ms more loaded latency",
+            slow_ms - fast_ms
+        ));
+    }
+    let wireless = slow
+        .results
+        .iter()
+        .rev()
+        .find(|r| r.invalid.is_none())
+        .and_then(|r| r.is_wireless)
+        == Some(true);
+    text.push_str(if wireless {
+        ": the Wi-Fi link is the bottleneck."
+    } else {
+        "."
+    });
+    Some(text)
+}
+
+/// Largest x and y over the points of both sides, so their charts share
+/// axes.
+fn shared_bounds<'a>(points: impl Iterator<Item = &'a [(f64, f64)]>) -> (f64, f64) {
+    points.fold((1.0, 1.0), |(x, y), p| {
+        let px = p.iter().map(|(x, _)| *x).fold(0.0, f64::max);
+        (x.max(px), y.max(max_y(p)))
+    })
+}
+
+fn line_dataset(points: &[(f64, f64)], color: Color) -> Dataset<'_> {
+    Dataset::default()
+        .graph_type(GraphType::Line)
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(color))
+        .data(points)
+}
+
+fn side_summary(view: &SplitView, side: usize, state: &UiState) -> Vec<Line<'static>> {
+    let s = &view.sides[side];
+    let unit = state.config.ui.units;
+    let kind = match s.results.last().and_then(|r| r.is_wireless) {
+        Some(true) => " (Wi-Fi)",
+        Some(false) => " (wired)",
+        None => "",
+    };
+    let status = if view.active == Some(side) {
+        format!("running: {}", state.phase_label())
+    } else {
+        format!("{} of {} run(s) done", s.results.len(), view.rounds)
+    };
+    let value = match side_medians(s) {
+        Some((down, up, lat)) => format!(
+            "Download {:.1} / upload {:.1} {} median, loaded latency {}",
+            unit.convert_mbps(down),
+            unit.convert_mbps(up),
+            unit.label(),
+            lat.map_or("-".to_string(), |ms| format!("{ms:.0} ms"))
+        ),
+        None => "No valid run yet".to_string(),
+    };
+    vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{}{kind}", s.interface),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(format!("  {status}"), Style::default().fg(Color::Gray)),
+        ]),
+        Line::from(value),
+    ]
+}
+
+pub fn draw_split(area: Rect, f: &mut Frame, state: &UiState, view: &SplitView) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(4),  // Per-side summary
+                Constraint::Min(8),     // Throughput
+                Constraint::Length(10), // Latency
+                Constraint::Length(4),  // Verdict and status
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    let columns = |area: Rect| {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(area)
+    };
+    let ui = &state.config.ui;
+    let unit = ui.units;
+    let (dl_color, ul_color) = throughput_colors(ui.theme);
+
+    let series = [view.series(0, state), view.series(1, state)];
+    let throughput: Vec<[Vec<(f64, f64)>; 2]> = series
+        .iter()
+        .map(|s| {
+            let convert = |p: &[(f64, f64)]| -> Vec<(f64, f64)> {
+                p.iter().map(|&(x, y)| (x, unit.convert_mbps(y))).collect()
+            };
+            [convert(s.dl), convert(s.ul)]
+        })
+        .collect();
+    let (thr_x, thr_y) = shared_bounds(throughput.iter().flatten().map(Vec::as_slice));
+    let (lat_x, lat_y) = shared_bounds(
+        series
+            .iter()
+            .flat_map(|s| [s.idle_lat, s.loaded_dl_lat, s.loaded_ul_lat]),
+    );
+    let x_max = thr_x.max(lat_x);
+
+    let (summary, thr, lat) = (columns(rows[0]), columns(rows[1]), columns(rows[2]));
+    for side in 0..2 {
+        f.render_widget(
+            Paragraph::new(side_summary(view, side, state))
+                .block(Block::default().borders(Borders::ALL)),
+            summary[side],
+        );
+
+        let [dl, ul] = &throughput[side];
+        let chart = Chart::new(vec![line_dataset(dl, dl_color), line_dataset(ul, ul_color)])
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(Line::from(vec![
+                        Span::raw(format!("{}: ", view.sides[side].interface)),
+                        Span::styled("download", Style::default().fg(dl_color)),
+                        Span::raw(" / "),
+                        Span::styled("upload", Style::default().fg(ul_color)),
+                    ])),
+            )
+            .x_axis(Axis::default().bounds([0.0, x_max]))
+            .y_axis(
+                Axis::default()
+                    .title(unit.label())
+                    .bounds([0.0, thr_y * 1.1])
+                    .labels([String::from("0"), format!("{:.0}", thr_y * 1.1)]),
+            );
+        f.render_widget(chart, thr[side]);
+
+        let s = &series[side];
+        let chart = Chart::new(vec![
+            line_dataset(s.idle_lat, Color::Gray),
+            line_dataset(s.loaded_dl_lat, dl_color),
+            line_dataset(s.loaded_ul_lat, ul_color),
+        ])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Latency (idle / loaded)"),
+        )
+        .x_axis(Axis::default().bounds([0.0, x_max]))
+        .y_axis(
+            Axis::default()
+                .title("ms")
+                .bounds([0.0, lat_y * 1.1])
+                .labels([String::from("0"), format!("{:.0}", lat_y * 1.1)]),
+        );
+        f.render_widget(chart, lat[side]);
+    }
+
+    let verdict = verdict(view).unwrap_or_else(|| {
+        "Both interfaces are tested in turn; the comparison follows their first runs.".into()
+    });
+    f.render_widget(
+        Paragraph::new(vec![
+            Line::from(verdict),
+            Line::from(Span::styled(
+                state.info.clone(),
+                Style::default().fg(Color::Gray),
+            )),
+        ])
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Comparison")),
+        rows[3],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_run;
+    use clap::Parser;
+
+    fn result(interface: &str, download: f64, upload: f64) -> RunResult {
+        let args = Cli::parse_from(["cloudflare-speed-cli", "--interface", interface]);
+        let mut r = test_run(download, upload);
+        r.config = Some(build_config(&args));
+        r
+    }
+
+    #[test]
+    fn test_split_run_order() {
+        let args = Cli::parse_from(["cloudflare-speed-cli"]);
+        let interfaces = ["eth0".to_string(), "wlan0".to_string()];
+        let mut view = SplitView::new(&interfaces, 2);
+        let queued: Vec<_> = view
+            .queue(&args, false)
+            .into_iter()
+            .map(|cfg| cfg.interface.unwrap())
+            .collect();
+        assert_eq!(queued, ["eth0", "wlan0", "eth0", "wlan0"]);
+
+        assert_eq!(view.run_started(), 0);
+        // A comparison queued in front runs before the rest of the first
+        view.queue(&args, true);
+        assert_eq!(view.pending, [0, 1, 0, 1, 1, 0, 1]);
+
+        // A result is kept on the side of its interface, whichever side the
+        // view thought was running
+        let state = UiState::default();
+        view.run_finished(&state, Some(&result("wlan0", 50.0, 10.0)));
+        assert_eq!(view.active, None);
+        assert_eq!(view.sides[1].results.len(), 1);
+        assert_eq!(view.run_started(), 0);
+        view.run_finished(&state, None);
+        assert!(view.sides[0].results.is_empty());
+    }
+
+    #[test]
+    fn test_split_verdict() {
+        let interfaces = ["eth0".to_string(), "wlan0".to_string()];
+        let mut view = SplitView::new(&interfaces, 3);
+        view.sides[0].results = vec![result("eth0", 100.0, 20.0)];
+        let mut asleep = result("wlan0", 1.0, 1.0);
+        asleep.invalid = Some("system slept during the test".into());
+        view.sides[1].results = vec![result("wlan0", 50.0, 10.0), asleep];
+        view.sides[1].results[0].is_wireless = Some(true);
+        assert_eq!(
+            verdict(&view).unwrap(),
+            "wlan0 reaches 50% of eth0's download and 50% of its upload: the Wi-Fi link is the \
+             bottleneck."
+        );
+
+        view.sides[1].results = vec![result("wlan0", 95.0, 19.0)];
+        assert!(verdict(&view).unwrap().contains("within 10% of each other"));
+
+        // Nothing measured on one side: no ratio to report
+        view.sides[0].results = vec![result("eth0", 100.0, 0.0)];
+        assert_eq!(verdict(&view), None);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::cli::Cli;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+/// Download/upload phase durations offered on the start screen, in seconds.
+const DURATIONS_SECS: [u64; 5] = [5, 10, 15, 20, 30];
+
+/// Test modes offered on the start screen.
+const MODES: [&str; 4] = [
+    "Standard",
+    "Skip diagnostics",
+    "With traceroute",
+    "Compare IPv4/IPv6",
+];
+
+const FIELD_DURATION: usize = 0;
+const FIELD_MODE: usize = 1;
+const FIELD_INTERFACE: usize = 2;
+const FIELD_COMMENTS: usize = 3;
+const FIELD_COUNT: usize = 4;
+
+/// What the TUI should do after a key press on the start screen.
+pub enum StartAction {
+    None,
+    Start,
+    Dismiss,
+    Quit,
+}
+
+/// Start screen shown instead of the dashboard when `--test-on-launch false`.
+pub struct StartForm {
+    selected: usize,
+    /// `DURATIONS_SECS`, plus the command line's duration if it isn't one
+    durations: Vec<Duration>,
+    duration_idx: usize,
+    mode_idx: usize,
+    /// Interface names; index 0 is "Auto" (default route)
+    interfaces: Vec<String>,
+    interface_idx: usize,
+    comments: String,
+}
+
+impl StartForm {
+    /// Pre-fill the form from the command-line arguments.
+    pub fn new(args: &Cli) -> Self {
+        let download = Duration::from(args.download_duration);
+        let mut durations: Vec<Duration> = DURATIONS_SECS
+            .iter()
+            .map(|&s| Duration::from_secs(s))
+            .collect();
+        if !durations.contains(&download) {
+            durations.push(download);
+            durations.sort();
+        }
+        let duration_idx = durations.iter().position(|&d| d == download).unwrap_or(1);
+        let mode_idx = if args.skip_diagnostics {
+            1
+        } else if args.traceroute {
+            2
+        } else if args.compare_ip_versions {
+            3
+        } else {
+            0
+        };
+
+        let mut interfaces = vec!["Auto".to_string()];
+        if let Ok(addrs) = if_addrs::get_if_addrs() {
+            let mut names: Vec<String> = addrs
+                .into_iter()
+                .filter(|a| !a.is_loopback())
+                .map(|a| a.name)
+                .collect();
+            names.sort();
+            names.dedup();
+            interfaces.extend(names);
+        }
+        let interface_idx = args
+            .interface
+            .as_ref()
+            .and_then(|name| interfaces.iter().position(|i| i == name))
+            .unwrap_or(0);
+
+        Self {
+            selected: 0,
+            durations,
+            duration_idx,
+            mode_idx,
+            interfaces,
+            interface_idx,
+            comments: args.comments.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Apply the chosen parameters to the arguments used for runs.
+    pub fn apply(&self, args: &mut Cli) {
+        let duration = self.durations[self.duration_idx];
+        args.download_duration = duration.into();
+        args.upload_duration = duration.into();
+        args.skip_diagnostics = self.mode_idx == 1;
+        args.traceroute = self.mode_idx == 2;
+        args.compare_ip_versions = self.mode_idx == 3;
+        args.interface = if self.interface_idx == 0 {
+            None
+        } else {
+            Some(self.interfaces[self.interface_idx].clone())
+        };
+        let comments = self.comments.trim();
+        args.comments = (!comments.is_empty()).then(|| comments.to_string());
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> StartAction {
+        match code {
+            KeyCode::Enter => return StartAction::Start,
+            KeyCode::Esc => return StartAction::Dismiss,
+            KeyCode::Up => {
+                self.selected = (self.selected + FIELD_COUNT - 1) % FIELD_COUNT;
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % FIELD_COUNT;
+            }
+            KeyCode::Left => self.cycle(false),
+            KeyCode::Right => self.cycle(true),
+            KeyCode::Backspace if self.selected == FIELD_COMMENTS => {
+                self.comments.pop();
+            }
+            KeyCode::Char(c) if self.selected == FIELD_COMMENTS => self.comments.push(c),
+            KeyCode::Char('q') => return StartAction::Quit,
+            _ => {}
+        }
+        StartAction::None
+    }
+
+    fn cycle(&mut self, forward: bool) {
+        let step = |idx: &mut usize, len: usize| {
+            *idx = if forward {
+                (*idx + 1) % len
+            } else {
+                (*idx + len - 1) % len
+            };
+        };
+        match self.selected {
+            FIELD_DURATION => step(&mut self.duration_idx, self.durations.len()),
+            FIELD_MODE => step(&mut self.mode_idx, MODES.len()),
+            FIELD_INTERFACE => step(&mut self.interface_idx, self.interfaces.len()),
+            _ => {}
+        }
+    }
+}
+
+pub fn draw_start_form(area: Rect, f: &mut Frame, form: &StartForm) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(9),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(60),
+            Constraint::Min(0),
+        ])
+        .split(rows[1]);
+
+    let field = |idx: usize, label: &str, value: String| {
+        let selected = form.selected == idx;
+        let marker = if selected { "> " } else { "  " };
+        let value_style = if selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        Line::from(vec![
+            Span::raw(marker),
+            Span::styled(format!("{label:<12}"), Style::default().fg(Color::Gray)),
+            Span::styled(value, value_style),
+        ])
+    };
+    let choice = |value: &str, idx: usize| {
+        if form.selected == idx {
+            format!("◀ {value} ▶")
+        } else {
+            value.to_string()
+        }
+    };
+
+    let duration = humantime::format_duration(form.durations[form.duration_idx]).to_string();
+    let comments = if form.selected == FIELD_COMMENTS {
+        format!("{}_", form.comments)
+    } else if form.comments.is_empty() {
+        "-".to_string()
+    } else {
+        form.comments.clone()
+    };
+    let lines = vec![
+        Line::from(""),
+        field(
+            FIELD_DURATION,
+            "Duration",
+            choice(&duration, FIELD_DURATION),
+        ),
+        field(FIELD_MODE, "Mode", choice(MODES[form.mode_idx], FIELD_MODE)),
+        field(
+            FIELD_INTERFACE,
+            "Interface",
+            choice(&form.interfaces[form.interface_idx], FIELD_INTERFACE),
+        ),
+        field(FIELD_COMMENTS, "Comments", comments),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("↑/↓", Style::default().fg(Color::Magenta)),
+            Span::raw(" select  "),
+            Span::styled("←/→", Style::default().fg(Color::Magenta)),
+            Span::raw(" change  "),
+            Span::styled("Enter", Style::default().fg(Color::Magenta)),
+            Span::raw(" start  "),
+            Span::styled("Esc", Style::default().fg(Color::Magenta)),
+            Span::raw(" skip"),
+        ])
+        .alignment(Alignment::Center),
+    ];
+    let p = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Start a speed test"),
+    );
+    f.render_widget(p, cols[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_start_form_keeps_custom_duration() {
+        let mut args = Cli::parse_from(["cloudflare-speed-cli", "--download-duration", "45s"]);
+        let form = StartForm::new(&args);
+        assert_eq!(form.durations.len(), DURATIONS_SECS.len() + 1);
+        form.apply(&mut args);
+        assert_eq!(
+            Duration::from(args.download_duration),
+            Duration::from_secs(45)
+        );
+
+        let args = Cli::parse_from(["cloudflare-speed-cli", "--download-duration", "15s"]);
+        let form = StartForm::new(&args);
+        assert_eq!(form.durations.len(), DURATIONS_SECS.len());
+        assert_eq!(form.durations[form.duration_idx], Duration::from_secs(15));
+    }
+}
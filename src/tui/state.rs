@@ -1,4 +1,9 @@
-use crate::model::{DnsSummary, IpVersionComparison, Phase, RunResult, TlsSummary, TracerouteSummary};
+use crate::config::Config;
+use crate::model::{
+    CellularSummary, DnsSummary, GeoSummary, IpVersionComparison, Phase, RunResult, TlsSummary,
+    TracerouteHop, TracerouteSummary,
+};
+use crate::orchestrator::controller::RunState;
 use ratatui::{
     style::Color,
     style::Style,
@@ -6,10 +11,19 @@ use ratatui::{
 };
 use std::time::Instant;
 
+use super::cursor::ChartCursor;
+use super::history::HistoryCache;
+use super::onboarding::Onboarding;
+use super::share::SharePopup;
+use super::split::SplitView;
+use super::start::StartForm;
+
 pub struct UiState {
     pub tab: usize,
     pub paused: bool,
     pub phase: Phase,
+    /// Controller lifecycle state, from its `StateChanged` events
+    pub run_state: RunState,
     pub info: String,
     pub comments: Option<String>,
 
@@ -50,12 +64,19 @@ pub struct UiState {
     pub udp_loss_received: u64,
     pub udp_loss_total: u64,
     pub udp_loss_latest_rtt_ms: Option<f64>,
+    /// RTT (ms) of each answered UDP probe, for the live sparkline
+    pub udp_rtt_series: Vec<u64>,
 
     pub last_result: Option<RunResult>,
     pub history: Vec<RunResult>,
     pub history_selected: usize, // Index of selected history item (0 = most recent)
     pub history_scroll_offset: usize,
     pub history_loaded_count: usize,
+    pub history_cache: HistoryCache,
+    /// A history load is running in the background
+    pub history_loading: bool,
+    /// Network information is still being looked up
+    pub network_info_loading: bool,
     pub initial_history_load_size: usize, // Initial load size based on terminal height
     // History filtering
     pub history_filter: String,       // Current filter text
@@ -63,6 +84,14 @@ pub struct UiState {
     // Charts tab state
     pub charts_network_filter: Option<String>, // None = all networks, Some(name) = specific network
     pub charts_available_networks: Vec<String>, // List of unique network names from history
+    pub charts_cursor: ChartCursor,
+    pub dashboard_cursor: ChartCursor,
+    /// Show latency histograms instead of box plots on the dashboard
+    pub latency_histogram: bool,
+    /// Show throughput as a CDF instead of over time (dashboard and Charts tab)
+    pub throughput_cdf: bool,
+    /// Show weekly box plots of run results on the Charts tab
+    pub charts_weekly: bool,
     // History detail view state
     pub history_detail_view: bool,    // Whether showing JSON detail view
     pub history_detail_scroll: usize, // Scroll position in detail view
@@ -72,12 +101,19 @@ pub struct UiState {
     pub asn: Option<String>,
     pub as_org: Option<String>,
     pub auto_save: bool,
+    pub redact: bool,
     pub last_exported_path: Option<String>,
     // Network interface information
     pub interface_name: Option<String>,
     pub network_name: Option<String>,
     pub is_wireless: Option<bool>,
     pub interface_mac: Option<String>,
+    pub link_speed_mbps: Option<u64>,
+    pub route_interface: Option<String>,
+    pub cellular: Option<CellularSummary>,
+    /// Set when the default route looks different from what the test expects
+    pub route_warning: Option<String>,
+    pub geo: Option<GeoSummary>,
     pub local_ipv4: Option<String>,
     pub local_ipv6: Option<String>,
     pub external_ipv4: Option<String>,
@@ -89,8 +125,21 @@ pub struct UiState {
     pub tls_summary: Option<TlsSummary>,
     pub ip_comparison: Option<IpVersionComparison>,
     pub traceroute_summary: Option<TracerouteSummary>,
+    /// Hops as the traceroute reaches them, for the Diagnostics tab
+    pub traceroute_hops: Vec<TracerouteHop>,
     /// None = check not completed, Some(None) = on latest, Some(Some(v)) = update available
     pub update_status: Option<Option<String>>,
+    /// Start screen shown until the first run when `--test-on-launch false`
+    pub start_form: Option<StartForm>,
+    /// Persistent settings, edited on the Settings tab
+    pub config: Config,
+    pub settings_selected: usize,
+    /// First-run privacy notice, shown until answered
+    pub onboarding: Option<Onboarding>,
+    /// QR code of a result summary, shown until any key is pressed
+    pub share_popup: Option<SharePopup>,
+    /// Two interfaces side by side on the Dashboard, from `--split-interfaces`
+    pub split: Option<SplitView>,
 }
 
 impl Default for UiState {
@@ -99,6 +148,7 @@ impl Default for UiState {
             tab: 0,
             paused: false,
             phase: Phase::IdleLatency,
+            run_state: RunState::Idle,
             info: String::new(),
             comments: None,
             dl_series: Vec::new(),
@@ -133,16 +183,25 @@ impl Default for UiState {
             udp_loss_received: 0,
             udp_loss_total: 0,
             udp_loss_latest_rtt_ms: None,
+            udp_rtt_series: Vec::new(),
             last_result: None,
             history: Vec::new(),
             history_selected: 0,
             history_scroll_offset: 0,
             history_loaded_count: 0,
+            history_cache: HistoryCache::default(),
+            history_loading: false,
+            network_info_loading: false,
             initial_history_load_size: 66, // Default initial load size
             history_filter: String::new(),
             history_filter_editing: false,
             charts_network_filter: None,
             charts_available_networks: Vec::new(),
+            charts_cursor: ChartCursor::default(),
+            dashboard_cursor: ChartCursor::default(),
+            latency_histogram: false,
+            throughput_cdf: false,
+            charts_weekly: false,
             history_detail_view: false,
             history_detail_scroll: 0,
             ip: None,
@@ -151,11 +210,17 @@ impl Default for UiState {
             asn: None,
             as_org: None,
             auto_save: true,
+            redact: false,
             last_exported_path: None,
             interface_name: None,
             network_name: None,
             is_wireless: None,
             interface_mac: None,
+            link_speed_mbps: None,
+            route_interface: None,
+            cellular: None,
+            route_warning: None,
+            geo: None,
             local_ipv4: None,
             local_ipv6: None,
             external_ipv4: None,
@@ -167,7 +232,14 @@ impl Default for UiState {
             tls_summary: None,
             ip_comparison: None,
             traceroute_summary: None,
+            traceroute_hops: Vec::new(),
             update_status: None,
+            start_form: None,
+            config: Config::default(),
+            settings_selected: 0,
+            onboarding: None,
+            share_popup: None,
+            split: None,
         }
     }
 }
@@ -177,7 +249,7 @@ pub fn update_available_networks(state: &mut UiState) {
     let mut networks: Vec<String> = state
         .history
         .iter()
-        .filter_map(|r| r.network_name.clone())
+        .filter_map(|r| state.config.network_label(r).map(str::to_string))
         .collect();
     networks.sort();
     networks.dedup();
@@ -238,6 +310,64 @@ pub fn push_wrapped_status_kv(
 }
 
 impl UiState {
+    /// The "Phase" shown in the status box: the test phase during a run,
+    /// otherwise what the controller is doing.
+    pub fn phase_label(&self) -> String {
+        if self.run_state.is_active() {
+            format!("{:?}", self.phase)
+        } else {
+            self.run_state.to_string()
+        }
+    }
+
+    /// Clear live measurements and diagnostics before a new run starts.
+    pub fn reset_for_new_run(&mut self) {
+        self.last_result = None;
+        self.run_start = Instant::now();
+        self.dl_series.clear();
+        self.ul_series.clear();
+        self.idle_lat_series.clear();
+        self.loaded_dl_lat_series.clear();
+        self.loaded_ul_lat_series.clear();
+        self.dl_points.clear();
+        self.ul_points.clear();
+        self.idle_lat_points.clear();
+        self.loaded_dl_lat_points.clear();
+        self.dashboard_cursor = ChartCursor::default();
+        self.loaded_ul_lat_points.clear();
+        self.dl_mbps = 0.0;
+        self.ul_mbps = 0.0;
+        self.dl_avg_mbps = 0.0;
+        self.ul_avg_mbps = 0.0;
+        self.dl_bytes_total = 0;
+        self.ul_bytes_total = 0;
+        self.dl_phase_start = None;
+        self.ul_phase_start = None;
+        self.idle_latency_samples.clear();
+        self.loaded_dl_latency_samples.clear();
+        self.loaded_ul_latency_samples.clear();
+        self.idle_latency_sent = 0;
+        self.idle_latency_received = 0;
+        self.loaded_dl_latency_sent = 0;
+        self.loaded_dl_latency_received = 0;
+        self.loaded_ul_latency_sent = 0;
+        self.loaded_ul_latency_received = 0;
+        self.phase = Phase::IdleLatency;
+        self.paused = false;
+        // Clear UDP loss counters
+        self.udp_loss_sent = 0;
+        self.udp_loss_received = 0;
+        self.udp_loss_total = 0;
+        self.udp_loss_latest_rtt_ms = None;
+        self.udp_rtt_series.clear();
+        // Clear diagnostic results
+        self.dns_summary = None;
+        self.tls_summary = None;
+        self.ip_comparison = None;
+        self.traceroute_summary = None;
+        self.traceroute_hops.clear();
+    }
+
     pub fn push_series(series: &mut Vec<u64>, v: u64) {
         const MAX: usize = 120;
         series.push(v);